@@ -1,8 +1,12 @@
-use crate::processor::fields::FieldContext;
-use proc_macro_error::abort;
-use quote::{format_ident, quote};
-use std::collections::HashSet;
-use syn::Ident;
+use crate::processor::attributes::FromPolicy;
+use crate::processor::fields::{
+    CreateRole, FieldContext, FieldKind, NestedWrapper, ProcessedField,
+};
+use crate::processor::validation::Validation;
+use proc_macro_error::{abort, emit_error};
+use quote::{format_ident, quote, quote_spanned};
+use syn::spanned::Spanned;
+use syn::{GenericArgument, Ident, PathArguments, Type, TypePath};
 
 /// Configuration for trait derivation
 #[derive(Debug, Clone)]
@@ -10,6 +14,8 @@ pub struct TraitConfig {
     pub required_traits: Vec<&'static str>,
     pub contains_f64: bool,
     pub contains_string: bool,
+    /// `Debug` is implemented by hand so sensitive fields can be redacted
+    pub redact_debug: bool,
 }
 
 impl Default for TraitConfig {
@@ -18,6 +24,7 @@ impl Default for TraitConfig {
             required_traits: vec!["Clone", "Debug", "Serialize", "Deserialize"],
             contains_f64: false,
             contains_string: false,
+            redact_debug: false,
         }
     }
 }
@@ -44,45 +51,158 @@ pub fn generate_derive_clause(
         trait_idents.retain(|ident| ident != "Eq");
     }
 
+    if config.redact_debug {
+        trait_idents.retain(|ident| ident != "Debug");
+    }
+
     quote! {
         #[derive(#(#trait_idents),*)]
     }
 }
 
+/// Initializers for the guard fields (`expected_version`, `expect_<field>`) of a new substruct
+///
+/// A substruct taken from a source snapshot expects the version it was taken at;
+/// field preconditions always start unset.
+fn guard_initializers(context: &FieldContext, from_source: bool) -> proc_macro2::TokenStream {
+    let expected_version = context.version_field.as_ref().map(|(version, _)| {
+        if from_source {
+            quote! { expected_version: Some(source.#version.clone()), }
+        } else {
+            quote! { expected_version: None, }
+        }
+    });
+    let expect_slot_names = &context.expect_slot_names;
+    quote! {
+        #expected_version
+        #(#expect_slot_names: None,)*
+    }
+}
+
+/// Statements binding `this` to the normalized substruct
+///
+/// Only pays for a normalized copy when the struct declares transforms.
+fn normalized_self(context: &FieldContext) -> proc_macro2::TokenStream {
+    if context.normalize_steps.is_empty() {
+        quote! { let this = self; }
+    } else {
+        quote! {
+            let mut normalized = self.clone();
+            normalized.normalize();
+            let this = &normalized;
+        }
+    }
+}
+
+/// Initializers for an empty substruct: every field in its "no change" state
+///
+/// Unwrapped fields use their sentinel (`Default::default()` or `default = "..."`).
+fn empty_initializers(context: &FieldContext) -> Vec<proc_macro2::TokenStream> {
+    context
+        .field_names
+        .iter()
+        .map(|name| {
+            match context
+                .unwrapped_field_names
+                .iter()
+                .position(|unwrapped| unwrapped == name)
+            {
+                Some(index) => {
+                    let default = &context.unwrapped_field_defaults[index];
+                    quote! { #name: #default }
+                }
+                None => quote! { #name: Default::default() },
+            }
+        })
+        .collect()
+}
+
+/// Whether the substruct has a version field or `expect` preconditions of its own
+fn is_guarded(context: &FieldContext) -> bool {
+    context.version_field.is_some() || !context.expect_slot_names.is_empty()
+}
+
 /// Generate the From<&T> implementation
+///
+/// Follows the struct's `from` policy: an empty substruct (the default) or a snapshot.
 pub fn generate_from_ref_impl(
     struct_name: &Ident,
     update_struct_name: &Ident,
     context: &FieldContext,
 ) -> proc_macro2::TokenStream {
-    let has_json_fields = !context.json_field_names.is_empty();
-    let handled_fields: HashSet<_> = context.json_field_names.iter().collect();
-    let defaulted_fields: Vec<_> = context
-        .field_names
-        .iter()
-        .filter(|ident| !handled_fields.contains(ident))
-        .collect();
-
-    if has_json_fields {
-        let json_field_names = &context.json_field_names;
-        quote! {
-            impl From<&#struct_name> for #update_struct_name {
-                fn from(source: &#struct_name) -> Self {
-                    Self {
-                        #(#defaulted_fields: Default::default(),)*
-                        #(#json_field_names: Some(serde_json::to_value(&source.#json_field_names)
-                            .expect("Failed to serialize field to JSON")),)*
-                    }
+    let body = match context.from_policy {
+        FromPolicy::Empty => {
+            let empty_fields = empty_initializers(context);
+            let source_version = guard_initializers(context, true);
+            quote! {
+                Self {
+                    #(#empty_fields,)*
+                    #source_version
                 }
             }
         }
-    } else {
-        quote! {
-            impl From<&#struct_name> for #update_struct_name {
-                fn from(source: &#struct_name) -> Self {
-                    Self {
-                        #(#defaulted_fields: Default::default(),)*
-                    }
+        FromPolicy::Snapshot => quote! { Self::snapshot(source) },
+    };
+    quote! {
+        impl From<&#struct_name> for #update_struct_name {
+            fn from(source: &#struct_name) -> Self {
+                #body
+            }
+        }
+    }
+}
+
+/// Generate `snapshot`, a substruct holding the source's current values
+pub fn generate_snapshot_impl(
+    update_struct_name: &Ident,
+    struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    let wrapped_field_names = &context.wrapped_field_names;
+    let unwrapped_field_names = &context.unwrapped_field_names;
+    let json_field_names = &context.json_field_names;
+    let nested_snapshots: Vec<_> = context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_types)
+        .zip(&context.nested_field_wrappers)
+        .map(|((name, ty), wrapper)| match wrapper {
+            NestedWrapper::Option => quote! {
+                #name: Some(source.#name.as_ref().map(|nested| #ty::snapshot(nested)))
+            },
+            // One substruct is applied to every element, so a list can't be captured
+            NestedWrapper::Vec => quote! { #name: None },
+            _ => quote! { #name: Some(#ty::snapshot(&source.#name)) },
+        })
+        .collect();
+    let keyed_vec_field_names = &context.keyed_vec_field_names;
+    let keyed_vec_op_types = &context.keyed_vec_op_types;
+    let source_version = guard_initializers(context, true);
+
+    quote! {
+        impl #update_struct_name {
+            /// Creates a substruct holding the source's current value in every field.
+            ///
+            /// Applying it to another value copies the source over, which is what a
+            /// full resync needs:
+            /// - Primitive and JSON fields are set to the source's values
+            /// - Nested substructs are snapshotted recursively; `Option`-wrapped ones are
+            ///   cleared when the source has none, `Vec`-wrapped ones are left unset
+            /// - Keyed collections become one `Upsert` per element
+            pub fn snapshot(source: &#struct_name) -> Self {
+                Self {
+                    #(#wrapped_field_names: Some(source.#wrapped_field_names.clone()),)*
+                    #(#unwrapped_field_names: source.#unwrapped_field_names.clone(),)*
+                    #(#json_field_names: Some(serde_json::to_value(&source.#json_field_names)
+                        .expect("Failed to serialize field to JSON")),)*
+                    #(#nested_snapshots,)*
+                    #(#keyed_vec_field_names: source
+                        .#keyed_vec_field_names
+                        .iter()
+                        .cloned()
+                        .map(#keyed_vec_op_types::Upsert)
+                        .collect(),)*
+                    #source_version
                 }
             }
         }
@@ -95,13 +215,273 @@ pub fn generate_struct_definition(
     context: &FieldContext,
 ) -> proc_macro2::TokenStream {
     let updatable_fields = &context.updatable_fields;
+    let rename_all = context
+        .serde_rename_all
+        .as_ref()
+        .map(|rule| quote! { #[serde(rename_all = #rule)] });
+    let deserialize_present = context.deserialize_present.then(|| {
+        quote! {
+            /// Deserializes a field that is present, `null` included, as `Some`.
+            fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+                T: serde::Deserialize<'de>,
+            {
+                T::deserialize(deserializer).map(Some)
+            }
+        }
+    });
+    let unset_checks: Vec<_> = context
+        .unwrapped_field_names
+        .iter()
+        .zip(&context.unwrapped_field_types)
+        .zip(&context.unwrapped_field_defaults)
+        .map(|((name, ty), default)| {
+            let check = format_ident!("is_unset_{}", name);
+            quote! {
+                /// Whether the field holds its "no change" value, so it is left out when serialized.
+                fn #check(value: &#ty) -> bool {
+                    *value == #default
+                }
+            }
+        })
+        .collect();
+    let serde_helpers = (context.deserialize_present || !unset_checks.is_empty()).then(|| {
+        quote! {
+            impl #update_struct_name {
+                #deserialize_present
+                #(#unset_checks)*
+            }
+        }
+    });
+    // Every field is optional on the wire, falling back to its "no change" state
     quote! {
+        #[serde(default)]
+        #rename_all
         pub struct #update_struct_name {
             #(#updatable_fields,)*
         }
+
+        #serde_helpers
     }
 }
 
+/// Expressions borrowing each nested field's substruct as `Option<&T>`
+///
+/// For `Option`-wrapped nested fields this skips the "clear" state (`Some(None)`).
+fn nested_refs(
+    receiver: &proc_macro2::TokenStream,
+    context: &FieldContext,
+) -> Vec<proc_macro2::TokenStream> {
+    context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+        .map(|(name, wrapper)| match wrapper {
+            NestedWrapper::Option => {
+                quote! { #receiver.#name.as_ref().and_then(|nested| nested.as_ref()) }
+            }
+            _ => quote! { #receiver.#name.as_ref() },
+        })
+        .collect()
+}
+
+/// Expressions mutably borrowing each nested field's substruct as `Option<&mut T>`
+fn nested_muts(
+    receiver: &proc_macro2::TokenStream,
+    context: &FieldContext,
+) -> Vec<proc_macro2::TokenStream> {
+    context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+        .map(|(name, wrapper)| match wrapper {
+            NestedWrapper::Option => {
+                quote! { #receiver.#name.as_mut().and_then(|nested| nested.as_mut()) }
+            }
+            _ => quote! { #receiver.#name.as_mut() },
+        })
+        .collect()
+}
+
+/// Statements applying each nested field of `this` to `target`
+///
+/// Guards are checked by the caller through `check_guards` before anything is written.
+fn nested_apply_statements(context: &FieldContext) -> Vec<proc_macro2::TokenStream> {
+    context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+        .map(|(name, wrapper)| match wrapper {
+            NestedWrapper::None => quote! {
+                if let Some(nested_update) = &this.#name {
+                    nested_update.apply_unchecked(&mut target.#name);
+                }
+            },
+            NestedWrapper::Box => quote! {
+                if let Some(nested_update) = &this.#name {
+                    nested_update.apply_unchecked(&mut *target.#name);
+                }
+            },
+            // Copy-on-write: only clones the pointee when it is shared
+            NestedWrapper::Arc => quote! {
+                if let Some(nested_update) = &this.#name {
+                    nested_update.apply_unchecked(std::sync::Arc::make_mut(&mut target.#name));
+                }
+            },
+            NestedWrapper::Rc => quote! {
+                if let Some(nested_update) = &this.#name {
+                    nested_update.apply_unchecked(std::rc::Rc::make_mut(&mut target.#name));
+                }
+            },
+            NestedWrapper::Vec => quote! {
+                if let Some(nested_update) = &this.#name {
+                    for item in target.#name.iter_mut() {
+                        nested_update.apply_unchecked(item);
+                    }
+                }
+            },
+            // Missing values are created from `Default` before being patched
+            NestedWrapper::Option => quote! {
+                match &this.#name {
+                    Some(Some(nested_update)) => {
+                        nested_update.apply_unchecked(target.#name.get_or_insert_with(Default::default));
+                    }
+                    Some(None) => target.#name = None,
+                    None => {}
+                }
+            },
+        })
+        .collect()
+}
+
+/// Whether the substruct has guards of its own or nested substructs that may have some
+///
+/// These structs get a `<Substruct>ApplyError` and a fallible `apply_to`; the others check
+/// their guards infallibly.
+fn has_apply_error(context: &FieldContext) -> bool {
+    is_guarded(context)
+        || !context.nested_field_names.is_empty()
+        || !context.keyed_vec_field_names.is_empty()
+}
+
+/// Statements returning the first failed guard of a nested field of `self` against `target`
+///
+/// Each nested substruct is checked against the value it would be applied to: every
+/// element of a `Vec`, and `Default::default()` for a missing `Option` value.
+fn nested_guard_checks(
+    context: &FieldContext,
+    apply_error: &Ident,
+) -> Vec<proc_macro2::TokenStream> {
+    context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+        .map(|(name, wrapper)| {
+            let nested_error = |path: proc_macro2::TokenStream| {
+                quote! {
+                    |err| #apply_error::Nested { path: #path, message: err.to_string() }
+                }
+            };
+            match wrapper {
+                NestedWrapper::Vec => {
+                    let map_err =
+                        nested_error(quote! { format!("{}[{}]", stringify!(#name), index) });
+                    quote! {
+                        if let Some(nested_update) = &self.#name {
+                            for (index, item) in target.#name.iter().enumerate() {
+                                nested_update.check_guards(item).map_err(#map_err)?;
+                            }
+                        }
+                    }
+                }
+                NestedWrapper::Option => {
+                    let map_err = nested_error(quote! { stringify!(#name).to_string() });
+                    quote! {
+                        if let Some(Some(nested_update)) = &self.#name {
+                            match &target.#name {
+                                Some(current) => nested_update.check_guards(current),
+                                None => nested_update.check_guards(&Default::default()),
+                            }
+                            .map_err(#map_err)?;
+                        }
+                    }
+                }
+                _ => {
+                    let map_err = nested_error(quote! { stringify!(#name).to_string() });
+                    quote! {
+                        if let Some(nested_update) = &self.#name {
+                            nested_update.check_guards(&target.#name).map_err(#map_err)?;
+                        }
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// Statements returning `true` when a nested field of `this` would change `target`
+fn nested_would_change_checks(context: &FieldContext) -> Vec<proc_macro2::TokenStream> {
+    context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+        .map(|(name, wrapper)| match wrapper {
+            NestedWrapper::None | NestedWrapper::Box | NestedWrapper::Arc | NestedWrapper::Rc => {
+                quote! {
+                    if let Some(nested_update) = &this.#name {
+                        if nested_update.would_change(&target.#name) {
+                            return true;
+                        }
+                    }
+                }
+            }
+            NestedWrapper::Vec => quote! {
+                if let Some(nested_update) = &this.#name {
+                    if target.#name.iter().any(|item| nested_update.would_change(item)) {
+                        return true;
+                    }
+                }
+            },
+            NestedWrapper::Option => quote! {
+                match (&this.#name, &target.#name) {
+                    (Some(Some(nested_update)), Some(current)) => {
+                        if nested_update.would_change(current) {
+                            return true;
+                        }
+                    }
+                    (Some(Some(_)), None) | (Some(None), Some(_)) => return true,
+                    _ => {}
+                }
+            },
+        })
+        .collect()
+}
+
+/// Expressions merging a nested field of `self` and `other`, recursing when both are set
+fn nested_merges(context: &FieldContext) -> Vec<proc_macro2::TokenStream> {
+    context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+        .map(|(name, wrapper)| match wrapper {
+            NestedWrapper::Option => quote! {
+                match (self.#name, other.#name) {
+                    (Some(Some(first)), Some(Some(second))) => Some(Some(first.merge(second))),
+                    (first, None) => first,
+                    (_, second) => second,
+                }
+            },
+            _ => quote! {
+                match (self.#name, other.#name) {
+                    (Some(first), Some(second)) => Some(first.merge(second)),
+                    (first, second) => second.or(first),
+                }
+            },
+        })
+        .collect()
+}
+
 /// Generate the impl block for the struct
 pub fn generate_struct_impl(
     update_struct_name: &Ident,
@@ -112,8 +492,202 @@ pub fn generate_struct_impl(
     let field_types = &context.field_types;
     let wrapped_field_names = &context.wrapped_field_names;
     let unwrapped_field_names = &context.unwrapped_field_names;
+    let unwrapped_field_defaults = &context.unwrapped_field_defaults;
     let json_field_names = &context.json_field_names;
     let nested_field_names = &context.nested_field_names;
+    let normalize_steps = &context.normalize_steps;
+    let keyed_vec_field_names = &context.keyed_vec_field_names;
+    let keyed_vec_op_types = &context.keyed_vec_op_types;
+    let keyed_vec_keys = &context.keyed_vec_keys;
+    let keyed_vec_nested_types = &context.keyed_vec_nested_types;
+    let nested_refs_self = nested_refs(&quote! { self }, context);
+    let nested_muts_self = nested_muts(&quote! { self }, context);
+    let nested_apply_statements = nested_apply_statements(context);
+    let nested_would_change_checks = nested_would_change_checks(context);
+    let nested_merges = nested_merges(context);
+    // Sensitive values are replaced with a placeholder
+    let wrapped_partial_inserts: Vec<_> = wrapped_field_names
+        .iter()
+        .map(|name| {
+            if context.sensitive_field_names.contains(name) {
+                quote! {
+                    if self.#name.is_some() {
+                        partial.insert(stringify!(#name).to_string(), "<redacted>".to_string());
+                    }
+                }
+            } else {
+                quote! {
+                    if let Some(value) = self.#name {
+                        partial.insert(stringify!(#name).to_string(), format!("{:?}", value));
+                    }
+                }
+            }
+        })
+        .collect();
+    let unwrapped_partial_inserts: Vec<_> = unwrapped_field_names
+        .iter()
+        .zip(unwrapped_field_defaults)
+        .map(|(name, default)| {
+            let value = if context.sensitive_field_names.contains(name) {
+                quote! { "<redacted>".to_string() }
+            } else {
+                quote! { format!("{:?}", &self.#name) }
+            };
+            quote! {
+                if self.#name != #default {
+                    partial.insert(stringify!(#name).to_string(), #value);
+                }
+            }
+        })
+        .collect();
+    let nested_partial_inserts: Vec<_> = nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+        .map(|(name, wrapper)| match wrapper {
+            NestedWrapper::Option => quote! {
+                match self.#name {
+                    Some(Some(nested)) => {
+                        partial.insert(stringify!(#name).to_string(), format!("{:?}", nested.into_partial()));
+                    }
+                    Some(None) => {
+                        partial.insert(stringify!(#name).to_string(), "None".to_string());
+                    }
+                    None => {}
+                }
+            },
+            _ => quote! {
+                if let Some(nested) = self.#name {
+                    partial.insert(stringify!(#name).to_string(), format!("{:?}", nested.into_partial()));
+                }
+            },
+        })
+        .collect();
+
+    // Guarded structs check every guard before writing and bump the version last
+    let apply_error = format_ident!("{}ApplyError", update_struct_name);
+    let version_check = context.version_field.as_ref().map(|(version, _)| {
+        quote! {
+            if let Some(expected) = &self.expected_version {
+                if *expected != target.#version {
+                    return Err(#apply_error::VersionConflict {
+                        expected: expected.clone(),
+                        found: target.#version.clone(),
+                    });
+                }
+            }
+        }
+    });
+    let version_bump = context.version_field.as_ref().map(|(version, _)| {
+        quote! {
+            if !this.is_empty() {
+                target.#version += 1;
+            }
+        }
+    });
+    let expect_slot_names = &context.expect_slot_names;
+    // Sensitive values never end up in the error message
+    let expect_checks: Vec<_> = context
+        .expect_field_names
+        .iter()
+        .zip(&context.expect_slot_names)
+        .map(|(name, slot)| {
+            let (expected, found) = if context.sensitive_field_names.contains(name) {
+                (
+                    quote! { "<redacted>".to_string() },
+                    quote! { "<redacted>".to_string() },
+                )
+            } else {
+                (
+                    quote! { format!("{:?}", expected) },
+                    quote! { format!("{:?}", target.#name) },
+                )
+            };
+            quote! {
+                if let Some(expected) = &self.#slot {
+                    if *expected != target.#name {
+                        return Err(#apply_error::PreconditionFailed {
+                            field: stringify!(#name),
+                            expected: #expected,
+                            found: #found,
+                        });
+                    }
+                }
+            }
+        })
+        .collect();
+    let nested_guard_checks = nested_guard_checks(context, &apply_error);
+    // Patches are checked against the elements as the earlier operations leave them
+    let keyed_guard_checks: Vec<_> = keyed_vec_field_names
+        .iter()
+        .zip(keyed_vec_op_types)
+        .zip(keyed_vec_keys)
+        .map(|((name, op_type), key_field)| {
+            quote! {
+                if self.#name.iter().any(|op| matches!(op, #op_type::Patch(..))) {
+                    let mut items = target.#name.clone();
+                    for op in &self.#name {
+                        match op {
+                            #op_type::Upsert(item) => {
+                                match items.iter_mut().find(|existing| existing.#key_field == item.#key_field) {
+                                    Some(existing) => *existing = item.clone(),
+                                    None => items.push(item.clone()),
+                                }
+                            }
+                            #op_type::Remove(key) => {
+                                items.retain(|existing| &existing.#key_field != key);
+                            }
+                            #op_type::Patch(key, patch) => {
+                                if let Some(existing) = items.iter_mut().find(|existing| &existing.#key_field == key) {
+                                    patch.check_guards(existing).map_err(|err| #apply_error::Nested {
+                                        path: format!("{}[{:?}]", stringify!(#name), key),
+                                        message: err.to_string(),
+                                    })?;
+                                    patch.apply_unchecked(existing);
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        })
+        .collect();
+    let guard_error = if has_apply_error(context) {
+        quote! { #apply_error }
+    } else {
+        quote! { std::convert::Infallible }
+    };
+    let (apply_return, apply_body) = if has_apply_error(context) {
+        (
+            quote! { -> Result<(), #apply_error> },
+            quote! {
+                self.check_guards(target)?;
+                self.apply_unchecked(target);
+                Ok(())
+            },
+        )
+    } else {
+        (quote! {}, quote! { self.apply_unchecked(target); })
+    };
+    let guard_defaults = guard_initializers(context, false);
+    let merge_version = context
+        .version_field
+        .as_ref()
+        .map(|_| quote! { expected_version: other.expected_version.or(self.expected_version), });
+
+    let normalized_self = normalized_self(context);
+    // Transforms can turn a value into the "no change" sentinel, so precedence is
+    // decided on normalized values, as `apply_to` sees them
+    let (merge_params, merge_normalize) = if context.normalize_steps.is_empty() {
+        (quote! { self, other: Self }, quote! {})
+    } else {
+        (
+            quote! { mut self, mut other: Self },
+            quote! {
+                self.normalize();
+                other.normalize();
+            },
+        )
+    };
 
     quote! {
         impl #update_struct_name {
@@ -131,16 +705,47 @@ pub fn generate_struct_impl(
             ///     Some(true),               // active field
             /// );
             /// ```
+            #[allow(clippy::too_many_arguments)]
             pub fn new(#(#field_names: #field_types),*) -> Self {
-                Self {
-                    #(#field_names),*
-                }
+                let mut update = Self {
+                    #(#field_names,)*
+                    #guard_defaults
+                };
+                update.normalize();
+                update
+            }
+
+            /// Applies the `transform`/`transform_with` normalizers to every set field.
+            ///
+            /// This runs automatically in `new` and `set_by_path`, and `apply_to`/`would_change`
+            /// work on a normalized copy, so fields assigned directly are normalized too.
+            /// Nested substructs are normalized recursively.
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// let mut update = #update_struct_name::default();
+            /// update.email = Some("  Alice@Example.COM ".to_string());
+            /// update.normalize();
+            /// assert_eq!(update.email, Some("alice@example.com".to_string()));
+            /// ```
+            pub fn normalize(&mut self) {
+                #(#normalize_steps)*
+                #(if let Some(nested) = #nested_muts_self {
+                    nested.normalize();
+                })*
+                #(for op in self.#keyed_vec_field_names.iter_mut() {
+                    if let #keyed_vec_op_types::Patch(_, patch) = op {
+                        patch.normalize();
+                    }
+                })*
             }
 
-            /// Creates a substruct from an existing instance where all fields indicate "no change".
+            /// Creates a substruct from an existing instance, the same way as `From<&T>`.
             ///
-            /// This is useful when you want to create a substruct that represents the current state
-            /// but doesn't actually change anything when applied.
+            /// With the default `from = "empty"` policy all fields indicate "no change", which is
+            /// useful as a starting point that doesn't change anything when applied. With
+            /// `from = "snapshot"` it is the same as `snapshot`.
             ///
             /// # Arguments
             ///
@@ -161,7 +766,7 @@ pub fn generate_struct_impl(
             ///
             /// This method checks if all fields are in their "no change" state:
             /// - Wrapped fields are `None`
-            /// - Unwrapped fields are `Default::default()`
+            /// - Unwrapped fields are at their default (`Default::default()` or `default = "..."`)
             /// - JSON fields are `None`
             /// - Nested fields are `None`
             ///
@@ -176,9 +781,10 @@ pub fn generate_struct_impl(
             /// ```
             pub fn is_empty(&self) -> bool {
                 #(if let Some(_) = &self.#wrapped_field_names { return false; })*
-                #(if self.#unwrapped_field_names != Default::default() { return false; })*
+                #(if self.#unwrapped_field_names != #unwrapped_field_defaults { return false; })*
                 #(if let Some(_) = &self.#json_field_names { return false; })*
                 #(if let Some(_) = &self.#nested_field_names { return false; })*
+                #(if !self.#keyed_vec_field_names.is_empty() { return false; })*
                 true
             }
 
@@ -186,7 +792,7 @@ pub fn generate_struct_impl(
             ///
             /// This method counts fields that would actually change something when applied:
             /// - Wrapped fields that are `Some(value)`
-            /// - Unwrapped fields that are not `Default::default()`
+            /// - Unwrapped fields that are not at their default
             /// - JSON fields that are `Some(value)`
             /// - Nested fields that are `Some(value)`
             ///
@@ -208,9 +814,10 @@ pub fn generate_struct_impl(
             pub fn field_count(&self) -> usize {
                 let mut count = 0;
                 #(if let Some(_) = &self.#wrapped_field_names { count += 1; })*
-                #(if self.#unwrapped_field_names != Default::default() { count += 1; })*
+                #(if self.#unwrapped_field_names != #unwrapped_field_defaults { count += 1; })*
                 #(if let Some(_) = &self.#json_field_names { count += 1; })*
                 #(if let Some(_) = &self.#nested_field_names { count += 1; })*
+                #(if !self.#keyed_vec_field_names.is_empty() { count += 1; })*
                 count
             }
 
@@ -218,7 +825,7 @@ pub fn generate_struct_impl(
             ///
             /// This method sets all fields to their "no change" state:
             /// - Wrapped fields become `None`
-            /// - Unwrapped fields become `Default::default()`
+            /// - Unwrapped fields are reset to their default
             /// - JSON fields become `None`
             /// - Nested fields become `None`
             ///
@@ -237,9 +844,10 @@ pub fn generate_struct_impl(
             /// ```
             pub fn clear(&mut self) {
                 #(self.#wrapped_field_names = None;)*
-                #(self.#unwrapped_field_names = Default::default();)*
+                #(self.#unwrapped_field_names = #unwrapped_field_defaults;)*
                 #(self.#json_field_names = None;)*
                 #(self.#nested_field_names = None;)*
+                #(self.#keyed_vec_field_names.clear();)*
             }
 
             /// Applies the updates to a target struct instance.
@@ -254,9 +862,9 @@ pub fn generate_struct_impl(
             /// # Behavior
             ///
             /// - **Wrapped fields**: Only applied if `Some(value)`
-            /// - **Unwrapped fields**: Only applied if not `Default::default()`
+            /// - **Unwrapped fields**: Only applied if not at their default
             /// - **JSON fields**: Deserialized and applied if `Some(value)`
-            /// - **Nested fields**: Recursively applied using their own `apply_to` method
+            /// - **Nested fields**: Recursively applied, after their guards are checked too
             ///
             /// # Examples
             ///
@@ -270,22 +878,81 @@ pub fn generate_struct_impl(
             /// update.apply_to(&mut user);
             /// // user.name is now "Bob", user.active remains false
             /// ```
-            pub fn apply_to(&self, target: &mut #struct_name) {
+            ///
+            /// # Errors
+            ///
+            /// Structs with a `#[substruct_field(version)]` or `expect` field, or with nested or
+            /// keyed fields whose guards may fail, return a `Result`. All guards, including
+            /// those of nested substructs and keyed element patches, are checked with
+            /// `check_guards` before anything is written:
+            /// - `VersionConflict` if `expected_version` is set and differs from the target's version
+            /// - `PreconditionFailed` if an `expect_<field>` is set and differs from the target's value
+            /// - `Nested` if a guard of a nested substruct or keyed element patch fails
+            ///
+            /// On success the target's version is incremented, unless the update is empty.
+            /// On failure nothing is written.
+            pub fn apply_to(&self, target: &mut #struct_name) #apply_return {
+                #apply_body
+            }
+
+            /// Checks every guard of the update against `target` without modifying it.
+            ///
+            /// Covers the `expected_version` and `expect_<field>` guards, and recursively those
+            /// of nested substructs and keyed element patches, each against the value it would
+            /// be applied to.
+            ///
+            /// # Errors
+            ///
+            /// The first guard that fails, as `apply_to` would report it.
+            pub fn check_guards(&self, target: &#struct_name) -> Result<(), #guard_error> {
+                #version_check
+                #(#expect_checks)*
+                #(#nested_guard_checks)*
+                #(#keyed_guard_checks)*
+                Ok(())
+            }
+
+            /// Applies the update like `apply_to`, without checking any guard.
+            ///
+            /// The version is still incremented for a non-empty update.
+            pub fn apply_unchecked(&self, target: &mut #struct_name) {
+                #normalized_self
+
                 // Apply primitive and JSON fields
-                #(if let Some(value) = &self.#wrapped_field_names {
+                #(if let Some(value) = &this.#wrapped_field_names {
                     target.#wrapped_field_names = value.clone();
                 })*
-                #(if self.#unwrapped_field_names != Default::default() {
-                    target.#unwrapped_field_names = self.#unwrapped_field_names.clone();
+                #(if this.#unwrapped_field_names != #unwrapped_field_defaults {
+                    target.#unwrapped_field_names = this.#unwrapped_field_names.clone();
                 })*
-                #(if let Some(value) = &self.#json_field_names {
+                #(if let Some(value) = &this.#json_field_names {
                     target.#json_field_names = serde_json::from_value(value.clone()).expect("Failed to deserialize JSON");
                 })*
 
                 // Apply nested fields recursively
-                #(if let Some(nested_update) = &self.#nested_field_names {
-                    nested_update.apply_to(&mut target.#nested_field_names);
+                #(#nested_apply_statements)*
+
+                // Apply keyed element operations in order
+                #(for op in &this.#keyed_vec_field_names {
+                    match op {
+                        #keyed_vec_op_types::Upsert(item) => {
+                            match target.#keyed_vec_field_names.iter_mut().find(|existing| existing.#keyed_vec_keys == item.#keyed_vec_keys) {
+                                Some(existing) => *existing = item.clone(),
+                                None => target.#keyed_vec_field_names.push(item.clone()),
+                            }
+                        }
+                        #keyed_vec_op_types::Remove(key) => {
+                            target.#keyed_vec_field_names.retain(|existing| &existing.#keyed_vec_keys != key);
+                        }
+                        #keyed_vec_op_types::Patch(key, patch) => {
+                            if let Some(existing) = target.#keyed_vec_field_names.iter_mut().find(|existing| &existing.#keyed_vec_keys == key) {
+                                patch.apply_unchecked(existing);
+                            }
+                        }
+                    }
                 })*
+
+                #version_bump
             }
 
             /// Checks if applying this update would modify the target struct.
@@ -305,7 +972,7 @@ pub fn generate_struct_impl(
             /// # Behavior
             ///
             /// - **Wrapped fields**: Compared if `Some(value)` and different from target
-            /// - **Unwrapped fields**: Compared if not `Default::default()` and different from target
+            /// - **Unwrapped fields**: Compared if not at their default and different from target
             /// - **JSON fields**: Serialized and compared if `Some(value)` and different from target
             /// - **Nested fields**: Recursively checked using their own `would_change` method
             ///
@@ -327,16 +994,18 @@ pub fn generate_struct_impl(
             /// assert!(!no_change.would_change(&user));  // No changes
             /// ```
             pub fn would_change(&self, target: &#struct_name) -> bool {
+                #normalized_self
+
                 // Check primitive and JSON fields
-                #(if let Some(value) = &self.#wrapped_field_names {
+                #(if let Some(value) = &this.#wrapped_field_names {
                     if value != &target.#wrapped_field_names {
                         return true;
                     }
                 })*
-                #(if self.#unwrapped_field_names != Default::default() && self.#unwrapped_field_names != target.#unwrapped_field_names {
+                #(if this.#unwrapped_field_names != #unwrapped_field_defaults && this.#unwrapped_field_names != target.#unwrapped_field_names {
                     return true;
                 })*
-                #(if let Some(value) = &self.#json_field_names {
+                #(if let Some(value) = &this.#json_field_names {
                     let original: serde_json::Value = serde_json::to_value(&target.#json_field_names).expect("Failed to serialize to JSON");
                     if !serde_json::Value::eq(value, &original) {
                         return true;
@@ -344,8 +1013,30 @@ pub fn generate_struct_impl(
                 })*
 
                 // Check nested fields recursively
-                #(if let Some(nested_update) = &self.#nested_field_names {
-                    if nested_update.would_change(&target.#nested_field_names) {
+                #(#nested_would_change_checks)*
+
+                // Replay keyed element operations on a copy and compare
+                #(if !this.#keyed_vec_field_names.is_empty() {
+                    let mut items = target.#keyed_vec_field_names.clone();
+                    for op in &this.#keyed_vec_field_names {
+                        match op {
+                            #keyed_vec_op_types::Upsert(item) => {
+                                match items.iter_mut().find(|existing| existing.#keyed_vec_keys == item.#keyed_vec_keys) {
+                                    Some(existing) => *existing = item.clone(),
+                                    None => items.push(item.clone()),
+                                }
+                            }
+                            #keyed_vec_op_types::Remove(key) => {
+                                items.retain(|existing| &existing.#keyed_vec_keys != key);
+                            }
+                            #keyed_vec_op_types::Patch(key, patch) => {
+                                if let Some(existing) = items.iter_mut().find(|existing| &existing.#keyed_vec_keys == key) {
+                                    patch.apply_unchecked(existing);
+                                }
+                            }
+                        }
+                    }
+                    if items != target.#keyed_vec_field_names {
                         return true;
                     }
                 })*
@@ -369,9 +1060,12 @@ pub fn generate_struct_impl(
             /// # Behavior
             ///
             /// - **Wrapped fields**: `other` value takes precedence if `Some`, otherwise uses `self`
-            /// - **Unwrapped fields**: `other` value takes precedence if not `Default::default()`, otherwise uses `self`
+            /// - **Unwrapped fields**: `other` value takes precedence if not at its default, otherwise uses `self`
             /// - **JSON fields**: `other` value takes precedence if `Some`, otherwise uses `self`
-            /// - **Nested fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+            /// - **Nested fields**: Merged recursively when both are set; a cleared optional
+            ///   nested field in `other` takes precedence
+            ///
+            /// Fields with a `transform` are normalized first.
             ///
             /// # Examples
             ///
@@ -389,16 +1083,24 @@ pub fn generate_struct_impl(
             /// let merged = update1.merge(update2);
             /// // merged has name: Some("Alice") and active: Some(true)
             /// ```
-            pub fn merge(self, other: Self) -> Self {
+            pub fn merge(#merge_params) -> Self {
+                #merge_normalize
                 Self {
                     #(#wrapped_field_names: other.#wrapped_field_names.or(self.#wrapped_field_names),)*
-                    #(#unwrapped_field_names: if other.#unwrapped_field_names != Default::default() {
+                    #(#unwrapped_field_names: if other.#unwrapped_field_names != #unwrapped_field_defaults {
                         other.#unwrapped_field_names
                     } else {
                         self.#unwrapped_field_names
                     },)*
                     #(#json_field_names: other.#json_field_names.or(self.#json_field_names),)*
-                    #(#nested_field_names: other.#nested_field_names.or(self.#nested_field_names),)*
+                    #(#nested_field_names: #nested_merges,)*
+                    #(#keyed_vec_field_names: {
+                        let mut ops = self.#keyed_vec_field_names;
+                        ops.extend(other.#keyed_vec_field_names);
+                        ops
+                    },)*
+                    #merge_version
+                    #(#expect_slot_names: other.#expect_slot_names.or(self.#expect_slot_names),)*
                 }
             }
 
@@ -418,9 +1120,10 @@ pub fn generate_struct_impl(
             /// # Behavior
             ///
             /// - **Wrapped fields**: Returns `true` if `Some(value)`
-            /// - **Unwrapped fields**: Returns `true` if not `Default::default()`
+            /// - **Unwrapped fields**: Returns `true` if not at their default
             /// - **JSON fields**: Returns `true` if `Some(value)`
             /// - **Nested fields**: Returns `true` if `Some(value)`
+            /// - **Nested paths**: `"address.city"` is checked recursively in the nested substruct
             /// - **Non-existent fields**: Returns `false`
             ///
             /// # Examples
@@ -436,11 +1139,20 @@ pub fn generate_struct_impl(
             /// assert!(!update.has_field("age"));    // age field doesn't exist in substruct
             /// ```
             pub fn has_field(&self, field_name: &str) -> bool {
+                if let Some((head, rest)) = field_name.split_once('.') {
+                    return match head {
+                        #(stringify!(#nested_field_names) => #nested_refs_self
+                            .is_some_and(|nested| nested.has_field(rest)),)*
+                        _ => false,
+                    };
+                }
+
                 match field_name {
                     #(stringify!(#wrapped_field_names) => self.#wrapped_field_names.is_some(),)*
-                    #(stringify!(#unwrapped_field_names) => self.#unwrapped_field_names != Default::default(),)*
+                    #(stringify!(#unwrapped_field_names) => self.#unwrapped_field_names != #unwrapped_field_defaults,)*
                     #(stringify!(#json_field_names) => self.#json_field_names.is_some(),)*
                     #(stringify!(#nested_field_names) => self.#nested_field_names.is_some(),)*
+                    #(stringify!(#keyed_vec_field_names) => !self.#keyed_vec_field_names.is_empty(),)*
                     _ => false,
                 }
             }
@@ -458,9 +1170,10 @@ pub fn generate_struct_impl(
             /// # Behavior
             ///
             /// - **Wrapped fields**: Included if `Some(value)`, formatted using `{:?}`
-            /// - **Unwrapped fields**: Included if not `Default::default()`, formatted using `{:?}`
+            /// - **Unwrapped fields**: Included if not at their default, formatted using `{:?}`
             /// - **JSON fields**: Included if `Some(value)`, converted using `.to_string()`
             /// - **Nested fields**: Recursively converted using their own `into_partial()` method
+            /// - **Sensitive fields**: Included as `"<redacted>"` instead of their value
             /// - **Fields without values**: Not included in the result
             ///
             /// # Examples
@@ -488,14 +1201,10 @@ pub fn generate_struct_impl(
                 let mut partial = std::collections::HashMap::new();
 
                 // Add wrapped fields
-                #(if let Some(value) = self.#wrapped_field_names {
-                    partial.insert(stringify!(#wrapped_field_names).to_string(), format!("{:?}", value));
-                })*
+                #(#wrapped_partial_inserts)*
 
                 // Add unwrapped fields (only if not default)
-                #(if self.#unwrapped_field_names != Default::default() {
-                    partial.insert(stringify!(#unwrapped_field_names).to_string(), format!("{:?}", &self.#unwrapped_field_names));
-                })*
+                #(#unwrapped_partial_inserts)*
 
                 // Add JSON fields
                 #(if let Some(value) = self.#json_field_names {
@@ -503,8 +1212,27 @@ pub fn generate_struct_impl(
                 })*
 
                 // Add nested fields (convert to partial recursively)
-                #(if let Some(nested) = self.#nested_field_names {
-                    partial.insert(stringify!(#nested_field_names).to_string(), format!("{:?}", nested.into_partial()));
+                #(#nested_partial_inserts)*
+
+                // Add keyed element operations (upserted elements and patches converted to
+                // partial recursively, so `sensitive` element fields are redacted)
+                #(if !self.#keyed_vec_field_names.is_empty() {
+                    let ops: Vec<String> = self
+                        .#keyed_vec_field_names
+                        .into_iter()
+                        .map(|op| match op {
+                            #keyed_vec_op_types::Upsert(item) => format!(
+                                "upsert {:?} {:?}",
+                                item.#keyed_vec_keys,
+                                #keyed_vec_nested_types::snapshot(&item).into_partial()
+                            ),
+                            #keyed_vec_op_types::Remove(key) => format!("remove {:?}", key),
+                            #keyed_vec_op_types::Patch(key, patch) => {
+                                format!("patch {:?} {:?}", key, patch.into_partial())
+                            }
+                        })
+                        .collect();
+                    partial.insert(stringify!(#keyed_vec_field_names).to_string(), format!("{:?}", ops));
                 })*
 
                 partial
@@ -513,48 +1241,4097 @@ pub fn generate_struct_impl(
     }
 }
 
-/// Generate the Default implementation
-pub fn generate_default_impl(
+/// Generate `field_mask` / `from_source_with_mask`, protobuf `FieldMask` interop
+pub fn generate_mask_impl(
     update_struct_name: &Ident,
+    struct_name: &Ident,
     context: &FieldContext,
 ) -> proc_macro2::TokenStream {
-    let field_names = &context.field_names;
+    if !context.mask_enabled {
+        return quote! {};
+    }
+
+    let error_name = format_ident!("{}MaskError", update_struct_name);
+    let wrapped_field_names = &context.wrapped_field_names;
+    let unwrapped_field_names = &context.unwrapped_field_names;
+    let unwrapped_field_defaults = &context.unwrapped_field_defaults;
+    let json_field_names = &context.json_field_names;
+    let keyed_vec_field_names = &context.keyed_vec_field_names;
+    let empty_fields = empty_initializers(context);
+    let source_version = guard_initializers(context, true);
+
+    // A `Vec`-wrapped nested substruct patches every element alike, so a source list
+    // can't be captured through a mask
+    let maskable_nested: Vec<_> = context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_types)
+        .zip(&context.nested_field_wrappers)
+        .filter(|(_, wrapper)| **wrapper != NestedWrapper::Vec)
+        .map(|((name, ty), wrapper)| (name, ty, wrapper))
+        .collect();
+    let maskable_nested_names: Vec<_> = maskable_nested.iter().map(|(name, ..)| *name).collect();
+    let maskable_nested_types: Vec<_> = maskable_nested.iter().map(|(_, ty, _)| *ty).collect();
+    let nested_masks: Vec<_> = maskable_nested
+        .iter()
+        .map(|(name, ty, wrapper)| {
+            let prefix = format!("{}.", name);
+            let masked = |source: proc_macro2::TokenStream| {
+                quote! {
+                    #ty::from_source_with_mask(#source, &paths).map_err(|err| #error_name {
+                        paths: err.paths.iter().map(|path| format!("{}{}", #prefix, path)).collect(),
+                    })?
+                }
+            };
+            let set = match wrapper {
+                // Paths into a missing optional value clear it
+                NestedWrapper::Option => {
+                    let masked = masked(quote! { nested });
+                    quote! {
+                        update.#name = Some(match source.#name.as_ref() {
+                            Some(nested) => Some(#masked),
+                            None => None,
+                        });
+                    }
+                }
+                _ => {
+                    let masked = masked(quote! { &source.#name });
+                    quote! { update.#name = Some(#masked); }
+                }
+            };
+            quote! {
+                let paths: Vec<&str> = mask
+                    .iter()
+                    .filter_map(|path| path.strip_prefix(#prefix))
+                    .collect();
+                // The whole field wins over paths inside it
+                if !paths.is_empty() && update.#name.is_none() {
+                    #set
+                }
+            }
+        })
+        .collect();
+    let nested_mask_paths: Vec<_> = context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+        .map(|(name, wrapper)| {
+            let nested_paths = quote! {
+                let nested_mask = nested.field_mask();
+                if nested_mask.is_empty() {
+                    mask.push(stringify!(#name).to_string());
+                } else {
+                    mask.extend(
+                        nested_mask
+                            .into_iter()
+                            .map(|path| format!("{}.{}", stringify!(#name), path)),
+                    );
+                }
+            };
+            match wrapper {
+                NestedWrapper::Option => quote! {
+                    match &self.#name {
+                        Some(Some(nested)) => { #nested_paths }
+                        Some(None) => mask.push(stringify!(#name).to_string()),
+                        None => {}
+                    }
+                },
+                _ => quote! {
+                    if let Some(nested) = &self.#name {
+                        #nested_paths
+                    }
+                },
+            }
+        })
+        .collect();
+
     quote! {
-        impl Default for #update_struct_name {
-            fn default() -> Self {
-                Self {
-                    #(#field_names: Default::default(),)*
+        /// Error returned by `from_source_with_mask` for paths that can't be patched.
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct #error_name {
+            /// Every rejected path, in mask order.
+            pub paths: Vec<String>,
+        }
+
+        impl std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "paths are not patchable: {}", self.paths.join(", "))
+            }
+        }
+
+        impl std::error::Error for #error_name {}
+
+        impl #update_struct_name {
+            /// Returns the dotted paths of the set fields, like a protobuf `FieldMask`.
+            ///
+            /// Nested substructs contribute their own paths (`"address.city"`); a nested
+            /// field that is set but empty, or cleared, contributes its own name. Keyed
+            /// collections with pending operations contribute their name.
+            pub fn field_mask(&self) -> Vec<String> {
+                #[allow(unused_mut)]
+                let mut mask = Vec::new();
+                #(if self.#wrapped_field_names.is_some() {
+                    mask.push(stringify!(#wrapped_field_names).to_string());
+                })*
+                #(if self.#unwrapped_field_names != #unwrapped_field_defaults {
+                    mask.push(stringify!(#unwrapped_field_names).to_string());
+                })*
+                #(if self.#json_field_names.is_some() {
+                    mask.push(stringify!(#json_field_names).to_string());
+                })*
+                #(#nested_mask_paths)*
+                #(if !self.#keyed_vec_field_names.is_empty() {
+                    mask.push(stringify!(#keyed_vec_field_names).to_string());
+                })*
+                mask
+            }
+
+            /// Whether `path` names a field that `from_source_with_mask` can copy.
+            pub fn is_patchable_path(path: &str) -> bool {
+                if let Some((head, rest)) = path.split_once('.') {
+                    return match head {
+                        #(stringify!(#maskable_nested_names) => #maskable_nested_types::is_patchable_path(rest),)*
+                        _ => false,
+                    };
+                }
+                [
+                    #(stringify!(#wrapped_field_names),)*
+                    #(stringify!(#unwrapped_field_names),)*
+                    #(stringify!(#json_field_names),)*
+                    #(stringify!(#maskable_nested_names),)*
+                    #(stringify!(#keyed_vec_field_names),)*
+                ]
+                .contains(&path)
+            }
+
+            /// Builds a substruct from a full message and a protobuf-style `FieldMask`.
+            ///
+            /// Each masked field is set to the source's value, as `snapshot` would; fields
+            /// outside the mask are left unchanged. Nested paths (`"address.city"`) descend
+            /// into nested substructs. An empty mask selects nothing.
+            ///
+            /// A masked keyed collection is additive: its elements are upserted, and elements
+            /// missing from the source are kept, as the target's keys aren't known here. Add
+            /// `Remove` operations for elements to delete.
+            ///
+            /// # Errors
+            ///
+            /// Returns every path that doesn't name a patchable field (unknown, skipped or
+            /// version fields, and `Vec`-wrapped nested fields).
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// let update = #update_struct_name::from_source_with_mask(&request.user, &["name", "address.city"])?;
+            /// update.apply_to(&mut stored);
+            /// ```
+            pub fn from_source_with_mask(
+                source: &#struct_name,
+                mask: &[&str],
+            ) -> Result<Self, #error_name> {
+                let rejected: Vec<String> = mask
+                    .iter()
+                    .filter(|path| !Self::is_patchable_path(path))
+                    .map(|path| path.to_string())
+                    .collect();
+                if !rejected.is_empty() {
+                    return Err(#error_name { paths: rejected });
+                }
+
+                let snapshot = Self::snapshot(source);
+                let mut update = Self {
+                    #(#empty_fields,)*
+                    #source_version
+                };
+                for path in mask {
+                    match *path {
+                        #(stringify!(#wrapped_field_names) => {
+                            update.#wrapped_field_names = snapshot.#wrapped_field_names.clone();
+                        })*
+                        #(stringify!(#unwrapped_field_names) => {
+                            update.#unwrapped_field_names = snapshot.#unwrapped_field_names.clone();
+                        })*
+                        #(stringify!(#json_field_names) => {
+                            update.#json_field_names = snapshot.#json_field_names.clone();
+                        })*
+                        #(stringify!(#maskable_nested_names) => {
+                            update.#maskable_nested_names = snapshot.#maskable_nested_names.clone();
+                        })*
+                        #(stringify!(#keyed_vec_field_names) => {
+                            update.#keyed_vec_field_names = snapshot.#keyed_vec_field_names.clone();
+                        })*
+                        _ => {}
+                    }
                 }
+                #(#nested_masks)*
+                Ok(update)
             }
         }
     }
 }
 
-/// Generate the From<T> implementation
-pub fn generate_from_impl(
+/// Generate the dotted-path accessors and their error type
+pub fn generate_path_impl(
     update_struct_name: &Ident,
-    struct_name: &Ident,
     context: &FieldContext,
 ) -> proc_macro2::TokenStream {
-    let field_names = &context.field_names;
+    if !context.paths_enabled {
+        return quote! {};
+    }
+
+    let error_name = format_ident!("{}PathError", update_struct_name);
+    let wrapped_field_names = &context.wrapped_field_names;
+    let unwrapped_field_names = &context.unwrapped_field_names;
+    let unwrapped_field_defaults = &context.unwrapped_field_defaults;
+    let json_field_names = &context.json_field_names;
+    let json_field_types: Vec<_> = context
+        .fields
+        .iter()
+        .filter(|processed| matches!(processed.kind, FieldKind::Json))
+        .map(|processed| &processed.field.ty)
+        .collect();
+    let nested_field_names = &context.nested_field_names;
+    let nested_error_names: Vec<_> = context
+        .nested_field_types
+        .iter()
+        .map(|ty| format_ident!("{}PathError", ty))
+        .collect();
+    let nested_refs_self = nested_refs(&quote! { self }, context);
+    let keyed_vec_field_names = &context.keyed_vec_field_names;
+    // Mutable slot for a nested path, creating the nested substruct when it isn't set
+    let nested_slots: Vec<_> = nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+        .map(|(name, wrapper)| match wrapper {
+            NestedWrapper::Option => quote! {
+                self.#name
+                    .get_or_insert_with(|| Some(Default::default()))
+                    .get_or_insert_with(Default::default)
+            },
+            _ => quote! { self.#name.get_or_insert_with(Default::default) },
+        })
+        .collect();
+
+    quote! {
+        /// Error returned by the dotted-path accessors of the generated substruct.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum #error_name {
+            /// No updatable field exists at the given path.
+            UnknownField { path: String },
+            /// The value could not be deserialized into the field's type.
+            TypeMismatch { path: String, message: String },
+        }
+
+        impl #error_name {
+            /// Returns the full path the error refers to.
+            pub fn path(&self) -> &str {
+                match self {
+                    Self::UnknownField { path } | Self::TypeMismatch { path, .. } => path,
+                }
+            }
+        }
+
+        impl std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::UnknownField { path } => write!(f, "unknown field path `{}`", path),
+                    Self::TypeMismatch { path, message } => {
+                        write!(f, "type mismatch at `{}`: {}", path, message)
+                    }
+                }
+            }
+        }
+
+        impl std::error::Error for #error_name {}
+
+        impl #update_struct_name {
+            /// Sets a field from a dotted path such as `"name"` or `"address.city"`.
+            ///
+            /// The value is deserialized into the field's concrete type; `json` fields keep the
+            /// JSON value but it must deserialize into the source type. Nested paths descend
+            /// into nested substructs, creating an empty one when the field is not yet set.
+            ///
+            /// # Errors
+            ///
+            /// - `UnknownField` if a path segment doesn't name an updatable field
+            /// - `TypeMismatch` if the value can't be deserialized into the field's type
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// let mut update = #update_struct_name::default();
+            /// update.set_by_path("address.city", serde_json::json!("NYC"))?;
+            /// assert!(update.has_field("address.city"));
+            /// ```
+            pub fn set_by_path(
+                &mut self,
+                path: &str,
+                value: serde_json::Value,
+            ) -> Result<(), #error_name> {
+                let type_mismatch = |err: serde_json::Error| #error_name::TypeMismatch {
+                    path: path.to_string(),
+                    message: err.to_string(),
+                };
+
+                if let Some((head, rest)) = path.split_once('.') {
+                    return match head {
+                        #(stringify!(#nested_field_names) => {
+                            let nested = #nested_slots;
+                            let result = nested.set_by_path(rest, value);
+                            if result.is_err() && nested.is_empty() {
+                                self.#nested_field_names = None;
+                            }
+                            result.map_err(|err| match err {
+                                #nested_error_names::UnknownField { path } => #error_name::UnknownField {
+                                    path: format!("{}.{}", head, path),
+                                },
+                                #nested_error_names::TypeMismatch { path, message } => #error_name::TypeMismatch {
+                                    path: format!("{}.{}", head, path),
+                                    message,
+                                },
+                            })
+                        })*
+                        _ => Err(#error_name::UnknownField { path: path.to_string() }),
+                    };
+                }
+
+                match path {
+                    #(stringify!(#wrapped_field_names) => {
+                        self.#wrapped_field_names = Some(serde_json::from_value(value).map_err(type_mismatch)?);
+                    })*
+                    #(stringify!(#unwrapped_field_names) => {
+                        self.#unwrapped_field_names = serde_json::from_value(value).map_err(type_mismatch)?;
+                    })*
+                    #(stringify!(#json_field_names) => {
+                        // Checked against the field's type so `apply_to` can't fail on it later
+                        serde_json::from_value::<#json_field_types>(value.clone()).map_err(type_mismatch)?;
+                        self.#json_field_names = Some(value);
+                    })*
+                    #(stringify!(#nested_field_names) => {
+                        self.#nested_field_names = Some(serde_json::from_value(value).map_err(type_mismatch)?);
+                    })*
+                    #(stringify!(#keyed_vec_field_names) => {
+                        self.#keyed_vec_field_names = serde_json::from_value(value).map_err(type_mismatch)?;
+                    })*
+                    _ => return Err(#error_name::UnknownField { path: path.to_string() }),
+                }
+                self.normalize();
+                Ok(())
+            }
+
+            /// Returns the value set at a dotted path as JSON, or `None` if it is not set.
+            ///
+            /// Fields in their "no change" state return `None`, matching `has_field`.
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// let update = #update_struct_name::new(Some("Alice".to_string()), None);
+            /// assert_eq!(update.get_by_path("name"), Some(serde_json::json!("Alice")));
+            /// assert_eq!(update.get_by_path("active"), None);
+            /// ```
+            pub fn get_by_path(&self, path: &str) -> Option<serde_json::Value> {
+                if let Some((head, rest)) = path.split_once('.') {
+                    return match head {
+                        #(stringify!(#nested_field_names) => #nested_refs_self
+                            .and_then(|nested| nested.get_by_path(rest)),)*
+                        _ => None,
+                    };
+                }
+
+                match path {
+                    #(stringify!(#wrapped_field_names) => self
+                        .#wrapped_field_names
+                        .as_ref()
+                        .and_then(|value| serde_json::to_value(value).ok()),)*
+                    #(stringify!(#unwrapped_field_names) => {
+                        if self.#unwrapped_field_names != #unwrapped_field_defaults {
+                            serde_json::to_value(&self.#unwrapped_field_names).ok()
+                        } else {
+                            None
+                        }
+                    })*
+                    #(stringify!(#json_field_names) => self.#json_field_names.clone(),)*
+                    #(stringify!(#nested_field_names) => self
+                        .#nested_field_names
+                        .as_ref()
+                        .and_then(|value| serde_json::to_value(value).ok()),)*
+                    #(stringify!(#keyed_vec_field_names) => {
+                        if self.#keyed_vec_field_names.is_empty() {
+                            None
+                        } else {
+                            serde_json::to_value(&self.#keyed_vec_field_names).ok()
+                        }
+                    })*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+/// Generate the `validate` / `try_apply_to` methods and their error type
+pub fn generate_validation_impl(
+    update_struct_name: &Ident,
+    struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    let error_name = format_ident!("{}ValidationErrors", update_struct_name);
+    let validation_checks = &context.validation_checks;
+    let normalized_self = normalized_self(context);
+    let nested_field_names = &context.nested_field_names;
+    let nested_refs_this = nested_refs(&quote! { this }, context);
+    let keyed_vec_field_names = &context.keyed_vec_field_names;
+    let keyed_vec_op_types = &context.keyed_vec_op_types;
+    // A failed guard is reported against the guarded field's path
+    let apply = if has_apply_error(context) {
+        quote! {
+            self.check_guards(target).map_err(|err| #error_name {
+                errors: vec![(err.field().to_string(), err.to_string())],
+            })?;
+            self.apply_unchecked(target);
+            Ok(())
+        }
+    } else {
+        quote! {
+            self.apply_to(target);
+            Ok(())
+        }
+    };
+
+    quote! {
+        /// All validation failures of a substruct, as `(field path, message)` pairs.
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct #error_name {
+            pub errors: Vec<(String, String)>,
+        }
+
+        impl #error_name {
+            /// Returns the number of failures.
+            pub fn len(&self) -> usize {
+                self.errors.len()
+            }
+
+            /// Returns `true` if there are no failures.
+            pub fn is_empty(&self) -> bool {
+                self.errors.is_empty()
+            }
+
+            /// Returns the messages reported for a given field path.
+            pub fn messages_for(&self, path: &str) -> Vec<&str> {
+                self.errors
+                    .iter()
+                    .filter(|(field, _)| field == path)
+                    .map(|(_, message)| message.as_str())
+                    .collect()
+            }
+        }
+
+        impl std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                for (index, (path, message)) in self.errors.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}: {}", path, message)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl std::error::Error for #error_name {}
+
+        impl #update_struct_name {
+            /// Validates every field that is set against its `validate(...)` rules.
+            ///
+            /// The rules check the values `apply_to` writes, after the `transform`/`transform_with`
+            /// normalizers. Fields in their "no change" state are not checked. Nested substructs are
+            /// validated recursively and their errors are prefixed with the field path.
+            /// All failures are collected rather than stopping at the first one.
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// let update = #update_struct_name::new(Some(200), None);
+            /// let errors = update.validate().unwrap_err();
+            /// assert_eq!(errors.messages_for("age"), vec!["must be at most 150"]);
+            /// ```
+            pub fn validate(&self) -> Result<(), #error_name> {
+                #normalized_self
+                #[allow(unused_mut)]
+                let mut errors: Vec<(String, String)> = Vec::new();
+
+                #(#validation_checks)*
+
+                #(if let Some(nested) = #nested_refs_this {
+                    if let Err(nested_errors) = nested.validate() {
+                        errors.extend(nested_errors.errors.into_iter().map(|(path, message)| {
+                            (format!("{}.{}", stringify!(#nested_field_names), path), message)
+                        }));
+                    }
+                })*
+
+                #(for op in &this.#keyed_vec_field_names {
+                    if let #keyed_vec_op_types::Patch(key, patch) = op {
+                        if let Err(nested_errors) = patch.validate() {
+                            errors.extend(nested_errors.errors.into_iter().map(|(path, message)| {
+                                (format!("{}[{:?}].{}", stringify!(#keyed_vec_field_names), key, path), message)
+                            }));
+                        }
+                    }
+                })*
+
+                if errors.is_empty() {
+                    Ok(())
+                } else {
+                    Err(#error_name { errors })
+                }
+            }
+
+            /// Validates the substruct and applies it to the target only if it is valid.
+            ///
+            /// The target is left untouched when validation or a guard fails. A failed guard,
+            /// including one of a nested substruct, is reported as an error on the guarded field.
+            pub fn try_apply_to(&self, target: &mut #struct_name) -> Result<(), #error_name> {
+                self.validate()?;
+                #apply
+            }
+        }
+    }
+}
+
+/// Generate `apply_with_report` / `report_changes` and the change set types
+pub fn generate_report_impl(
+    update_struct_name: &Ident,
+    struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if !context.report_enabled {
+        return quote! {};
+    }
+
+    let change_set = format_ident!("{}ChangeSet", update_struct_name);
+    let field_change = format_ident!("{}FieldChange", update_struct_name);
+    let normalized_self = normalized_self(context);
+
+    // Record `old` -> `new` at `path`, or a no-op when they are equal
+    let record = |path: proc_macro2::TokenStream| {
+        quote! {
+            if old == new {
+                changes.unchanged.push(#path);
+            } else {
+                changes.changed.push(#field_change {
+                    path: #path,
+                    old: serde_json::to_value(old).expect("Failed to serialize field to JSON"),
+                    new: serde_json::to_value(new).expect("Failed to serialize field to JSON"),
+                });
+            }
+        }
+    };
+    // Whole nested values are recorded with their sensitive fields redacted
+    let record_whole = |path: proc_macro2::TokenStream, nested_type: &Ident| {
+        quote! {
+            if old == new {
+                changes.unchanged.push(#path);
+            } else {
+                let mut old = serde_json::to_value(old).expect("Failed to serialize field to JSON");
+                let mut new = serde_json::to_value(new).expect("Failed to serialize field to JSON");
+                #nested_type::redact_json(&mut old);
+                #nested_type::redact_json(&mut new);
+                changes.changed.push(#field_change { path: #path, old, new });
+            }
+        }
+    };
+    // Sensitive fields report that they changed, but not their values
+    let record_redacted = |path: proc_macro2::TokenStream| {
+        quote! {
+            if old == new {
+                changes.unchanged.push(#path);
+            } else {
+                changes.changed.push(#field_change {
+                    path: #path,
+                    old: serde_json::Value::String("<redacted>".to_string()),
+                    new: serde_json::Value::String("<redacted>".to_string()),
+                });
+            }
+        }
+    };
+    let record_primitive = |name: &Ident| {
+        let path = quote! { stringify!(#name).to_string() };
+        if context.sensitive_field_names.contains(name) {
+            record_redacted(path)
+        } else {
+            record(path)
+        }
+    };
+    // Merge a nested change set, prefixing its paths
+    let extend_nested = |prefix: proc_macro2::TokenStream| {
+        quote! {
+            let prefix = #prefix;
+            changes.changed.extend(nested_changes.changed.into_iter().map(|change| #field_change {
+                path: format!("{}.{}", prefix, change.path),
+                old: change.old,
+                new: change.new,
+            }));
+            changes.unchanged.extend(
+                nested_changes.unchanged.into_iter().map(|path| format!("{}.{}", prefix, path)),
+            );
+        }
+    };
+
+    // Per field: when it is set in `this`, and how `old` / `new` are compared
+    let mut set_checks = Vec::new();
+    let mut names = Vec::new();
+    let mut bodies = Vec::new();
+
+    for name in &context.wrapped_field_names {
+        set_checks.push(quote! { this.#name.is_some() });
+        names.push(name.clone());
+        bodies.push(record_primitive(name));
+    }
+    for (name, default) in context
+        .unwrapped_field_names
+        .iter()
+        .zip(&context.unwrapped_field_defaults)
+    {
+        set_checks.push(quote! { this.#name != #default });
+        names.push(name.clone());
+        bodies.push(record_primitive(name));
+    }
+    for name in &context.json_field_names {
+        set_checks.push(quote! { this.#name.is_some() });
+        names.push(name.clone());
+        bodies.push(record(quote! { stringify!(#name).to_string() }));
+    }
+
+    for (((name, wrapper), nested_type), nested_ref) in context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+        .zip(&context.nested_field_types)
+        .zip(nested_refs(&quote! { this }, context))
+    {
+        let path = quote! { stringify!(#name).to_string() };
+        let extend = extend_nested(path.clone());
+        let body = match wrapper {
+            NestedWrapper::Vec => {
+                let extend = extend_nested(quote! { format!("{}[{}]", stringify!(#name), index) });
+                quote! {
+                    if let Some(nested_update) = #nested_ref {
+                        for (index, (old, new)) in old.iter().zip(new.iter()).enumerate() {
+                            let nested_changes = nested_update.report_changes(old, new);
+                            #extend
+                        }
+                    }
+                }
+            }
+            // Creating or clearing the value is reported as a whole
+            NestedWrapper::Option => {
+                let record = record_whole(path, nested_type);
+                quote! {
+                    match (#nested_ref, old, new) {
+                        (Some(nested_update), Some(old), Some(new)) => {
+                            let nested_changes = nested_update.report_changes(old, new);
+                            #extend
+                        }
+                        _ => {
+                            #record
+                        }
+                    }
+                }
+            }
+            _ => quote! {
+                if let Some(nested_update) = #nested_ref {
+                    let nested_changes = nested_update.report_changes(old, new);
+                    #extend
+                }
+            },
+        };
+        set_checks.push(quote! { this.#name.is_some() });
+        names.push(name.clone());
+        bodies.push(body);
+    }
+
+    // Keyed elements are reported once per key, as whole elements
+    for (((name, op_type), key), nested_type) in context
+        .keyed_vec_field_names
+        .iter()
+        .zip(&context.keyed_vec_op_types)
+        .zip(&context.keyed_vec_keys)
+        .zip(&context.keyed_vec_nested_types)
+    {
+        let record = record_whole(
+            quote! { format!("{}[{:?}]", stringify!(#name), key) },
+            nested_type,
+        );
+        set_checks.push(quote! { !this.#name.is_empty() });
+        names.push(name.clone());
+        bodies.push(quote! {
+            let mut keys = Vec::new();
+            for op in &this.#name {
+                let key = match op {
+                    #op_type::Upsert(item) => &item.#key,
+                    #op_type::Remove(key) | #op_type::Patch(key, _) => key,
+                };
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+            let (old_items, new_items) = (old, new);
+            for key in keys {
+                let old = &old_items.iter().find(|item| &item.#key == key);
+                let new = &new_items.iter().find(|item| &item.#key == key);
+                #record
+            }
+        });
+    }
+
+    let version_report = context.version_field.as_ref().map(|(version, _)| {
+        let record = record(quote! { stringify!(#version).to_string() });
+        quote! {
+            if old != new {
+                #record
+            }
+        }
+    });
+    let (version_snapshot, version_after, version_between) = match &context.version_field {
+        Some((version, _)) => (
+            quote! { let old_version = target.#version.clone(); },
+            quote! {
+                let (old, new) = (&old_version, &target.#version);
+                #version_report
+            },
+            quote! {
+                let (old, new) = (&before.#version, &after.#version);
+                #version_report
+            },
+        ),
+        None => Default::default(),
+    };
+
+    // Serialized fields to redact, and the nested substructs that redact their own
+    let redacted_keys: Vec<_> = context
+        .fields
+        .iter()
+        .filter(|processed| context.sensitive_field_names.contains(&processed.ident))
+        .map(|processed| &processed.wire_name)
+        .collect();
+    let (nested_keys, nested_redactors): (Vec<_>, Vec<_>) = context
+        .fields
+        .iter()
+        .filter_map(|processed| {
+            let nested_type = match (&processed.nested, &processed.keyed) {
+                (Some((_, nested_type)), _) => nested_type,
+                (_, Some(keyed)) => &keyed.nested_type,
+                _ => return None,
+            };
+            Some((&processed.wire_name, nested_type))
+        })
+        .unzip();
+
+    let snapshots: Vec<_> = names
+        .iter()
+        .map(|name| format_ident!("old_{}", name))
+        .collect();
+    let (apply_return, apply_call, report_return) = if has_apply_error(context) {
+        let apply_error = format_ident!("{}ApplyError", update_struct_name);
+        (
+            quote! { Result<#change_set, #apply_error> },
+            quote! { self.apply_to(target)?; },
+            quote! { Ok(changes) },
+        )
+    } else {
+        (
+            quote! { #change_set },
+            quote! { self.apply_to(target); },
+            quote! { changes },
+        )
+    };
+
+    quote! {
+        /// One modified field: its path and its old and new values as JSON.
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct #field_change {
+            pub path: String,
+            pub old: serde_json::Value,
+            pub new: serde_json::Value,
+        }
+
+        /// The fields an update modified, and the ones it set to their current value.
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct #change_set {
+            /// Fields whose value changed.
+            pub changed: Vec<#field_change>,
+            /// Paths of fields that were set to the value they already had.
+            pub unchanged: Vec<String>,
+        }
+
+        impl #change_set {
+            /// Returns `true` if nothing was modified.
+            pub fn is_empty(&self) -> bool {
+                self.changed.is_empty()
+            }
+
+            /// Returns the paths of the modified fields.
+            pub fn changed_paths(&self) -> Vec<&str> {
+                self.changed.iter().map(|change| change.path.as_str()).collect()
+            }
+
+            /// Returns the change recorded for a path, if it was modified.
+            pub fn get(&self, path: &str) -> Option<&#field_change> {
+                self.changed.iter().find(|change| change.path == path)
+            }
+        }
+
+        impl #update_struct_name {
+            /// Applies the update and reports which fields it actually modified.
+            ///
+            /// Nested substructs are reported field by field (`address.city`), keyed
+            /// elements once per key (`items["a"]`). Fields set to their current value
+            /// are listed as no-ops in `unchanged`.
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// let changes = update.apply_with_report(&mut user);
+            /// assert_eq!(changes.changed_paths(), vec!["name", "address.city"]);
+            /// ```
+            pub fn apply_with_report(&self, target: &mut #struct_name) -> #apply_return {
+                #normalized_self
+
+                // Snapshot only the fields this update touches
+                #(let #snapshots = if #set_checks { Some(target.#names.clone()) } else { None };)*
+                #version_snapshot
+
+                #apply_call
+
+                #[allow(unused_mut)]
+                let mut changes = #change_set::default();
+                #(if let Some(old) = &#snapshots {
+                    let new = &target.#names;
+                    #bodies
+                })*
+                #version_after
+                #report_return
+            }
+
+            /// Replaces the `sensitive` fields of a serialized source value with `"<redacted>"`.
+            ///
+            /// Descends into nested values, arrays and keyed elements. Change reports use it
+            /// for values recorded as a whole, such as a created `Option` value or a keyed element.
+            pub fn redact_json(value: &mut serde_json::Value) {
+                match value {
+                    serde_json::Value::Object(object) => {
+                        #(if let Some(field) = object.get_mut(#redacted_keys) {
+                            *field = serde_json::Value::String("<redacted>".to_string());
+                        })*
+                        #(if let Some(field) = object.get_mut(#nested_keys) {
+                            #nested_redactors::redact_json(field);
+                        })*
+                    }
+                    serde_json::Value::Array(items) => items.iter_mut().for_each(Self::redact_json),
+                    _ => {}
+                }
+            }
+
+            /// Reports the fields of this update that differ between two states of the target.
+            ///
+            /// Only fields set in this update are compared, so calling it with the target
+            /// before and after `apply_to` yields the same report as `apply_with_report`.
+            pub fn report_changes(&self, before: &#struct_name, after: &#struct_name) -> #change_set {
+                #normalized_self
+
+                #[allow(unused_mut)]
+                let mut changes = #change_set::default();
+                #(if #set_checks {
+                    let (old, new) = (&before.#names, &after.#names);
+                    #bodies
+                })*
+                #version_between
+                changes
+            }
+        }
+    }
+}
+
+/// Generate the keyed element operation enums and their builder methods
+pub fn generate_keyed_vec_impl(
+    update_struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    let definitions = context
+        .keyed_vec_field_names
+        .iter()
+        .zip(&context.keyed_vec_op_types)
+        .zip(&context.keyed_vec_key_types)
+        .zip(&context.keyed_vec_item_types)
+        .zip(&context.keyed_vec_nested_types)
+        .zip(&context.keyed_vec_keys)
+        .map(|(((((name, op_type), key_type), item_type), nested_type), key)| {
+            let upsert = format_ident!("upsert_{}", name);
+            let remove = format_ident!("remove_{}", name);
+            let patch = format_ident!("patch_{}", name);
+            quote! {
+                /// A keyed operation on one element of the `#name` collection.
+                #[derive(Clone, Serialize, Deserialize)]
+                #[serde(rename_all = "snake_case")]
+                #[allow(private_interfaces)]
+                pub enum #op_type {
+                    /// Replaces the element with the same key, or appends it.
+                    Upsert(#item_type),
+                    /// Removes the element with this key.
+                    Remove(#key_type),
+                    /// Patches the element with this key; missing keys are ignored.
+                    Patch(#key_type, #nested_type),
+                }
+
+                // Upserted elements are printed as their snapshot, whose `Debug` redacts
+                // `sensitive` fields, after their key
+                impl std::fmt::Debug for #op_type {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        match self {
+                            Self::Upsert(item) => f
+                                .debug_tuple("Upsert")
+                                .field(&item.#key)
+                                .field(&#nested_type::snapshot(item))
+                                .finish(),
+                            Self::Remove(key) => f.debug_tuple("Remove").field(key).finish(),
+                            Self::Patch(key, patch) => {
+                                f.debug_tuple("Patch").field(key).field(patch).finish()
+                            }
+                        }
+                    }
+                }
+
+                impl #update_struct_name {
+                    /// Queues an upsert of a whole element, matched by key.
+                    pub fn #upsert(mut self, item: #item_type) -> Self {
+                        self.#name.push(#op_type::Upsert(item));
+                        self
+                    }
+
+                    /// Queues the removal of the element with the given key.
+                    pub fn #remove(mut self, key: impl Into<#key_type>) -> Self {
+                        self.#name.push(#op_type::Remove(key.into()));
+                        self
+                    }
+
+                    /// Queues a patch of the element with the given key.
+                    pub fn #patch(mut self, key: impl Into<#key_type>, patch: #nested_type) -> Self {
+                        self.#name.push(#op_type::Patch(key.into(), patch));
+                        self
+                    }
+                }
+            }
+        });
+
+    quote! { #(#definitions)* }
+}
+
+/// Generate the guard error type and the `expected_version` / `expect_<field>` builders
+pub fn generate_guard_impl(
+    update_struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if !has_apply_error(context) {
+        return quote! {};
+    }
+    let error_name = format_ident!("{}ApplyError", update_struct_name);
+
+    let version_field = &context.version_field;
+    let version_variant = version_field.as_ref().map(|(_, version_type)| {
+        quote! {
+            /// The target's version differs from `expected_version`.
+            VersionConflict {
+                expected: #version_type,
+                found: #version_type,
+            },
+        }
+    });
+    let version_name = version_field
+        .as_ref()
+        .map(|(version, _)| quote! { Self::VersionConflict { .. } => stringify!(#version), });
+    let version_display = version_field.as_ref().map(|(version, _)| {
+        quote! {
+            Self::VersionConflict { expected, found } => write!(
+                f,
+                "version conflict on `{}`: expected {:?}, found {:?}",
+                stringify!(#version),
+                expected,
+                found
+            ),
+        }
+    });
+    let version_builder = version_field.as_ref().map(|(_, version_type)| {
+        quote! {
+            /// Guards the update: `apply_to` fails unless the target is at this version.
+            pub fn with_expected_version(mut self, version: #version_type) -> Self {
+                self.expected_version = Some(version);
+                self
+            }
+        }
+    });
+
+    let has_preconditions = !context.expect_slot_names.is_empty();
+    let precondition_variant = has_preconditions.then(|| {
+        quote! {
+            /// The target's value of `field` differs from its `expect_<field>` precondition.
+            PreconditionFailed {
+                field: &'static str,
+                expected: String,
+                found: String,
+            },
+        }
+    });
+    let precondition_name =
+        has_preconditions.then(|| quote! { Self::PreconditionFailed { field, .. } => field, });
+    let precondition_display = has_preconditions.then(|| {
+        quote! {
+            Self::PreconditionFailed { field, expected, found } => write!(
+                f,
+                "precondition failed on `{}`: expected {}, found {}",
+                field, expected, found
+            ),
+        }
+    });
+
+    let has_nested =
+        !context.nested_field_names.is_empty() || !context.keyed_vec_field_names.is_empty();
+    let nested_variant = has_nested.then(|| {
+        quote! {
+            /// A guard of the nested substruct or keyed element patch at `path` failed.
+            Nested {
+                path: String,
+                message: String,
+            },
+        }
+    });
+    let nested_name = has_nested.then(|| quote! { Self::Nested { path, .. } => path, });
+    let nested_display = has_nested.then(|| {
+        quote! {
+            Self::Nested { path, message } => write!(f, "guard failed in `{}`: {}", path, message),
+        }
+    });
+
+    let expect_builders = context
+        .expect_slot_names
+        .iter()
+        .zip(&context.expect_field_types)
+        .map(|(slot, ty)| {
+            quote! {
+                /// Guards the update: `apply_to` fails unless the target's field has this value.
+                pub fn #slot(mut self, value: #ty) -> Self {
+                    self.#slot = Some(value);
+                    self
+                }
+            }
+        });
+
+    quote! {
+        /// Error returned by `apply_to` when a guard on the target fails.
+        ///
+        /// Guards are checked before anything is written, so the target is unchanged.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum #error_name {
+            #version_variant
+            #precondition_variant
+            #nested_variant
+        }
+
+        impl #error_name {
+            /// Returns the name of the guarded field that failed, or the path of the nested one.
+            pub fn field(&self) -> &str {
+                match self {
+                    #version_name
+                    #precondition_name
+                    #nested_name
+                }
+            }
+        }
+
+        impl std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    #version_display
+                    #precondition_display
+                    #nested_display
+                }
+            }
+        }
+
+        impl std::error::Error for #error_name {}
+
+        impl #update_struct_name {
+            #version_builder
+            #(#expect_builders)*
+        }
+    }
+}
+
+/// Generate the Default implementation
+pub fn generate_default_impl(
+    update_struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    let empty_fields = empty_initializers(context);
+    let guard_defaults = guard_initializers(context, false);
+    quote! {
+        impl Default for #update_struct_name {
+            fn default() -> Self {
+                Self {
+                    #(#empty_fields,)*
+                    #guard_defaults
+                }
+            }
+        }
+    }
+}
+
+/// Generate the From<T> implementation
+pub fn generate_from_impl(
+    update_struct_name: &Ident,
+    struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    let body = match context.from_policy {
+        FromPolicy::Empty => {
+            let empty_fields = empty_initializers(context);
+            let source_version = guard_initializers(context, true);
+            quote! {
+                Self {
+                    #(#empty_fields,)*
+                    #source_version
+                }
+            }
+        }
+        FromPolicy::Snapshot => quote! { Self::snapshot(&source) },
+    };
+    quote! {
+        impl From<#struct_name> for #update_struct_name {
+            fn from(source: #struct_name) -> Self {
+                #body
+            }
+        }
+    }
+}
+
+/// Validate the context and ensure it's ready for code generation
+pub fn validate_context(context: &FieldContext, struct_name: &Ident) {
+    if context.updatable_fields.is_empty() {
+        abort!(
+            struct_name,
+            "No fields are tagged with #[substruct_field]. At least one field must be tagged to generate a substruct."
+        );
+    }
+}
+
+/// The `T` of an `Option<T>` type
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(TypePath { path, .. }) = ty else {
+        return None;
+    };
+    let segment = path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(args) => match args.args.first() {
+            Some(GenericArgument::Type(inner)) => Some(inner),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// The type a primitive field's raw value is parsed as, and how a parsed `value` is stored
+///
+/// Mirrors the update type: `Option<Option<T>>`, `Option<T>` or `T`.
+fn primitive_target(ty: &Type, option: bool) -> (&Type, proc_macro2::TokenStream) {
+    match option_inner(ty) {
+        Some(inner) if option => (inner, quote! { Some(Some(value)) }),
+        Some(inner) => (inner, quote! { Some(value) }),
+        None if option => (ty, quote! { Some(value) }),
+        None => (ty, quote! { value }),
+    }
+}
+
+/// The variable a field is read from: `env = "..."` verbatim, or `PREFIX_FIELD`
+fn env_key(ident: &Ident, env: Option<&str>) -> proc_macro2::TokenStream {
+    match env {
+        Some(name) => quote! { #name.to_string() },
+        None => {
+            let segment = ident.to_string().trim_start_matches("r#").to_uppercase();
+            quote! {
+                if prefix.is_empty() {
+                    #segment.to_string()
+                } else {
+                    format!("{}_{}", prefix, #segment)
+                }
+            }
+        }
+    }
+}
+
+/// Generate the statements loading one field in `from_env_with`
+///
+/// Primitive fields are parsed with `FromStr`, JSON fields with `serde_json`, and
+/// nested substructs are loaded recursively from `KEY__FIELD` variables. Every
+/// failure is pushed to `errors` as `(variable, message)`.
+fn env_load(processed: &ProcessedField) -> Option<proc_macro2::TokenStream> {
+    let ident = &processed.ident;
+    let span = processed.field.span();
+    let ty = &processed.field.ty;
+    let key = env_key(ident, processed.options.env.as_deref());
+
+    let load = match &processed.kind {
+        FieldKind::Primitive {
+            option, sensitive, ..
+        } => {
+            let (parse_ty, set) = primitive_target(ty, *option);
+            // Parse errors may echo the rejected input
+            let message = if *sensitive {
+                quote! { "invalid value".to_string() }
+            } else {
+                quote! { err.to_string() }
+            };
+            quote_spanned! {span=>
+                if let Some(raw) = lookup(&key) {
+                    match raw.parse::<#parse_ty>() {
+                        Ok(value) => update.#ident = #set,
+                        #[allow(unused_variables)]
+                        Err(err) => errors.push((key, #message)),
+                    }
+                }
+            }
+        }
+        FieldKind::Json => quote_spanned! {span=>
+            if let Some(raw) = lookup(&key) {
+                match serde_json::from_str::<#ty>(&raw) {
+                    Ok(value) => {
+                        update.#ident = Some(
+                            serde_json::to_value(&value).expect("Failed to serialize field to JSON"),
+                        );
+                    }
+                    Err(err) => errors.push((key, err.to_string())),
+                }
+            }
+        },
+        FieldKind::Nested { .. } => {
+            let (wrapper, nested_type) = processed.nested.as_ref()?;
+            let set = match wrapper {
+                NestedWrapper::Option => quote! { Some(Some(nested)) },
+                _ => quote! { Some(nested) },
+            };
+            quote_spanned! {span=>
+                match #nested_type::from_env_with(&format!("{}_", key), &lookup) {
+                    Ok(nested) => {
+                        if !nested.is_empty() {
+                            update.#ident = #set;
+                        }
+                    }
+                    Err(err) => errors.extend(err.errors),
+                }
+            }
+        }
+        _ => return None,
+    };
+
+    Some(quote! {
+        {
+            let key = #key;
+            #load
+        }
+    })
+}
+
+/// Generate `from_env` / `from_env_with` and their error type
+pub fn generate_env_impl(
+    update_struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if !context.env_enabled {
+        return quote! {};
+    }
+
+    let error_name = format_ident!("{}EnvError", update_struct_name);
+    let env_loads: Vec<_> = context.fields.iter().filter_map(env_load).collect();
+
+    quote! {
+        /// Environment variables that could not be parsed, as `(variable, message)` pairs.
+        #[derive(Debug, Clone, PartialEq, Default)]
+        pub struct #error_name {
+            pub errors: Vec<(String, String)>,
+        }
+
+        impl #error_name {
+            /// Returns the names of the variables that failed to parse.
+            pub fn variables(&self) -> Vec<&str> {
+                self.errors.iter().map(|(variable, _)| variable.as_str()).collect()
+            }
+        }
+
+        impl std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                for (index, (variable, message)) in self.errors.iter().enumerate() {
+                    if index > 0 {
+                        write!(f, "; ")?;
+                    }
+                    write!(f, "{}: {}", variable, message)?;
+                }
+                Ok(())
+            }
+        }
+
+        impl std::error::Error for #error_name {}
+
+        impl #update_struct_name {
+            /// Loads an update from environment variables, e.g. for config overlays.
+            ///
+            /// Each field is read from `PREFIX_FIELD` (or its `env = "..."` name) and parsed
+            /// with `FromStr`; JSON fields are parsed with `serde_json`. Nested substructs
+            /// are read from `PREFIX_FIELD__NESTED`. Unset variables leave the field unchanged.
+            ///
+            /// # Errors
+            ///
+            /// Returns every variable that failed to parse, not just the first one.
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// // APP_PORT=8080 APP_DATABASE__HOST=db.internal
+            /// let overrides = #update_struct_name::from_env("APP")?;
+            /// overrides.apply_to(&mut config);
+            /// ```
+            pub fn from_env(prefix: &str) -> Result<Self, #error_name> {
+                Self::from_env_with(prefix, |key| std::env::var(key).ok())
+            }
+
+            /// Like `from_env`, but reads variables through `lookup` instead of the process
+            /// environment.
+            pub fn from_env_with(
+                prefix: &str,
+                lookup: impl Fn(&str) -> Option<String>,
+            ) -> Result<Self, #error_name> {
+                #[allow(unused_mut)]
+                let mut update = Self::default();
+                #[allow(unused_mut)]
+                let mut errors: Vec<(String, String)> = Vec::new();
+                #(#env_loads)*
+                if !errors.is_empty() {
+                    return Err(#error_name { errors });
+                }
+                update.normalize();
+                Ok(update)
+            }
+        }
+    }
+}
+
+/// Generate the GraphQL input object fields for one field and their conversions
+///
+/// Returns `(field, conversion)` pairs: `field` declares an input object field, with the
+/// source field's doc comments as its description, and `conversion` copies it from `input`
+/// into `update`. Keyed collections take a list of `graphql_keyed_inputs` operations.
+fn graphql_fields(
+    processed: &ProcessedField,
+) -> Vec<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+    let ident = &processed.ident;
+    let kind = &processed.kind;
+    let ty = &processed.field.ty;
+    let docs: Vec<_> = processed
+        .field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("doc"))
+        .collect();
+    let maybe_undefined = |value: proc_macro2::TokenStream| {
+        quote! {
+            update.#ident = match input.#ident {
+                ::async_graphql::MaybeUndefined::Undefined => None,
+                ::async_graphql::MaybeUndefined::Null => Some(None),
+                ::async_graphql::MaybeUndefined::Value(value) => Some(Some(#value)),
+            };
+        }
+    };
+
+    let (input_ty, conversion) = match kind {
+        FieldKind::Primitive { option: false, .. } => (
+            quote! { Option<#ty> },
+            quote! {
+                if let Some(value) = input.#ident {
+                    update.#ident = value;
+                }
+            },
+        ),
+        // `null` clears an `Option<T>` field, leaving it out changes nothing
+        FieldKind::Primitive { .. } if option_inner(ty).is_some() => {
+            let inner = option_inner(ty).unwrap_or(ty);
+            (
+                quote! { ::async_graphql::MaybeUndefined<#inner> },
+                maybe_undefined(quote! { value }),
+            )
+        }
+        FieldKind::Primitive { .. } => (
+            quote! { Option<#ty> },
+            quote! { update.#ident = input.#ident; },
+        ),
+        FieldKind::Json => (
+            quote! { Option<::async_graphql::Json<#ty>> },
+            quote! {
+                update.#ident = input.#ident.map(|json| {
+                    serde_json::to_value(json.0).expect("Failed to serialize to JSON")
+                });
+            },
+        ),
+        FieldKind::Nested { .. } => {
+            let Some((wrapper, nested_type)) = &processed.nested else {
+                return Vec::new();
+            };
+            let nested_input = format_ident!("{}Input", nested_type);
+            match wrapper {
+                NestedWrapper::Option => (
+                    quote! { ::async_graphql::MaybeUndefined<#nested_input> },
+                    maybe_undefined(quote! { value.into() }),
+                ),
+                _ => (
+                    quote! { Option<#nested_input> },
+                    quote! { update.#ident = input.#ident.map(Into::into); },
+                ),
+            }
+        }
+        // Omitting the list queues no operations
+        FieldKind::NestedVec { .. } => {
+            let Some(keyed) = &processed.keyed else {
+                return Vec::new();
+            };
+            let op_type = &keyed.op_type;
+            let op_input = format_ident!("{}Input", op_type);
+            (
+                quote! { Option<Vec<#op_input>> },
+                quote! {
+                    update.#ident = input
+                        .#ident
+                        .unwrap_or_default()
+                        .into_iter()
+                        .map(|op| match op {
+                            #op_input::Upsert(item) => #op_type::Upsert(item.0),
+                            #op_input::Remove(key) => #op_type::Remove(key),
+                            #op_input::Patch(patch) => #op_type::Patch(patch.key, patch.patch.into()),
+                        })
+                        .collect();
+                },
+            )
+        }
+        FieldKind::Version => {
+            return vec![(
+                quote! {
+                    /// Version the target must be at for the update to apply.
+                    pub expected_version: Option<#ty>
+                },
+                quote! { update.expected_version = input.expected_version; },
+            )];
+        }
+        _ => return Vec::new(),
+    };
+
+    let mut fields = vec![(
+        quote! {
+            #(#docs)*
+            pub #ident: #input_ty
+        },
+        conversion,
+    )];
+    if let FieldKind::Primitive { expect: true, .. } = kind {
+        let slot = format_ident!("expect_{}", ident);
+        let description = format!(" Value `{}` must have for the update to apply.", ident);
+        fields.push((
+            quote! {
+                #[doc = #description]
+                pub #slot: Option<#ty>
+            },
+            quote! { update.#slot = input.#slot; },
+        ));
+    }
+    fields
+}
+
+/// Generate the input types of one keyed collection's operations
+///
+/// GraphQL has no input unions, so an operation is a `OneofObject`: exactly one of
+/// `upsert` (the whole element, as a `Json` scalar), `remove` (a key) or `patch`.
+fn graphql_keyed_inputs(processed: &ProcessedField) -> Option<proc_macro2::TokenStream> {
+    let keyed = processed.keyed.as_ref()?;
+    let op_type = &keyed.op_type;
+    let key_type = &keyed.key_type;
+    let item_type = &keyed.item_type;
+    let nested_input = format_ident!("{}Input", keyed.nested_type);
+    let op_input = format_ident!("{}Input", op_type);
+    let patch_input = format_ident!("{}PatchInput", op_type);
+    let description = format!(
+        " One operation on `{}`, matching elements by `{}`.",
+        processed.ident, keyed.key
+    );
+
+    Some(quote! {
+        #[doc = #description]
+        #[derive(Clone, ::async_graphql::OneofObject)]
+        pub enum #op_input {
+            /// Inserts the element, or replaces the one with the same key.
+            Upsert(::async_graphql::Json<#item_type>),
+            /// Removes the element with this key.
+            Remove(#key_type),
+            /// Patches the element with this key, if there is one.
+            Patch(#patch_input),
+        }
+
+        /// A patch of the element with `key`.
+        #[derive(Clone, ::async_graphql::InputObject)]
+        pub struct #patch_input {
+            pub key: #key_type,
+            pub patch: #nested_input,
+        }
+    })
+}
+
+/// Generate the `async_graphql::InputObject` of the substruct and its conversion
+pub fn generate_graphql_impl(
+    update_struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if !context.graphql_enabled {
+        return quote! {};
+    }
+
+    let input_name = format_ident!("{}Input", update_struct_name);
+    let graphql_docs = &context.graphql_docs;
+    let (graphql_fields, graphql_conversions): (Vec<_>, Vec<_>) =
+        context.fields.iter().flat_map(graphql_fields).unzip();
+    let keyed_inputs = context.fields.iter().filter_map(graphql_keyed_inputs);
+
+    quote! {
+        #(#keyed_inputs)*
+
+        #(#graphql_docs)*
+        #[derive(Clone, ::async_graphql::InputObject)]
+        pub struct #input_name {
+            #(#graphql_fields,)*
+        }
+
+        impl From<#input_name> for #update_struct_name {
+            fn from(input: #input_name) -> Self {
+                #[allow(unused_mut)]
+                let mut update = Self::default();
+                #(#graphql_conversions)*
+                update
+            }
+        }
+    }
+}
+
+/// Generate the statements writing one field, by its serialized `name`, into the single
+/// update document of `write_update_document`
+fn document_write(processed: &ProcessedField) -> Option<proc_macro2::TokenStream> {
+    let ident = &processed.ident;
+    let field = &processed.field;
+    let name = processed.wire_name.as_str();
+    let path = quote! { format!("{}{}", prefix, #name) };
+    let set = |value: proc_macro2::TokenStream| {
+        quote! {
+            operator(document, "$set").insert(#path, #value);
+        }
+    };
+    let unset = quote! {
+        operator(document, "$unset").insert(#path, serde_json::Value::String(String::new()));
+    };
+    let serialized = quote! { serde_json::to_value(value).expect("Failed to serialize to JSON") };
+
+    let write = match &processed.kind {
+        FieldKind::Primitive { option: false, .. } => {
+            let default = processed.no_change.as_ref()?;
+            let set = set(quote! {
+                serde_json::to_value(&self.#ident).expect("Failed to serialize to JSON")
+            });
+            quote! {
+                if self.#ident != #default {
+                    #set
+                }
+            }
+        }
+        // Clearing an `Option<T>` field removes it from the document
+        FieldKind::Primitive { .. } if option_inner(&field.ty).is_some() => {
+            let set = set(serialized);
+            quote! {
+                match &self.#ident {
+                    Some(Some(value)) => { #set }
+                    Some(None) => { #unset }
+                    None => {}
+                }
+            }
+        }
+        FieldKind::Primitive { .. } => {
+            let set = set(serialized);
+            quote! {
+                if let Some(value) = &self.#ident {
+                    #set
+                }
+            }
+        }
+        FieldKind::Json => {
+            let set = set(quote! { value.clone() });
+            quote! {
+                if let Some(value) = &self.#ident {
+                    #set
+                }
+            }
+        }
+        FieldKind::Nested { .. } => match processed.nested.as_ref()?.0 {
+            NestedWrapper::Option => quote! {
+                match &self.#ident {
+                    Some(Some(nested)) => {
+                        nested.write_update_document(
+                            &format!("{}{}.", prefix, #name),
+                            document,
+                            array_filters,
+                            identifiers,
+                        );
+                    }
+                    Some(None) => { #unset }
+                    None => {}
+                }
+            },
+            // Every element is patched, through an array filter that matches them all
+            NestedWrapper::Vec => quote! {
+                if let Some(nested) = &self.#ident {
+                    let identifier = format!("elem{}", *identifiers);
+                    *identifiers += 1;
+                    array_filters.push(serde_json::json!({ identifier.clone(): { "$type": "object" } }));
+                    nested.write_update_document(
+                        &format!("{}{}.$[{}].", prefix, #name, identifier),
+                        document,
+                        array_filters,
+                        identifiers,
+                    );
+                }
+            },
+            _ => quote! {
+                if let Some(nested) = &self.#ident {
+                    nested.write_update_document(
+                        &format!("{}{}.", prefix, #name),
+                        document,
+                        array_filters,
+                        identifiers,
+                    );
+                }
+            },
+        },
+        // Upserts are pushed, removed keys pulled and patches address their element through
+        // an array filter on its key
+        FieldKind::NestedVec { .. } => {
+            let keyed = processed.keyed.as_ref()?;
+            let op_type = &keyed.op_type;
+            let key = keyed.key.to_string();
+            quote! {
+                for op in &self.#ident {
+                    let path = #path;
+                    match op {
+                        #op_type::Upsert(item) => {
+                            let push = operator(document, "$push")
+                                .entry(path)
+                                .or_insert_with(|| serde_json::json!({ "$each": [] }));
+                            if let Some(serde_json::Value::Array(items)) = push.get_mut("$each") {
+                                items.push(serde_json::to_value(item).expect("Failed to serialize to JSON"));
+                            }
+                        }
+                        #op_type::Remove(key) => {
+                            let pull = operator(document, "$pull")
+                                .entry(path)
+                                .or_insert_with(|| serde_json::json!({ #key: { "$in": [] } }));
+                            if let Some(serde_json::Value::Array(keys)) = pull.pointer_mut(concat!("/", #key, "/$in")) {
+                                keys.push(serde_json::to_value(key).expect("Failed to serialize to JSON"));
+                            }
+                        }
+                        #op_type::Patch(key, patch) => {
+                            if !patch.is_empty() {
+                                let identifier = format!("elem{}", *identifiers);
+                                *identifiers += 1;
+                                array_filters.push(serde_json::json!({
+                                    format!("{}.{}", identifier, #key):
+                                        serde_json::to_value(key).expect("Failed to serialize to JSON")
+                                }));
+                                patch.write_update_document(
+                                    &format!("{}.$[{}].", path, identifier),
+                                    document,
+                                    array_filters,
+                                    identifiers,
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        // `apply_to` bumps the version of a non-empty update
+        FieldKind::Version => quote! {
+            if !self.is_empty() {
+                operator(document, "$inc").insert(#path, serde_json::json!(1));
+            }
+        },
+        _ => return None,
+    };
+    Some(write)
+}
+
+/// Generate the statements writing one field, by its serialized `name`, into the update
+/// stages of `write_update_stages`
+fn stage_write(processed: &ProcessedField) -> Option<proc_macro2::TokenStream> {
+    let ident = &processed.ident;
+    let field = &processed.field;
+    let name = processed.wire_name.as_str();
+    let path = quote! { format!("{}{}", prefix, #name) };
+    let set = |value: proc_macro2::TokenStream| {
+        quote! {
+            let path = #path;
+            stage(stages, filter, scope, "$set", &path, false).insert(path, #value);
+        }
+    };
+    let unset = quote! {
+        let path = #path;
+        stage(stages, filter, scope, "$unset", &path, false)
+            .insert(path, serde_json::Value::String(String::new()));
+    };
+    let serialized = quote! { serde_json::to_value(value).expect("Failed to serialize to JSON") };
+
+    let write = match &processed.kind {
+        FieldKind::Primitive { option: false, .. } => {
+            let default = processed.no_change.as_ref()?;
+            let set = set(quote! {
+                serde_json::to_value(&self.#ident).expect("Failed to serialize to JSON")
+            });
+            quote! {
+                if self.#ident != #default {
+                    #set
+                }
+            }
+        }
+        // Clearing an `Option<T>` field removes it from the document
+        FieldKind::Primitive { .. } if option_inner(&field.ty).is_some() => {
+            let set = set(serialized);
+            quote! {
+                match &self.#ident {
+                    Some(Some(value)) => { #set }
+                    Some(None) => { #unset }
+                    None => {}
+                }
+            }
+        }
+        FieldKind::Primitive { .. } => {
+            let set = set(serialized);
+            quote! {
+                if let Some(value) = &self.#ident {
+                    #set
+                }
+            }
+        }
+        FieldKind::Json => {
+            let set = set(quote! { value.clone() });
+            quote! {
+                if let Some(value) = &self.#ident {
+                    #set
+                }
+            }
+        }
+        FieldKind::Nested { .. } => match processed.nested.as_ref()?.0 {
+            // A missing or `null` value can't take dotted paths, so it is set as a whole:
+            // the patch applies where the value exists, the created value where it doesn't
+            NestedWrapper::Option => quote! {
+                match &self.#ident {
+                    Some(Some(nested)) => {
+                        let path = #path;
+                        let (existing, existing_scope) =
+                            condition(filter, scope, &path, serde_json::json!({ "$ne": null }));
+                        nested.write_update_stages(
+                            &format!("{}.", path),
+                            &existing,
+                            &existing_scope,
+                            stages,
+                            identifiers,
+                        );
+                        let mut created = Default::default();
+                        nested.apply_unchecked(&mut created);
+                        let (missing, missing_scope) =
+                            condition(filter, scope, &path, serde_json::Value::Null);
+                        stage(stages, &missing, &missing_scope, "$set", &path, false).insert(
+                            path,
+                            serde_json::to_value(&created).expect("Failed to serialize to JSON"),
+                        );
+                    }
+                    Some(None) => { #unset }
+                    None => {}
+                }
+            },
+            // Every element is patched, through an array filter that matches them all
+            NestedWrapper::Vec => quote! {
+                if let Some(nested) = &self.#ident {
+                    let identifier = format!("elem{}", *identifiers);
+                    *identifiers += 1;
+                    let mut element = serde_json::Map::new();
+                    element.insert(identifier.clone(), serde_json::json!({ "$type": "object" }));
+                    let mut element_scope = scope.to_vec();
+                    element_scope.push((identifier.clone(), element));
+                    nested.write_update_stages(
+                        &format!("{}{}.$[{}].", prefix, #name, identifier),
+                        filter,
+                        &element_scope,
+                        stages,
+                        identifiers,
+                    );
+                }
+            },
+            _ => quote! {
+                if let Some(nested) = &self.#ident {
+                    nested.write_update_stages(
+                        &format!("{}{}.", prefix, #name),
+                        filter,
+                        scope,
+                        stages,
+                        identifiers,
+                    );
+                }
+            },
+        },
+        FieldKind::NestedVec { .. } => {
+            let keyed = processed.keyed.as_ref()?;
+            let op_type = &keyed.op_type;
+            let key_field = &keyed.key;
+            let key = keyed.key.to_string();
+            // Elements are matched by an array filter on their key; an upsert replaces a
+            // matching element and is pushed only where none matches
+            quote! {
+                for op in &self.#ident {
+                    let path = #path;
+                    let element_scope = |key: serde_json::Value, identifiers: &mut usize| {
+                        let identifier = format!("elem{}", *identifiers);
+                        *identifiers += 1;
+                        let mut element = serde_json::Map::new();
+                        element.insert(format!("{}.{}", identifier, #key), key);
+                        let mut element_scope = scope.to_vec();
+                        element_scope.push((identifier.clone(), element));
+                        (identifier, element_scope)
+                    };
+                    match op {
+                        #op_type::Upsert(item) => {
+                            let key = serde_json::to_value(&item.#key_field).expect("Failed to serialize to JSON");
+                            let item = serde_json::to_value(item).expect("Failed to serialize to JSON");
+                            let (identifier, replace_scope) = element_scope(key.clone(), identifiers);
+                            let element_path = format!("{}.$[{}]", path, identifier);
+                            stage(stages, filter, &replace_scope, "$set", &element_path, false)
+                                .insert(element_path, item.clone());
+                            let (absent, absent_scope) = condition(
+                                filter,
+                                scope,
+                                &format!("{}.{}", path, #key),
+                                serde_json::json!({ "$ne": key }),
+                            );
+                            stage(stages, &absent, &absent_scope, "$push", &path, false).insert(path, item);
+                        }
+                        #op_type::Remove(key) => {
+                            let pull = stage(stages, filter, scope, "$pull", &path, true)
+                                .entry(path)
+                                .or_insert_with(|| serde_json::json!({ #key: { "$in": [] } }));
+                            if let Some(serde_json::Value::Array(keys)) = pull.pointer_mut(concat!("/", #key, "/$in")) {
+                                keys.push(serde_json::to_value(key).expect("Failed to serialize to JSON"));
+                            }
+                        }
+                        #op_type::Patch(key, patch) => {
+                            if !patch.is_empty() {
+                                let key = serde_json::to_value(key).expect("Failed to serialize to JSON");
+                                let (identifier, patch_scope) = element_scope(key, identifiers);
+                                patch.write_update_stages(
+                                    &format!("{}.$[{}].", path, identifier),
+                                    filter,
+                                    &patch_scope,
+                                    stages,
+                                    identifiers,
+                                );
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        // `apply_to` bumps the version of a non-empty update
+        FieldKind::Version => quote! {
+            if !self.is_empty() {
+                let path = #path;
+                stage(stages, filter, scope, "$inc", &path, false).insert(path, serde_json::json!(1));
+            }
+        },
+        _ => return None,
+    };
+    Some(write)
+}
+
+/// Generate `to_update_document` and `to_update_stages`, MongoDB-style update documents
+pub fn generate_document_impl(
+    update_struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if !context.document_enabled {
+        return quote! {};
+    }
+
+    let stage_name = format_ident!("{}UpdateStage", update_struct_name);
+    let document_writes: Vec<_> = context.fields.iter().filter_map(document_write).collect();
+    // The version bump comes last, so it lands in the last stage
+    let (version_writes, stage_writes): (Vec<_>, Vec<_>) = context
+        .fields
+        .iter()
+        .partition(|processed| matches!(processed.kind, FieldKind::Version));
+    let stage_writes: Vec<_> = stage_writes
+        .into_iter()
+        .chain(version_writes)
+        .filter_map(stage_write)
+        .collect();
+    let normalized_self = normalized_self(context);
+    let version_filter = context.version_field.as_ref().map(|(version, _)| {
+        let name = context
+            .fields
+            .iter()
+            .find(|processed| &processed.ident == version)
+            .map(|processed| processed.wire_name.clone())
+            .unwrap_or_else(|| version.to_string());
+        quote! {
+            /// Returns `{"version": expected_version}` to add to the query filter of every
+            /// stage, or an empty filter when `expected_version` is unset.
+            ///
+            /// The version is bumped in the last stage, so a stale update matches no
+            /// document there.
+            pub fn version_filter(&self) -> serde_json::Value {
+                let mut filter = serde_json::Map::new();
+                if let Some(expected) = &self.expected_version {
+                    filter.insert(
+                        #name.to_string(),
+                        serde_json::to_value(expected).expect("Failed to serialize to JSON"),
+                    );
+                }
+                serde_json::Value::Object(filter)
+            }
+        }
+    });
+
+    quote! {
+        /// One update of `to_update_stages`, to run in order.
+        #[derive(Debug, Clone, PartialEq)]
+        pub struct #stage_name {
+            /// Conditions to add to the query filter; the stage is skipped where they don't hold
+            pub filter: serde_json::Value,
+            /// The update document
+            pub update: serde_json::Value,
+            /// The `arrayFilters` matching the `$[elemN]` identifiers of `update`
+            pub array_filters: Vec<serde_json::Value>,
+        }
+
+        impl #update_struct_name {
+            /// Returns the update as a single MongoDB-style update document.
+            ///
+            /// Keys are the serialized field names, with dotted paths for nested fields.
+            /// Set values go to `$set`, cleared `Option` fields to `$unset` and the version
+            /// bump to `$inc`. Keyed collections `$push` upserted items and `$pull` removed
+            /// keys; patches address their element as `$[elemN]`, matched by key through
+            /// `update_array_filters`. An empty update gives `{}`.
+            ///
+            /// The document doesn't order the operations, replace the element of an upserted
+            /// key that already exists, or create a nested value that is `null`, and a store
+            /// rejects operators on conflicting paths such as a `$push` and a patch of the same
+            /// array. `to_update_stages` handles every update.
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// let document = update.to_update_document();
+            /// // {"$set": {"address.city": "Boston"}, "$unset": {"nickname": ""}}
+            /// collection.update_one(filter, bson::to_document(&document)?).await?;
+            /// ```
+            pub fn to_update_document(&self) -> serde_json::Value {
+                serde_json::Value::Object(self.update_document_parts().0)
+            }
+
+            /// Returns the `arrayFilters` that `to_update_document` refers to as `$[elemN]`.
+            pub fn update_array_filters(&self) -> Vec<serde_json::Value> {
+                self.update_document_parts().1
+            }
+
+            /// Returns the update as MongoDB-style updates, to run in order against the
+            /// same document, for updates that `to_update_document` can't express.
+            ///
+            /// Keys are the serialized field names, with dotted paths for nested fields.
+            /// Set values go to `$set`, cleared `Option` fields to `$unset` and the version
+            /// bump to `$inc`. Keyed collections pull removed keys with `$pull`; upserts
+            /// replace the matching element through `$[elemN]` and are pushed in a stage
+            /// filtered on the key being absent. Patches address `$[elemN]` too, and
+            /// `Vec`-wrapped nested fields patch every element. A created optional nested
+            /// value is set as a whole, in a stage filtered on it being `null`.
+            ///
+            /// Operators that would conflict on one path go to separate stages. An empty
+            /// update gives no stages.
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// for stage in update.to_update_stages() {
+            ///     let mut filter = doc! { "_id": id };
+            ///     filter.extend(bson::to_document(&stage.filter)?);
+            ///     let options = UpdateOptions::builder()
+            ///         .array_filters(stage.array_filters.iter().map(bson::to_document).collect::<Result<_, _>>()?)
+            ///         .build();
+            ///     collection.update_one(filter, bson::to_document(&stage.update)?).with_options(options).await?;
+            /// }
+            /// ```
+            pub fn to_update_stages(&self) -> Vec<#stage_name> {
+                #normalized_self
+                let mut stages = Vec::new();
+                this.write_update_stages("", &serde_json::Map::new(), &[], &mut stages, &mut 0);
+                stages
+                    .into_iter()
+                    .map(|(filter, update, elements)| #stage_name {
+                        filter: serde_json::Value::Object(filter),
+                        update: serde_json::Value::Object(update),
+                        array_filters: elements
+                            .into_iter()
+                            .map(|(_, element)| serde_json::Value::Object(element))
+                            .collect(),
+                    })
+                    .collect()
+            }
+
+            #version_filter
+
+            fn update_document_parts(
+                &self,
+            ) -> (serde_json::Map<String, serde_json::Value>, Vec<serde_json::Value>) {
+                #normalized_self
+                let mut document = serde_json::Map::new();
+                let mut array_filters = Vec::new();
+                this.write_update_document("", &mut document, &mut array_filters, &mut 0);
+                (document, array_filters)
+            }
+
+            #[doc(hidden)]
+            #[allow(unused_variables, clippy::ptr_arg)]
+            pub fn write_update_document(
+                &self,
+                prefix: &str,
+                document: &mut serde_json::Map<String, serde_json::Value>,
+                array_filters: &mut Vec<serde_json::Value>,
+                identifiers: &mut usize,
+            ) {
+                #[allow(dead_code)]
+                fn operator<'a>(
+                    document: &'a mut serde_json::Map<String, serde_json::Value>,
+                    operator: &str,
+                ) -> &'a mut serde_json::Map<String, serde_json::Value> {
+                    match document
+                        .entry(operator)
+                        .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()))
+                    {
+                        serde_json::Value::Object(paths) => paths,
+                        _ => unreachable!("update operators are objects"),
+                    }
+                }
+
+                #(#document_writes)*
+            }
+
+            #[doc(hidden)]
+            #[allow(unused_variables, clippy::ptr_arg, clippy::type_complexity)]
+            pub fn write_update_stages(
+                &self,
+                prefix: &str,
+                filter: &serde_json::Map<String, serde_json::Value>,
+                scope: &[(String, serde_json::Map<String, serde_json::Value>)],
+                stages: &mut Vec<(
+                    serde_json::Map<String, serde_json::Value>,
+                    serde_json::Map<String, serde_json::Value>,
+                    Vec<(String, serde_json::Map<String, serde_json::Value>)>,
+                )>,
+                identifiers: &mut usize,
+            ) {
+                // Array positions all count as one path segment, as any two of them may
+                // address the same element
+                #[allow(dead_code)]
+                fn conflicts(first: &str, second: &str) -> bool {
+                    let segments = |path: &str| -> Vec<String> {
+                        path.split('.')
+                            .map(|segment| if segment.starts_with('$') { "$".to_string() } else { segment.to_string() })
+                            .collect()
+                    };
+                    let (first, second) = (segments(first), segments(second));
+                    first.iter().zip(&second).all(|(first, second)| first == second)
+                }
+
+                // The operator of the last stage when it has the same filters and no path
+                // conflicting with `path`, or of a new stage; `batch` lets the same operator
+                // extend an entry on the same path
+                #[allow(dead_code)]
+                fn stage<'a>(
+                    stages: &'a mut Vec<(
+                        serde_json::Map<String, serde_json::Value>,
+                        serde_json::Map<String, serde_json::Value>,
+                        Vec<(String, serde_json::Map<String, serde_json::Value>)>,
+                    )>,
+                    filter: &serde_json::Map<String, serde_json::Value>,
+                    scope: &[(String, serde_json::Map<String, serde_json::Value>)],
+                    operator: &str,
+                    path: &str,
+                    batch: bool,
+                ) -> &'a mut serde_json::Map<String, serde_json::Value> {
+                    let fits = stages.last().is_some_and(|(stage_filter, update, elements)| {
+                        stage_filter == filter
+                            && scope.iter().all(|(identifier, element)| {
+                                elements
+                                    .iter()
+                                    .all(|(existing, other)| existing != identifier || other == element)
+                            })
+                            && update.iter().all(|(existing_operator, paths)| {
+                                paths.as_object().is_some_and(|paths| {
+                                    paths.keys().all(|existing| {
+                                        (batch && existing_operator == operator && existing == path)
+                                            || !conflicts(existing, path)
+                                    })
+                                })
+                            })
+                    });
+                    if !fits {
+                        stages.push((filter.clone(), serde_json::Map::new(), Vec::new()));
+                    }
+                    let (_, update, elements) = stages.last_mut().expect("a stage was just pushed");
+                    for (identifier, element) in scope {
+                        if !elements.iter().any(|(existing, _)| existing == identifier) {
+                            elements.push((identifier.clone(), element.clone()));
+                        }
+                    }
+                    match update
+                        .entry(operator)
+                        .or_insert_with(|| serde_json::Value::Object(serde_json::Map::new()))
+                    {
+                        serde_json::Value::Object(paths) => paths,
+                        _ => unreachable!("update operators are objects"),
+                    }
+                }
+
+                // The filters with `predicate` on `path`: in the query filter, or in the array
+                // filter of the innermost element `path` is in
+                #[allow(dead_code)]
+                fn condition(
+                    filter: &serde_json::Map<String, serde_json::Value>,
+                    scope: &[(String, serde_json::Map<String, serde_json::Value>)],
+                    path: &str,
+                    predicate: serde_json::Value,
+                ) -> (
+                    serde_json::Map<String, serde_json::Value>,
+                    Vec<(String, serde_json::Map<String, serde_json::Value>)>,
+                ) {
+                    let mut filter = filter.clone();
+                    let mut scope = scope.to_vec();
+                    match scope.last_mut() {
+                        Some((identifier, element)) => {
+                            let marker = format!("$[{}].", identifier);
+                            let relative = path.rsplit_once(&marker).map_or(path, |(_, relative)| relative);
+                            element.insert(format!("{}.{}", identifier, relative), predicate);
+                        }
+                        None => {
+                            filter.insert(path.to_string(), predicate);
+                        }
+                    }
+                    (filter, scope)
+                }
+
+                #(#stage_writes)*
+            }
+        }
+    }
+}
+
+/// Generate the database integrations: diesel's `AsChangeset` and sqlx's `push_set_clauses`
+pub fn generate_column_impl(
+    update_struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if context.diesel_table.is_none() && !context.sqlx_enabled {
+        return quote! {};
+    }
+
+    let error_name = format_ident!("{}EmptyUpdateError", update_struct_name);
+    let normalized_self = normalized_self(context);
+    // Whether each column is set on `receiver`
+    let column_sets = |receiver: proc_macro2::TokenStream| -> Vec<proc_macro2::TokenStream> {
+        context
+            .column_fields
+            .iter()
+            .map(|name| {
+                match context
+                    .unwrapped_field_names
+                    .iter()
+                    .position(|unwrapped| unwrapped == name)
+                {
+                    Some(index) => {
+                        let default = &context.unwrapped_field_defaults[index];
+                        quote! { #receiver.#name != #default }
+                    }
+                    None => quote! { #receiver.#name.is_some() },
+                }
+            })
+            .collect()
+    };
+    let this_column_sets = column_sets(quote! { this });
+
+    let changeset_name = format_ident!("{}Changeset", update_struct_name);
+    // The version column and its type, when updates bump a version
+    let version = context
+        .version_field
+        .as_ref()
+        .zip(context.version_column.as_ref())
+        .map(|((_, ty), column)| (format_ident!("{}", column), ty));
+
+    let diesel_impl = context.diesel_table.as_ref().map(|table| {
+        let columns: Vec<_> = context
+            .column_names
+            .iter()
+            .map(|column| format_ident!("{}", column))
+            .collect();
+        let value_types = context.column_types.iter().map(|ty| quote! { &'a #ty });
+        let assignments: Vec<_> = context
+            .column_fields
+            .iter()
+            .zip(&columns)
+            .zip(column_sets(quote! { update }))
+            .map(|((name, column), set)| {
+                if context.unwrapped_field_names.contains(name) {
+                    quote! { (#set).then(|| #table::#column.eq(&update.#name)) }
+                } else {
+                    quote! { update.#name.as_ref().map(|value| #table::#column.eq(value)) }
+                }
+            })
+            .collect();
+        let version_bump_type = version.as_ref().map(|(column, ty)| {
+            quote! {
+                ::diesel::dsl::Eq<#table::#column, ::diesel::dsl::Add<#table::#column, #ty>>,
+            }
+        });
+        let version_bump = version.as_ref().map(|(column, ty)| {
+            quote! { #table::#column.eq(#table::#column + (1 as #ty)), }
+        });
+        let version_filter = version.as_ref().map(|(column, ty)| {
+            quote! {
+                impl #update_struct_name {
+                    /// Returns `version = expected_version` to filter the updated row by, or
+                    /// `None` when `expected_version` is unset.
+                    ///
+                    /// A stale update then matches no row, so `execute` returns `0`.
+                    ///
+                    /// # Examples
+                    ///
+                    /// ```rust,ignore
+                    /// let guard = update.version_filter().ok_or(MissingVersion)?;
+                    /// let updated = diesel::update(users::table.find(id).filter(guard))
+                    ///     .set(&update.changeset()?)
+                    ///     .execute(&mut conn)?;
+                    /// ```
+                    pub fn version_filter(&self) -> Option<::diesel::dsl::Eq<#table::#column, #ty>> {
+                        use ::diesel::ExpressionMethods;
+
+                        self.expected_version.clone().map(|expected| #table::#column.eq(expected))
+                    }
+                }
+            }
+        });
+
+        quote! {
+            /// A normalized update with at least one column, returned by `changeset()`.
+            #[derive(Debug, Clone)]
+            pub struct #changeset_name(#update_struct_name);
+
+            impl #update_struct_name {
+                /// Returns the normalized update to pass to diesel's `set`.
+                ///
+                /// # Errors
+                ///
+                /// Returns an error instead of a changeset without columns, which diesel would
+                /// only reject when the query runs.
+                ///
+                /// # Examples
+                ///
+                /// ```rust,ignore
+                /// diesel::update(users::table.find(id))
+                ///     .set(&update.changeset()?)
+                ///     .execute(&mut conn)?;
+                /// ```
+                pub fn changeset(&self) -> Result<#changeset_name, #error_name> {
+                    #normalized_self
+                    if #(#this_column_sets)||* {
+                        Ok(#changeset_name(this.clone()))
+                    } else {
+                        Err(#error_name)
+                    }
+                }
+            }
+
+            #version_filter
+
+            /// Sets the columns of the set primitive fields, and bumps the version column;
+            /// other fields aren't columns.
+            impl<'a> ::diesel::query_builder::AsChangeset for &'a #changeset_name {
+                type Target = #table::table;
+                type Changeset = <(
+                    #(Option<::diesel::dsl::Eq<#table::#columns, #value_types>>,)*
+                    #version_bump_type
+                ) as ::diesel::query_builder::AsChangeset>::Changeset;
+
+                fn as_changeset(self) -> Self::Changeset {
+                    use ::diesel::ExpressionMethods;
+
+                    let update = &self.0;
+                    ::diesel::query_builder::AsChangeset::as_changeset((
+                        #(#assignments,)*
+                        #version_bump
+                    ))
+                }
+            }
+        }
+    });
+
+    let sqlx_impl = context.sqlx_enabled.then(|| {
+        let column_types = &context.column_types;
+        let version_bump = version.as_ref().map(|(column, _)| {
+            let bump = format!("{} = {} + 1", column, column);
+            quote! { separated.push(#bump); }
+        });
+        let version_filter = version.as_ref().map(|(column, ty)| {
+            let filter = format!(" AND {} = ", column);
+            quote! {
+                /// Pushes ` AND version = ?` with `expected_version` bound, to follow the
+                /// query's `WHERE` clause; pushes nothing when `expected_version` is unset.
+                ///
+                /// A stale update then matches no row, so `rows_affected()` is `0`.
+                ///
+                /// # Examples
+                ///
+                /// ```rust,ignore
+                /// query.push(" WHERE id = ").push_bind(id);
+                /// update.push_version_filter(&mut query);
+                /// ```
+                pub fn push_version_filter<'args, DB>(&self, builder: &mut ::sqlx::QueryBuilder<'args, DB>)
+                where
+                    DB: ::sqlx::Database,
+                    #ty: 'args + ::sqlx::Encode<'args, DB> + ::sqlx::Type<DB>,
+                {
+                    if let Some(expected) = &self.expected_version {
+                        builder.push(#filter);
+                        builder.push_bind(expected.clone());
+                    }
+                }
+            }
+        });
+        let pushes: Vec<_> = context
+            .column_fields
+            .iter()
+            .zip(&context.column_names)
+            .map(|(name, column)| {
+                let assignment = format!("{} = ", column);
+                if let Some(index) = context
+                    .unwrapped_field_names
+                    .iter()
+                    .position(|unwrapped| unwrapped == name)
+                {
+                    let default = &context.unwrapped_field_defaults[index];
+                    quote! {
+                        if this.#name != #default {
+                            separated.push(#assignment);
+                            separated.push_bind_unseparated(this.#name.clone());
+                        }
+                    }
+                } else {
+                    quote! {
+                        if let Some(value) = &this.#name {
+                            separated.push(#assignment);
+                            separated.push_bind_unseparated(value.clone());
+                        }
+                    }
+                }
+            })
+            .collect();
+
+        quote! {
+            impl #update_struct_name {
+                /// Pushes `column = value` assignments for the set primitive fields, separated
+                /// by commas, with the values bound as parameters.
+                ///
+                /// Values are normalized first; other fields aren't columns. A version column
+                /// is bumped with `version = version + 1`.
+                ///
+                /// # Errors
+                ///
+                /// Returns an error, without pushing anything, when no column is set.
+                ///
+                /// # Examples
+                ///
+                /// ```rust,ignore
+                /// let mut query = QueryBuilder::new("UPDATE users SET ");
+                /// update.push_set_clauses(&mut query)?;
+                /// query.push(" WHERE id = ").push_bind(id);
+                /// query.build().execute(&pool).await?;
+                /// ```
+                pub fn push_set_clauses<'args, DB>(
+                    &self,
+                    builder: &mut ::sqlx::QueryBuilder<'args, DB>,
+                ) -> Result<(), #error_name>
+                where
+                    DB: ::sqlx::Database,
+                    #(#column_types: 'args + ::sqlx::Encode<'args, DB> + ::sqlx::Type<DB>,)*
+                {
+                    #normalized_self
+                    if !(#(#this_column_sets)||*) {
+                        return Err(#error_name);
+                    }
+
+                    let mut separated = builder.separated(", ");
+                    #(#pushes)*
+                    #version_bump
+                    Ok(())
+                }
+
+                #version_filter
+            }
+        }
+    });
+
+    quote! {
+        /// Error returned when an update sets no table column.
+        #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+        pub struct #error_name;
+
+        impl std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                write!(f, "update sets no columns")
+            }
+        }
+
+        impl std::error::Error for #error_name {}
+
+        #diesel_impl
+
+        #sqlx_impl
+    }
+}
+
+/// Generate the `proptest` `Arbitrary` implementation and the law checks
+pub fn generate_proptest_impl(
+    update_struct_name: &Ident,
+    struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if !context.proptest_enabled {
+        return quote! {};
+    }
+
+    let empty_fields = empty_initializers(context);
+    let guards = guard_initializers(context, false);
+
+    // JSON values must deserialize into the source type, so they stay unset; keyed
+    // collections get removals and patches, as their items needn't be `Arbitrary`
+    let field_strategies: Vec<_> = context
+        .field_names
+        .iter()
+        .zip(&context.field_types)
+        .filter(|(name, _)| !context.json_field_names.contains(name))
+        .map(|(name, ty)| {
+            let values = match context
+                .keyed_vec_field_names
+                .iter()
+                .position(|keyed| keyed == name)
+            {
+                Some(index) => {
+                    let op_type = &context.keyed_vec_op_types[index];
+                    let key_type = &context.keyed_vec_key_types[index];
+                    let nested_type = &context.keyed_vec_nested_types[index];
+                    quote! {
+                        ::proptest::collection::vec(
+                            ::proptest::prop_oneof![
+                                ::proptest::arbitrary::any::<#key_type>().prop_map(#op_type::Remove),
+                                (
+                                    ::proptest::arbitrary::any::<#key_type>(),
+                                    ::proptest::arbitrary::any::<#nested_type>(),
+                                )
+                                    .prop_map(|(key, patch)| #op_type::Patch(key, patch)),
+                            ],
+                            0..3,
+                        )
+                    }
+                }
+                None => quote! { ::proptest::arbitrary::any::<#ty>() },
+            };
+            quote! {
+                let strategy = (strategy, #values)
+                    .prop_map(|(mut update, value)| {
+                        update.#name = value;
+                        update
+                    })
+                    .boxed();
+            }
+        })
+        .collect();
+
+    // A guard that fails is a broken law too
+    let apply =
+        |label: &str, update: proc_macro2::TokenStream, target: proc_macro2::TokenStream| {
+            if has_apply_error(context) {
+                quote! {
+                    #update.apply_to(&mut #target)
+                        .map_err(|err| format!("{}.apply_to failed: {}", #label, err))?;
+                }
+            } else {
+                quote! { #update.apply_to(&mut #target); }
+            }
+        };
+    let apply_a = apply("a", quote! { a }, quote! { sequential });
+    let apply_b = apply("b", quote! { b }, quote! { sequential });
+    let apply_merged = apply("a.merge(b)", quote! { merged_update }, quote! { merged });
+
+    // The version counts applications, so the sequential side bumps it once more
+    let sync_version = context.version_field.as_ref().map(|(version, _)| {
+        quote! { merged.#version = sequential.#version.clone(); }
+    });
+
+    quote! {
+        impl ::proptest::arbitrary::Arbitrary for #update_struct_name {
+            type Parameters = ();
+            type Strategy = ::proptest::strategy::BoxedStrategy<Self>;
+
+            /// Any combination of set and unset fields, without guards.
+            fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+                use ::proptest::strategy::Strategy;
+
+                let strategy = ::proptest::strategy::Just(Self {
+                    #(#empty_fields,)*
+                    #guards
+                })
+                .boxed();
+                #(#field_strategies)*
+                strategy
+            }
+        }
+
+        impl #update_struct_name {
+            /// Checks that `merge` is associative: `(a ⊕ b) ⊕ c == a ⊕ (b ⊕ c)`.
+            ///
+            /// # Errors
+            ///
+            /// Describes both sides when they differ.
+            pub fn check_merge_associative(a: &Self, b: &Self, c: &Self) -> Result<(), String> {
+                let left = a.clone().merge(b.clone()).merge(c.clone());
+                let right = a.clone().merge(b.clone().merge(c.clone()));
+                if serde_json::to_value(&left).ok() == serde_json::to_value(&right).ok() {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "merge is not associative:\n(a.merge(b)).merge(c) = {:?}\na.merge(b.merge(c)) = {:?}",
+                        left, right
+                    ))
+                }
+            }
+
+            /// Checks that applying `a.merge(b)` equals applying `a`, then `b`.
+            ///
+            /// The version field, which counts applications, is left out of the comparison.
+            ///
+            /// # Errors
+            ///
+            /// Describes both results when they differ, or the guard that failed when an
+            /// update can't be applied.
+            pub fn check_merge_apply(a: &Self, b: &Self, target: &#struct_name) -> Result<(), String> {
+                let mut sequential = target.clone();
+                #apply_a
+                #apply_b
+                let merged_update = a.clone().merge(b.clone());
+                let mut merged = target.clone();
+                #apply_merged
+                #sync_version
+                if merged == sequential {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "applying a.merge(b) differs from applying a, then b:\nmerged = {:?}\nsequential = {:?}",
+                        merged, sequential
+                    ))
+                }
+            }
+
+            /// Checks that an empty update never changes `target`.
+            ///
+            /// # Errors
+            ///
+            /// Describes the update when `is_empty()` holds but `would_change(target)` does too.
+            pub fn check_empty_no_change(update: &Self, target: &#struct_name) -> Result<(), String> {
+                if update.is_empty() && update.would_change(target) {
+                    Err(format!("{:?} is empty but would change the target", update))
+                } else {
+                    Ok(())
+                }
+            }
+
+            /// Runs every law check on the given substructs and target.
+            ///
+            /// # Errors
+            ///
+            /// Returns the first violated law.
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// proptest! {
+            ///     #[test]
+            ///     fn substruct_laws(a: #update_struct_name, b: #update_struct_name, c: #update_struct_name, target in target_strategy()) {
+            ///         #update_struct_name::check_laws(&a, &b, &c, &target).map_err(TestCaseError::fail)?;
+            ///     }
+            /// }
+            /// ```
+            pub fn check_laws(
+                a: &Self,
+                b: &Self,
+                c: &Self,
+                target: &#struct_name,
+            ) -> Result<(), String> {
+                Self::check_merge_associative(a, b, c)?;
+                Self::check_merge_apply(a, b, target)?;
+                Self::check_empty_no_change(a, target)
+            }
+        }
+    }
+}
+
+/// The flag name of a field: `address_line` becomes `address-line`
+fn flag_name(ident: &Ident) -> String {
+    ident.to_string().trim_start_matches("r#").replace('_', "-")
+}
+
+/// Generate the clap argument definitions and the matching reads for one field
+///
+/// Returns `(augment, read)`: `augment` adds the field's arguments to `cmd`, `read`
+/// copies a matched value into `update`. Flags are `--{prefix}{field}`; fields that can
+/// be cleared also get `--no-{prefix}{field}`.
+fn cli_arg(
+    processed: &ProcessedField,
+) -> Option<(proc_macro2::TokenStream, proc_macro2::TokenStream)> {
+    let ident = &processed.ident;
+    let label = processed.options.label.as_deref();
+    let span = processed.field.span();
+    let ty = &processed.field.ty;
+    let name = flag_name(ident);
+    let no_name = format!("no-{}", name);
+    let display = label.map_or_else(|| format!("`{}`", ident), str::to_string);
+    let help = label.map(|label| quote! { .help(#label) });
+    let clear_help = format!("Clear {}", display);
+
+    let clear_arg = quote! {
+        let cmd = cmd.arg(
+            ::clap::Arg::new(format!("{}{}", prefix, #no_name))
+                .long(format!("{}{}", prefix, #no_name))
+                .action(::clap::ArgAction::SetTrue)
+                .help(#clear_help),
+        );
+    };
+    let clear_read = quote! {
+        if matches.get_flag(&format!("{}{}", prefix, #no_name)) {
+            update.#ident = Some(None);
+        }
+    };
+
+    let arg = |value_parser: proc_macro2::TokenStream| {
+        quote_spanned! {span=>
+            ::clap::Arg::new(format!("{}{}", prefix, #name))
+                .long(format!("{}{}", prefix, #name))
+                .value_parser(#value_parser)
+                .action(::clap::ArgAction::Set)
+                #help
+        }
+    };
+
+    match &processed.kind {
+        FieldKind::Primitive { option, .. } => {
+            let (parse_ty, set) = primitive_target(ty, *option);
+            let value_arg = arg(quote_spanned! {span=> ::clap::value_parser!(#parse_ty) });
+            let read = quote_spanned! {span=>
+                if let Some(value) = matches
+                    .get_one::<#parse_ty>(&format!("{}{}", prefix, #name))
+                    .cloned()
+                {
+                    update.#ident = #set;
+                }
+            };
+
+            // Only `Option<Option<T>>` fields can be cleared
+            let clearable = *option && option_inner(ty).is_some();
+            if clearable {
+                Some((
+                    quote! {
+                        let cmd = cmd.arg(
+                            #value_arg.conflicts_with(format!("{}{}", prefix, #no_name)),
+                        );
+                        #clear_arg
+                    },
+                    quote! {
+                        #read
+                        #clear_read
+                    },
+                ))
+            } else {
+                Some((quote! { let cmd = cmd.arg(#value_arg); }, read))
+            }
+        }
+        FieldKind::Json => {
+            let value_arg = arg(quote_spanned! {span=>
+                |raw: &str| -> Result<serde_json::Value, String> {
+                    serde_json::from_str::<#ty>(raw)
+                        .and_then(|value| serde_json::to_value(&value))
+                        .map_err(|err| err.to_string())
+                }
+            });
+            Some((
+                quote! { let cmd = cmd.arg(#value_arg); },
+                quote! {
+                    if let Some(value) =
+                        matches.get_one::<serde_json::Value>(&format!("{}{}", prefix, #name))
+                    {
+                        update.#ident = Some(value.clone());
+                    }
+                },
+            ))
+        }
+        FieldKind::Nested { .. } => {
+            let (wrapper, nested_type) = processed.nested.as_ref()?;
+            let nested_prefix = format!("{}-", name);
+            let nested_args = quote_spanned! {span=>
+                let cmd = #nested_type::augment_cli_args(cmd, &format!("{}{}", prefix, #nested_prefix));
+            };
+            let nested_read = |set: proc_macro2::TokenStream| {
+                quote_spanned! {span=>
+                    let nested = #nested_type::from_cli_matches(
+                        matches,
+                        &format!("{}{}", prefix, #nested_prefix),
+                    )?;
+                    if !nested.is_empty() {
+                        update.#ident = #set;
+                    }
+                }
+            };
+
+            match wrapper {
+                // The clear flag wins over the nested flags
+                NestedWrapper::Option => {
+                    let read = nested_read(quote! { Some(Some(nested)) });
+                    Some((
+                        quote! {
+                            #nested_args
+                            #clear_arg
+                        },
+                        quote! {
+                            #read
+                            #clear_read
+                        },
+                    ))
+                }
+                _ => Some((nested_args, nested_read(quote! { Some(nested) }))),
+            }
+        }
+        _ => None,
+    }
+}
+
+/// Generate the `clap::Args` / `clap::FromArgMatches` implementations
+pub fn generate_cli_impl(
+    update_struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if !context.cli_enabled {
+        return quote! {};
+    }
+
+    let (cli_args, cli_reads): (Vec<_>, Vec<_>) = context.fields.iter().filter_map(cli_arg).unzip();
+
+    quote! {
+        impl #update_struct_name {
+            /// Adds a `--{prefix}{field}` argument for every field to `cmd`.
+            ///
+            /// Nested fields use their path (`--address-city`), and fields that can be
+            /// cleared also get a `--no-{prefix}{field}` flag. Used by the `clap::Args` impl
+            /// with an empty prefix; argument names are built at runtime, so clap's `string`
+            /// feature must be enabled.
+            pub fn augment_cli_args(cmd: ::clap::Command, prefix: &str) -> ::clap::Command {
+                #(#cli_args)*
+                cmd
+            }
+
+            /// Reads the arguments added by `augment_cli_args`; absent flags leave the field
+            /// unchanged.
+            pub fn from_cli_matches(
+                matches: &::clap::ArgMatches,
+                prefix: &str,
+            ) -> Result<Self, ::clap::Error> {
+                #[allow(unused_mut)]
+                let mut update = Self::default();
+                #({ #cli_reads })*
+                update.normalize();
+                Ok(update)
+            }
+        }
+
+        impl ::clap::FromArgMatches for #update_struct_name {
+            fn from_arg_matches(matches: &::clap::ArgMatches) -> Result<Self, ::clap::Error> {
+                Self::from_cli_matches(matches, "")
+            }
+
+            fn update_from_arg_matches(
+                &mut self,
+                matches: &::clap::ArgMatches,
+            ) -> Result<(), ::clap::Error> {
+                let update = Self::from_cli_matches(matches, "")?;
+                *self = std::mem::take(self).merge(update);
+                Ok(())
+            }
+        }
+
+        impl ::clap::Args for #update_struct_name {
+            fn augment_args(cmd: ::clap::Command) -> ::clap::Command {
+                Self::augment_cli_args(cmd, "")
+            }
+
+            fn augment_args_for_update(cmd: ::clap::Command) -> ::clap::Command {
+                Self::augment_cli_args(cmd, "")
+            }
+        }
+    }
+}
+
+/// The generic arguments of a path type's last segment, e.g. `[K, V]` for `HashMap<K, V>`
+fn type_arguments(ty: &Type) -> Option<(String, Vec<&Type>)> {
+    let Type::Path(TypePath { path, .. }) = ty else {
+        return None;
+    };
+    let segment = path.segments.last()?;
+    let arguments = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    Some((segment.ident.to_string(), arguments))
+}
+
+/// The JSON Schema of a Rust type as a `serde_json::json!` expression
+///
+/// Returns whether the schema is an array, so `length` rules can pick `minItems` over
+/// `minLength`. Types without a known JSON shape are free-form (`{}`).
+fn type_schema(ty: &Type) -> (proc_macro2::TokenStream, bool) {
+    let Some((name, arguments)) = type_arguments(ty) else {
+        return match ty {
+            Type::Reference(reference) => type_schema(&reference.elem),
+            Type::Array(array) => {
+                let (items, _) = type_schema(&array.elem);
+                let len = &array.len;
+                (
+                    quote! { serde_json::json!({ "type": "array", "items": #items, "minItems": #len, "maxItems": #len }) },
+                    true,
+                )
+            }
+            _ => (quote! { serde_json::json!({}) }, false),
+        };
+    };
+
+    let schema = match (name.as_str(), arguments.as_slice()) {
+        ("u8" | "u16" | "u32" | "u64" | "u128" | "usize", []) => {
+            quote! { serde_json::json!({ "type": "integer", "minimum": 0 }) }
+        }
+        ("i8" | "i16" | "i32" | "i64" | "i128" | "isize", []) => {
+            quote! { serde_json::json!({ "type": "integer" }) }
+        }
+        ("f32" | "f64", []) => quote! { serde_json::json!({ "type": "number" }) },
+        ("bool", []) => quote! { serde_json::json!({ "type": "boolean" }) },
+        ("String" | "str" | "PathBuf", []) => quote! { serde_json::json!({ "type": "string" }) },
+        ("char", []) => {
+            quote! { serde_json::json!({ "type": "string", "minLength": 1, "maxLength": 1 }) }
+        }
+        ("Option", [inner]) => {
+            let (inner, _) = type_schema(inner);
+            quote! { serde_json::json!({ "anyOf": [#inner, { "type": "null" }] }) }
+        }
+        ("Box" | "Arc" | "Rc", [inner]) => return type_schema(inner),
+        ("Vec" | "VecDeque", [item]) => {
+            let (items, _) = type_schema(item);
+            return (
+                quote! { serde_json::json!({ "type": "array", "items": #items }) },
+                true,
+            );
+        }
+        ("HashSet" | "BTreeSet", [item]) => {
+            let (items, _) = type_schema(item);
+            return (
+                quote! { serde_json::json!({ "type": "array", "items": #items, "uniqueItems": true }) },
+                true,
+            );
+        }
+        ("HashMap" | "BTreeMap", [_, value]) => {
+            let (values, _) = type_schema(value);
+            quote! { serde_json::json!({ "type": "object", "additionalProperties": #values }) }
+        }
+        _ => quote! { serde_json::json!({}) },
+    };
+    (schema, false)
+}
+
+/// Statements adding the `validate(...)` rules to the schema bound to `schema`
+fn constraint_inserts(validations: &[Validation], is_array: bool) -> Vec<proc_macro2::TokenStream> {
+    let (min_length, max_length) = if is_array {
+        ("minItems", "maxItems")
+    } else {
+        ("minLength", "maxLength")
+    };
+    let bound = |key: &str, value: &Option<proc_macro2::TokenStream>| {
+        value.as_ref().map(|value| {
+            quote! { schema.insert(#key.to_string(), serde_json::json!(#value)); }
+        })
+    };
+
+    validations
+        .iter()
+        .flat_map(|validation| match validation {
+            Validation::Range { min, max } => {
+                vec![bound("minimum", min), bound("maximum", max)]
+            }
+            Validation::Length { min, max } => {
+                vec![bound(min_length, min), bound(max_length, max)]
+            }
+            Validation::Regex(pattern) => vec![Some(quote! {
+                schema.insert("pattern".to_string(), serde_json::json!(#pattern));
+            })],
+            // Arbitrary Rust code has no schema equivalent
+            Validation::Custom(_) => vec![None],
+        })
+        .flatten()
+        .collect()
+}
+
+/// Generate the statement adding one field, by its serialized `name`, to the `properties`
+/// map of `json_schema`
+fn schema_property(processed: &ProcessedField) -> Option<proc_macro2::TokenStream> {
+    let ty = &processed.field.ty;
+    let name = processed.wire_name.as_str();
+    let label = processed.options.label.as_deref();
+
+    let schema = match &processed.kind {
+        FieldKind::Primitive {
+            validations,
+            sensitive,
+            ..
+        } => {
+            // Constraints apply to the value; `Option<T>` fields also accept `null`
+            let inner = option_inner(ty).unwrap_or(ty);
+            let (base, is_array) = type_schema(inner);
+            let constraints = constraint_inserts(validations, is_array);
+            let write_only = sensitive.then(|| {
+                quote! { schema.insert("writeOnly".to_string(), serde_json::json!(true)); }
+            });
+            let nullable = option_inner(ty).map(|_| {
+                quote! { let schema = serde_json::json!({ "anyOf": [schema, { "type": "null" }] }); }
+            });
+            quote! {
+                {
+                    let mut schema = match #base {
+                        serde_json::Value::Object(schema) => schema,
+                        _ => serde_json::Map::new(),
+                    };
+                    #(#constraints)*
+                    #write_only
+                    let schema = serde_json::Value::Object(schema);
+                    #nullable
+                    schema
+                }
+            }
+        }
+        // Any JSON value, validated by the source type when applied
+        FieldKind::Json => quote! { serde_json::json!({}) },
+        FieldKind::Nested { .. } => {
+            let (wrapper, nested_type) = processed.nested.as_ref()?;
+            match wrapper {
+                NestedWrapper::Option => quote! {
+                    serde_json::json!({
+                        "anyOf": [#nested_type::json_schema_ref(defs), { "type": "null" }]
+                    })
+                },
+                _ => quote! { #nested_type::json_schema_ref(defs) },
+            }
+        }
+        FieldKind::NestedVec { .. } => {
+            let keyed = processed.keyed.as_ref()?;
+            let nested_type = &keyed.nested_type;
+            let (key, _) = type_schema(&keyed.key_type);
+            // Externally tagged `Upsert(item)` / `Remove(key)` / `Patch(key, patch)`
+            quote! {
+                {
+                    let key = #key;
+                    let operation = |tag: &str, value: serde_json::Value| {
+                        serde_json::json!({
+                            "type": "object",
+                            "properties": { tag: value },
+                            "required": [tag],
+                            "additionalProperties": false
+                        })
+                    };
+                    serde_json::json!({
+                        "type": "array",
+                        "items": {
+                            "oneOf": [
+                                operation("upsert", serde_json::json!({ "type": "object" })),
+                                operation("remove", key.clone()),
+                                operation("patch", serde_json::json!({
+                                    "type": "array",
+                                    "prefixItems": [key, #nested_type::json_schema_ref(defs)],
+                                    "minItems": 2,
+                                    "maxItems": 2
+                                })),
+                            ]
+                        }
+                    })
+                }
+            }
+        }
+        _ => return None,
+    };
+
+    let title = label.map(|label| {
+        quote! {
+            if let serde_json::Value::Object(schema) = &mut schema {
+                schema.insert("title".to_string(), serde_json::json!(#label));
+            }
+        }
+    });
+    Some(quote! {
+        {
+            #[allow(unused_mut)]
+            let mut schema = #schema;
+            #title
+            properties.insert(#name.to_string(), schema);
+        }
+    })
+}
+
+/// Generate `json_schema`, the JSON Schema of the substruct's wire format
+pub fn generate_schema_impl(
+    update_struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if !context.schema_enabled {
+        return quote! {};
+    }
+
+    let schema_properties: Vec<_> = context.fields.iter().filter_map(schema_property).collect();
+    let version_property = context.version_field.as_ref().map(|(_, ty)| {
+        let (schema, _) = type_schema(ty);
+        let name = context.wire_name("expected_version", None);
+        quote! { properties.insert(#name.to_string(), #schema); }
+    });
+    // The expected value of a sensitive field is as secret as the value itself
+    let expect_properties: Vec<_> = context
+        .expect_slot_names
+        .iter()
+        .zip(&context.expect_field_types)
+        .zip(&context.expect_field_names)
+        .map(|((slot, ty), field)| {
+            let (schema, _) = type_schema(ty);
+            let name = context.wire_name(&slot.to_string(), None);
+            if context.sensitive_field_names.contains(field) {
+                quote! {
+                    {
+                        let mut schema = #schema;
+                        if let serde_json::Value::Object(schema) = &mut schema {
+                            schema.insert("writeOnly".to_string(), serde_json::json!(true));
+                        }
+                        properties.insert(#name.to_string(), schema);
+                    }
+                }
+            } else {
+                quote! { properties.insert(#name.to_string(), #schema); }
+            }
+        })
+        .collect();
+
+    quote! {
+        impl #update_struct_name {
+            /// Returns the JSON Schema (draft 2020-12) of the substruct's serialized form.
+            ///
+            /// Every field is optional; `Option<T>` fields are nullable, `json` fields are
+            /// free-form, and nested substructs are referenced through `$defs`. `validate(...)`
+            /// rules become `minimum`/`maximum`, `minLength`/`maxLength` (`minItems`/`maxItems`
+            /// for collections) and `pattern`.
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// let schema = #update_struct_name::json_schema();
+            /// std::fs::write("patch.schema.json", serde_json::to_string_pretty(&schema)?)?;
+            /// ```
+            pub fn json_schema() -> serde_json::Value {
+                let mut defs = serde_json::Map::new();
+                let mut schema = Self::json_schema_definition(&mut defs);
+                if let serde_json::Value::Object(schema) = &mut schema {
+                    schema.insert(
+                        "$schema".to_string(),
+                        serde_json::json!("https://json-schema.org/draft/2020-12/schema"),
+                    );
+                    if !defs.is_empty() {
+                        schema.insert("$defs".to_string(), serde_json::Value::Object(defs));
+                    }
+                }
+                schema
+            }
+
+            /// The schema of this substruct, adding nested substructs to `defs`.
+            #[doc(hidden)]
+            #[allow(unused_variables)]
+            pub fn json_schema_definition(
+                defs: &mut serde_json::Map<String, serde_json::Value>,
+            ) -> serde_json::Value {
+                let mut properties = serde_json::Map::new();
+                #(#schema_properties)*
+                #version_property
+                #(#expect_properties)*
+                serde_json::json!({
+                    "title": stringify!(#update_struct_name),
+                    "type": "object",
+                    "properties": properties
+                })
+            }
+
+            /// A `$ref` to this substruct's schema, adding it to `defs` on first use.
+            #[doc(hidden)]
+            pub fn json_schema_ref(
+                defs: &mut serde_json::Map<String, serde_json::Value>,
+            ) -> serde_json::Value {
+                let name = stringify!(#update_struct_name);
+                if !defs.contains_key(name) {
+                    // Reserved first, so recursive substructs terminate
+                    defs.insert(name.to_string(), serde_json::Value::Null);
+                    let definition = Self::json_schema_definition(defs);
+                    defs.insert(name.to_string(), definition);
+                }
+                serde_json::json!({ "$ref": format!("#/$defs/{}", name) })
+            }
+        }
+    }
+}
+
+/// The TypeScript type of a Rust type, following serde's JSON representation
+///
+/// Types without a known JSON shape are `unknown`.
+fn ts_type(ty: &Type) -> String {
+    let array_of = |item: &Type| {
+        let item = ts_type(item);
+        if item.contains(' ') {
+            format!("({})[]", item)
+        } else {
+            format!("{}[]", item)
+        }
+    };
+
+    let path = match ty {
+        Type::Path(TypePath { path, .. }) => path,
+        Type::Reference(reference) => return ts_type(&reference.elem),
+        Type::Array(array) => return array_of(&array.elem),
+        Type::Slice(slice) => return array_of(&slice.elem),
+        Type::Tuple(tuple) if tuple.elems.is_empty() => return "null".to_string(),
+        Type::Tuple(tuple) => {
+            let elems: Vec<_> = tuple.elems.iter().map(ts_type).collect();
+            return format!("[{}]", elems.join(", "));
+        }
+        _ => return "unknown".to_string(),
+    };
+    let Some(segment) = path.segments.last() else {
+        return "unknown".to_string();
+    };
+    let arguments: Vec<&Type> = match &segment.arguments {
+        PathArguments::AngleBracketed(args) => args
+            .args
+            .iter()
+            .filter_map(|arg| match arg {
+                GenericArgument::Type(ty) => Some(ty),
+                _ => None,
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+
+    match (segment.ident.to_string().as_str(), arguments.as_slice()) {
+        (
+            "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64" | "i128"
+            | "isize" | "f32" | "f64",
+            [],
+        ) => "number".to_string(),
+        ("bool", []) => "boolean".to_string(),
+        ("String" | "str" | "char" | "PathBuf", []) => "string".to_string(),
+        ("Option", [inner]) => format!("{} | null", ts_type(inner)),
+        ("Box" | "Arc" | "Rc", [inner]) => ts_type(inner),
+        ("Vec" | "VecDeque" | "HashSet" | "BTreeSet", [item]) => array_of(item),
+        ("HashMap" | "BTreeMap", [_, value]) => format!("Record<string, {}>", ts_type(value)),
+        _ => "unknown".to_string(),
+    }
+}
+
+/// A property key, quoted unless it is a valid identifier
+fn property_key(name: &str) -> String {
+    let is_identifier = name
+        .chars()
+        .next()
+        .is_some_and(|first| !first.is_ascii_digit())
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if is_identifier {
+        name.to_string()
+    } else {
+        format!("{:?}", name)
+    }
+}
+
+/// Generate the optional interface property for one field, by its serialized `name`
+///
+/// Returns the property line(s) and the nested substructs the property references.
+fn ts_property(processed: &ProcessedField) -> Option<(String, Vec<Ident>)> {
+    let ty = &processed.field.ty;
+    let name = processed.wire_name.as_str();
+    let label = processed.options.label.as_deref();
+    let (ts, references) = match &processed.kind {
+        // `Option<T>` fields are `T | null`: serde leaves unset fields out and reads a present
+        // `null` of a clearable `Option<Option<T>>` as a clear
+        FieldKind::Primitive { .. } => (ts_type(ty), Vec::new()),
+        FieldKind::Json => ("unknown".to_string(), Vec::new()),
+        FieldKind::Nested { .. } => {
+            let (wrapper, nested_type) = processed.nested.as_ref()?;
+            let ts = match wrapper {
+                NestedWrapper::Option => format!("{} | null", nested_type),
+                _ => nested_type.to_string(),
+            };
+            (ts, vec![nested_type.clone()])
+        }
+        FieldKind::NestedVec { .. } => {
+            let keyed = processed.keyed.as_ref()?;
+            let nested_type = &keyed.nested_type;
+            let key = ts_type(&keyed.key_type);
+            let ts = format!(
+                "Array<{{ upsert: unknown }} | {{ remove: {key} }} | {{ patch: [{key}, {nested_type}] }}>"
+            );
+            (ts, vec![nested_type.clone()])
+        }
+        _ => return None,
+    };
+
+    let doc = label.map_or_else(String::new, |label| format!("  /** {} */\n", label));
+    Some((
+        format!("{}  {}?: {};\n", doc, property_key(name), ts),
+        references,
+    ))
+}
+
+/// Generate `ts_declaration`, the TypeScript interface of the substruct's wire format
+pub fn generate_ts_impl(
+    update_struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if !context.ts_enabled {
+        return quote! {};
+    }
+
+    let mut interface = format!("export interface {} {{\n", update_struct_name);
+    let mut references: Vec<Ident> = Vec::new();
+    for (property, property_references) in context.fields.iter().filter_map(ts_property) {
+        interface.push_str(&property);
+        for reference in property_references {
+            if !references.contains(&reference) {
+                references.push(reference);
+            }
+        }
+    }
+    if let Some((_, ty)) = &context.version_field {
+        let name = context.wire_name("expected_version", None);
+        interface.push_str(&format!("  {}?: {};\n", property_key(&name), ts_type(ty)));
+    }
+    for (slot, ty) in context
+        .expect_slot_names
+        .iter()
+        .zip(&context.expect_field_types)
+    {
+        let name = context.wire_name(&slot.to_string(), None);
+        interface.push_str(&format!("  {}?: {};\n", property_key(&name), ts_type(ty)));
+    }
+    interface.push_str("}\n");
+
     quote! {
-        impl From<#struct_name> for #update_struct_name {
-            fn from(source: #struct_name) -> Self {
-                Self {
-                    #(#field_names: Default::default(),)*
+        impl #update_struct_name {
+            /// Returns the TypeScript interface of the substruct's serialized form.
+            ///
+            /// Every property is optional, `Option<T>` fields are `T | null` and `json` fields are
+            /// `unknown`. The interfaces of nested substructs follow, each declared once, so the
+            /// result can be written straight to a `.d.ts` file.
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// // build.rs or a test
+            /// std::fs::write("frontend/src/types/patch.d.ts", #update_struct_name::ts_declaration())?;
+            /// ```
+            pub fn ts_declaration() -> String {
+                let mut declarations = Vec::new();
+                Self::ts_declarations(&mut declarations);
+                declarations
+                    .into_iter()
+                    .map(|(_, declaration)| declaration)
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            }
+
+            /// Adds this substruct's interface and those it references to `declarations`.
+            #[doc(hidden)]
+            pub fn ts_declarations(declarations: &mut Vec<(&'static str, &'static str)>) {
+                let name = stringify!(#update_struct_name);
+                if declarations.iter().any(|(declared, _)| *declared == name) {
+                    return;
                 }
+                declarations.push((name, #interface));
+                #(#references::ts_declarations(declarations);)*
             }
         }
     }
 }
 
-/// Validate the context and ensure it's ready for code generation
-pub fn validate_context(context: &FieldContext, struct_name: &Ident) {
-    if context.updatable_fields.is_empty() {
-        abort!(
-            struct_name,
-            "No fields are tagged with #[substruct_field]. At least one field must be tagged to generate a substruct."
+/// Generate `merge_all`, folding ordered layers while recording which layer set each field
+///
+/// Nested substructs are merged field by field across layers, like `merge` does, so a later
+/// layer setting `database.port` keeps an earlier layer's `database.host`.
+pub fn generate_layered_impl(
+    update_struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if !context.layered_enabled {
+        return quote! {};
+    }
+
+    let layered_name = format_ident!("{}Layered", update_struct_name);
+    let flat_field_names: Vec<_> = context
+        .wrapped_field_names
+        .iter()
+        .chain(&context.unwrapped_field_names)
+        .chain(&context.json_field_names)
+        .chain(&context.keyed_vec_field_names)
+        .collect();
+    let nested_layer_vars: Vec<_> = context
+        .nested_field_names
+        .iter()
+        .map(|name| format_ident!("{}_layers", name))
+        .collect();
+    let nested_field_types = &context.nested_field_types;
+
+    // Nested values are taken out of the layer so `merge` leaves them to the fold below
+    let nested_collect: Vec<_> = context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+        .zip(&nested_layer_vars)
+        .map(|((name, wrapper), layers)| match wrapper {
+            NestedWrapper::Option => quote! {
+                match layer.#name.take() {
+                    Some(Some(nested)) => #layers.push((name.clone(), nested)),
+                    Some(None) => {
+                        // A clear discards everything the earlier layers set
+                        #layers.clear();
+                        merged.#name = Some(None);
+                        layered.record(stringify!(#name).to_string(), &name);
+                    }
+                    None => {}
+                }
+            },
+            _ => quote! {
+                if let Some(nested) = layer.#name.take() {
+                    #layers.push((name.clone(), nested));
+                }
+            },
+        })
+        .collect();
+    let nested_finish: Vec<_> = context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+        .zip(nested_layer_vars.iter().zip(nested_field_types))
+        .map(|((name, wrapper), (layers, nested_type))| {
+            let set = match wrapper {
+                NestedWrapper::Option => quote! { Some(Some(nested.merged)) },
+                _ => quote! { Some(nested.merged) },
+            };
+            quote! {
+                if let Some(last) = #layers.last().map(|(layer, _)| layer.clone()) {
+                    layered.record(stringify!(#name).to_string(), &last);
+                    let nested = #nested_type::merge_all(#layers);
+                    for (path, layer) in &nested.sources {
+                        layered.record(format!("{}.{}", stringify!(#name), path), layer);
+                    }
+                    merged.#name = #set;
+                }
+            }
+        })
+        .collect();
+
+    quote! {
+        /// The result of `merge_all`: the merged substruct and the layer that set each field.
+        #[derive(Debug, Clone)]
+        pub struct #layered_name {
+            pub merged: #update_struct_name,
+            sources: Vec<(String, String)>,
+        }
+
+        impl #layered_name {
+            /// Returns the name of the layer that supplied the final value of `path`.
+            ///
+            /// Nested fields use dotted paths (`"database.host"`); a nested field itself is
+            /// attributed to the last layer that set any of its fields. Returns `None` when no
+            /// layer set the field.
+            pub fn explain(&self, path: &str) -> Option<&str> {
+                self.sources
+                    .iter()
+                    .find(|(source_path, _)| source_path == path)
+                    .map(|(_, layer)| layer.as_str())
+            }
+
+            /// Returns every set field path with the layer that supplied it.
+            pub fn sources(&self) -> impl Iterator<Item = (&str, &str)> {
+                self.sources
+                    .iter()
+                    .map(|(path, layer)| (path.as_str(), layer.as_str()))
+            }
+
+            /// Returns the merged substruct, dropping the provenance.
+            pub fn into_inner(self) -> #update_struct_name {
+                self.merged
+            }
+
+            fn record(&mut self, path: String, layer: &str) {
+                match self.sources.iter_mut().find(|(source_path, _)| *source_path == path) {
+                    Some((_, source)) => *source = layer.to_string(),
+                    None => self.sources.push((path, layer.to_string())),
+                }
+            }
+        }
+
+        impl #update_struct_name {
+            /// Folds named layers in order, later layers taking precedence, and records which
+            /// layer supplied each final value.
+            ///
+            /// # Examples
+            ///
+            /// ```rust,ignore
+            /// let layered = #update_struct_name::merge_all([
+            ///     ("defaults", defaults),
+            ///     ("config.toml", file),
+            ///     ("env", #update_struct_name::from_env("APP")?),
+            ///     ("cli", cli),
+            /// ]);
+            /// println!("port comes from {:?}", layered.explain("port"));
+            /// layered.merged.apply_to(&mut config);
+            /// ```
+            pub fn merge_all<N: Into<String>>(layers: impl IntoIterator<Item = (N, Self)>) -> #layered_name {
+                let mut layered = #layered_name {
+                    merged: Self::default(),
+                    sources: Vec::new(),
+                };
+                let mut merged = Self::default();
+                #(let mut #nested_layer_vars: Vec<(String, #nested_field_types)> = Vec::new();)*
+
+                for (name, layer) in layers {
+                    let name: String = name.into();
+                    #[allow(unused_mut)]
+                    let mut layer = layer;
+                    #(if layer.has_field(stringify!(#flat_field_names)) {
+                        layered.record(stringify!(#flat_field_names).to_string(), &name);
+                    })*
+                    #(#nested_collect)*
+                    merged = merged.merge(layer);
+                }
+
+                #(#nested_finish)*
+                layered.merged = merged;
+                layered
+            }
+        }
+    }
+}
+
+/// Generate `describe` / `describe_against`, human-readable summaries of an update
+pub fn generate_describe_impl(
+    update_struct_name: &Ident,
+    struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if !context.describe_enabled {
+        return quote! {};
+    }
+
+    let normalized_self = normalized_self(context);
+    let label = |name: &Ident| match context.field_labels.iter().find(|(field, _)| field == name) {
+        Some((_, label)) => quote! { #label },
+        None => quote! { stringify!(#name) },
+    };
+    let to_json = |value: proc_macro2::TokenStream| {
+        quote! { serde_json::to_value(#value).expect("Failed to serialize field to JSON") }
+    };
+
+    // Per field: the parts describing it on its own, and against a target
+    let mut parts = Vec::new();
+    let mut parts_against = Vec::new();
+
+    // Borrow the value a primitive field is set to, if any
+    let primitives = context
+        .wrapped_field_names
+        .iter()
+        .map(|name| (name, quote! { let new = this.#name.as_ref(); }))
+        .chain(
+            context
+                .unwrapped_field_names
+                .iter()
+                .zip(&context.unwrapped_field_defaults)
+                .map(|(name, default)| {
+                    (
+                        name,
+                        quote! { let new = Some(&this.#name).filter(|new| **new != #default); },
+                    )
+                }),
+        );
+    for (name, set_check) in primitives {
+        let label = label(name);
+        let new_json = to_json(quote! { new });
+        let old_json = to_json(quote! { &target.#name });
+        if context.sensitive_field_names.contains(name) {
+            parts.push(quote! {
+                #set_check
+                if new.is_some() {
+                    parts.push(format!("{}: set", #label));
+                }
+            });
+            parts_against.push(quote! {
+                #set_check
+                if let Some(new) = new {
+                    if #old_json != #new_json {
+                        parts.push(format!("{}: changed", #label));
+                    }
+                }
+            });
+        } else {
+            parts.push(quote! {
+                #set_check
+                if let Some(new) = new {
+                    parts.push(format!("{}: {}", #label, describe_set(&#new_json)));
+                }
+            });
+            parts_against.push(quote! {
+                #set_check
+                if let Some(new) = new {
+                    let (old, new) = (#old_json, #new_json);
+                    if old != new {
+                        parts.push(format!("{}: {}", #label, describe_change(&old, &new)));
+                    }
+                }
+            });
+        }
+    }
+
+    for name in &context.json_field_names {
+        let label = label(name);
+        let old_json = to_json(quote! { &target.#name });
+        parts.push(quote! {
+            if let Some(new) = &this.#name {
+                parts.push(format!("{}: {}", #label, describe_set(new)));
+            }
+        });
+        parts_against.push(quote! {
+            if let Some(new) = &this.#name {
+                let old = #old_json;
+                if &old != new {
+                    parts.push(format!("{}: {}", #label, describe_change(&old, new)));
+                }
+            }
+        });
+    }
+
+    // Nested parts are prefixed with the field's label
+    let prefixed = |prefix: proc_macro2::TokenStream, nested_parts: proc_macro2::TokenStream| {
+        quote! {
+            let prefix = #prefix;
+            parts.extend(#nested_parts.into_iter().map(|part| format!("{}.{}", prefix, part)));
+        }
+    };
+    for (name, wrapper) in context
+        .nested_field_names
+        .iter()
+        .zip(&context.nested_field_wrappers)
+    {
+        let label = label(name);
+        match wrapper {
+            NestedWrapper::Option => {
+                let own = prefixed(quote! { #label }, quote! { nested.describe_parts() });
+                let against = prefixed(
+                    quote! { #label },
+                    quote! { nested.describe_parts_against(current) },
+                );
+                parts.push(quote! {
+                    match &this.#name {
+                        Some(Some(nested)) => { #own }
+                        Some(None) => parts.push(format!("{}: cleared", #label)),
+                        None => {}
+                    }
+                });
+                parts_against.push(quote! {
+                    match (&this.#name, &target.#name) {
+                        (Some(Some(nested)), Some(current)) => { #against }
+                        (Some(Some(nested)), None) => {
+                            parts.push(format!("{}: set ({})", #label, nested.describe()));
+                        }
+                        (Some(None), Some(_)) => parts.push(format!("{}: cleared", #label)),
+                        _ => {}
+                    }
+                });
+            }
+            NestedWrapper::Vec => {
+                let own = prefixed(
+                    quote! { format!("{}[*]", #label) },
+                    quote! { nested.describe_parts() },
+                );
+                let against = prefixed(
+                    quote! { format!("{}[{}]", #label, index) },
+                    quote! { nested.describe_parts_against(item) },
+                );
+                parts.push(quote! {
+                    if let Some(nested) = &this.#name {
+                        #own
+                    }
+                });
+                parts_against.push(quote! {
+                    if let Some(nested) = &this.#name {
+                        for (index, item) in target.#name.iter().enumerate() {
+                            #against
+                        }
+                    }
+                });
+            }
+            _ => {
+                let own = prefixed(quote! { #label }, quote! { nested.describe_parts() });
+                let against = prefixed(
+                    quote! { #label },
+                    quote! { nested.describe_parts_against(&target.#name) },
+                );
+                parts.push(quote! {
+                    if let Some(nested) = &this.#name {
+                        #own
+                    }
+                });
+                parts_against.push(quote! {
+                    if let Some(nested) = &this.#name {
+                        #against
+                    }
+                });
+            }
+        }
+    }
+
+    for ((name, op_type), key) in context
+        .keyed_vec_field_names
+        .iter()
+        .zip(&context.keyed_vec_op_types)
+        .zip(&context.keyed_vec_keys)
+    {
+        let label = label(name);
+        let own = prefixed(
+            quote! { format!("{}[{:?}]", #label, key) },
+            quote! { patch.describe_parts() },
+        );
+        let against = prefixed(
+            quote! { format!("{}[{:?}]", #label, key) },
+            quote! { patch.describe_parts_against(existing) },
         );
+        parts.push(quote! {
+            for op in &this.#name {
+                match op {
+                    #op_type::Upsert(item) => {
+                        parts.push(format!("{}[{:?}]: upserted", #label, item.#key));
+                    }
+                    #op_type::Remove(key) => parts.push(format!("{}[{:?}]: removed", #label, key)),
+                    #op_type::Patch(key, patch) => { #own }
+                }
+            }
+        });
+        parts_against.push(quote! {
+            for op in &this.#name {
+                match op {
+                    #op_type::Upsert(item) => {
+                        match target.#name.iter().find(|existing| existing.#key == item.#key) {
+                            None => parts.push(format!("{}[{:?}]: added", #label, item.#key)),
+                            Some(existing) if existing != item => {
+                                parts.push(format!("{}[{:?}]: replaced", #label, item.#key));
+                            }
+                            Some(_) => {}
+                        }
+                    }
+                    #op_type::Remove(key) => {
+                        if target.#name.iter().any(|existing| &existing.#key == key) {
+                            parts.push(format!("{}[{:?}]: removed", #label, key));
+                        }
+                    }
+                    #op_type::Patch(key, patch) => {
+                        if let Some(existing) = target.#name.iter().find(|existing| &existing.#key == key) {
+                            #against
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    let helpers = quote! {
+        #[allow(unused_variables)]
+        let show = |value: &serde_json::Value| match value {
+            serde_json::Value::String(text) => format!("'{}'", text),
+            other => other.to_string(),
+        };
+        #[allow(unused_variables)]
+        let describe_set = |new: &serde_json::Value| {
+            if new.is_null() {
+                "cleared".to_string()
+            } else {
+                format!("set to {}", show(new))
+            }
+        };
+        #[allow(unused_variables)]
+        let describe_change = |old: &serde_json::Value, new: &serde_json::Value| {
+            if old.is_null() {
+                format!("set to {}", show(new))
+            } else if new.is_null() {
+                "cleared".to_string()
+            } else {
+                format!("{} → {}", show(old), show(new))
+            }
+        };
+    };
+
+    quote! {
+        impl #update_struct_name {
+            /// Summarizes what the update sets, e.g. `name: set to 'Bob'; nickname: cleared`.
+            ///
+            /// Fields are named by their `label` (or field name), nested substructs by
+            /// dotted paths (`address.city`). Returns `"no changes"` for an empty update.
+            pub fn describe(&self) -> String {
+                let parts = self.describe_parts();
+                if parts.is_empty() {
+                    "no changes".to_string()
+                } else {
+                    parts.join("; ")
+                }
+            }
+
+            /// Summarizes what the update would change on `target`, e.g.
+            /// `name: 'Alice' → 'Bob'; address.city: set to 'NYC'; nickname: cleared`.
+            ///
+            /// Fields that would keep their value are left out. Keyed operations are
+            /// described against the target as it is. Guards are not checked.
+            pub fn describe_against(&self, target: &#struct_name) -> String {
+                let parts = self.describe_parts_against(target);
+                if parts.is_empty() {
+                    "no changes".to_string()
+                } else {
+                    parts.join("; ")
+                }
+            }
+
+            #[doc(hidden)]
+            pub fn describe_parts(&self) -> Vec<String> {
+                #normalized_self
+                #helpers
+
+                #[allow(unused_mut)]
+                let mut parts = Vec::new();
+                #(#parts)*
+                parts
+            }
+
+            #[doc(hidden)]
+            pub fn describe_parts_against(&self, target: &#struct_name) -> Vec<String> {
+                #normalized_self
+                #helpers
+
+                #[allow(unused_mut)]
+                let mut parts = Vec::new();
+                #(#parts_against)*
+                parts
+            }
+        }
+    }
+}
+
+/// Whether a type is spelled `Option<...>`
+fn is_option_type(ty: &Type) -> bool {
+    matches!(
+        ty,
+        Type::Path(TypePath { path, .. })
+            if path.segments.last().is_some_and(|segment| segment.ident == "Option")
+    )
+}
+
+/// Generate the create type: a payload that builds the full source struct
+///
+/// Only emitted with `#[substruct_builder(create)]`. Every source field must be
+/// covered, either by being updatable or by having a `default`.
+pub fn generate_create_impl(
+    struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    let Some(create_name) = &context.create_struct_name else {
+        return quote! {};
+    };
+    let error_name = format_ident!("{}Error", create_name);
+    let validation_errors = format_ident!("{}ValidationErrors", context.update_struct_name);
+
+    let mut uncovered = false;
+    for field in &context.create_fields {
+        if field.role == CreateRole::Uncovered {
+            emit_error!(
+                field.ident, "`{}` can't be built by `{}`", field.ident, create_name;
+                note = "`create` requires every field of `{}` to be covered", struct_name;
+                help = "annotate it with #[substruct_field(...)] or give it #[substruct_field(default = \"...\")]"
+            );
+            uncovered = true;
+        }
+    }
+    if uncovered {
+        return quote! {};
+    }
+
+    let mut payload_fields = Vec::new();
+    let mut required_names = Vec::new();
+    let mut required_types = Vec::new();
+    let mut initializers = Vec::new();
+    for field in &context.create_fields {
+        let name = &field.ident;
+        let ty = &field.ty;
+        let rename = field
+            .serde_rename
+            .as_ref()
+            .map(|rename| quote! { #[serde(rename = #rename)] });
+        match (field.role, &field.default) {
+            (CreateRole::Payload, Some(default)) => {
+                payload_fields.push(quote! { #rename pub #name: Option<#ty> });
+                initializers.push(quote! { #name: self.#name.unwrap_or_else(|| #default) });
+            }
+            (CreateRole::Payload, None) if is_option_type(ty) => {
+                payload_fields.push(quote! { #rename pub #name: #ty });
+                initializers.push(quote! { #name: self.#name });
+            }
+            (CreateRole::Payload, None) => {
+                payload_fields.push(quote! { #rename pub #name: Option<#ty> });
+                required_names.push(name);
+                required_types.push(ty);
+                initializers.push(quote! { #name });
+            }
+            (CreateRole::Version, None) => {
+                initializers.push(quote! { #name: <#ty as Default>::default() });
+            }
+            (_, Some(default)) => initializers.push(quote! { #name: #default }),
+            (_, None) => {}
+        }
+    }
+
+    let new_body = if required_names.is_empty() {
+        quote! { Self::default() }
+    } else {
+        // Optional payload fields start unset
+        let rest =
+            (payload_fields.len() > required_names.len()).then(|| quote! { ..Default::default() });
+        quote! {
+            Self {
+                #(#required_names: Some(#required_names),)*
+                #rest
+            }
+        }
+    };
+    let required_check = (!required_names.is_empty()).then(|| {
+        quote! {
+            let mut missing = Vec::new();
+            #(if self.#required_names.is_none() {
+                missing.push(stringify!(#required_names));
+            })*
+            if !missing.is_empty() {
+                return Err(#error_name::MissingFields { fields: missing });
+            }
+            let (#(Some(#required_names),)*) = (#(self.#required_names,)*) else {
+                unreachable!("required fields were checked above");
+            };
+        }
+    });
+    let create_normalize_steps = &context.create_normalize_steps;
+    let create_validation_checks = &context.create_validation_checks;
+    let validate_built = (!create_validation_checks.is_empty()).then(|| {
+        quote! {
+            let mut errors: Vec<(String, String)> = Vec::new();
+            #(#create_validation_checks)*
+            if !errors.is_empty() {
+                return Err(#error_name::Invalid(#validation_errors { errors }));
+            }
+        }
+    });
+    let built_binding = if create_normalize_steps.is_empty() {
+        quote! { let built }
+    } else {
+        quote! { let mut built }
+    };
+    // Sensitive values must not leak through a derived `Debug`
+    let rename_all = context
+        .serde_rename_all
+        .as_ref()
+        .map(|rule| quote! { #[serde(rename_all = #rule)] });
+    let debug_derive = context
+        .sensitive_field_names
+        .is_empty()
+        .then(|| quote! { Debug, });
+
+    quote! {
+        /// Payload for creating a new value of the source struct.
+        ///
+        /// Non-`Option` fields without a `default` are required; `try_build` reports the
+        /// ones that are missing.
+        #[derive(#debug_derive Clone, Default, Serialize, Deserialize)]
+        #rename_all
+        pub struct #create_name {
+            #(#payload_fields,)*
+        }
+
+        /// Error returned by `try_build`.
+        #[derive(Debug, Clone, PartialEq)]
+        pub enum #error_name {
+            /// Required fields were not provided.
+            MissingFields {
+                /// Names of the missing fields, in declaration order.
+                fields: Vec<&'static str>,
+            },
+            /// The built value failed its `validate(...)` rules, after its transforms ran.
+            Invalid(#validation_errors),
+        }
+
+        impl std::fmt::Display for #error_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    Self::MissingFields { fields } => {
+                        write!(f, "missing required fields: {}", fields.join(", "))
+                    }
+                    Self::Invalid(errors) => write!(f, "invalid fields: {}", errors),
+                }
+            }
+        }
+
+        impl std::error::Error for #error_name {}
+
+        impl #create_name {
+            /// Creates a payload with every required field set and the rest left to their defaults.
+            #[allow(clippy::too_many_arguments)]
+            pub fn new(#(#required_names: #required_types),*) -> Self {
+                #new_body
+            }
+
+            /// Builds the source struct, filling unset fields from their `default`.
+            ///
+            /// The `transform`/`transform_with` normalizers run on the built value, which
+            /// is then checked against the `validate(...)` rules.
+            ///
+            /// # Errors
+            ///
+            /// - `MissingFields` with the names of the required fields that are not set
+            /// - `Invalid` with every failed rule, as `validate` reports them
+            pub fn try_build(self) -> Result<#struct_name, #error_name> {
+                #required_check
+                #built_binding = #struct_name {
+                    #(#initializers,)*
+                };
+                #(#create_normalize_steps)*
+                #validate_built
+                Ok(built)
+            }
+        }
+    }
+}
+
+/// Generate a `Debug` impl that hides the values of sensitive fields
+///
+/// Only emitted when a field is marked `sensitive`; otherwise `Debug` is derived.
+pub fn generate_debug_impl(
+    update_struct_name: &Ident,
+    context: &FieldContext,
+) -> proc_macro2::TokenStream {
+    if context.sensitive_field_names.is_empty() {
+        return quote! {};
+    }
+
+    let is_sensitive = |name: &Ident| context.sensitive_field_names.contains(name);
+    let mut debug_fields: Vec<_> = context
+        .field_names
+        .iter()
+        .map(|name| {
+            if !is_sensitive(name) {
+                quote! { .field(stringify!(#name), &self.#name) }
+            } else if context.unwrapped_field_names.contains(name) {
+                quote! { .field(stringify!(#name), &Redacted) }
+            } else {
+                quote! { .field(stringify!(#name), &self.#name.as_ref().map(|_| Redacted)) }
+            }
+        })
+        .collect();
+    if context.version_field.is_some() {
+        debug_fields.push(quote! { .field("expected_version", &self.expected_version) });
+    }
+    for (name, slot) in context
+        .expect_field_names
+        .iter()
+        .zip(&context.expect_slot_names)
+    {
+        if is_sensitive(name) {
+            debug_fields
+                .push(quote! { .field(stringify!(#slot), &self.#slot.as_ref().map(|_| Redacted)) });
+        } else {
+            debug_fields.push(quote! { .field(stringify!(#slot), &self.#slot) });
+        }
+    }
+
+    quote! {
+        impl std::fmt::Debug for #update_struct_name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                struct Redacted;
+
+                impl std::fmt::Debug for Redacted {
+                    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                        f.write_str("<redacted>")
+                    }
+                }
+
+                f.debug_struct(stringify!(#update_struct_name))
+                    #(#debug_fields)*
+                    .finish()
+            }
+        }
     }
 }
 
@@ -572,6 +5349,7 @@ pub fn generate_complete_output(
     let trait_config = TraitConfig {
         contains_f64: context.contains_f64,
         contains_string: context.contains_string,
+        redact_debug: !context.sensitive_field_names.is_empty(),
         ..Default::default()
     };
 
@@ -582,6 +5360,25 @@ pub fn generate_complete_output(
     let default_impl = generate_default_impl(update_struct_name, context);
     let from_impl = generate_from_impl(update_struct_name, struct_name, context);
     let from_ref_impl = generate_from_ref_impl(struct_name, update_struct_name, context);
+    let snapshot_impl = generate_snapshot_impl(update_struct_name, struct_name, context);
+    let path_impl = generate_path_impl(update_struct_name, context);
+    let validation_impl = generate_validation_impl(update_struct_name, struct_name, context);
+    let keyed_vec_impl = generate_keyed_vec_impl(update_struct_name, context);
+    let guard_impl = generate_guard_impl(update_struct_name, context);
+    let report_impl = generate_report_impl(update_struct_name, struct_name, context);
+    let describe_impl = generate_describe_impl(update_struct_name, struct_name, context);
+    let env_impl = generate_env_impl(update_struct_name, context);
+    let mask_impl = generate_mask_impl(update_struct_name, struct_name, context);
+    let document_impl = generate_document_impl(update_struct_name, context);
+    let graphql_impl = generate_graphql_impl(update_struct_name, context);
+    let layered_impl = generate_layered_impl(update_struct_name, context);
+    let schema_impl = generate_schema_impl(update_struct_name, context);
+    let ts_impl = generate_ts_impl(update_struct_name, context);
+    let cli_impl = generate_cli_impl(update_struct_name, context);
+    let proptest_impl = generate_proptest_impl(update_struct_name, struct_name, context);
+    let column_impl = generate_column_impl(update_struct_name, context);
+    let create_impl = generate_create_impl(struct_name, context);
+    let debug_impl = generate_debug_impl(update_struct_name, context);
 
     quote! {
         #derive_clause
@@ -594,5 +5391,43 @@ pub fn generate_complete_output(
         #from_impl
 
         #from_ref_impl
+
+        #snapshot_impl
+
+        #path_impl
+
+        #validation_impl
+
+        #keyed_vec_impl
+
+        #guard_impl
+
+        #report_impl
+
+        #describe_impl
+
+        #env_impl
+
+        #mask_impl
+
+        #document_impl
+
+        #layered_impl
+
+        #schema_impl
+
+        #ts_impl
+
+        #cli_impl
+
+        #proptest_impl
+
+        #column_impl
+
+        #graphql_impl
+
+        #debug_impl
+
+        #create_impl
     }
 }
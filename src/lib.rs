@@ -1,5 +1,5 @@
 use proc_macro::TokenStream;
-use proc_macro_error::{abort, proc_macro_error};
+use proc_macro_error::{abort, abort_if_dirty, emit_error, proc_macro_error};
 use quote::format_ident;
 use syn::{Data, DeriveInput, parse_macro_input};
 
@@ -7,7 +7,9 @@ mod generator;
 mod processor;
 
 use generator::generate_complete_output;
-use processor::attributes::{extract_struct_name, extract_trait_idents};
+use processor::attributes::{
+    diesel_table_name, extract_struct_options, extract_trait_idents, serde_rename_all,
+};
 use processor::fields::{FieldContext, get_redis_updatable_kind, process_field};
 
 /// Generates a substruct builder for partial updates with comprehensive utility methods.
@@ -52,23 +54,74 @@ use processor::fields::{FieldContext, get_redis_updatable_kind, process_field};
 /// struct Profile {
 ///     #[substruct_field(nested)]
 ///     address: Address,                                // -> Option<AddressSubstruct>
+///     #[substruct_field(nested)]
+///     billing: Option<Address>,                        // -> Option<Option<AddressSubstruct>>
+///     #[substruct_field(nested)]
+///     office: Arc<Address>,                            // -> Option<AddressSubstruct>
 /// }
 /// ```
 ///
+/// Nested source types may be wrapped in `Option`, `Box`, `Arc`, `Rc` or `Vec`:
+/// - `Option<T>`: `Some(None)` clears the value, `Some(Some(patch))` patches it, creating it
+///   from `T::default()` when missing
+/// - `Box<T>`: patched through the box
+/// - `Arc<T>` / `Rc<T>`: patched copy-on-write via `make_mut` (requires `T: Clone`)
+/// - `Vec<T>`: the patch is applied to every element
+///
 /// # Generated Methods
 ///
 /// The macro generates the following methods for your substruct:
 ///
 /// - `new(...)` - Constructor that takes all updatable fields as parameters
-/// - `from_source(source: &T) -> Self` - Creates a substruct from an existing instance
+/// - `from_source(source: &T) -> Self` - Creates a substruct from an existing instance (same as `From<&T>`)
+/// - `snapshot(source: &T) -> Self` - Creates a substruct holding the source's current values
+/// - `from_env(prefix: &str) -> Result<Self, EnvError>` - Loads `PREFIX_FIELD` variables (with
+///   `#[substruct_builder(env)]`)
 /// - `is_empty(&self) -> bool` - Returns true if no fields would be changed
 /// - `field_count(&self) -> usize` - Returns the number of fields with values set
 /// - `clear(&mut self)` - Resets all fields to their default values
-/// - `apply_to(&self, target: &mut T)` - Applies updates to a target struct
+/// - `apply_to(&self, target: &mut T)` - Applies updates to a target struct (returns
+///   `Result<(), ApplyError>` for structs with a version, `expect`, nested or keyed field)
+/// - `check_guards(&self, target: &T) -> Result<(), ApplyError>` - Checks the version and `expect` guards,
+///   including those of nested substructs and keyed element patches, without writing anything
+/// - `apply_unchecked(&self, target: &mut T)` - Applies updates without checking any guard
+/// - `apply_with_report(&self, target: &mut T) -> ChangeSet` - Applies updates and reports the modified
+///   field paths with old and new values, plus fields that were set to their current value
+///   (with `#[substruct_builder(report)]`)
+/// - `report_changes(&self, before: &T, after: &T) -> ChangeSet` - The same report for two states of the target
+///   (with `#[substruct_builder(report)]`)
+/// - `describe(&self) -> String` / `describe_against(&self, target: &T) -> String` - Human-readable
+///   summaries such as `name: 'Alice' → 'Bob'; nickname: cleared`, using field labels
+///   (with `#[substruct_builder(describe)]`)
 /// - `would_change(&self, target: &T) -> bool` - Checks if updates would modify target
 /// - `merge(self, other: Self) -> Self` - Combines two substructs
-/// - `has_field(&self, field_name: &str) -> bool` - Checks if a specific field is set
+/// - `json_schema() -> serde_json::Value` - JSON Schema of the substruct's wire format, with nested
+///   substructs in `$defs` and `validate(...)` rules as constraints (with `#[substruct_builder(json_schema)]`)
+/// - `ts_declaration() -> String` - TypeScript interfaces of the substruct and its nested substructs
+///   (with `#[substruct_builder(typescript)]`)
+/// - `merge_all(layers) -> Layered` - Folds named layers in order; `explain("database.host")` returns the
+///   layer that supplied a field's final value (with `#[substruct_builder(layered)]`)
+/// - `has_field(&self, field_name: &str) -> bool` - Checks if a specific field (or dotted path) is set
 /// - `into_partial(self) -> HashMap<String, String>` - Converts to flexible HashMap representation
+/// - `set_by_path(&mut self, path: &str, value: serde_json::Value) -> Result<(), PathError>` - Sets a field from a dotted path
+///   (with `#[substruct_builder(paths)]`)
+/// - `get_by_path(&self, path: &str) -> Option<serde_json::Value>` - Reads a set field from a dotted path
+///   (with `#[substruct_builder(paths)]`)
+/// - `to_update_document(&self) -> serde_json::Value` - MongoDB-style `$set` / `$unset` / `$push` / `$pull`
+///   update document with dotted paths for nested fields, plus `update_array_filters()` for keyed element
+///   patches (with `#[substruct_builder(document)]`)
+/// - `to_update_stages(&self) -> Vec<UpdateStage>` - The same update as ordered stages, each with its filter
+///   and `arrayFilters`, for updates a single document can't express; `version_filter()` returns the version
+///   guard (with `#[substruct_builder(document)]`)
+/// - `field_mask(&self) -> Vec<String>` - Dotted paths of the set fields, like a protobuf `FieldMask`
+///   (with `#[substruct_builder(mask)]`)
+/// - `from_source_with_mask(source: &T, mask: &[&str]) -> Result<Self, MaskError>` - Copies the masked
+///   fields of a full message, rejecting paths that can't be patched (with `#[substruct_builder(mask)]`)
+/// - `validate(&self) -> Result<(), ValidationErrors>` - Checks the normalized set fields against their `validate(...)` rules
+/// - `try_apply_to(&self, target: &mut T) -> Result<(), ValidationErrors>` - Validates, then applies
+/// - `normalize(&mut self)` - Applies `transform`/`transform_with` normalizers to set fields
+/// - `with_expected_version(self, version) -> Self` - Guards `apply_to` on the target's version (versioned structs only)
+/// - `expect_<field>(self, value) -> Self` - Guards `apply_to` on the target's field value (`expect` fields only)
 ///
 /// # Examples
 ///
@@ -140,14 +193,58 @@ use processor::fields::{FieldContext, get_redis_updatable_kind, process_field};
 /// - `#[substruct_field(json)]` - Include as JSON field (Option<serde_json::Value>)
 /// - `#[substruct_field(nested)]` - Include as nested substruct (Option<TypeSubstruct>)
 /// - `#[substruct_field(nested, nested_type = "CustomName")]` - Use custom name for nested type
+/// - `#[substruct_field(nested_vec, key = "id")]` - Patch `Vec<T>` elements by key through a list of
+///   `<Substruct><Field>Op` operations (`Upsert`, `Remove`, `Patch`); `key_type = "u64"` sets the key type (default `String`)
+/// - `#[substruct_field(primitive, validate(...))]` - Validation rules: `range(min = .., max = ..)`,
+///   `length(min = .., max = ..)`, `regex = "..."` (requires the `regex` crate) and `custom = "path::to::fn"`
+/// - `#[substruct_field(primitive, transform = "trim|lowercase")]` - Normalize set values (`trim`, `lowercase`, `uppercase`)
+/// - `#[substruct_field(version)]` - Optimistic concurrency version: the substruct carries `expected_version`,
+///   `apply_to` fails with a `VersionConflict` when the target's version differs and bumps it on success
+/// - `#[substruct_field(primitive, expect)]` - Precondition: the substruct carries `expect_<field>`, and `apply_to`
+///   fails with `PreconditionFailed` when the target's value differs, before anything is written
+/// - `#[substruct_field(..., default = "expr")]` - Fallback value used by the create type, and the "no change"
+///   value of `option = false` fields (instead of `Default::default()`); on its own,
+///   `#[substruct_field(default = "expr")]` covers a field that isn't updatable
+/// - `#[substruct_field(primitive, label = "Display Name")]` - Name the field in `describe` summaries
+///   (any kind except `version` and `skip`)
+/// - `#[substruct_field(primitive, sensitive)]` - Redact the value as `<redacted>` in the substruct's `Debug`,
+///   `into_partial`, change reports and precondition errors
+/// - `#[substruct_field(primitive, transform_with = "path::to::fn")]` - Normalize set values with a `fn(T) -> T`
+/// - `#[substruct_field(primitive, env = "NAME")]` - Read the field from `NAME` instead of `PREFIX_FIELD`
+///   in `from_env` (primitive, json and nested fields)
 ///
 /// ## Struct Attributes
 /// - `#[substruct_builder(name = "CustomName")]` - Set custom name for the generated substruct
+/// - `#[substruct_builder(from = "empty" | "snapshot")]` - What `From<T>` / `From<&T>` produce: an empty
+///   substruct (default) or a `snapshot`
+/// - `#[substruct_builder(create)]` - Also generate `Create<Struct>` (or `create = "CustomName"`), a payload
+///   whose `try_build()` returns the normalized and validated source struct, or a `Create<Struct>Error`
+/// - `#[substruct_builder(env)]` - Also generate `from_env` / `from_env_with`; primitive fields must
+///   implement `FromStr` and nested types must opt in too
+/// - `#[substruct_builder(clap)]` - Implement `clap::Args` and `clap::FromArgMatches` with `--field`,
+///   `--address-city` and `--no-field` flags (requires the `clap` feature)
+/// - `#[substruct_builder(proptest)]` - Implement `proptest::arbitrary::Arbitrary` and generate the
+///   `check_laws` family of law checks for property tests (requires the `proptest` feature and a
+///   `proptest` dependency; use `cfg_attr(test, ...)` to keep it a dev-dependency)
+/// - `#[substruct_builder(diesel)]` - Generate a `changeset()` that rejects empty updates and implements
+///   diesel's `AsChangeset` for the primitive fields' columns, using the source's
+///   `#[diesel(table_name = ...)]`, plus `version_filter()` for versioned structs (requires the `diesel` feature)
+/// - `#[substruct_builder(sqlx)]` - Generate `push_set_clauses(&mut QueryBuilder)` for the primitive fields'
+///   columns, rejecting empty updates, plus `push_version_filter` for versioned structs (requires the `sqlx`
+///   feature)
+/// - `#[substruct_builder(graphql)]` - Generate a `<Substruct>Input` deriving async-graphql's `InputObject`,
+///   convertible into the substruct, with keyed collections as lists of `@oneOf` operations (requires the
+///   `async-graphql` feature)
+/// - `#[substruct_builder(paths, report, describe, json_schema, typescript, mask, document, layered)]` - Opt
+///   into the matching generated methods listed above; each one recurses into nested substructs, so nested
+///   types must opt in too
 ///
 /// # Requirements
 ///
 /// - **Rust 1.85.0+** (Rust Edition 2024)
 /// - `serde` for serialization support
+/// - `serde_json` for `json` fields and the `paths`, `report`, `describe`, `json_schema`, `document`
+///   and `proptest` struct options
 /// - Fields must implement `Clone` and `PartialEq`
 /// - At least one field must be tagged with `#[substruct_field]`
 #[proc_macro_error]
@@ -157,7 +254,10 @@ pub fn derive_updatable_struct(input: TokenStream) -> TokenStream {
     let struct_name = &input.ident;
 
     // Extract the custom name from the struct-level attribute, or use default
-    let update_struct_name = extract_struct_name(&input.attrs)
+    let struct_options = extract_struct_options(&input.attrs);
+    let update_struct_name = struct_options
+        .name
+        .clone()
         .unwrap_or_else(|| format_ident!("{}Substruct", struct_name));
 
     let fields = match &input.data {
@@ -170,12 +270,65 @@ pub fn derive_updatable_struct(input: TokenStream) -> TokenStream {
 
     let mut trait_idents = extract_trait_idents(&input.attrs);
 
-    let mut context = FieldContext::new();
+    let mut context = FieldContext::new(&update_struct_name);
+    context.from_policy = struct_options.from_policy;
+    context.env_enabled = struct_options.env;
+    context.paths_enabled = struct_options.paths;
+    context.report_enabled = struct_options.report;
+    context.describe_enabled = struct_options.describe;
+    context.schema_enabled = struct_options.json_schema;
+    context.ts_enabled = struct_options.typescript;
+    context.mask_enabled = struct_options.mask;
+    context.document_enabled = struct_options.document;
+    context.layered_enabled = struct_options.layered;
+    context.cli_enabled = struct_options.clap;
+    context.proptest_enabled = struct_options.proptest;
+    context.sqlx_enabled = struct_options.sqlx;
+    if struct_options.graphql {
+        context.graphql_enabled = true;
+        context.graphql_docs = input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("doc"))
+            .cloned()
+            .collect();
+    }
+    if struct_options.diesel {
+        context.diesel_table = diesel_table_name(&input.attrs);
+        if context.diesel_table.is_none() {
+            emit_error!(
+                input.ident, "`diesel` needs the table of the source struct";
+                help = "add #[diesel(table_name = ...)] to the struct"
+            );
+        }
+    }
+    context.serde_rename_all = serde_rename_all(&input.attrs);
+    if struct_options.create {
+        context.create_struct_name = Some(
+            struct_options
+                .create_name
+                .clone()
+                .unwrap_or_else(|| format_ident!("Create{}", struct_name)),
+        );
+    }
+
+    // Parse every attribute first so all attribute errors are reported together
+    let parsed_fields: Vec<_> = fields
+        .iter()
+        .map(|field| get_redis_updatable_kind(&field.attrs))
+        .collect();
+    abort_if_dirty();
 
-    for field in fields.iter() {
-        let field_kind = get_redis_updatable_kind(&field.attrs);
-        process_field(field, &field_kind, &mut context);
+    for (field, (field_kind, options)) in fields.iter().zip(&parsed_fields) {
+        process_field(field, field_kind, options, &mut context);
+    }
+    if (struct_options.diesel || struct_options.sqlx) && context.column_fields.is_empty() {
+        emit_error!(
+            input.ident, "`diesel` and `sqlx` need at least one primitive field";
+            note = "only primitive fields map to table columns"
+        );
     }
+    abort_if_dirty();
 
     // Generate the complete output using the generator module
     let output = generate_complete_output(
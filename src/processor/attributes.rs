@@ -1,37 +1,435 @@
-use quote::format_ident;
+use proc_macro_error::emit_error;
+use quote::ToTokens;
 use syn::punctuated::Punctuated;
 use syn::token::Comma;
-use syn::{Attribute, Meta, Path, Token};
+use syn::{Attribute, Expr, Lit, LitStr, Meta, Path, Token};
 
-/// Extract the custom struct name from the `substruct_builder` attribute
+/// Options parsed from the struct-level `substruct_builder` attribute
+#[derive(Default)]
+pub struct StructOptions {
+    pub name: Option<syn::Ident>,
+    /// Generate a create type (`create`), optionally with a custom name (`create = "NewUser"`)
+    pub create: bool,
+    pub create_name: Option<syn::Ident>,
+    /// What `From<T>` / `From<&T>` produce (`from = "empty" | "snapshot"`)
+    pub from_policy: FromPolicy,
+    /// Generate `from_env` / `from_env_with` (`env`)
+    pub env: bool,
+    /// Implement `clap::Args` / `clap::FromArgMatches` (`clap`, requires the `clap` feature)
+    pub clap: bool,
+    /// Implement `proptest::arbitrary::Arbitrary` and the law checks (`proptest`, requires the
+    /// `proptest` feature)
+    pub proptest: bool,
+    /// Implement diesel's `AsChangeset` (`diesel`, requires the `diesel` feature)
+    pub diesel: bool,
+    /// Generate `push_set_clauses` for sqlx's `QueryBuilder` (`sqlx`, requires the `sqlx` feature)
+    pub sqlx: bool,
+    /// Generate a GraphQL input object (`graphql`, requires the `async-graphql` feature)
+    pub graphql: bool,
+    /// Generate `set_by_path` / `get_by_path` (`paths`)
+    pub paths: bool,
+    /// Generate `apply_with_report` / `report_changes` (`report`)
+    pub report: bool,
+    /// Generate `describe` / `describe_against` (`describe`)
+    pub describe: bool,
+    /// Generate `json_schema` (`json_schema`)
+    pub json_schema: bool,
+    /// Generate `ts_declaration` (`typescript`)
+    pub typescript: bool,
+    /// Generate `field_mask` / `from_source_with_mask` (`mask`)
+    pub mask: bool,
+    /// Generate `to_update_document` and `to_update_stages` (`document`)
+    pub document: bool,
+    /// Generate `merge_all` and the `Layered` result type (`layered`)
+    pub layered: bool,
+}
+
+/// Policy for the generated `From<T>` / `From<&T>` impls
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum FromPolicy {
+    /// Every field in its "no change" state
+    #[default]
+    Empty,
+    /// Every field set to the source's current value, like `snapshot`
+    Snapshot,
+}
+
+const STRUCT_OPTIONS: &[&str] = &[
+    "name",
+    "create",
+    "from",
+    "env",
+    "clap",
+    "proptest",
+    "diesel",
+    "sqlx",
+    "graphql",
+    "paths",
+    "report",
+    "describe",
+    "json_schema",
+    "typescript",
+    "mask",
+    "document",
+    "layered",
+];
+const FROM_POLICIES: &[&str] = &["empty", "snapshot"];
+
+/// Extract the struct-level options from the `substruct_builder` attribute
 ///
-/// This function looks for the `name` parameter in the `substruct_builder` attribute
-/// and returns the custom name if found, or None if not specified.
-pub fn extract_struct_name(attrs: &[Attribute]) -> Option<syn::Ident> {
+/// Unknown keys, duplicate keys and wrong literal types are reported as spanned
+/// errors; every problem is emitted before the macro aborts.
+pub fn extract_struct_options(attrs: &[Attribute]) -> StructOptions {
+    let mut options = StructOptions::default();
+    let mut seen = Vec::new();
+
     for attr in attrs {
-        if attr.path().is_ident("substruct_builder") {
-            let Ok(meta_list) =
-                attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
-            else {
+        if !attr.path().is_ident("substruct_builder") {
+            continue;
+        }
+
+        let meta_list = match attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+        {
+            Ok(meta_list) => meta_list,
+            Err(err) => {
+                emit_error!(
+                    attr, "invalid #[substruct_builder] attribute: {}", err;
+                    help = "expected e.g. #[substruct_builder(name = \"UserUpdate\")]"
+                );
                 continue;
-            };
+            }
+        };
 
-            for meta in meta_list {
-                if let Meta::NameValue(name_value) = &meta {
-                    if name_value.path.is_ident("name") {
-                        if let syn::Expr::Lit(syn::ExprLit {
-                            lit: syn::Lit::Str(lit_str),
-                            ..
-                        }) = &name_value.value
-                        {
-                            return Some(format_ident!("{}", lit_str.value()));
+        for meta in meta_list {
+            let key = meta_key(&meta);
+            if !check_duplicate(&meta, &key, &mut seen) {
+                continue;
+            }
+
+            match &meta {
+                Meta::NameValue(name_value) if key == "name" => {
+                    if let Some(lit_str) = expect_lit_str(&name_value.value, &key) {
+                        match syn::parse_str::<syn::Ident>(&lit_str.value()) {
+                            Ok(ident) => options.name = Some(ident),
+                            Err(_) => emit_error!(
+                                lit_str,
+                                "`{}` is not a valid struct name",
+                                lit_str.value()
+                            ),
+                        }
+                    }
+                }
+                Meta::Path(_) if key == "create" => options.create = true,
+                Meta::Path(_) if key == "env" => options.env = true,
+                Meta::Path(_) if key == "paths" => options.paths = true,
+                Meta::Path(_) if key == "report" => options.report = true,
+                Meta::Path(_) if key == "describe" => options.describe = true,
+                Meta::Path(_) if key == "json_schema" => options.json_schema = true,
+                Meta::Path(_) if key == "typescript" => options.typescript = true,
+                Meta::Path(_) if key == "mask" => options.mask = true,
+                Meta::Path(_) if key == "document" => options.document = true,
+                Meta::Path(_) if key == "layered" => options.layered = true,
+                Meta::Path(_) if key == "clap" => {
+                    if cfg!(feature = "clap") {
+                        options.clap = true;
+                    } else {
+                        emit_error!(
+                            meta, "`clap` requires the `clap` feature of substruct-genesis";
+                            help = "enable it with `features = [\"clap\"]`"
+                        );
+                    }
+                }
+                Meta::Path(_) if key == "proptest" => {
+                    if cfg!(feature = "proptest") {
+                        options.proptest = true;
+                    } else {
+                        emit_error!(
+                            meta, "`proptest` requires the `proptest` feature of substruct-genesis";
+                            help = "enable it with `features = [\"proptest\"]`"
+                        );
+                    }
+                }
+                Meta::Path(_) if key == "diesel" => {
+                    if cfg!(feature = "diesel") {
+                        options.diesel = true;
+                    } else {
+                        emit_error!(
+                            meta, "`diesel` requires the `diesel` feature of substruct-genesis";
+                            help = "enable it with `features = [\"diesel\"]`"
+                        );
+                    }
+                }
+                Meta::Path(_) if key == "sqlx" => {
+                    if cfg!(feature = "sqlx") {
+                        options.sqlx = true;
+                    } else {
+                        emit_error!(
+                            meta, "`sqlx` requires the `sqlx` feature of substruct-genesis";
+                            help = "enable it with `features = [\"sqlx\"]`"
+                        );
+                    }
+                }
+                Meta::Path(_) if key == "graphql" => {
+                    if cfg!(feature = "async-graphql") {
+                        options.graphql = true;
+                    } else {
+                        emit_error!(
+                            meta, "`graphql` requires the `async-graphql` feature of substruct-genesis";
+                            help = "enable it with `features = [\"async-graphql\"]`"
+                        );
+                    }
+                }
+                Meta::NameValue(name_value) if key == "create" => {
+                    if let Some(lit_str) = expect_lit_str(&name_value.value, &key) {
+                        match syn::parse_str::<syn::Ident>(&lit_str.value()) {
+                            Ok(ident) => {
+                                options.create = true;
+                                options.create_name = Some(ident);
+                            }
+                            Err(_) => emit_error!(
+                                lit_str,
+                                "`{}` is not a valid struct name",
+                                lit_str.value()
+                            ),
                         }
                     }
                 }
+                Meta::NameValue(name_value) if key == "from" => {
+                    if let Some(lit_str) = expect_lit_str(&name_value.value, &key) {
+                        match lit_str.value().as_str() {
+                            "empty" => options.from_policy = FromPolicy::Empty,
+                            "snapshot" => options.from_policy = FromPolicy::Snapshot,
+                            other => emit_error!(
+                                lit_str, "unknown `from` policy `{}`", other;
+                                help = "expected one of: {}", FROM_POLICIES.join(", ")
+                            ),
+                        }
+                    }
+                }
+                _ => report_unknown_key(&meta, &key, "substruct_builder", STRUCT_OPTIONS, &[]),
+            }
+        }
+    }
+
+    options
+}
+
+/// The key of a meta item as a string (empty for multi-segment paths)
+pub fn meta_key(meta: &Meta) -> String {
+    meta.path()
+        .get_ident()
+        .map(|ident| ident.to_string())
+        .unwrap_or_default()
+}
+
+/// Record `key` as seen, emitting an error and returning `false` if it was already given
+pub fn check_duplicate(meta: &Meta, key: &str, seen: &mut Vec<String>) -> bool {
+    if seen.iter().any(|previous| previous == key) {
+        emit_error!(
+            meta.path(), "duplicate key `{}`", key;
+            help = "remove one of the `{}` entries", key
+        );
+        return false;
+    }
+    seen.push(key.to_string());
+    true
+}
+
+/// Emit an "unknown key" error with a "did you mean" suggestion
+///
+/// `value_keys` take a value (`key = ..` or `key(..)`) while `flag_keys` are bare
+/// words; using one in the other's form gets a targeted message instead.
+pub fn report_unknown_key(
+    meta: &Meta,
+    key: &str,
+    attribute: &str,
+    value_keys: &[&str],
+    flag_keys: &[&str],
+) {
+    let takes_value = !matches!(meta, Meta::Path(_));
+    if value_keys.contains(&key) && !takes_value {
+        emit_error!(
+            meta, "`{}` expects a value", key;
+            help = "write it as `{} = ...`", key
+        );
+    } else if flag_keys.contains(&key) && takes_value {
+        emit_error!(
+            meta, "`{}` does not take a value", key;
+            help = "write it as a bare `{}`", key
+        );
+    } else {
+        let candidates: Vec<&str> = value_keys.iter().chain(flag_keys).copied().collect();
+        match did_you_mean(key, &candidates) {
+            Some(suggestion) => emit_error!(
+                meta.path(), "unknown {} option `{}`", attribute, key;
+                help = "did you mean `{}`?", suggestion
+            ),
+            None => emit_error!(
+                meta.path(), "unknown {} option `{}`", attribute, key;
+                help = "expected one of: {}", candidates.join(", ")
+            ),
+        }
+    }
+}
+
+/// Return the candidate closest to `name`, if it is close enough to be a typo
+pub fn did_you_mean<'a>(name: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(name, candidate), *candidate))
+        .filter(|(distance, candidate)| *distance <= (candidate.len() / 3).max(1))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Expect a string literal for `key`, emitting an error otherwise
+pub fn expect_lit_str(value: &Expr, key: &str) -> Option<LitStr> {
+    match value {
+        Expr::Lit(syn::ExprLit {
+            lit: Lit::Str(lit_str),
+            ..
+        }) => Some(lit_str.clone()),
+        _ => {
+            emit_error!(
+                value, "expected a string literal for `{}`", key;
+                help = "write it as `{} = \"...\"`", key
+            );
+            None
+        }
+    }
+}
+
+/// Parse the function path of `key = "path::to::fn"`, emitting an error at the literal otherwise
+pub fn expect_fn_path(lit_str: &LitStr, key: &str) -> Option<proc_macro2::TokenStream> {
+    match lit_str.parse::<Path>() {
+        Ok(path) => Some(path.to_token_stream()),
+        Err(_) => {
+            emit_error!(
+                lit_str,
+                "`{}` must be a path to a function, e.g. \"path::to::fn\"",
+                key
+            );
+            None
+        }
+    }
+}
+
+/// Expect a boolean literal for `key`, emitting an error otherwise
+pub fn expect_lit_bool(value: &Expr, key: &str) -> Option<bool> {
+    match value {
+        Expr::Lit(syn::ExprLit {
+            lit: Lit::Bool(lit_bool),
+            ..
+        }) => Some(lit_bool.value),
+        _ => {
+            emit_error!(
+                value, "expected a boolean literal for `{}`", key;
+                help = "write it as `{} = true` or `{} = false`", key, key
+            );
+            None
+        }
+    }
+}
+
+/// The value of `key = "..."` (or `key = path`) in the source's `#[attr(...)]` attributes
+///
+/// These attributes belong to other derives (serde, diesel, sqlx), which report malformed
+/// attributes themselves, so anything unexpected is ignored here.
+fn foreign_attr_value(attrs: &[Attribute], attr: &str, key: &str) -> Option<String> {
+    attrs
+        .iter()
+        .filter(|candidate| candidate.path().is_ident(attr))
+        .filter_map(|candidate| {
+            candidate
+                .parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+                .ok()
+        })
+        .flatten()
+        .find_map(|meta| match meta {
+            Meta::NameValue(name_value) if name_value.path.is_ident(key) => {
+                match name_value.value {
+                    Expr::Lit(syn::ExprLit {
+                        lit: Lit::Str(lit_str),
+                        ..
+                    }) => Some(lit_str.value()),
+                    Expr::Path(path) => Some(
+                        path.path
+                            .segments
+                            .iter()
+                            .map(|segment| segment.ident.to_string())
+                            .collect::<Vec<_>>()
+                            .join("::"),
+                    ),
+                    _ => None,
+                }
             }
+            _ => None,
+        })
+}
+
+/// The source struct's `#[serde(rename_all = "...")]` rule
+pub fn serde_rename_all(attrs: &[Attribute]) -> Option<String> {
+    foreign_attr_value(attrs, "serde", "rename_all")
+}
+
+/// A source field's `#[serde(rename = "...")]` name
+pub fn serde_rename(attrs: &[Attribute]) -> Option<String> {
+    foreign_attr_value(attrs, "serde", "rename")
+}
+
+/// A source field's column name: `#[diesel(column_name = ...)]`, else `#[sqlx(rename = "...")]`
+pub fn column_rename(attrs: &[Attribute]) -> Option<String> {
+    foreign_attr_value(attrs, "diesel", "column_name")
+        .or_else(|| foreign_attr_value(attrs, "sqlx", "rename"))
+}
+
+/// The source struct's `#[diesel(table_name = ...)]` table module
+pub fn diesel_table_name(attrs: &[Attribute]) -> Option<Path> {
+    foreign_attr_value(attrs, "diesel", "table_name")
+        .and_then(|table| syn::parse_str::<Path>(&table).ok())
+}
+
+/// Apply a serde `rename_all` rule to a snake_case field name, as serde does
+pub fn apply_rename_rule(rule: &str, name: &str) -> String {
+    let pascal = || {
+        name.split('_')
+            .map(|word| {
+                let mut chars = word.chars();
+                chars.next().map_or_else(String::new, |first| {
+                    first.to_uppercase().chain(chars).collect()
+                })
+            })
+            .collect::<String>()
+    };
+    match rule {
+        "UPPERCASE" | "SCREAMING_SNAKE_CASE" => name.to_ascii_uppercase(),
+        "PascalCase" => pascal(),
+        "camelCase" => {
+            let pascal = pascal();
+            let mut chars = pascal.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_lowercase().chain(chars).collect()
+            })
         }
+        "kebab-case" => name.replace('_', "-"),
+        "SCREAMING-KEBAB-CASE" => name.to_ascii_uppercase().replace('_', "-"),
+        _ => name.to_string(),
     }
-    None
 }
 
 /// Extract trait identifiers from the `derive` attribute
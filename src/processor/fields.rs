@@ -1,55 +1,277 @@
-use proc_macro_error::abort;
-use quote::{format_ident, quote_spanned};
+use proc_macro_error::{abort, emit_error};
+use proc_macro2::Span;
+use quote::{format_ident, quote, quote_spanned};
 use syn::punctuated::Punctuated;
 use syn::spanned::Spanned;
-use syn::{Attribute, Field, Ident, Meta, Token, Type, TypePath};
+use syn::{Attribute, Expr, Field, Ident, Meta, Token, Type, TypePath};
+
+use super::attributes::{
+    FromPolicy, apply_rename_rule, check_duplicate, column_rename, expect_fn_path, expect_lit_bool,
+    expect_lit_str, meta_key, report_unknown_key, serde_rename,
+};
+use super::transform::{Transform, generate_transform_steps, parse_transforms};
+use super::validation::{Validation, generate_validation_checks, parse_validations};
 
 /// Enum representing different types of field processing
 #[derive(Debug, Clone)]
 pub enum FieldKind {
-    Primitive { option: bool },
-    Nested { nested_type: Option<String> },
+    Primitive {
+        option: bool,
+        validations: Vec<Validation>,
+        transforms: Vec<Transform>,
+        expect: bool,
+        sensitive: bool,
+    },
+    Nested {
+        nested_type: Option<String>,
+    },
+    NestedVec {
+        nested_type: Option<String>,
+        key: String,
+        key_type: Option<String>,
+    },
     Json,
+    Version,
     Skip,
     None,
 }
 
+/// Options shared by every field kind
+#[derive(Clone, Default)]
+pub struct FieldOptions {
+    /// Human-readable name used by `describe` / `describe_against`
+    pub label: Option<String>,
+    /// Value the create type falls back to when the field is not provided
+    pub default: Option<Expr>,
+    /// Variable name used by `from_env` instead of `PREFIX_FIELD`
+    pub env: Option<String>,
+    /// The source field's `#[serde(rename = "...")]`, carried over to the substruct
+    pub serde_rename: Option<String>,
+    /// The source field's diesel `column_name` or sqlx `rename`
+    pub column_rename: Option<String>,
+}
+
+/// How a source field is filled in by the generated create type
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CreateRole {
+    /// Provided in the payload (required unless it is an `Option` or has a default)
+    Payload,
+    /// A version field, starting at its default
+    Version,
+    /// Not in the payload, always set to its `default` expression
+    Fixed,
+    /// Neither updatable nor given a default; the create type can't build it
+    Uncovered,
+}
+
+/// A source field as seen by the generated create type
+pub struct CreateField {
+    pub ident: Ident,
+    pub ty: Type,
+    pub default: Option<Expr>,
+    pub role: CreateRole,
+    pub serde_rename: Option<String>,
+}
+
+/// The generated types of a keyed (`nested_vec`) field
+#[derive(Clone)]
+pub struct KeyedVec {
+    /// The `<Substruct><Field>Op` enum
+    pub op_type: Ident,
+    /// The element field the operations match on
+    pub key: Ident,
+    pub key_type: Type,
+    pub item_type: Type,
+    /// The substruct patching one element
+    pub nested_type: Ident,
+}
+
+/// A processed source field with the details the per-field generators need
+pub struct ProcessedField {
+    pub field: Field,
+    pub ident: Ident,
+    pub kind: FieldKind,
+    pub options: FieldOptions,
+    /// The serialized name of the field in the substruct
+    pub wire_name: String,
+    /// The "no change" sentinel of an `option = false` primitive field
+    pub no_change: Option<proc_macro2::TokenStream>,
+    /// The wrapper and substruct type of a nested field
+    pub nested: Option<(NestedWrapper, Ident)>,
+    pub keyed: Option<KeyedVec>,
+}
+
 /// Context for field processing that accumulates state during struct generation
 pub struct FieldContext {
+    pub update_struct_name: Ident,
+    /// Every field in declaration order, for the per-field generators
+    pub fields: Vec<ProcessedField>,
     pub updatable_fields: Vec<proc_macro2::TokenStream>,
+    /// Whether a field deserializes through `deserialize_present`
+    pub deserialize_present: bool,
     pub field_names: Vec<Ident>,
     pub field_types: Vec<proc_macro2::TokenStream>,
     pub contains_f64: bool,
     pub contains_string: bool,
     pub json_field_names: Vec<Ident>,
     pub unwrapped_field_names: Vec<Ident>,
+    pub unwrapped_field_defaults: Vec<proc_macro2::TokenStream>,
+    pub unwrapped_field_types: Vec<Type>,
     pub wrapped_field_names: Vec<Ident>,
     pub nested_field_names: Vec<Ident>,
+    pub nested_field_types: Vec<Ident>,
+    pub nested_field_wrappers: Vec<NestedWrapper>,
+    pub keyed_vec_field_names: Vec<Ident>,
+    pub keyed_vec_op_types: Vec<Ident>,
+    pub keyed_vec_keys: Vec<Ident>,
+    pub keyed_vec_key_types: Vec<Type>,
+    pub keyed_vec_item_types: Vec<Type>,
+    pub keyed_vec_nested_types: Vec<Ident>,
+    pub validation_checks: Vec<proc_macro2::TokenStream>,
+    pub normalize_steps: Vec<proc_macro2::TokenStream>,
+    /// `normalize_steps` and `validation_checks` for the value `try_build` creates, bound as `built`
+    pub create_normalize_steps: Vec<proc_macro2::TokenStream>,
+    pub create_validation_checks: Vec<proc_macro2::TokenStream>,
+    pub version_field: Option<(Ident, Type)>,
+    pub expect_field_names: Vec<Ident>,
+    pub expect_slot_names: Vec<Ident>,
+    pub expect_field_types: Vec<Type>,
+    pub sensitive_field_names: Vec<Ident>,
+    pub field_labels: Vec<(Ident, String)>,
+    pub create_struct_name: Option<Ident>,
+    pub create_fields: Vec<CreateField>,
+    pub from_policy: FromPolicy,
+    pub env_enabled: bool,
+    pub paths_enabled: bool,
+    pub report_enabled: bool,
+    pub describe_enabled: bool,
+    pub schema_enabled: bool,
+    pub ts_enabled: bool,
+    pub mask_enabled: bool,
+    pub document_enabled: bool,
+    pub layered_enabled: bool,
+    pub cli_enabled: bool,
+    pub proptest_enabled: bool,
+    /// The table module of `#[substruct_builder(diesel)]`
+    pub diesel_table: Option<syn::Path>,
+    pub sqlx_enabled: bool,
+    /// Primitive fields, which map to table columns
+    pub column_fields: Vec<Ident>,
+    pub column_names: Vec<String>,
+    pub column_types: Vec<Type>,
+    /// The version field's column, bumped by the database integrations
+    pub version_column: Option<String>,
+    pub graphql_enabled: bool,
+    /// The source struct's doc comments, the input object's description
+    pub graphql_docs: Vec<Attribute>,
+    /// The source struct's `#[serde(rename_all = "...")]`, carried over to the substruct
+    pub serde_rename_all: Option<String>,
 }
 
 impl FieldContext {
-    pub fn new() -> Self {
+    pub fn new(update_struct_name: &Ident) -> Self {
         Self {
+            update_struct_name: update_struct_name.clone(),
+            fields: Vec::new(),
             updatable_fields: Vec::new(),
+            deserialize_present: false,
             field_names: Vec::new(),
             field_types: Vec::new(),
             contains_f64: false,
             contains_string: false,
             json_field_names: Vec::new(),
             unwrapped_field_names: Vec::new(),
+            unwrapped_field_defaults: Vec::new(),
+            unwrapped_field_types: Vec::new(),
             wrapped_field_names: Vec::new(),
             nested_field_names: Vec::new(),
+            nested_field_types: Vec::new(),
+            nested_field_wrappers: Vec::new(),
+            keyed_vec_field_names: Vec::new(),
+            keyed_vec_op_types: Vec::new(),
+            keyed_vec_keys: Vec::new(),
+            keyed_vec_key_types: Vec::new(),
+            keyed_vec_item_types: Vec::new(),
+            keyed_vec_nested_types: Vec::new(),
+            validation_checks: Vec::new(),
+            normalize_steps: Vec::new(),
+            create_normalize_steps: Vec::new(),
+            create_validation_checks: Vec::new(),
+            version_field: None,
+            expect_field_names: Vec::new(),
+            expect_slot_names: Vec::new(),
+            expect_field_types: Vec::new(),
+            sensitive_field_names: Vec::new(),
+            field_labels: Vec::new(),
+            create_struct_name: None,
+            create_fields: Vec::new(),
+            from_policy: FromPolicy::Empty,
+            env_enabled: false,
+            paths_enabled: false,
+            report_enabled: false,
+            describe_enabled: false,
+            schema_enabled: false,
+            ts_enabled: false,
+            mask_enabled: false,
+            document_enabled: false,
+            layered_enabled: false,
+            cli_enabled: false,
+            proptest_enabled: false,
+            diesel_table: None,
+            sqlx_enabled: false,
+            column_fields: Vec::new(),
+            column_names: Vec::new(),
+            column_types: Vec::new(),
+            version_column: None,
+            graphql_enabled: false,
+            graphql_docs: Vec::new(),
+            serde_rename_all: None,
+        }
+    }
+
+    /// The serialized name of a substruct field, following the source's serde renames
+    pub fn wire_name(&self, name: &str, rename: Option<&str>) -> String {
+        let name = name.trim_start_matches("r#");
+        match (rename, &self.serde_rename_all) {
+            (Some(rename), _) => rename.to_string(),
+            (None, Some(rule)) => apply_rename_rule(rule, name),
+            (None, None) => name.to_string(),
         }
     }
 }
 
+/// Serde attributes of an `Option` update field: unset fields are left out when serialized
+fn skip_unset() -> proc_macro2::TokenStream {
+    quote! { #[serde(skip_serializing_if = "Option::is_none")] }
+}
+
+/// Serde attributes of an `Option<Option<_>>` update field
+///
+/// Besides leaving unset fields out, a present `null` deserializes to `Some(None)`, "clear",
+/// instead of `None`, "no change", so clears survive a round trip.
+fn skip_unset_keep_null(context: &mut FieldContext) -> proc_macro2::TokenStream {
+    context.deserialize_present = true;
+    let deserialize_with = format!("{}::deserialize_present", context.update_struct_name);
+    quote! {
+        #[serde(skip_serializing_if = "Option::is_none", deserialize_with = #deserialize_with)]
+    }
+}
+
 /// Process a primitive field with optional Option wrapping
+///
+/// Returns the "no change" sentinel of an `option = false` field.
+#[allow(clippy::too_many_arguments)]
 pub fn handle_primitive_field(
     field: &Field,
     ident: &Ident,
     option: bool,
+    validations: &[Validation],
+    transforms: &[Transform],
+    expect: bool,
+    sensitive: bool,
+    default: Option<&Expr>,
     context: &mut FieldContext,
-) {
+) -> Option<proc_macro2::TokenStream> {
     let span = field.span();
     let ty = &field.ty;
 
@@ -100,29 +322,180 @@ pub fn handle_primitive_field(
         quote_spanned! {span=> Option<#inner_ty> }
     };
 
+    let serde_attrs = if !option {
+        // Unwrapped fields are left out while they hold their "no change" value
+        let skip = format!("{}::is_unset_{}", context.update_struct_name, ident);
+        quote! { #[serde(skip_serializing_if = #skip)] }
+    } else if is_option {
+        skip_unset_keep_null(context)
+    } else {
+        skip_unset()
+    };
     context.updatable_fields.push(quote_spanned! {span=>
+        #serde_attrs
         pub #ident: #update_ty
     });
 
     context.field_names.push(ident.clone());
     context.field_types.push(update_ty.clone());
 
-    if !option {
+    // The "no change" sentinel of an unwrapped field; spell out the type so the
+    // comparison never needs inference
+    let no_change = (!option).then(|| match default {
+        Some(default) => quote_spanned! {span=> #default },
+        None => quote_spanned! {span=> <#ty as Default>::default() },
+    });
+
+    if !validations.is_empty() {
+        // Borrow the inner value of the normalized `this` only when the field is set to
+        // something. Sentinel comparisons keep the call-site span like the other generated
+        // ones, so lints on the user's default expression don't fire here
+        let set_value = match (&no_change, is_option) {
+            (None, true) => {
+                quote_spanned! {span=> this.#ident.as_ref().and_then(|value| value.as_ref()) }
+            }
+            (None, false) => quote_spanned! {span=> this.#ident.as_ref() },
+            (Some(no_change), true) => quote! {
+                (this.#ident != #no_change).then_some(&this.#ident).and_then(|value| value.as_ref())
+            },
+            (Some(no_change), false) => quote! {
+                (this.#ident != #no_change).then_some(&this.#ident)
+            },
+        };
+        let is_string = matches!(
+            &inner_ty,
+            Type::Path(TypePath { path, .. }) if path.segments.last().is_some_and(|segment| segment.ident == "String")
+        );
+        let checks = generate_validation_checks(ident, validations, is_string);
+        context.validation_checks.push(quote_spanned! {span=>
+            if let Some(value) = #set_value {
+                #checks
+            }
+        });
+        // A created value is always set, unless it is an `Option` holding `None`
+        let built_value = if is_option {
+            quote_spanned! {span=> built.#ident.as_ref() }
+        } else {
+            quote_spanned! {span=> Some(&built.#ident) }
+        };
+        context.create_validation_checks.push(quote_spanned! {span=>
+            if let Some(value) = #built_value {
+                #checks
+            }
+        });
+    }
+
+    if !transforms.is_empty() {
+        // The sentinel is left as is, so a transform can't turn it into a change
+        let set_value_mut = match (&no_change, is_option) {
+            (None, true) => {
+                quote_spanned! {span=> self.#ident.as_mut().and_then(|value| value.as_mut()) }
+            }
+            (None, false) => quote_spanned! {span=> self.#ident.as_mut() },
+            (Some(no_change), true) => quote! {
+                if self.#ident != #no_change { self.#ident.as_mut() } else { None }
+            },
+            (Some(no_change), false) => quote! {
+                if self.#ident != #no_change { Some(&mut self.#ident) } else { None }
+            },
+        };
+        let steps = generate_transform_steps(transforms);
+        context.normalize_steps.push(quote_spanned! {span=>
+            if let Some(value) = #set_value_mut {
+                #steps
+            }
+        });
+        let built_value_mut = if is_option {
+            quote_spanned! {span=> built.#ident.as_mut() }
+        } else {
+            quote_spanned! {span=> Some(&mut built.#ident) }
+        };
+        context.create_normalize_steps.push(quote_spanned! {span=>
+            if let Some(value) = #built_value_mut {
+                #steps
+            }
+        });
+    }
+
+    if sensitive {
+        context.sensitive_field_names.push(ident.clone());
+    }
+
+    if expect {
+        // Preconditions compare against the source value, so they use the source type
+        let slot = format_ident!("expect_{}", ident);
+        let serde_attrs = if is_option {
+            skip_unset_keep_null(context)
+        } else {
+            skip_unset()
+        };
+        context.updatable_fields.push(quote_spanned! {span=>
+            #serde_attrs
+            pub #slot: Option<#ty>
+        });
+        context.expect_field_names.push(ident.clone());
+        context.expect_slot_names.push(slot);
+        context.expect_field_types.push(ty.clone());
+    }
+
+    if let Some(no_change) = &no_change {
         context.unwrapped_field_names.push(ident.clone());
+        context.unwrapped_field_defaults.push(no_change.clone());
+        context.unwrapped_field_types.push(ty.clone());
+        Some(no_change.clone())
     } else {
         context.wrapped_field_names.push(ident.clone());
+        None
+    }
+}
+
+/// Smart pointer or container wrapping the source type of a nested field
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NestedWrapper {
+    None,
+    Option,
+    Box,
+    Arc,
+    Rc,
+    Vec,
+}
+
+/// Split a nested field type into its wrapper and the wrapped named type
+fn unwrap_nested_type(ty: &Type) -> (NestedWrapper, &Type) {
+    if let Type::Path(TypePath { path, .. }) = ty {
+        if let Some(segment) = path.segments.last() {
+            if let syn::PathArguments::AngleBracketed(args) = &segment.arguments {
+                if let Some(syn::GenericArgument::Type(inner)) = args.args.first() {
+                    let wrapper = match segment.ident.to_string().as_str() {
+                        "Option" => NestedWrapper::Option,
+                        "Box" => NestedWrapper::Box,
+                        "Arc" => NestedWrapper::Arc,
+                        "Rc" => NestedWrapper::Rc,
+                        "Vec" => NestedWrapper::Vec,
+                        _ => return (NestedWrapper::None, ty),
+                    };
+                    return (wrapper, inner);
+                }
+            }
+        }
     }
+    (NestedWrapper::None, ty)
 }
 
 /// Process a nested field with optional custom type name
+///
+/// The source type may be wrapped in `Option`, `Box`, `Arc`, `Rc` or `Vec`; the
+/// substruct name is derived from the wrapped type. `Option` wrappers get an
+/// `Option<Option<_>>` update type so the nested value can also be cleared.
+/// Returns the wrapper and the nested substruct type.
 pub fn handle_nested_field(
     field: &Field,
     ident: &Ident,
     nested_type: Option<String>,
     context: &mut FieldContext,
-) {
+) -> (NestedWrapper, Ident) {
     let span = field.span();
-    let ty = &field.ty;
+    let (wrapper, ty) = unwrap_nested_type(&field.ty);
 
     let update_type = if let Some(nested_name) = nested_type {
         // Use the specified nested type name
@@ -141,17 +514,116 @@ pub fn handle_nested_field(
         }
     };
 
+    let field_type = if wrapper == NestedWrapper::Option {
+        quote_spanned! {span=> Option<Option<#update_type>> }
+    } else {
+        quote_spanned! {span=> Option<#update_type> }
+    };
+
     context.field_names.push(ident.clone());
-    context
-        .field_types
-        .push(quote_spanned! {span=> Option<#update_type> });
+    context.field_types.push(field_type.clone());
 
     // Add to nested_field_names since nested fields are always Option<T>
     context.nested_field_names.push(ident.clone());
+    context.nested_field_types.push(update_type.clone());
+    context.nested_field_wrappers.push(wrapper);
 
+    let serde_attrs = if wrapper == NestedWrapper::Option {
+        skip_unset_keep_null(context)
+    } else {
+        skip_unset()
+    };
     context.updatable_fields.push(quote_spanned! {span=>
-        pub #ident: Option<#update_type>
+        #serde_attrs
+        pub #ident: #field_type
     });
+    (wrapper, update_type)
+}
+
+/// Convert a snake_case field name into PascalCase for generated type names
+fn to_pascal_case(name: &str) -> String {
+    name.split('_')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect::<String>(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+/// Process a `Vec<T>` field whose elements are patched individually by key
+///
+/// The field becomes a list of keyed operations (`Upsert`, `Remove`, `Patch`) whose
+/// enum is generated next to the substruct as `<Substruct><Field>Op`.
+pub fn handle_nested_vec_field(
+    field: &Field,
+    ident: &Ident,
+    nested_type: Option<String>,
+    key: &str,
+    key_type: Option<String>,
+    context: &mut FieldContext,
+) -> KeyedVec {
+    let span = field.span();
+    let (wrapper, item_ty) = unwrap_nested_type(&field.ty);
+    if wrapper != NestedWrapper::Vec {
+        abort!(span, "`nested_vec` fields must be of type `Vec<T>`");
+    }
+
+    let nested_update_type = match nested_type {
+        Some(nested_name) => format_ident!("{}", nested_name),
+        None => match item_ty {
+            Type::Path(TypePath { path, .. }) => {
+                let last = path
+                    .segments
+                    .last()
+                    .unwrap_or_else(|| abort!(span, "Expected type segment in path"));
+                format_ident!("{}Substruct", last.ident)
+            }
+            _ => abort!(span, "`nested_vec` elements must be a named type"),
+        },
+    };
+
+    // Keys are compared with `==`, so the key type defaults to `String`
+    let key_type: Type = match key_type {
+        Some(key_type) => syn::parse_str(&key_type)
+            .unwrap_or_else(|_| abort!(span, "`key_type` must be a valid type")),
+        None => syn::parse_quote_spanned! {span=> String },
+    };
+
+    let op_type = format_ident!(
+        "{}{}Op",
+        context.update_struct_name,
+        to_pascal_case(&ident.to_string())
+    );
+    let field_type = quote_spanned! {span=> Vec<#op_type> };
+
+    context.field_names.push(ident.clone());
+    context.field_types.push(field_type.clone());
+
+    let keyed = KeyedVec {
+        op_type,
+        key: format_ident!("{}", key),
+        key_type,
+        item_type: item_ty.clone(),
+        nested_type: nested_update_type,
+    };
+    context.keyed_vec_field_names.push(ident.clone());
+    context.keyed_vec_op_types.push(keyed.op_type.clone());
+    context.keyed_vec_keys.push(keyed.key.clone());
+    context.keyed_vec_key_types.push(keyed.key_type.clone());
+    context.keyed_vec_item_types.push(keyed.item_type.clone());
+    context
+        .keyed_vec_nested_types
+        .push(keyed.nested_type.clone());
+
+    context.updatable_fields.push(quote_spanned! {span=>
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        pub #ident: #field_type
+    });
+    keyed
 }
 
 /// Process a JSON field
@@ -178,91 +650,468 @@ pub fn handle_json_field(field: &Field, ident: &Ident, context: &mut FieldContex
     context.field_names.push(ident.clone());
     context.field_types.push(json_ty.clone());
 
+    let serde_attrs = skip_unset();
     context.updatable_fields.push(quote_spanned! {span=>
+        #serde_attrs
         pub #ident: #json_ty
     });
 }
 
+/// Process the field marked as the optimistic concurrency version
+///
+/// The field itself is not updatable; the substruct instead carries an
+/// `expected_version` that `apply_to` compares before writing anything.
+pub fn handle_version_field(field: &Field, ident: &Ident, context: &mut FieldContext) {
+    let span = field.span();
+    if let Some((previous, _)) = &context.version_field {
+        abort!(
+            span, "only one field can be marked `version`";
+            note = "`{}` is already the version field", previous
+        );
+    }
+
+    let ty = &field.ty;
+    let serde_attrs = skip_unset();
+    context.updatable_fields.push(quote_spanned! {span=>
+        #serde_attrs
+        pub expected_version: Option<#ty>
+    });
+    context.version_field = Some((ident.clone(), ty.clone()));
+}
+
 /// Main field processing function that dispatches to appropriate handlers
-pub fn process_field(field: &Field, field_kind: &FieldKind, context: &mut FieldContext) {
+pub fn process_field(
+    field: &Field,
+    field_kind: &FieldKind,
+    options: &FieldOptions,
+    context: &mut FieldContext,
+) {
     let ident = match &field.ident {
         Some(ident) => ident,
         None => return, // Skip unnamed fields
     };
 
+    if let Some(label) = &options.label {
+        context.field_labels.push((ident.clone(), label.clone()));
+    }
+    let role = match field_kind {
+        FieldKind::Version => CreateRole::Version,
+        FieldKind::Skip | FieldKind::None if options.default.is_some() => CreateRole::Fixed,
+        FieldKind::Skip | FieldKind::None => CreateRole::Uncovered,
+        _ => CreateRole::Payload,
+    };
+    context.create_fields.push(CreateField {
+        ident: ident.clone(),
+        ty: field.ty.clone(),
+        default: options.default.clone(),
+        role,
+        serde_rename: options.serde_rename.clone(),
+    });
+
+    let first_update_field = context.updatable_fields.len();
+    let mut no_change = None;
+    let mut nested = None;
+    let mut keyed = None;
     match field_kind {
-        FieldKind::Skip => return,
-        FieldKind::Primitive { option } => {
-            handle_primitive_field(field, ident, *option, context);
+        FieldKind::Skip => (),
+        FieldKind::Primitive {
+            option,
+            validations,
+            transforms,
+            expect,
+            sensitive,
+        } => {
+            no_change = handle_primitive_field(
+                field,
+                ident,
+                *option,
+                validations,
+                transforms,
+                *expect,
+                *sensitive,
+                options.default.as_ref(),
+                context,
+            );
         }
         FieldKind::Nested { nested_type } => {
-            handle_nested_field(field, ident, nested_type.clone(), context);
+            nested = Some(handle_nested_field(
+                field,
+                ident,
+                nested_type.clone(),
+                context,
+            ));
+        }
+        FieldKind::NestedVec {
+            nested_type,
+            key,
+            key_type,
+        } => {
+            keyed = Some(handle_nested_vec_field(
+                field,
+                ident,
+                nested_type.clone(),
+                key,
+                key_type.clone(),
+                context,
+            ));
         }
         FieldKind::Json => {
             handle_json_field(field, ident, context);
         }
-        FieldKind::None => return,
+        FieldKind::Version => {
+            handle_version_field(field, ident, context);
+        }
+        FieldKind::None => (),
     }
+
+    if !context.env_enabled && options.env.is_some() {
+        emit_error!(
+            ident, "`env` requires `from_env` to be enabled";
+            help = "add #[substruct_builder(env)] to the struct"
+        );
+    }
+
+    // The field itself is the first one a kind handler adds
+    if let Some(rename) = &options.serde_rename {
+        if !matches!(
+            field_kind,
+            FieldKind::Version | FieldKind::Skip | FieldKind::None
+        ) {
+            if let Some(update_field) = context.updatable_fields.get_mut(first_update_field) {
+                *update_field =
+                    quote_spanned! {field.span()=> #[serde(rename = #rename)] #update_field };
+            }
+        }
+    }
+
+    let column = || {
+        options
+            .column_rename
+            .clone()
+            .unwrap_or_else(|| ident.to_string().trim_start_matches("r#").to_string())
+    };
+    match field_kind {
+        FieldKind::Primitive { .. } => {
+            context.column_fields.push(ident.clone());
+            context.column_names.push(column());
+            context.column_types.push(field.ty.clone());
+        }
+        FieldKind::Version => context.version_column = Some(column()),
+        _ => {}
+    }
+
+    let wire_name = context.wire_name(&ident.to_string(), options.serde_rename.as_deref());
+    context.fields.push(ProcessedField {
+        field: field.clone(),
+        ident: ident.clone(),
+        kind: field_kind.clone(),
+        options: options.clone(),
+        wire_name,
+        no_change,
+        nested,
+        keyed,
+    });
 }
 
-/// Parse field attributes to determine the field kind
-pub fn get_redis_updatable_kind(attrs: &[Attribute]) -> FieldKind {
-    for attr in attrs {
-        if attr.path().is_ident("substruct_field") {
-            let Ok(meta_list) =
-                attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
-            else {
-                return FieldKind::Primitive { option: true };
-            };
+const FIELD_KINDS: &[&str] = &[
+    "primitive",
+    "nested",
+    "nested_vec",
+    "json",
+    "version",
+    "skip",
+];
+const FIELD_OPTIONS: &[&str] = &[
+    "option",
+    "nested_type",
+    "transform",
+    "transform_with",
+    "key",
+    "key_type",
+    "validate",
+    "label",
+    "default",
+    "env",
+];
+const FIELD_FLAGS: &[&str] = &["expect", "sensitive"];
 
-            let mut option = true; // Default to Option wrapping
-            let mut field_type = None;
-            let mut nested_type = None;
-
-            for meta in meta_list {
-                match meta {
-                    Meta::Path(path) => {
-                        if path.is_ident("primitive") {
-                            field_type = Some("primitive");
-                        } else if path.is_ident("nested") {
-                            field_type = Some("nested");
-                        } else if path.is_ident("json") {
-                            field_type = Some("json");
-                        } else if path.is_ident("skip") {
-                            field_type = Some("skip");
-                        }
+/// Parse field attributes to determine the field kind and its kind-independent options
+///
+/// Parsing is strict: unknown, duplicate, mistyped and conflicting options are
+/// emitted as spanned errors (with "did you mean" suggestions) and the field is
+/// treated as skipped so every problem is reported in one compilation.
+pub fn get_redis_updatable_kind(attrs: &[Attribute]) -> (FieldKind, FieldOptions) {
+    let mut field_attrs = attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("substruct_field"));
+    let Some(attr) = field_attrs.next() else {
+        return (FieldKind::None, FieldOptions::default());
+    };
+    for duplicate in field_attrs {
+        emit_error!(
+            duplicate, "duplicate #[substruct_field] attribute";
+            help = "combine the options into a single #[substruct_field(...)]"
+        );
+    }
+
+    let meta_list = match attr.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) {
+        Ok(meta_list) => meta_list,
+        Err(err) => {
+            emit_error!(
+                attr, "invalid #[substruct_field] attribute: {}", err;
+                help = "expected e.g. #[substruct_field(primitive)]"
+            );
+            return (FieldKind::Skip, FieldOptions::default());
+        }
+    };
+
+    let mut field_type: Option<(String, Span)> = None;
+    let mut option: Option<(bool, Span)> = None;
+    let mut nested_type: Option<(String, Span)> = None;
+    let mut key: Option<(String, Span)> = None;
+    let mut key_type: Option<(String, Span)> = None;
+    let mut validations: Option<(Vec<Validation>, Span)> = None;
+    let mut transforms: Vec<Transform> = Vec::new();
+    let mut transform_span: Option<Span> = None;
+    let mut expect: Option<Span> = None;
+    let mut sensitive: Option<Span> = None;
+    let mut label: Option<(String, Span)> = None;
+    let mut default: Option<Expr> = None;
+    let mut env: Option<(String, Span)> = None;
+    let mut seen = Vec::new();
+    let mut reported_unknown = false;
+
+    for meta in meta_list {
+        let name = meta_key(&meta);
+        let span = meta.path().span();
+
+        if let Meta::Path(path) = &meta {
+            if FIELD_KINDS.contains(&name.as_str()) {
+                match &field_type {
+                    Some((previous, _)) if *previous == name => {
+                        emit_error!(path, "duplicate field kind `{}`", name);
                     }
-                    Meta::NameValue(name_value) => {
-                        if name_value.path.is_ident("option") {
-                            if let syn::Expr::Lit(syn::ExprLit {
-                                lit: syn::Lit::Bool(lit_bool),
-                                ..
-                            }) = &name_value.value
-                            {
-                                option = lit_bool.value;
-                            }
-                        } else if name_value.path.is_ident("nested_type") {
-                            if let syn::Expr::Lit(syn::ExprLit {
-                                lit: syn::Lit::Str(lit_str),
-                                ..
-                            }) = &name_value.value
-                            {
-                                nested_type = Some(lit_str.value());
-                            }
-                        }
+                    Some((previous, _)) => emit_error!(
+                        path, "conflicting field kinds `{}` and `{}`", previous, name;
+                        help = "a field has exactly one kind"
+                    ),
+                    None => field_type = Some((name, span)),
+                }
+                continue;
+            }
+        }
+
+        if (FIELD_OPTIONS.contains(&name.as_str()) || FIELD_FLAGS.contains(&name.as_str()))
+            && !check_duplicate(&meta, &name, &mut seen)
+        {
+            continue;
+        }
+
+        match &meta {
+            Meta::NameValue(name_value) if name == "option" => {
+                if let Some(value) = expect_lit_bool(&name_value.value, &name) {
+                    option = Some((value, span));
+                }
+            }
+            Meta::NameValue(name_value) if name == "nested_type" => {
+                if let Some(lit_str) = expect_lit_str(&name_value.value, &name) {
+                    nested_type = Some((lit_str.value(), span));
+                }
+            }
+            Meta::NameValue(name_value) if name == "transform" => {
+                if let Some(lit_str) = expect_lit_str(&name_value.value, &name) {
+                    transforms.extend(parse_transforms(&lit_str));
+                    transform_span = Some(span);
+                }
+            }
+            Meta::NameValue(name_value) if name == "transform_with" => {
+                if let Some(function) = expect_lit_str(&name_value.value, &name)
+                    .and_then(|lit_str| expect_fn_path(&lit_str, &name))
+                {
+                    transforms.push(Transform::With(function));
+                    transform_span = Some(span);
+                }
+            }
+            Meta::NameValue(name_value) if name == "key" => {
+                if let Some(lit_str) = expect_lit_str(&name_value.value, &name) {
+                    key = Some((lit_str.value(), span));
+                }
+            }
+            Meta::NameValue(name_value) if name == "key_type" => {
+                if let Some(lit_str) = expect_lit_str(&name_value.value, &name) {
+                    key_type = Some((lit_str.value(), span));
+                }
+            }
+            Meta::NameValue(name_value) if name == "default" => {
+                if let Some(lit_str) = expect_lit_str(&name_value.value, &name) {
+                    match lit_str.parse::<Expr>() {
+                        Ok(expr) => default = Some(expr),
+                        Err(err) => emit_error!(
+                            lit_str, "`default` is not a valid expression: {}", err;
+                            help = "write it as e.g. `default = \"u32::MAX\"`"
+                        ),
                     }
-                    _ => {}
                 }
             }
+            Meta::NameValue(name_value) if name == "env" => {
+                if let Some(lit_str) = expect_lit_str(&name_value.value, &name) {
+                    env = Some((lit_str.value(), span));
+                }
+            }
+            Meta::NameValue(name_value) if name == "label" => {
+                if let Some(lit_str) = expect_lit_str(&name_value.value, &name) {
+                    label = Some((lit_str.value(), span));
+                }
+            }
+            Meta::List(list) if name == "validate" => {
+                validations = Some((parse_validations(list), span));
+            }
+            Meta::Path(_) if name == "expect" => expect = Some(span),
+            Meta::Path(_) if name == "sensitive" => sensitive = Some(span),
+            _ => {
+                report_unknown_key(
+                    &meta,
+                    &name,
+                    "substruct_field",
+                    FIELD_OPTIONS,
+                    &[FIELD_KINDS, FIELD_FLAGS].concat(),
+                );
+                reported_unknown = true;
+            }
+        }
+    }
 
-            match field_type {
-                Some("primitive") => return FieldKind::Primitive { option },
-                Some("nested") => return FieldKind::Nested { nested_type },
-                Some("json") => return FieldKind::Json,
-                Some("skip") => return FieldKind::Skip,
-                _ => return FieldKind::Skip,
+    let field_type = match field_type {
+        Some((field_type, _)) => field_type,
+        // A bare `default` only fills the field in when building through the create type
+        None if default.is_some() && !reported_unknown => "skip".to_string(),
+        None => {
+            // A misspelled kind has already been reported with a suggestion
+            if reported_unknown {
+                return (FieldKind::Skip, FieldOptions::default());
             }
+            emit_error!(
+                attr, "missing field kind in #[substruct_field]";
+                help = "add one of: {}", FIELD_KINDS.join(", ")
+            );
+            return (FieldKind::Skip, FieldOptions::default());
+        }
+    };
+
+    // Options only make sense for some kinds
+    if let Some((value, span)) = option {
+        if field_type != "primitive" {
+            let reason = match field_type.as_str() {
+                "json" => "JSON fields are always wrapped as `Option<serde_json::Value>`",
+                "nested" | "nested_vec" => "nested fields manage their own wrapping",
+                "version" => "the version is guarded through `expected_version`, not updated",
+                _ => "skipped fields are not part of the substruct",
+            };
+            emit_error!(
+                span, "`option = {}` conflicts with `{}`", value, field_type;
+                note = reason;
+                help = "remove `option`, it is only supported on primitive fields"
+            );
+        }
+    }
+    if let Some((_, span)) = &nested_type {
+        if field_type != "nested" && field_type != "nested_vec" {
+            emit_error!(
+                *span, "`nested_type` conflicts with `{}`", field_type;
+                help = "`nested_type` is only supported on `nested` and `nested_vec` fields"
+            );
         }
     }
-    FieldKind::None
+    for (option_name, option_span) in [("key", &key), ("key_type", &key_type)] {
+        if let Some((_, span)) = option_span {
+            if field_type != "nested_vec" {
+                emit_error!(
+                    *span, "`{}` conflicts with `{}`", option_name, field_type;
+                    help = "`{}` is only supported on `nested_vec` fields", option_name
+                );
+            }
+        }
+    }
+    if let Some((_, span)) = &validations {
+        if field_type != "primitive" {
+            emit_error!(
+                *span, "`validate(...)` conflicts with `{}`", field_type;
+                help = "`validate(...)` is only supported on primitive fields"
+            );
+        }
+    }
+    for (flag, flag_span) in [("expect", expect), ("sensitive", sensitive)] {
+        if let Some(span) = flag_span {
+            if field_type != "primitive" {
+                emit_error!(
+                    span, "`{}` conflicts with `{}`", flag, field_type;
+                    help = "`{}` is only supported on primitive fields", flag
+                );
+            }
+        }
+    }
+    if let Some(span) = transform_span {
+        if field_type != "primitive" {
+            emit_error!(
+                span, "`transform` conflicts with `{}`", field_type;
+                help = "`transform` and `transform_with` are only supported on primitive fields"
+            );
+        }
+    }
+
+    if let Some((_, span)) = &label {
+        if field_type == "version" || field_type == "skip" {
+            emit_error!(
+                *span, "`label` conflicts with `{}`", field_type;
+                note = "only updatable fields appear in change descriptions"
+            );
+        }
+    }
+
+    if let Some((_, span)) = &env {
+        if !matches!(field_type.as_str(), "primitive" | "json" | "nested") {
+            emit_error!(
+                *span, "`env` conflicts with `{}`", field_type;
+                help = "`env` is only supported on primitive, json and nested fields"
+            );
+        }
+    }
+
+    let options = FieldOptions {
+        label: label.map(|(label, _)| label),
+        default,
+        env: env.map(|(env, _)| env),
+        serde_rename: serde_rename(attrs),
+        column_rename: column_rename(attrs),
+    };
+    let kind = match field_type.as_str() {
+        "primitive" => FieldKind::Primitive {
+            option: option.is_none_or(|(value, _)| value), // Default to Option wrapping
+            validations: validations.map(|(rules, _)| rules).unwrap_or_default(),
+            transforms,
+            expect: expect.is_some(),
+            sensitive: sensitive.is_some(),
+        },
+        "nested" => FieldKind::Nested {
+            nested_type: nested_type.map(|(name, _)| name),
+        },
+        "nested_vec" => {
+            let Some((key, _)) = key else {
+                emit_error!(
+                    attr, "`nested_vec` fields require a key to match elements by";
+                    help = "add `key = \"id\"` naming the element's key field"
+                );
+                return (FieldKind::Skip, FieldOptions::default());
+            };
+            FieldKind::NestedVec {
+                nested_type: nested_type.map(|(name, _)| name),
+                key,
+                key_type: key_type.map(|(name, _)| name),
+            }
+        }
+        "json" => FieldKind::Json,
+        "version" => FieldKind::Version,
+        _ => FieldKind::Skip,
+    };
+    (kind, options)
 }
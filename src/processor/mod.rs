@@ -1,2 +1,4 @@
 pub mod attributes;
 pub mod fields;
+pub mod transform;
+pub mod validation;
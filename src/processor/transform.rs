@@ -0,0 +1,54 @@
+use proc_macro_error::emit_error;
+use quote::quote;
+use syn::LitStr;
+
+use super::attributes::did_you_mean;
+
+const TRANSFORMS: &[&str] = &["trim", "lowercase", "uppercase"];
+
+/// A normalization step declared with `transform = "..."` or `transform_with = "..."`
+#[derive(Debug, Clone)]
+pub enum Transform {
+    Trim,
+    Lowercase,
+    Uppercase,
+    With(proc_macro2::TokenStream),
+}
+
+/// Parse a `transform = "trim|lowercase"` pipeline
+pub fn parse_transforms(pipeline: &LitStr) -> Vec<Transform> {
+    pipeline
+        .value()
+        .split('|')
+        .filter_map(|step| match step.trim() {
+            "trim" => Some(Transform::Trim),
+            "lowercase" => Some(Transform::Lowercase),
+            "uppercase" => Some(Transform::Uppercase),
+            unknown => {
+                match did_you_mean(unknown, TRANSFORMS) {
+                    Some(suggestion) => emit_error!(
+                        pipeline, "unknown transform `{}`", unknown;
+                        help = "did you mean `{}`?", suggestion
+                    ),
+                    None => emit_error!(
+                        pipeline, "unknown transform `{}`", unknown;
+                        help = "expected `trim`, `lowercase` or `uppercase`, or use `transform_with = \"path::to::fn\"`"
+                    ),
+                }
+                None
+            }
+        })
+        .collect()
+}
+
+/// Generate the statements that normalize `value: &mut T` in place
+pub fn generate_transform_steps(transforms: &[Transform]) -> proc_macro2::TokenStream {
+    let steps = transforms.iter().map(|transform| match transform {
+        Transform::Trim => quote! { *value = value.trim().to_string(); },
+        Transform::Lowercase => quote! { *value = value.to_lowercase(); },
+        Transform::Uppercase => quote! { *value = value.to_uppercase(); },
+        Transform::With(function) => quote! { *value = #function(value.clone()); },
+    });
+
+    quote! { #(#steps)* }
+}
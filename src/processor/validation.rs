@@ -0,0 +1,188 @@
+use proc_macro_error::emit_error;
+use quote::{ToTokens, quote};
+use syn::punctuated::Punctuated;
+use syn::{Ident, Meta, MetaList, Token};
+
+use super::attributes::{did_you_mean, expect_fn_path, expect_lit_str, meta_key};
+
+const VALIDATION_RULES: &[&str] = &["range", "length", "regex", "custom"];
+
+/// A single validation rule declared in `#[substruct_field(validate(...))]`
+#[derive(Debug, Clone)]
+pub enum Validation {
+    Range {
+        min: Option<proc_macro2::TokenStream>,
+        max: Option<proc_macro2::TokenStream>,
+    },
+    Length {
+        min: Option<proc_macro2::TokenStream>,
+        max: Option<proc_macro2::TokenStream>,
+    },
+    Regex(String),
+    Custom(proc_macro2::TokenStream),
+}
+
+/// Parse the rules inside a `validate(...)` list
+pub fn parse_validations(list: &MetaList) -> Vec<Validation> {
+    let Ok(meta_list) = list.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated)
+    else {
+        emit_error!(list, "Expected a comma-separated list of validation rules");
+        return Vec::new();
+    };
+
+    let mut validations = Vec::new();
+    for meta in meta_list {
+        match &meta {
+            Meta::List(rule) if rule.path.is_ident("range") => {
+                if let Some((min, max)) = parse_bounds(rule) {
+                    validations.push(Validation::Range { min, max });
+                }
+            }
+            Meta::List(rule) if rule.path.is_ident("length") => {
+                if let Some((min, max)) = parse_bounds(rule) {
+                    validations.push(Validation::Length { min, max });
+                }
+            }
+            Meta::NameValue(rule) if rule.path.is_ident("regex") => {
+                if let Some(pattern) = expect_lit_str(&rule.value, "regex") {
+                    validations.push(Validation::Regex(pattern.value()));
+                }
+            }
+            Meta::NameValue(rule) if rule.path.is_ident("custom") => {
+                if let Some(function) = expect_lit_str(&rule.value, "custom")
+                    .and_then(|function| expect_fn_path(&function, "custom"))
+                {
+                    validations.push(Validation::Custom(function));
+                }
+            }
+            _ => {
+                let key = meta_key(&meta);
+                match did_you_mean(&key, VALIDATION_RULES) {
+                    Some(suggestion) => emit_error!(
+                        meta.path(), "unknown validation rule `{}`", key;
+                        help = "did you mean `{}`?", suggestion
+                    ),
+                    None => emit_error!(
+                        meta.path(), "unknown validation rule `{}`", key;
+                        help = "expected `range(...)`, `length(...)`, `regex = \"...\"` or `custom = \"path::to::fn\"`"
+                    ),
+                }
+            }
+        }
+    }
+    validations
+}
+
+/// Parse the `min = ..` / `max = ..` pair of a `range` or `length` rule
+fn parse_bounds(
+    rule: &MetaList,
+) -> Option<(
+    Option<proc_macro2::TokenStream>,
+    Option<proc_macro2::TokenStream>,
+)> {
+    let Ok(bounds) = rule.parse_args_with(Punctuated::<Meta, Token![,]>::parse_terminated) else {
+        emit_error!(rule, "Expected `min = ..` and/or `max = ..`");
+        return None;
+    };
+
+    let mut min = None;
+    let mut max = None;
+    let mut malformed = false;
+    for bound in bounds {
+        match &bound {
+            Meta::NameValue(nv) if nv.path.is_ident("min") && min.is_none() => {
+                min = Some(nv.value.to_token_stream())
+            }
+            Meta::NameValue(nv) if nv.path.is_ident("max") && max.is_none() => {
+                max = Some(nv.value.to_token_stream())
+            }
+            Meta::NameValue(nv) if nv.path.is_ident("min") || nv.path.is_ident("max") => {
+                emit_error!(bound, "duplicate bound `{}`", meta_key(&bound));
+            }
+            _ => {
+                emit_error!(bound, "Expected `min = ..` or `max = ..`");
+                malformed = true;
+            }
+        }
+    }
+
+    if min.is_none() && max.is_none() && !malformed {
+        emit_error!(rule, "At least one of `min` or `max` must be given");
+        return None;
+    }
+    Some((min, max))
+}
+
+/// Generate the checks for one field, run against `value: &T` when the field is set
+///
+/// Each failing rule pushes a `(path, message)` pair onto the `errors` vector that
+/// is in scope in the generated `validate` method.
+pub fn generate_validation_checks(
+    ident: &Ident,
+    validations: &[Validation],
+    is_string: bool,
+) -> proc_macro2::TokenStream {
+    let checks = validations.iter().map(|validation| match validation {
+        Validation::Range { min, max } => {
+            let min_check = min.as_ref().map(|min| {
+                quote! {
+                    if *value < #min {
+                        errors.push((stringify!(#ident).to_string(), format!("must be at least {}", #min)));
+                    }
+                }
+            });
+            let max_check = max.as_ref().map(|max| {
+                quote! {
+                    if *value > #max {
+                        errors.push((stringify!(#ident).to_string(), format!("must be at most {}", #max)));
+                    }
+                }
+            });
+            quote! { #min_check #max_check }
+        }
+        Validation::Length { min, max } => {
+            // Strings are measured in characters, everything else by `len()`
+            let length = if is_string {
+                quote! { value.chars().count() }
+            } else {
+                quote! { value.len() }
+            };
+            let min_check = min.as_ref().map(|min| {
+                quote! {
+                    if length < #min {
+                        errors.push((stringify!(#ident).to_string(), format!("length must be at least {}", #min)));
+                    }
+                }
+            });
+            let max_check = max.as_ref().map(|max| {
+                quote! {
+                    if length > #max {
+                        errors.push((stringify!(#ident).to_string(), format!("length must be at most {}", #max)));
+                    }
+                }
+            });
+            quote! {
+                let length = #length;
+                #min_check
+                #max_check
+            }
+        }
+        Validation::Regex(pattern) => quote! {
+            static PATTERN: std::sync::OnceLock<regex::Regex> = std::sync::OnceLock::new();
+            let pattern = PATTERN.get_or_init(|| {
+                regex::Regex::new(#pattern).expect("Invalid regex in validate attribute")
+            });
+            if !pattern.is_match(value) {
+                errors.push((stringify!(#ident).to_string(), format!("must match pattern `{}`", #pattern)));
+            }
+        },
+        Validation::Custom(function) => quote! {
+            if let Err(message) = #function(value) {
+                errors.push((stringify!(#ident).to_string(), message.to_string()));
+            }
+        },
+    });
+
+    // Each rule gets its own block so `length`/`PATTERN` bindings don't collide
+    quote! { #({ #checks })* }
+}
@@ -91,6 +91,7 @@ fn test_basic_struct_clear() {
 }
 
 #[test]
+#[allow(clippy::bool_assert_comparison)]
 fn test_basic_struct_apply_to() {
     let mut user = BasicStruct {
         name: "Alice".to_string(),
@@ -0,0 +1,209 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// CHANGE REPORT TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(report)]
+struct Address {
+    #[substruct_field(primitive)]
+    street: String,
+    #[substruct_field(primitive)]
+    city: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Preferences {
+    theme: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(report)]
+struct Tag {
+    #[substruct_field(primitive)]
+    id: String,
+    #[substruct_field(primitive)]
+    color: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(report)]
+struct Profile {
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(primitive)]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false)]
+    age: u32,
+    #[substruct_field(json)]
+    preferences: Preferences,
+    #[substruct_field(nested)]
+    address: Address,
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+    #[substruct_field(nested_vec, key = "id")]
+    tags: Vec<Tag>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(report)]
+struct Document {
+    #[substruct_field(primitive, transform = "trim")]
+    title: String,
+    #[substruct_field(version)]
+    version: u64,
+}
+
+fn profile() -> Profile {
+    Profile {
+        name: "Alice".to_string(),
+        nickname: Some("Al".to_string()),
+        age: 30,
+        preferences: Preferences {
+            theme: "light".to_string(),
+        },
+        address: Address {
+            street: "1 Main St".to_string(),
+            city: "Boston".to_string(),
+        },
+        billing: None,
+        tags: vec![Tag {
+            id: "a".to_string(),
+            color: "red".to_string(),
+        }],
+    }
+}
+
+#[test]
+fn test_report_lists_changed_fields_with_old_and_new_values() {
+    let mut target = profile();
+    let update = ProfileSubstruct {
+        name: Some("Bob".to_string()),
+        nickname: Some(None),
+        age: 31,
+        ..Default::default()
+    };
+
+    let changes = update.apply_with_report(&mut target).unwrap();
+
+    assert_eq!(changes.changed_paths(), vec!["name", "nickname", "age"]);
+    let name = changes.get("name").unwrap();
+    assert_eq!(name.old, json!("Alice"));
+    assert_eq!(name.new, json!("Bob"));
+    assert_eq!(changes.get("nickname").unwrap().new, json!(null));
+    assert_eq!(changes.get("age").unwrap().old, json!(30));
+    assert!(changes.unchanged.is_empty());
+    assert_eq!(target.name, "Bob");
+}
+
+#[test]
+fn test_report_separates_no_op_fields() {
+    let mut target = profile();
+    let update = ProfileSubstruct {
+        name: Some("Alice".to_string()),
+        nickname: Some(Some("Ally".to_string())),
+        preferences: Some(json!({ "theme": "light" })),
+        ..Default::default()
+    };
+
+    let changes = update.apply_with_report(&mut target).unwrap();
+
+    assert_eq!(changes.changed_paths(), vec!["nickname"]);
+    assert_eq!(changes.unchanged, vec!["name", "preferences"]);
+    assert!(changes.get("name").is_none());
+}
+
+#[test]
+fn test_report_recurses_into_nested_substructs() {
+    let mut target = profile();
+    let update = ProfileSubstruct {
+        address: Some(AddressSubstruct::new(
+            Some("1 Main St".to_string()),
+            Some("NYC".to_string()),
+        )),
+        billing: Some(Some(AddressSubstruct::new(None, Some("Paris".to_string())))),
+        ..Default::default()
+    };
+
+    let changes = update.apply_with_report(&mut target).unwrap();
+
+    assert_eq!(changes.changed_paths(), vec!["address.city", "billing"]);
+    assert_eq!(changes.unchanged, vec!["address.street"]);
+    assert_eq!(changes.get("address.city").unwrap().old, json!("Boston"));
+    // Creating an `Option` nested value is reported as a whole
+    let billing = changes.get("billing").unwrap();
+    assert_eq!(billing.old, json!(null));
+    assert_eq!(billing.new, json!({ "street": "", "city": "Paris" }));
+
+    // Once it exists, the nested value is patched and reported field by field
+    let update = ProfileSubstruct {
+        billing: Some(Some(AddressSubstruct::new(None, Some("Lyon".to_string())))),
+        ..Default::default()
+    };
+    let changes = update.apply_with_report(&mut target).unwrap();
+    assert_eq!(changes.changed_paths(), vec!["billing.city"]);
+}
+
+#[test]
+fn test_report_keyed_elements() {
+    let mut target = profile();
+    let update = ProfileSubstruct::default()
+        .patch_tags("a", TagSubstruct::new(None, Some("blue".to_string())))
+        .upsert_tags(Tag {
+            id: "b".to_string(),
+            color: "green".to_string(),
+        })
+        .remove_tags("missing");
+
+    let changes = update.apply_with_report(&mut target).unwrap();
+
+    assert_eq!(
+        changes.changed_paths(),
+        vec![r#"tags["a"]"#, r#"tags["b"]"#]
+    );
+    assert_eq!(changes.unchanged, vec![r#"tags["missing"]"#]);
+    let added = changes.get(r#"tags["b"]"#).unwrap();
+    assert_eq!(added.old, json!(null));
+    assert_eq!(added.new, json!({ "id": "b", "color": "green" }));
+}
+
+#[test]
+fn test_report_changes_between_states_and_empty_update() {
+    let before = profile();
+    let mut after = before.clone();
+    let update = ProfileSubstruct::new(Some("Bob".to_string()), None, 0, None, None, None, vec![]);
+    update.apply_to(&mut after).unwrap();
+
+    assert_eq!(
+        update.report_changes(&before, &after),
+        update.apply_with_report(&mut before.clone()).unwrap()
+    );
+
+    let changes = ProfileSubstruct::default()
+        .apply_with_report(&mut after)
+        .unwrap();
+    assert!(changes.is_empty());
+    assert!(changes.unchanged.is_empty());
+}
+
+#[test]
+fn test_report_on_versioned_struct() {
+    let mut document = Document {
+        title: "Draft".to_string(),
+        version: 1,
+    };
+
+    let update = DocumentSubstruct::new(Some("  Final ".to_string())).with_expected_version(1);
+    let changes = update.apply_with_report(&mut document).unwrap();
+
+    assert_eq!(changes.changed_paths(), vec!["title", "version"]);
+    assert_eq!(changes.get("title").unwrap().new, json!("Final"));
+    assert_eq!(changes.get("version").unwrap().new, json!(2));
+
+    // A failed guard writes nothing and reports nothing
+    assert!(update.apply_with_report(&mut document).is_err());
+    assert_eq!(document.version, 2);
+}
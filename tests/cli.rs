@@ -0,0 +1,204 @@
+#![cfg(feature = "clap")]
+
+use clap::{Args, Command, CommandFactory, FromArgMatches, Parser};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// COMMAND-LINE OVERRIDE TESTS (require `--features clap`)
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(clap)]
+struct Address {
+    #[substruct_field(primitive, label = "City name")]
+    city: String,
+    #[substruct_field(primitive)]
+    zip_code: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Limits {
+    requests: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(clap)]
+struct Config {
+    #[substruct_field(primitive, transform = "trim")]
+    name: String,
+    #[substruct_field(primitive)]
+    port: u16,
+    #[substruct_field(primitive)]
+    log_level: Option<String>,
+    #[substruct_field(primitive, option = false)]
+    workers: u32,
+    #[substruct_field(json)]
+    limits: Limits,
+    #[substruct_field(nested)]
+    address: Address,
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+    #[substruct_field(version)]
+    version: u64,
+}
+
+fn parse(args: &[&str]) -> Result<ConfigSubstruct, clap::Error> {
+    let cmd = ConfigSubstruct::augment_args(Command::new("app"));
+    let matches = cmd.try_get_matches_from(std::iter::once("app").chain(args.iter().copied()))?;
+    ConfigSubstruct::from_arg_matches(&matches)
+}
+
+#[test]
+fn test_flags_set_fields_and_absent_flags_are_no_change() {
+    let update = parse(&[
+        "--name",
+        " api ",
+        "--port",
+        "8080",
+        "--workers",
+        "4",
+        "--limits",
+        r#"{"requests": 100}"#,
+    ])
+    .unwrap();
+
+    assert_eq!(update.name, Some("api".to_string()));
+    assert_eq!(update.port, Some(8080));
+    assert_eq!(update.workers, 4);
+    assert_eq!(update.limits, Some(json!({ "requests": 100 })));
+    assert_eq!(update.log_level, None);
+    assert!(update.address.is_none());
+    assert_eq!(update.field_count(), 4);
+
+    assert!(parse(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn test_nested_flags_use_field_paths() {
+    let update = parse(&["--address-city", "Boston", "--billing-zip-code", "02101"]).unwrap();
+
+    assert_eq!(update.address.unwrap().city, Some("Boston".to_string()));
+    let billing = update.billing.unwrap().unwrap();
+    assert_eq!(billing.zip_code, Some("02101".to_string()));
+    assert_eq!(billing.city, None);
+}
+
+#[test]
+fn test_no_flags_clear_optional_fields() {
+    let update = parse(&["--no-log-level", "--no-billing"]).unwrap();
+    assert_eq!(update.log_level, Some(None));
+    assert!(matches!(update.billing, Some(None)));
+
+    let update = parse(&["--log-level", "debug"]).unwrap();
+    assert_eq!(update.log_level, Some(Some("debug".to_string())));
+
+    // A value and its clear flag can't be combined
+    let err = parse(&["--log-level", "debug", "--no-log-level"]).unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+}
+
+#[test]
+fn test_invalid_values_are_rejected() {
+    let err = parse(&["--port", "http"]).unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation);
+
+    let err = parse(&["--limits", "{}"]).unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::ValueValidation);
+
+    let err = parse(&["--version", "2"]).unwrap_err();
+    assert_eq!(err.kind(), clap::error::ErrorKind::UnknownArgument);
+}
+
+#[test]
+fn test_flattened_into_a_derived_parser() {
+    #[derive(Parser)]
+    struct Cli {
+        #[arg(long)]
+        config: String,
+        #[command(flatten)]
+        overrides: ConfigSubstruct,
+    }
+
+    let cli = Cli::try_parse_from(["app", "--config", "app.toml", "--port", "9000"]).unwrap();
+    assert_eq!(cli.config, "app.toml");
+    assert_eq!(cli.overrides.port, Some(9000));
+
+    let help = Cli::command().render_help().to_string();
+    assert!(help.contains("--address-city <address-city>"));
+    assert!(help.contains("City name"));
+    assert!(help.contains("--no-billing"));
+}
+
+// ============================================================================
+// SENTINEL, GUARD AND SENSITIVE FLAG TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Member {
+    #[substruct_field(primitive)]
+    id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(clap)]
+struct Deploy {
+    #[substruct_field(primitive, option = false, default = "u32::MAX")]
+    replicas: u32,
+    #[substruct_field(primitive, expect)]
+    region: String,
+    #[substruct_field(primitive, sensitive)]
+    token: String,
+    #[substruct_field(nested_vec, key = "id")]
+    members: Vec<Member>,
+}
+
+fn parse_deploy(args: &[&str]) -> Result<DeploySubstruct, clap::Error> {
+    let cmd = DeploySubstruct::augment_args(Command::new("deploy"));
+    let matches =
+        cmd.try_get_matches_from(std::iter::once("deploy").chain(args.iter().copied()))?;
+    DeploySubstruct::from_arg_matches(&matches)
+}
+
+#[test]
+fn test_sentinel_values_are_no_change() {
+    assert!(parse(&["--workers", "0"]).unwrap().is_empty());
+
+    // Zero is a real value when the sentinel is something else
+    let update = parse_deploy(&["--replicas", "0"]).unwrap();
+    assert_eq!(update.replicas, 0);
+    assert_eq!(update.field_count(), 1);
+
+    let update = parse_deploy(&["--replicas", "4294967295"]).unwrap();
+    assert!(update.is_empty());
+}
+
+#[test]
+fn test_guards_and_keyed_fields_have_no_flags() {
+    for flag in ["--expect-region", "--expected-version", "--members"] {
+        let err = parse_deploy(&[flag, "x"]).unwrap_err();
+        assert_eq!(
+            err.kind(),
+            clap::error::ErrorKind::UnknownArgument,
+            "{}",
+            flag
+        );
+    }
+
+    let update = parse_deploy(&["--region", "eu-west"]).unwrap();
+    assert_eq!(update.region, Some("eu-west".to_string()));
+    assert_eq!(update.expect_region, None);
+    assert!(update.members.is_empty());
+}
+
+#[test]
+fn test_sensitive_flags_are_redacted_in_debug() {
+    let update = parse_deploy(&["--token", "ghp_abc123", "--region", "eu"]).unwrap();
+    assert_eq!(update.token, Some("ghp_abc123".to_string()));
+
+    let debug = format!("{:?}", update);
+    assert!(!debug.contains("ghp_abc123"));
+    assert!(debug.contains("token: Some(<redacted>)"));
+    assert!(debug.contains("region: Some(\"eu\")"));
+}
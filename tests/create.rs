@@ -0,0 +1,211 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// CREATE TYPE TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Address {
+    #[substruct_field(primitive)]
+    city: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Preferences {
+    theme: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(create)]
+struct User {
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(primitive)]
+    email: String,
+    #[substruct_field(primitive)]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false, default = "18")]
+    age: u32,
+    #[substruct_field(json, default = "Preferences { theme: \"light\".to_string() }")]
+    preferences: Preferences,
+    #[substruct_field(nested)]
+    address: Option<Address>,
+    #[substruct_field(version)]
+    version: u64,
+    #[substruct_field(default = "vec![\"member\".to_string()]")]
+    roles: Vec<String>,
+    #[substruct_field(skip, default = "String::new()")]
+    password_hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(name = "TokenPatch", create = "NewToken")]
+struct Token {
+    #[substruct_field(primitive, sensitive)]
+    secret: String,
+    #[substruct_field(primitive, default = "3600")]
+    ttl: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(create)]
+struct Signup {
+    #[substruct_field(primitive, transform = "trim|lowercase", validate(length(min = 3)))]
+    email: String,
+    #[substruct_field(primitive, transform = "trim")]
+    nickname: Option<String>,
+    #[substruct_field(
+        primitive,
+        option = false,
+        default = "18",
+        validate(range(min = 13, max = 120))
+    )]
+    age: u32,
+    #[substruct_field(primitive, validate(length(max = 3)))]
+    tags: Option<Vec<String>>,
+}
+
+#[test]
+fn test_new_sets_required_fields_and_defaults_the_rest() {
+    let user = CreateUser::new("Alice".to_string(), "alice@example.com".to_string())
+        .try_build()
+        .unwrap();
+
+    assert_eq!(
+        user,
+        User {
+            name: "Alice".to_string(),
+            email: "alice@example.com".to_string(),
+            nickname: None,
+            age: 18,
+            preferences: Preferences {
+                theme: "light".to_string(),
+            },
+            address: None,
+            version: 0,
+            roles: vec!["member".to_string()],
+            password_hash: String::new(),
+        }
+    );
+}
+
+#[test]
+fn test_provided_values_override_defaults() {
+    let create = CreateUser {
+        nickname: Some("Al".to_string()),
+        age: Some(42),
+        address: Some(Address {
+            city: "Boston".to_string(),
+        }),
+        ..CreateUser::new("Alice".to_string(), "alice@example.com".to_string())
+    };
+
+    let user = create.try_build().unwrap();
+    assert_eq!(user.nickname, Some("Al".to_string()));
+    assert_eq!(user.age, 42);
+    assert_eq!(user.address.unwrap().city, "Boston");
+}
+
+#[test]
+fn test_missing_required_fields_are_reported() {
+    let create = CreateUser {
+        nickname: Some("Al".to_string()),
+        ..Default::default()
+    };
+
+    let err = create.try_build().unwrap_err();
+    assert_eq!(
+        err,
+        CreateUserError::MissingFields {
+            fields: vec!["name", "email"]
+        }
+    );
+    assert_eq!(err.to_string(), "missing required fields: name, email");
+}
+
+#[test]
+fn test_create_payload_deserializes_from_json() {
+    let create: CreateUser = serde_json::from_value(json!({
+        "name": "Bob",
+        "email": "bob@example.com",
+        "preferences": { "theme": "dark" }
+    }))
+    .unwrap();
+
+    let user = create.try_build().unwrap();
+    assert_eq!(user.name, "Bob");
+    assert_eq!(user.preferences.theme, "dark");
+    assert_eq!(user.age, 18);
+
+    let create: CreateUser = serde_json::from_value(json!({ "name": "Bob" })).unwrap();
+    assert!(matches!(
+        create.try_build(),
+        Err(CreateUserError::MissingFields { fields }) if fields == vec!["email"]
+    ));
+}
+
+#[test]
+fn test_custom_create_name_and_update_type_share_a_source() {
+    let token = NewToken::new("s3cr3t".to_string()).try_build().unwrap();
+    assert_eq!(token.ttl, 3600);
+
+    let mut token = token;
+    TokenPatch::new(None, Some(60)).apply_to(&mut token);
+    assert_eq!(
+        token,
+        Token {
+            secret: "s3cr3t".to_string(),
+            ttl: 60,
+        }
+    );
+}
+
+#[test]
+fn test_try_build_runs_transforms() {
+    let create = CreateSignup {
+        nickname: Some("  Al ".to_string()),
+        ..CreateSignup::new("  Alice@Example.COM ".to_string())
+    };
+
+    let signup = create.try_build().unwrap();
+    assert_eq!(signup.email, "alice@example.com");
+    assert_eq!(signup.nickname, Some("Al".to_string()));
+    assert_eq!(signup.age, 18);
+    assert_eq!(signup.tags, None);
+}
+
+#[test]
+fn test_try_build_validates_the_built_value() {
+    // Validation sees the trimmed email, and provided values replace the defaults
+    let create = CreateSignup {
+        age: Some(7),
+        tags: Some(vec!["a".to_string(); 4]),
+        ..CreateSignup::new("  ab  ".to_string())
+    };
+
+    let err = create.try_build().unwrap_err();
+    let CreateSignupError::Invalid(errors) = &err else {
+        panic!("expected validation errors, got {err:?}");
+    };
+    assert_eq!(
+        errors.messages_for("email"),
+        vec!["length must be at least 3"]
+    );
+    assert_eq!(errors.messages_for("age"), vec!["must be at least 13"]);
+    assert_eq!(
+        errors.messages_for("tags"),
+        vec!["length must be at most 3"]
+    );
+    assert!(
+        err.to_string()
+            .starts_with("invalid fields: email: length must be at least 3")
+    );
+
+    // Missing fields are reported before anything is validated
+    assert!(matches!(
+        CreateSignup::default().try_build(),
+        Err(CreateSignupError::MissingFields { .. })
+    ));
+}
@@ -0,0 +1,243 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// CHANGE DESCRIPTION TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(describe)]
+struct Address {
+    #[substruct_field(primitive, label = "City")]
+    city: String,
+    #[substruct_field(primitive)]
+    zip: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(describe)]
+struct Tag {
+    #[substruct_field(primitive)]
+    id: String,
+    #[substruct_field(primitive)]
+    color: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Preferences {
+    theme: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(describe)]
+struct Profile {
+    #[substruct_field(primitive, transform = "trim")]
+    name: String,
+    #[substruct_field(primitive, label = "Nickname")]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false)]
+    age: u32,
+    #[substruct_field(primitive, sensitive)]
+    password: String,
+    #[substruct_field(json, label = "Preferences")]
+    preferences: Preferences,
+    #[substruct_field(nested, label = "Address")]
+    address: Address,
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+    #[substruct_field(nested_vec, key = "id", label = "Tags")]
+    tags: Vec<Tag>,
+}
+
+fn profile() -> Profile {
+    Profile {
+        name: "Alice".to_string(),
+        nickname: Some("Al".to_string()),
+        age: 30,
+        password: "hunter2".to_string(),
+        preferences: Preferences {
+            theme: "light".to_string(),
+        },
+        address: Address {
+            city: "Boston".to_string(),
+            zip: "02101".to_string(),
+        },
+        billing: None,
+        tags: vec![Tag {
+            id: "a".to_string(),
+            color: "red".to_string(),
+        }],
+    }
+}
+
+#[test]
+fn test_describe_lists_set_fields_with_labels() {
+    let update = ProfileSubstruct {
+        name: Some("  Bob ".to_string()),
+        nickname: Some(None),
+        age: 31,
+        preferences: Some(json!({ "theme": "dark" })),
+        address: Some(AddressSubstruct::new(Some("NYC".to_string()), None)),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        update.describe(),
+        "name: set to 'Bob'; Nickname: cleared; age: set to 31; \
+         Preferences: set to {\"theme\":\"dark\"}; Address.City: set to 'NYC'"
+    );
+    assert_eq!(ProfileSubstruct::default().describe(), "no changes");
+}
+
+#[test]
+fn test_describe_against_shows_old_and_new_values() {
+    let target = profile();
+    let update = ProfileSubstruct {
+        name: Some("Bob".to_string()),
+        nickname: Some(None),
+        age: 30,
+        address: Some(AddressSubstruct::new(
+            Some("NYC".to_string()),
+            Some("02101".to_string()),
+        )),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        update.describe_against(&target),
+        "name: 'Alice' → 'Bob'; Nickname: cleared; Address.City: 'Boston' → 'NYC'"
+    );
+    // Describing doesn't modify the target
+    assert_eq!(target, profile());
+}
+
+#[test]
+fn test_describe_against_skips_no_ops() {
+    let target = profile();
+    let update = ProfileSubstruct {
+        name: Some(" Alice ".to_string()),
+        preferences: Some(json!({ "theme": "light" })),
+        ..Default::default()
+    };
+
+    assert_eq!(update.describe_against(&target), "no changes");
+    assert_eq!(
+        update.describe(),
+        "name: set to 'Alice'; Preferences: set to {\"theme\":\"light\"}"
+    );
+}
+
+#[test]
+fn test_describe_optional_nested_and_sensitive_fields() {
+    let mut target = profile();
+    let update = ProfileSubstruct {
+        password: Some("hunter3".to_string()),
+        billing: Some(Some(AddressSubstruct::new(Some("Paris".to_string()), None))),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        update.describe(),
+        "password: set; billing.City: set to 'Paris'"
+    );
+    assert_eq!(
+        update.describe_against(&target),
+        "password: changed; billing: set (City: set to 'Paris')"
+    );
+
+    target.billing = Some(Address::default());
+    assert_eq!(
+        update.describe_against(&target),
+        "password: changed; billing.City: '' → 'Paris'"
+    );
+
+    let clear = ProfileSubstruct {
+        billing: Some(None),
+        ..Default::default()
+    };
+    assert_eq!(clear.describe_against(&target), "billing: cleared");
+    assert_eq!(clear.describe_against(&profile()), "no changes");
+}
+
+#[test]
+fn test_describe_keyed_operations() {
+    let target = profile();
+    let update = ProfileSubstruct::default()
+        .patch_tags("a", TagSubstruct::new(None, Some("blue".to_string())))
+        .upsert_tags(Tag {
+            id: "b".to_string(),
+            color: "green".to_string(),
+        })
+        .remove_tags("a")
+        .remove_tags("missing");
+
+    assert_eq!(
+        update.describe(),
+        r#"Tags["a"].color: set to 'blue'; Tags["b"]: upserted; Tags["a"]: removed; Tags["missing"]: removed"#
+    );
+    assert_eq!(
+        update.describe_against(&target),
+        r#"Tags["a"].color: 'red' → 'blue'; Tags["b"]: added; Tags["a"]: removed"#
+    );
+}
+
+// ============================================================================
+// NESTED SENSITIVE AND SENTINEL TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(describe)]
+struct Login {
+    #[substruct_field(primitive, sensitive)]
+    pin: String,
+    #[substruct_field(primitive, option = false, default = "u32::MAX")]
+    attempts: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(describe)]
+struct Device {
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(nested, label = "Login")]
+    login: Login,
+}
+
+#[test]
+fn test_nested_sensitive_values_and_sentinels() {
+    let device = Device {
+        name: "kiosk".to_string(),
+        login: Login {
+            pin: "0000".to_string(),
+            attempts: 3,
+        },
+    };
+    let update = DeviceSubstruct {
+        login: Some(LoginSubstruct {
+            pin: Some("4821".to_string()),
+            attempts: 0,
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        update.describe(),
+        "Login.pin: set; Login.attempts: set to 0"
+    );
+    assert_eq!(
+        update.describe_against(&device),
+        "Login.pin: changed; Login.attempts: 3 → 0"
+    );
+
+    // The sentinel is no change, and so is setting the current pin
+    let update = DeviceSubstruct {
+        login: Some(LoginSubstruct {
+            pin: Some("0000".to_string()),
+            attempts: u32::MAX,
+        }),
+        ..Default::default()
+    };
+    assert_eq!(update.describe(), "Login.pin: set");
+    assert_eq!(update.describe_against(&device), "no changes");
+}
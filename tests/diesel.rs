@@ -0,0 +1,232 @@
+#![cfg(feature = "diesel")]
+
+use diesel::prelude::*;
+use diesel::sqlite::SqliteConnection;
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// DIESEL CHANGESET TESTS (require `--features diesel`)
+// ============================================================================
+
+diesel::table! {
+    users (id) {
+        id -> Integer,
+        display_name -> Text,
+        nickname -> Nullable<Text>,
+        login_count -> Integer,
+        email -> Text,
+    }
+}
+
+diesel::table! {
+    documents (id) {
+        id -> Integer,
+        title -> Text,
+        revision -> BigInt,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Identifiable, SubstructBuilder)]
+#[diesel(table_name = documents)]
+#[substruct_builder(diesel)]
+struct Document {
+    id: i32,
+    #[substruct_field(primitive)]
+    title: String,
+    #[diesel(column_name = revision)]
+    #[substruct_field(version)]
+    version: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Preferences {
+    #[substruct_field(primitive)]
+    theme: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, Identifiable, SubstructBuilder)]
+#[diesel(table_name = users)]
+#[substruct_builder(diesel)]
+struct User {
+    id: i32,
+    #[diesel(column_name = display_name)]
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(primitive)]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false)]
+    login_count: i32,
+    #[substruct_field(primitive, transform = "trim|lowercase")]
+    email: String,
+    #[substruct_field(nested)]
+    preferences: Preferences,
+}
+
+fn connection() -> SqliteConnection {
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    diesel::sql_query(
+        "CREATE TABLE users (
+            id INTEGER PRIMARY KEY,
+            display_name TEXT NOT NULL,
+            nickname TEXT,
+            login_count INTEGER NOT NULL,
+            email TEXT NOT NULL
+        )",
+    )
+    .execute(&mut conn)
+    .unwrap();
+    diesel::sql_query("INSERT INTO users VALUES (1, 'Alice', 'Al', 3, 'alice@example.com')")
+        .execute(&mut conn)
+        .unwrap();
+    conn
+}
+
+fn row(conn: &mut SqliteConnection) -> (String, Option<String>, i32, String) {
+    users::table
+        .find(1)
+        .select((
+            users::display_name,
+            users::nickname,
+            users::login_count,
+            users::email,
+        ))
+        .first(conn)
+        .unwrap()
+}
+
+#[test]
+fn test_changeset_updates_only_set_columns() {
+    let mut conn = connection();
+    let update = UserSubstruct {
+        name: Some("Alicia".to_string()),
+        ..Default::default()
+    };
+
+    diesel::update(users::table.find(1))
+        .set(&update.changeset().unwrap())
+        .execute(&mut conn)
+        .unwrap();
+
+    assert_eq!(
+        row(&mut conn),
+        (
+            "Alicia".to_string(),
+            Some("Al".to_string()),
+            3,
+            "alice@example.com".to_string()
+        )
+    );
+}
+
+#[test]
+fn test_changeset_clears_nullable_and_sets_unwrapped_columns() {
+    let mut conn = connection();
+    let update = UserSubstruct {
+        nickname: Some(None),
+        login_count: 4,
+        ..Default::default()
+    };
+
+    diesel::update(users::table.find(1))
+        .set(&update.changeset().unwrap())
+        .execute(&mut conn)
+        .unwrap();
+
+    let (_, nickname, login_count, _) = row(&mut conn);
+    assert_eq!(nickname, None);
+    assert_eq!(login_count, 4);
+}
+
+#[test]
+fn test_changeset_normalizes_values() {
+    let mut conn = connection();
+    let update = UserSubstruct {
+        email: Some("  ALICE@Work.COM ".to_string()),
+        ..Default::default()
+    };
+
+    diesel::update(users::table.find(1))
+        .set(&update.changeset().unwrap())
+        .execute(&mut conn)
+        .unwrap();
+
+    assert_eq!(row(&mut conn).3, "alice@work.com");
+}
+
+#[test]
+fn test_changeset_rejects_updates_without_columns() {
+    assert_eq!(
+        UserSubstruct::default().changeset().unwrap_err(),
+        UserSubstructEmptyUpdateError
+    );
+
+    // Nested fields aren't columns
+    let update = UserSubstruct {
+        preferences: Some(PreferencesSubstruct {
+            theme: Some("dark".to_string()),
+        }),
+        ..Default::default()
+    };
+    let err = update.changeset().unwrap_err();
+    assert_eq!(err.to_string(), "update sets no columns");
+}
+
+fn document_connection() -> SqliteConnection {
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    diesel::sql_query(
+        "CREATE TABLE documents (id INTEGER PRIMARY KEY, title TEXT NOT NULL, revision BIGINT NOT NULL)",
+    )
+    .execute(&mut conn)
+    .unwrap();
+    diesel::sql_query("INSERT INTO documents VALUES (1, 'Draft', 3)")
+        .execute(&mut conn)
+        .unwrap();
+    conn
+}
+
+fn update_document(conn: &mut SqliteConnection, update: &DocumentSubstruct) -> usize {
+    diesel::update(
+        documents::table
+            .find(1)
+            .filter(update.version_filter().unwrap()),
+    )
+    .set(&update.changeset().unwrap())
+    .execute(conn)
+    .unwrap()
+}
+
+#[test]
+fn test_changeset_bumps_the_version_of_matching_rows() {
+    let mut conn = document_connection();
+    let update = DocumentSubstruct {
+        expected_version: Some(3),
+        title: Some("Final".to_string()),
+    };
+
+    assert_eq!(update_document(&mut conn, &update), 1);
+    let (title, revision): (String, i64) = documents::table
+        .find(1)
+        .select((documents::title, documents::revision))
+        .first(&mut conn)
+        .unwrap();
+    assert_eq!((title.as_str(), revision), ("Final", 4));
+
+    // The same update is now stale and matches no row
+    assert_eq!(update_document(&mut conn, &update), 0);
+    let revision: i64 = documents::table
+        .find(1)
+        .select(documents::revision)
+        .first(&mut conn)
+        .unwrap();
+    assert_eq!(revision, 4);
+}
+
+#[test]
+fn test_version_filter_is_unset_without_expected_version() {
+    let update = DocumentSubstruct {
+        title: Some("Final".to_string()),
+        ..Default::default()
+    };
+    assert!(update.version_filter().is_none());
+}
@@ -57,6 +57,7 @@ struct MixedFieldTypesStruct {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_mixed_field_types_edge_cases() {
     let update = MixedFieldTypesStructSubstruct::new(
         Some("test".to_string()),        // string_field
@@ -0,0 +1,289 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// ENVIRONMENT LOADING TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(env)]
+struct Database {
+    #[substruct_field(primitive)]
+    host: String,
+    #[substruct_field(primitive)]
+    port: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Limits {
+    requests: u32,
+    burst: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(env)]
+struct Config {
+    #[substruct_field(primitive, transform = "trim")]
+    name: String,
+    #[substruct_field(primitive)]
+    port: u16,
+    #[substruct_field(primitive, option = false)]
+    workers: u32,
+    #[substruct_field(primitive)]
+    log_level: Option<String>,
+    #[substruct_field(primitive, env = "DATABASE_URL", sensitive)]
+    database_url: String,
+    #[substruct_field(json)]
+    limits: Limits,
+    #[substruct_field(nested)]
+    database: Database,
+    #[substruct_field(nested)]
+    replica: Option<Database>,
+    #[substruct_field(skip)]
+    started_at: u64,
+}
+
+fn lookup(vars: &[(&str, &str)]) -> impl Fn(&str) -> Option<String> {
+    let vars: HashMap<String, String> = vars
+        .iter()
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect();
+    move |key| vars.get(key).cloned()
+}
+
+#[test]
+fn test_prefixed_variables_are_parsed() {
+    let update = ConfigSubstruct::from_env_with(
+        "APP",
+        lookup(&[
+            ("APP_NAME", "  api "),
+            ("APP_PORT", "8080"),
+            ("APP_WORKERS", "4"),
+            ("APP_LOG_LEVEL", "debug"),
+            ("APP_LIMITS", r#"{"requests": 100, "burst": 10}"#),
+            ("APP_STARTED_AT", "12"),
+            ("PORT", "9090"),
+        ]),
+    )
+    .unwrap();
+
+    assert_eq!(update.name, Some("api".to_string()));
+    assert_eq!(update.port, Some(8080));
+    assert_eq!(update.workers, 4);
+    assert_eq!(update.log_level, Some(Some("debug".to_string())));
+    assert_eq!(update.limits, Some(json!({ "requests": 100, "burst": 10 })));
+    assert_eq!(update.database_url, None);
+    assert!(update.database.is_none());
+    assert_eq!(update.field_count(), 5);
+}
+
+#[test]
+fn test_nested_and_custom_variables() {
+    let update = ConfigSubstruct::from_env_with(
+        "APP",
+        lookup(&[
+            ("APP_DATABASE__HOST", "db.internal"),
+            ("APP_REPLICA__PORT", "5433"),
+            ("DATABASE_URL", "postgres://db"),
+            ("APP_DATABASE_URL", "ignored"),
+        ]),
+    )
+    .unwrap();
+
+    let database = update.database.unwrap();
+    assert_eq!(database.host, Some("db.internal".to_string()));
+    assert_eq!(database.port, None);
+    assert_eq!(update.replica.unwrap().unwrap().port, Some(5433));
+    assert_eq!(update.database_url, Some("postgres://db".to_string()));
+
+    let empty = ConfigSubstruct::from_env_with("APP", lookup(&[])).unwrap();
+    assert!(empty.is_empty());
+
+    let unprefixed = DatabaseSubstruct::from_env_with("", lookup(&[("HOST", "localhost")]));
+    assert_eq!(unprefixed.unwrap().host, Some("localhost".to_string()));
+}
+
+#[test]
+fn test_every_parse_failure_is_reported() {
+    let err = ConfigSubstruct::from_env_with(
+        "APP",
+        lookup(&[
+            ("APP_PORT", "http"),
+            ("APP_WORKERS", "-1"),
+            ("APP_LIMITS", "{}"),
+            ("APP_DATABASE__PORT", "99999"),
+            ("APP_NAME", "api"),
+        ]),
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err.variables(),
+        vec![
+            "APP_PORT",
+            "APP_WORKERS",
+            "APP_LIMITS",
+            "APP_DATABASE__PORT"
+        ]
+    );
+    assert_eq!(err.errors[0].1, "invalid digit found in string");
+    assert!(err.to_string().starts_with(
+        "APP_PORT: invalid digit found in string; APP_WORKERS: invalid digit found in string; "
+    ));
+}
+
+// ============================================================================
+// SENSITIVE, SENTINEL AND GUARD TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(env)]
+struct Secrets {
+    #[substruct_field(primitive, sensitive)]
+    pin: u32,
+    #[substruct_field(primitive, sensitive)]
+    token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(env)]
+struct Vault {
+    #[substruct_field(primitive)]
+    region: String,
+    #[substruct_field(nested)]
+    secrets: Secrets,
+}
+
+#[test]
+fn test_sensitive_parse_errors_are_redacted() {
+    let err =
+        SecretsSubstruct::from_env_with("SECRETS", lookup(&[("SECRETS_PIN", "12a4")])).unwrap_err();
+    assert_eq!(
+        err.errors,
+        vec![("SECRETS_PIN".to_string(), "invalid value".to_string())]
+    );
+    assert!(!err.to_string().contains("12a4"));
+
+    // Through a nested field, next to a plain error that keeps its message
+    let err = VaultSubstruct::from_env_with(
+        "VAULT",
+        lookup(&[("VAULT_SECRETS__PIN", "12a4"), ("VAULT_REGION", "eu")]),
+    )
+    .unwrap_err();
+    assert_eq!(err.to_string(), "VAULT_SECRETS__PIN: invalid value");
+}
+
+#[test]
+fn test_loaded_sensitive_values_are_redacted_in_debug() {
+    let update = VaultSubstruct::from_env_with(
+        "VAULT",
+        lookup(&[
+            ("VAULT_REGION", "eu"),
+            ("VAULT_SECRETS__TOKEN", "tok-9f8e"),
+            ("VAULT_SECRETS__PIN", "1234"),
+        ]),
+    )
+    .unwrap();
+
+    let secrets = update.secrets.clone().unwrap();
+    assert_eq!(secrets.pin, Some(1234));
+    assert_eq!(secrets.token, Some("tok-9f8e".to_string()));
+
+    let debug = format!("{:?}", update);
+    assert!(debug.contains("region: Some(\"eu\")"));
+    assert!(!debug.contains("tok-9f8e"));
+    assert!(!debug.contains("1234"));
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(env)]
+struct Pool {
+    #[substruct_field(primitive, option = false, default = "u32::MAX")]
+    size: u32,
+    #[substruct_field(primitive, option = false)]
+    idle: u32,
+}
+
+#[test]
+fn test_values_equal_to_the_sentinel_are_no_change() {
+    let update =
+        PoolSubstruct::from_env_with("POOL", lookup(&[("POOL_SIZE", "0"), ("POOL_IDLE", "0")]))
+            .unwrap();
+    assert_eq!(update.size, 0);
+    assert!(update.has_field("size"));
+    assert!(!update.has_field("idle"));
+
+    let update =
+        PoolSubstruct::from_env_with("POOL", lookup(&[("POOL_SIZE", "4294967295")])).unwrap();
+    assert!(update.is_empty());
+
+    let config = ConfigSubstruct::from_env_with("APP", lookup(&[("APP_WORKERS", "0")])).unwrap();
+    assert!(config.is_empty());
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Member {
+    #[substruct_field(primitive)]
+    id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(env)]
+struct Team {
+    #[substruct_field(primitive, expect)]
+    owner: String,
+    #[substruct_field(nested_vec, key = "id")]
+    members: Vec<Member>,
+    #[substruct_field(version)]
+    version: u64,
+}
+
+#[test]
+fn test_guards_and_keyed_fields_are_not_loaded() {
+    let update = TeamSubstruct::from_env_with(
+        "TEAM",
+        lookup(&[
+            ("TEAM_OWNER", "alice"),
+            ("TEAM_EXPECT_OWNER", "bob"),
+            ("TEAM_VERSION", "3"),
+            ("TEAM_EXPECTED_VERSION", "3"),
+            ("TEAM_MEMBERS", r#"[{"upsert": {"id": "a"}}]"#),
+        ]),
+    )
+    .unwrap();
+
+    assert_eq!(update.owner, Some("alice".to_string()));
+    assert_eq!(update.expect_owner, None);
+    assert_eq!(update.expected_version, None);
+    assert!(update.members.is_empty());
+    assert_eq!(update.field_count(), 1);
+
+    // Without a guard the loaded update applies to any version
+    let mut team = Team {
+        owner: "bob".to_string(),
+        members: vec![],
+        version: 7,
+    };
+    update.apply_to(&mut team).unwrap();
+    assert_eq!(team.owner, "alice");
+    assert_eq!(team.version, 8);
+}
+
+#[test]
+fn test_from_env_reads_the_process_environment() {
+    // SAFETY: the variable names are unique to this test
+    unsafe {
+        std::env::set_var("SUBSTRUCT_ENV_TEST_HOST", "db.example.com");
+        std::env::set_var("SUBSTRUCT_ENV_TEST_PORT", "6543");
+    }
+
+    let mut database = Database::default();
+    DatabaseSubstruct::from_env("SUBSTRUCT_ENV_TEST")
+        .unwrap()
+        .apply_to(&mut database);
+
+    assert_eq!(database.host, "db.example.com");
+    assert_eq!(database.port, 6543);
+}
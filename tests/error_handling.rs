@@ -61,6 +61,7 @@ struct NestedStruct {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_mixed_field_types_validation() {
     let update = MixedFieldTypesStructSubstruct::new(
         Some("test".to_string()),                                     // string_field
@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+// ============================================================================
+// MACRO EXPANSION SNAPSHOT TESTS
+// ============================================================================
+
+// Each fixture in `tests/expand/` is expanded with `-Zunpretty=expanded` and the
+// code generated by `SubstructBuilder` is compared against the snapshot next to
+// it in a `.expanded.rs` file. Standard derives (`Clone`, `Serialize`, ...) and
+// the fixture's own items are filtered out so the snapshot only shows what this
+// crate generates. `-Zunpretty` is unstable, so the test only runs on a nightly
+// toolchain and is skipped otherwise:
+//
+//     cargo +nightly test --test expand
+//
+// A fixture without a snapshot fails the test. After changing the generator or
+// adding a fixture, write the snapshots with:
+//
+//     EXPAND=overwrite cargo +nightly test --test expand
+//
+// and review the diff.
+
+const FIXTURE_DIR: &str = "tests/expand";
+
+#[test]
+fn test_expansion_snapshots() {
+    if !is_nightly() {
+        eprintln!("skipping expansion snapshots: `-Zunpretty=expanded` needs a nightly toolchain");
+        return;
+    }
+
+    let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+    let project = ExpandProject::create(manifest_dir);
+    let overwrite = std::env::var("EXPAND").is_ok_and(|mode| mode == "overwrite");
+
+    let mut fixtures: Vec<PathBuf> = fs::read_dir(manifest_dir.join(FIXTURE_DIR))
+        .expect("Failed to read fixture directory")
+        .map(|entry| entry.expect("Failed to read fixture").path())
+        .filter(|path| {
+            path.extension().is_some_and(|ext| ext == "rs")
+                && !path.to_string_lossy().ends_with(".expanded.rs")
+        })
+        .collect();
+    fixtures.sort();
+    assert!(!fixtures.is_empty(), "No expansion fixtures found");
+
+    let mut mismatches = Vec::new();
+    for fixture in &fixtures {
+        let actual = project.expand(fixture);
+        let snapshot = fixture.with_extension("expanded.rs");
+
+        if overwrite {
+            fs::write(&snapshot, &actual).expect("Failed to write snapshot");
+            continue;
+        }
+        if !snapshot.exists() {
+            mismatches.push(format!("{} is missing", snapshot.display()));
+            continue;
+        }
+
+        let expected = fs::read_to_string(&snapshot).expect("Failed to read snapshot");
+        if expected != actual {
+            mismatches.push(format!(
+                "{}\n{}",
+                snapshot.display(),
+                line_diff(&expected, &actual)
+            ));
+        }
+    }
+
+    assert!(
+        mismatches.is_empty(),
+        "Expansion snapshots differ (run with EXPAND=overwrite to update):\n\n{}",
+        mismatches.join("\n\n")
+    );
+}
+
+/// Whether `rustc` is a nightly (or locally built) toolchain
+fn is_nightly() -> bool {
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    Command::new(rustc)
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| {
+            let version = String::from_utf8_lossy(&output.stdout);
+            version.contains("-nightly") || version.contains("-dev")
+        })
+}
+
+/// A scratch crate that depends on this one and holds the fixtures as binaries
+struct ExpandProject {
+    dir: PathBuf,
+}
+
+impl ExpandProject {
+    fn create(manifest_dir: &Path) -> Self {
+        let dir = Path::new(env!("CARGO_TARGET_TMPDIR")).join("expand");
+        fs::create_dir_all(dir.join("src/bin")).expect("Failed to create expand project");
+
+        let manifest = format!(
+            r#"[package]
+name = "substruct-genesis-expand"
+version = "0.0.0"
+edition = "2024"
+publish = false
+
+[dependencies]
+substruct-genesis = {{ path = {:?} }}
+serde = {{ version = "1.0", features = ["derive"] }}
+serde_json = "1.0"
+regex = "1"
+
+[workspace]
+"#,
+            manifest_dir.display().to_string()
+        );
+        fs::write(dir.join("Cargo.toml"), manifest).expect("Failed to write manifest");
+
+        // Reuse our lockfile so the scratch crate resolves the same versions
+        let lockfile = manifest_dir.join("Cargo.lock");
+        if lockfile.exists() {
+            fs::copy(lockfile, dir.join("Cargo.lock")).expect("Failed to copy lockfile");
+        }
+
+        Self { dir }
+    }
+
+    /// Expand one fixture and return the pretty-printed generated items
+    fn expand(&self, fixture: &Path) -> String {
+        let name = fixture
+            .file_stem()
+            .and_then(|stem| stem.to_str())
+            .expect("Fixture name must be valid UTF-8");
+        let source = fs::read_to_string(fixture).expect("Failed to read fixture");
+        fs::write(self.dir.join("src/bin").join(format!("{name}.rs")), &source)
+            .expect("Failed to copy fixture");
+
+        let cargo = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_string());
+        let output = Command::new(cargo)
+            .current_dir(&self.dir)
+            .env("CARGO_TARGET_DIR", self.dir.join("target"))
+            .args(["rustc", "--quiet", "--profile=check", "--bin", name])
+            .args(["--", "-Zunpretty=expanded"])
+            .output()
+            .expect("Failed to run cargo");
+        assert!(
+            output.status.success(),
+            "Failed to expand {}:\n{}",
+            fixture.display(),
+            String::from_utf8_lossy(&output.stderr)
+        );
+
+        let expanded = String::from_utf8(output.stdout).expect("Expansion must be valid UTF-8");
+        let fixture_file = syn::parse_file(&source).expect("Failed to parse fixture");
+        let mut expanded_file = syn::parse_file(&expanded).expect("Failed to parse expansion");
+
+        let fixture_items: HashSet<String> =
+            fixture_file.items.iter().filter_map(item_name).collect();
+        expanded_file.attrs.clear();
+        expanded_file
+            .items
+            .retain(|item| is_generated(item, &fixture_items));
+
+        prettyplease::unparse(&expanded_file)
+    }
+}
+
+/// The name of a struct, enum or function item
+fn item_name(item: &syn::Item) -> Option<String> {
+    match item {
+        syn::Item::Struct(item) => Some(item.ident.to_string()),
+        syn::Item::Enum(item) => Some(item.ident.to_string()),
+        syn::Item::Fn(item) => Some(item.sig.ident.to_string()),
+        _ => None,
+    }
+}
+
+/// Whether an item was generated by `SubstructBuilder` rather than written by
+/// hand in the fixture or produced by a standard derive
+fn is_generated(item: &syn::Item, fixture_items: &HashSet<String>) -> bool {
+    let attrs = match item {
+        // `use` items, `extern crate std` and serde's `const _: () = { .. }` blocks
+        syn::Item::Use(_) | syn::Item::ExternCrate(_) | syn::Item::Const(_) => return false,
+        syn::Item::Impl(item) => &item.attrs,
+        _ => return !item_name(item).is_some_and(|name| fixture_items.contains(&name)),
+    };
+    !attrs
+        .iter()
+        .any(|attr| attr.path().is_ident("automatically_derived"))
+}
+
+/// A minimal line diff of the two snapshots for the failure message
+fn line_diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut diff = Vec::new();
+    for index in 0..expected.len().max(actual.len()) {
+        match (expected.get(index), actual.get(index)) {
+            (Some(left), Some(right)) if left == right => {}
+            (left, right) => {
+                if let Some(left) = left {
+                    diff.push(format!("{:>5} - {}", index + 1, left));
+                }
+                if let Some(right) = right {
+                    diff.push(format!("{:>5} + {}", index + 1, right));
+                }
+            }
+        }
+    }
+    diff.join("\n")
+}
@@ -0,0 +1,745 @@
+#[serde(default)]
+pub struct AccountSubstruct {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "AccountSubstruct::deserialize_present"
+    )]
+    pub nickname: Option<Option<String>>,
+    #[serde(skip_serializing_if = "AccountSubstruct::is_unset_quota")]
+    pub quota: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expected_version: Option<u64>,
+}
+impl AccountSubstruct {
+    /// Deserializes a field that is present, `null` included, as `Some`.
+    fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        T: serde::Deserialize<'de>,
+    {
+        T::deserialize(deserializer).map(Some)
+    }
+    /// Whether the field holds its "no change" value, so it is left out when serialized.
+    fn is_unset_quota(value: &u32) -> bool {
+        *value == 10
+    }
+}
+impl AccountSubstruct {
+    /// Creates a new substruct with the specified field values.
+    ///
+    /// # Arguments
+    ///
+    /// * `#(#field_names: #field_types)` - The values for each updatable field
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("John".to_string()),  // name field
+    ///     Some(true),               // active field
+    /// );
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        email: Option<String>,
+        nickname: Option<Option<String>>,
+        quota: u32,
+    ) -> Self {
+        let mut update = Self {
+            email,
+            nickname,
+            quota,
+            expected_version: None,
+        };
+        update.normalize();
+        update
+    }
+    /// Applies the `transform`/`transform_with` normalizers to every set field.
+    ///
+    /// This runs automatically in `new` and `set_by_path`, and `apply_to`/`would_change`
+    /// work on a normalized copy, so fields assigned directly are normalized too.
+    /// Nested substructs are normalized recursively.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut update = #update_struct_name::default();
+    /// update.email = Some("  Alice@Example.COM ".to_string());
+    /// update.normalize();
+    /// assert_eq!(update.email, Some("alice@example.com".to_string()));
+    /// ```
+    pub fn normalize(&mut self) {}
+    /// Creates a substruct from an existing instance, the same way as `From<&T>`.
+    ///
+    /// With the default `from = "empty"` policy all fields indicate "no change", which is
+    /// useful as a starting point that doesn't change anything when applied. With
+    /// `from = "snapshot"` it is the same as `snapshot`.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source struct to create the substruct from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let user = User::new("Alice".to_string(), false, 25);
+    /// let no_change_update = #update_struct_name::from_source(&user);
+    /// assert!(no_change_update.is_empty());
+    /// ```
+    pub fn from_source(source: &Account) -> Self {
+        Self::from(source)
+    }
+    /// Returns `true` if no fields would be changed by this update.
+    ///
+    /// This method checks if all fields are in their "no change" state:
+    /// - Wrapped fields are `None`
+    /// - Unwrapped fields are at their default (`Default::default()` or `default = "..."`)
+    /// - JSON fields are `None`
+    /// - Nested fields are `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let empty_update = #update_struct_name::default();
+    /// assert!(empty_update.is_empty());
+    ///
+    /// let update = #update_struct_name::new(Some("John".to_string()), None);
+    /// assert!(!update.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        if let Some(_) = &self.email {
+            return false;
+        }
+        if let Some(_) = &self.nickname {
+            return false;
+        }
+        if self.quota != 10 {
+            return false;
+        }
+        true
+    }
+    /// Returns the number of fields that have values set (non-default fields).
+    ///
+    /// This method counts fields that would actually change something when applied:
+    /// - Wrapped fields that are `Some(value)`
+    /// - Unwrapped fields that are not at their default
+    /// - JSON fields that are `Some(value)`
+    /// - Nested fields that are `Some(value)`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("John".to_string()),  // name field is set
+    ///     None,                     // active field is not set
+    /// );
+    /// assert_eq!(update.field_count(), 1);
+    ///
+    /// let full_update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),
+    ///     Some(true),
+    /// );
+    /// assert_eq!(full_update.field_count(), 2);
+    /// ```
+    pub fn field_count(&self) -> usize {
+        let mut count = 0;
+        if let Some(_) = &self.email {
+            count += 1;
+        }
+        if let Some(_) = &self.nickname {
+            count += 1;
+        }
+        if self.quota != 10 {
+            count += 1;
+        }
+        count
+    }
+    /// Resets all fields to their default values (no change state).
+    ///
+    /// This method sets all fields to their "no change" state:
+    /// - Wrapped fields become `None`
+    /// - Unwrapped fields are reset to their default
+    /// - JSON fields become `None`
+    /// - Nested fields become `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut update = #update_struct_name::new(
+    ///     Some("John".to_string()),
+    ///     Some(true),
+    /// );
+    /// assert_eq!(update.field_count(), 2);
+    ///
+    /// update.clear();
+    /// assert_eq!(update.field_count(), 0);
+    /// assert!(update.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.email = None;
+        self.nickname = None;
+        self.quota = 10;
+    }
+    /// Applies the updates to a target struct instance.
+    ///
+    /// This method modifies the target struct by applying all non-default field values
+    /// from this substruct. Fields that are in their "no change" state are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The mutable reference to the target struct to update
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Only applied if `Some(value)`
+    /// - **Unwrapped fields**: Only applied if not at their default
+    /// - **JSON fields**: Deserialized and applied if `Some(value)`
+    /// - **Nested fields**: Recursively applied, after their guards are checked too
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut user = User::new("Alice".to_string(), false, 25);
+    /// let update = #update_struct_name::new(
+    ///     Some("Bob".to_string()),  // Will change name
+    ///     None,                    // Will not change active
+    /// );
+    ///
+    /// update.apply_to(&mut user);
+    /// // user.name is now "Bob", user.active remains false
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Structs with a `#[substruct_field(version)]` or `expect` field, or with nested or
+    /// keyed fields whose guards may fail, return a `Result`. All guards, including
+    /// those of nested substructs and keyed element patches, are checked with
+    /// `check_guards` before anything is written:
+    /// - `VersionConflict` if `expected_version` is set and differs from the target's version
+    /// - `PreconditionFailed` if an `expect_<field>` is set and differs from the target's value
+    /// - `Nested` if a guard of a nested substruct or keyed element patch fails
+    ///
+    /// On success the target's version is incremented, unless the update is empty.
+    /// On failure nothing is written.
+    pub fn apply_to(
+        &self,
+        target: &mut Account,
+    ) -> Result<(), AccountSubstructApplyError> {
+        self.check_guards(target)?;
+        self.apply_unchecked(target);
+        Ok(())
+    }
+    /// Checks every guard of the update against `target` without modifying it.
+    ///
+    /// Covers the `expected_version` and `expect_<field>` guards, and recursively those
+    /// of nested substructs and keyed element patches, each against the value it would
+    /// be applied to.
+    ///
+    /// # Errors
+    ///
+    /// The first guard that fails, as `apply_to` would report it.
+    pub fn check_guards(
+        &self,
+        target: &Account,
+    ) -> Result<(), AccountSubstructApplyError> {
+        if let Some(expected) = &self.expected_version {
+            if *expected != target.version {
+                return Err(AccountSubstructApplyError::VersionConflict {
+                    expected: expected.clone(),
+                    found: target.version.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+    /// Applies the update like `apply_to`, without checking any guard.
+    ///
+    /// The version is still incremented for a non-empty update.
+    pub fn apply_unchecked(&self, target: &mut Account) {
+        let this = self;
+        if let Some(value) = &this.email {
+            target.email = value.clone();
+        }
+        if let Some(value) = &this.nickname {
+            target.nickname = value.clone();
+        }
+        if this.quota != 10 {
+            target.quota = this.quota.clone();
+        }
+        if !this.is_empty() {
+            target.version += 1;
+        }
+    }
+    /// Checks if applying this update would modify the target struct.
+    ///
+    /// This method compares the values in this substruct with the corresponding fields
+    /// in the target struct to determine if any changes would occur.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target struct to compare against
+    ///
+    /// # Returns
+    ///
+    /// * `true` if applying this update would change the target struct
+    /// * `false` if no changes would occur
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Compared if `Some(value)` and different from target
+    /// - **Unwrapped fields**: Compared if not at their default and different from target
+    /// - **JSON fields**: Serialized and compared if `Some(value)` and different from target
+    /// - **Nested fields**: Recursively checked using their own `would_change` method
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let user = User::new("Alice".to_string(), false, 25);
+    /// let update = #update_struct_name::new(
+    ///     Some("Bob".to_string()),  // Would change name
+    ///     Some(false),             // Would not change active (same value)
+    /// );
+    ///
+    /// assert!(update.would_change(&user));  // Would change name
+    ///
+    /// let no_change = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // Same as current name
+    ///     Some(false),               // Same as current active
+    /// );
+    /// assert!(!no_change.would_change(&user));  // No changes
+    /// ```
+    pub fn would_change(&self, target: &Account) -> bool {
+        let this = self;
+        if let Some(value) = &this.email {
+            if value != &target.email {
+                return true;
+            }
+        }
+        if let Some(value) = &this.nickname {
+            if value != &target.nickname {
+                return true;
+            }
+        }
+        if this.quota != 10 && this.quota != target.quota {
+            return true;
+        }
+        false
+    }
+    /// Combines two substructs, with the `other` substruct taking precedence for conflicting fields.
+    ///
+    /// This method merges the field values from two substructs, with the `other` substruct
+    /// taking precedence when both substructs have values for the same field.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other substruct to merge with (takes precedence for conflicts)
+    ///
+    /// # Returns
+    ///
+    /// A new substruct containing the merged field values.
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+    /// - **Unwrapped fields**: `other` value takes precedence if not at its default, otherwise uses `self`
+    /// - **JSON fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+    /// - **Nested fields**: Merged recursively when both are set; a cleared optional
+    ///   nested field in `other` takes precedence
+    ///
+    /// Fields with a `transform` are normalized first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update1 = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field
+    ///     None,                      // active field not set
+    /// );
+    ///
+    /// let update2 = #update_struct_name::new(
+    ///     None,                      // name field not set
+    ///     Some(true),               // active field
+    /// );
+    ///
+    /// let merged = update1.merge(update2);
+    /// // merged has name: Some("Alice") and active: Some(true)
+    /// ```
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            email: other.email.or(self.email),
+            nickname: other.nickname.or(self.nickname),
+            quota: if other.quota != 10 { other.quota } else { self.quota },
+            expected_version: other.expected_version.or(self.expected_version),
+        }
+    }
+    /// Checks if a specific field has a value set (non-default value).
+    ///
+    /// This method determines whether a field would actually change something when applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_name` - The name of the field to check (as a string)
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the field has a value set and would change something
+    /// * `false` if the field is in its "no change" state or doesn't exist
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Returns `true` if `Some(value)`
+    /// - **Unwrapped fields**: Returns `true` if not at their default
+    /// - **JSON fields**: Returns `true` if `Some(value)`
+    /// - **Nested fields**: Returns `true` if `Some(value)`
+    /// - **Nested paths**: `"address.city"` is checked recursively in the nested substruct
+    /// - **Non-existent fields**: Returns `false`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field is set
+    ///     None,                      // active field is not set
+    /// );
+    ///
+    /// assert!(update.has_field("name"));    // name field has a value
+    /// assert!(!update.has_field("active")); // active field is not set
+    /// assert!(!update.has_field("age"));    // age field doesn't exist in substruct
+    /// ```
+    pub fn has_field(&self, field_name: &str) -> bool {
+        if let Some((head, rest)) = field_name.split_once('.') {
+            return match head {
+                _ => false,
+            };
+        }
+        match field_name {
+            "email" => self.email.is_some(),
+            "nickname" => self.nickname.is_some(),
+            "quota" => self.quota != 10,
+            _ => false,
+        }
+    }
+    /// Converts the substruct into a flexible HashMap representation with string values.
+    ///
+    /// This method creates a HashMap where keys are field names and values are string
+    /// representations of the field values. This is useful for dynamic field access,
+    /// serialization, or when you need to work with field values in a generic way.
+    ///
+    /// # Returns
+    ///
+    /// A `HashMap<String, String>` containing only fields that have values set.
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Included if `Some(value)`, formatted using `{:?}`
+    /// - **Unwrapped fields**: Included if not at their default, formatted using `{:?}`
+    /// - **JSON fields**: Included if `Some(value)`, converted using `.to_string()`
+    /// - **Nested fields**: Recursively converted using their own `into_partial()` method
+    /// - **Sensitive fields**: Included as `"<redacted>"` instead of their value
+    /// - **Fields without values**: Not included in the result
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field
+    ///     Some(true),                // active field
+    /// );
+    ///
+    /// let partial = update.into_partial();
+    ///
+    /// // Check that fields are present
+    /// assert!(partial.contains_key("name"));
+    /// assert!(partial.contains_key("active"));
+    ///
+    /// // Compare actual values (as string representations)
+    /// assert_eq!(partial.get("name"), Some(&"\"Alice\"".to_string()));
+    /// assert_eq!(partial.get("active"), Some(&"true".to_string()));
+    ///
+    /// // Fields that aren't set are not included
+    /// assert!(!partial.contains_key("age")); // age field doesn't exist in substruct
+    /// ```
+    pub fn into_partial(self) -> std::collections::HashMap<String, String> {
+        let mut partial = std::collections::HashMap::new();
+        if let Some(value) = self.email {
+            partial
+                .insert(
+                    "email".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(format_args!("{0:?}", value))
+                    }),
+                );
+        }
+        if let Some(value) = self.nickname {
+            partial
+                .insert(
+                    "nickname".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(format_args!("{0:?}", value))
+                    }),
+                );
+        }
+        if self.quota != 10 {
+            partial
+                .insert(
+                    "quota".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(format_args!("{0:?}", & self.quota))
+                    }),
+                );
+        }
+        partial
+    }
+}
+impl Default for AccountSubstruct {
+    fn default() -> Self {
+        Self {
+            email: Default::default(),
+            nickname: Default::default(),
+            quota: 10,
+            expected_version: None,
+        }
+    }
+}
+impl From<Account> for AccountSubstruct {
+    fn from(source: Account) -> Self {
+        Self {
+            email: Default::default(),
+            nickname: Default::default(),
+            quota: 10,
+            expected_version: Some(source.version.clone()),
+        }
+    }
+}
+impl From<&Account> for AccountSubstruct {
+    fn from(source: &Account) -> Self {
+        Self {
+            email: Default::default(),
+            nickname: Default::default(),
+            quota: 10,
+            expected_version: Some(source.version.clone()),
+        }
+    }
+}
+impl AccountSubstruct {
+    /// Creates a substruct holding the source's current value in every field.
+    ///
+    /// Applying it to another value copies the source over, which is what a
+    /// full resync needs:
+    /// - Primitive and JSON fields are set to the source's values
+    /// - Nested substructs are snapshotted recursively; `Option`-wrapped ones are
+    ///   cleared when the source has none, `Vec`-wrapped ones are left unset
+    /// - Keyed collections become one `Upsert` per element
+    pub fn snapshot(source: &Account) -> Self {
+        Self {
+            email: Some(source.email.clone()),
+            nickname: Some(source.nickname.clone()),
+            quota: source.quota.clone(),
+            expected_version: Some(source.version.clone()),
+        }
+    }
+}
+/// All validation failures of a substruct, as `(field path, message)` pairs.
+pub struct AccountSubstructValidationErrors {
+    pub errors: Vec<(String, String)>,
+}
+impl AccountSubstructValidationErrors {
+    /// Returns the number of failures.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+    /// Returns `true` if there are no failures.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+    /// Returns the messages reported for a given field path.
+    pub fn messages_for(&self, path: &str) -> Vec<&str> {
+        self.errors
+            .iter()
+            .filter(|(field, _)| field == path)
+            .map(|(_, message)| message.as_str())
+            .collect()
+    }
+}
+impl std::fmt::Display for AccountSubstructValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (path, message)) in self.errors.iter().enumerate() {
+            if index > 0 {
+                f.write_fmt(format_args!("; "))?;
+            }
+            f.write_fmt(format_args!("{0}: {1}", path, message))?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for AccountSubstructValidationErrors {}
+impl AccountSubstruct {
+    /// Validates every field that is set against its `validate(...)` rules.
+    ///
+    /// The rules check the values `apply_to` writes, after the `transform`/`transform_with`
+    /// normalizers. Fields in their "no change" state are not checked. Nested substructs are
+    /// validated recursively and their errors are prefixed with the field path.
+    /// All failures are collected rather than stopping at the first one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(Some(200), None);
+    /// let errors = update.validate().unwrap_err();
+    /// assert_eq!(errors.messages_for("age"), vec!["must be at most 150"]);
+    /// ```
+    pub fn validate(&self) -> Result<(), AccountSubstructValidationErrors> {
+        let this = self;
+        #[allow(unused_mut)]
+        let mut errors: Vec<(String, String)> = Vec::new();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AccountSubstructValidationErrors {
+                errors,
+            })
+        }
+    }
+    /// Validates the substruct and applies it to the target only if it is valid.
+    ///
+    /// The target is left untouched when validation or a guard fails. A failed guard,
+    /// including one of a nested substruct, is reported as an error on the guarded field.
+    pub fn try_apply_to(
+        &self,
+        target: &mut Account,
+    ) -> Result<(), AccountSubstructValidationErrors> {
+        self.validate()?;
+        self.check_guards(target)
+            .map_err(|err| AccountSubstructValidationErrors {
+                errors: ::alloc::boxed::box_assume_init_into_vec_unsafe(
+                    ::alloc::intrinsics::write_box_via_move(
+                        ::alloc::boxed::Box::new_uninit(),
+                        [(err.field().to_string(), err.to_string())],
+                    ),
+                ),
+            })?;
+        self.apply_unchecked(target);
+        Ok(())
+    }
+}
+/// Error returned by `apply_to` when a guard on the target fails.
+///
+/// Guards are checked before anything is written, so the target is unchanged.
+pub enum AccountSubstructApplyError {
+    /// The target's version differs from `expected_version`.
+    VersionConflict { expected: u64, found: u64 },
+}
+impl AccountSubstructApplyError {
+    /// Returns the name of the guarded field that failed, or the path of the nested one.
+    pub fn field(&self) -> &str {
+        match self {
+            Self::VersionConflict { .. } => "version",
+        }
+    }
+}
+impl std::fmt::Display for AccountSubstructApplyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::VersionConflict { expected, found } => {
+                f.write_fmt(
+                    format_args!(
+                        "version conflict on `{0}`: expected {1:?}, found {2:?}",
+                        "version", expected, found
+                    ),
+                )
+            }
+        }
+    }
+}
+impl std::error::Error for AccountSubstructApplyError {}
+impl AccountSubstruct {
+    /// Guards the update: `apply_to` fails unless the target is at this version.
+    pub fn with_expected_version(mut self, version: u64) -> Self {
+        self.expected_version = Some(version);
+        self
+    }
+}
+/// Payload for creating a new value of the source struct.
+///
+/// Non-`Option` fields without a `default` are required; `try_build` reports the
+/// ones that are missing.
+pub struct CreateAccount {
+    pub email: Option<String>,
+    pub nickname: Option<String>,
+    pub quota: Option<u32>,
+}
+/// Error returned by `try_build`.
+pub enum CreateAccountError {
+    /// Required fields were not provided.
+    MissingFields {
+        /// Names of the missing fields, in declaration order.
+        fields: Vec<&'static str>,
+    },
+    /// The built value failed its `validate(...)` rules, after its transforms ran.
+    Invalid(AccountSubstructValidationErrors),
+}
+impl std::fmt::Display for CreateAccountError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingFields { fields } => {
+                f.write_fmt(
+                    format_args!("missing required fields: {0}", fields.join(", ")),
+                )
+            }
+            Self::Invalid(errors) => {
+                f.write_fmt(format_args!("invalid fields: {0}", errors))
+            }
+        }
+    }
+}
+impl std::error::Error for CreateAccountError {}
+impl CreateAccount {
+    /// Creates a payload with every required field set and the rest left to their defaults.
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(email: String) -> Self {
+        Self {
+            email: Some(email),
+            ..Default::default()
+        }
+    }
+    /// Builds the source struct, filling unset fields from their `default`.
+    ///
+    /// The `transform`/`transform_with` normalizers run on the built value, which
+    /// is then checked against the `validate(...)` rules.
+    ///
+    /// # Errors
+    ///
+    /// - `MissingFields` with the names of the required fields that are not set
+    /// - `Invalid` with every failed rule, as `validate` reports them
+    pub fn try_build(self) -> Result<Account, CreateAccountError> {
+        let mut missing = Vec::new();
+        if self.email.is_none() {
+            missing.push("email");
+        }
+        if !missing.is_empty() {
+            return Err(CreateAccountError::MissingFields {
+                fields: missing,
+            });
+        }
+        let (Some(email),) = (self.email,) else {
+            {
+                ::core::panicking::panic_fmt(
+                    format_args!(
+                        "internal error: entered unreachable code: {0}",
+                        format_args!("required fields were checked above")
+                    ),
+                );
+            };
+        };
+        let built = Account {
+            email,
+            nickname: self.nickname,
+            quota: self.quota.unwrap_or_else(|| 10),
+            version: <u64 as Default>::default(),
+            verified: false,
+        };
+        Ok(built)
+    }
+}
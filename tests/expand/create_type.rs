@@ -0,0 +1,19 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(create)]
+struct Account {
+    #[substruct_field(primitive)]
+    email: String,
+    #[substruct_field(primitive)]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false, default = "10")]
+    quota: u32,
+    #[substruct_field(version)]
+    version: u64,
+    #[substruct_field(default = "false")]
+    verified: bool,
+}
+
+fn main() {}
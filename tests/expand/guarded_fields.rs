@@ -0,0 +1,15 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(from = "snapshot")]
+struct Document {
+    #[substruct_field(primitive)]
+    title: String,
+    #[substruct_field(primitive, expect)]
+    status: String,
+    #[substruct_field(version)]
+    version: u64,
+}
+
+fn main() {}
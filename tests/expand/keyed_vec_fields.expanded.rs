@@ -0,0 +1,1314 @@
+#[serde(default)]
+pub struct OrderSubstruct {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub items: Vec<OrderSubstructItemsOp>,
+}
+impl OrderSubstruct {
+    /// Creates a new substruct with the specified field values.
+    ///
+    /// # Arguments
+    ///
+    /// * `#(#field_names: #field_types)` - The values for each updatable field
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("John".to_string()),  // name field
+    ///     Some(true),               // active field
+    /// );
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(status: Option<String>, items: Vec<OrderSubstructItemsOp>) -> Self {
+        let mut update = Self { status, items };
+        update.normalize();
+        update
+    }
+    /// Applies the `transform`/`transform_with` normalizers to every set field.
+    ///
+    /// This runs automatically in `new` and `set_by_path`, and `apply_to`/`would_change`
+    /// work on a normalized copy, so fields assigned directly are normalized too.
+    /// Nested substructs are normalized recursively.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut update = #update_struct_name::default();
+    /// update.email = Some("  Alice@Example.COM ".to_string());
+    /// update.normalize();
+    /// assert_eq!(update.email, Some("alice@example.com".to_string()));
+    /// ```
+    pub fn normalize(&mut self) {
+        for op in self.items.iter_mut() {
+            if let OrderSubstructItemsOp::Patch(_, patch) = op {
+                patch.normalize();
+            }
+        }
+    }
+    /// Creates a substruct from an existing instance, the same way as `From<&T>`.
+    ///
+    /// With the default `from = "empty"` policy all fields indicate "no change", which is
+    /// useful as a starting point that doesn't change anything when applied. With
+    /// `from = "snapshot"` it is the same as `snapshot`.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source struct to create the substruct from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let user = User::new("Alice".to_string(), false, 25);
+    /// let no_change_update = #update_struct_name::from_source(&user);
+    /// assert!(no_change_update.is_empty());
+    /// ```
+    pub fn from_source(source: &Order) -> Self {
+        Self::from(source)
+    }
+    /// Returns `true` if no fields would be changed by this update.
+    ///
+    /// This method checks if all fields are in their "no change" state:
+    /// - Wrapped fields are `None`
+    /// - Unwrapped fields are at their default (`Default::default()` or `default = "..."`)
+    /// - JSON fields are `None`
+    /// - Nested fields are `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let empty_update = #update_struct_name::default();
+    /// assert!(empty_update.is_empty());
+    ///
+    /// let update = #update_struct_name::new(Some("John".to_string()), None);
+    /// assert!(!update.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        if let Some(_) = &self.status {
+            return false;
+        }
+        if !self.items.is_empty() {
+            return false;
+        }
+        true
+    }
+    /// Returns the number of fields that have values set (non-default fields).
+    ///
+    /// This method counts fields that would actually change something when applied:
+    /// - Wrapped fields that are `Some(value)`
+    /// - Unwrapped fields that are not at their default
+    /// - JSON fields that are `Some(value)`
+    /// - Nested fields that are `Some(value)`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("John".to_string()),  // name field is set
+    ///     None,                     // active field is not set
+    /// );
+    /// assert_eq!(update.field_count(), 1);
+    ///
+    /// let full_update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),
+    ///     Some(true),
+    /// );
+    /// assert_eq!(full_update.field_count(), 2);
+    /// ```
+    pub fn field_count(&self) -> usize {
+        let mut count = 0;
+        if let Some(_) = &self.status {
+            count += 1;
+        }
+        if !self.items.is_empty() {
+            count += 1;
+        }
+        count
+    }
+    /// Resets all fields to their default values (no change state).
+    ///
+    /// This method sets all fields to their "no change" state:
+    /// - Wrapped fields become `None`
+    /// - Unwrapped fields are reset to their default
+    /// - JSON fields become `None`
+    /// - Nested fields become `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut update = #update_struct_name::new(
+    ///     Some("John".to_string()),
+    ///     Some(true),
+    /// );
+    /// assert_eq!(update.field_count(), 2);
+    ///
+    /// update.clear();
+    /// assert_eq!(update.field_count(), 0);
+    /// assert!(update.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.status = None;
+        self.items.clear();
+    }
+    /// Applies the updates to a target struct instance.
+    ///
+    /// This method modifies the target struct by applying all non-default field values
+    /// from this substruct. Fields that are in their "no change" state are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The mutable reference to the target struct to update
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Only applied if `Some(value)`
+    /// - **Unwrapped fields**: Only applied if not at their default
+    /// - **JSON fields**: Deserialized and applied if `Some(value)`
+    /// - **Nested fields**: Recursively applied, after their guards are checked too
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut user = User::new("Alice".to_string(), false, 25);
+    /// let update = #update_struct_name::new(
+    ///     Some("Bob".to_string()),  // Will change name
+    ///     None,                    // Will not change active
+    /// );
+    ///
+    /// update.apply_to(&mut user);
+    /// // user.name is now "Bob", user.active remains false
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Structs with a `#[substruct_field(version)]` or `expect` field, or with nested or
+    /// keyed fields whose guards may fail, return a `Result`. All guards, including
+    /// those of nested substructs and keyed element patches, are checked with
+    /// `check_guards` before anything is written:
+    /// - `VersionConflict` if `expected_version` is set and differs from the target's version
+    /// - `PreconditionFailed` if an `expect_<field>` is set and differs from the target's value
+    /// - `Nested` if a guard of a nested substruct or keyed element patch fails
+    ///
+    /// On success the target's version is incremented, unless the update is empty.
+    /// On failure nothing is written.
+    pub fn apply_to(&self, target: &mut Order) -> Result<(), OrderSubstructApplyError> {
+        self.check_guards(target)?;
+        self.apply_unchecked(target);
+        Ok(())
+    }
+    /// Checks every guard of the update against `target` without modifying it.
+    ///
+    /// Covers the `expected_version` and `expect_<field>` guards, and recursively those
+    /// of nested substructs and keyed element patches, each against the value it would
+    /// be applied to.
+    ///
+    /// # Errors
+    ///
+    /// The first guard that fails, as `apply_to` would report it.
+    pub fn check_guards(&self, target: &Order) -> Result<(), OrderSubstructApplyError> {
+        if self
+            .items
+            .iter()
+            .any(|op| {
+                #[allow(non_exhaustive_omitted_patterns)]
+                match op {
+                    OrderSubstructItemsOp::Patch(..) => true,
+                    _ => false,
+                }
+            })
+        {
+            let mut items = target.items.clone();
+            for op in &self.items {
+                match op {
+                    OrderSubstructItemsOp::Upsert(item) => {
+                        match items.iter_mut().find(|existing| existing.sku == item.sku)
+                        {
+                            Some(existing) => *existing = item.clone(),
+                            None => items.push(item.clone()),
+                        }
+                    }
+                    OrderSubstructItemsOp::Remove(key) => {
+                        items.retain(|existing| &existing.sku != key);
+                    }
+                    OrderSubstructItemsOp::Patch(key, patch) => {
+                        if let Some(existing) = items
+                            .iter_mut()
+                            .find(|existing| &existing.sku == key)
+                        {
+                            patch
+                                .check_guards(existing)
+                                .map_err(|err| OrderSubstructApplyError::Nested {
+                                    path: ::alloc::__export::must_use({
+                                        ::alloc::fmt::format(
+                                            format_args!("{0}[{1:?}]", "items", key),
+                                        )
+                                    }),
+                                    message: err.to_string(),
+                                })?;
+                            patch.apply_unchecked(existing);
+                        }
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+    /// Applies the update like `apply_to`, without checking any guard.
+    ///
+    /// The version is still incremented for a non-empty update.
+    pub fn apply_unchecked(&self, target: &mut Order) {
+        let this = self;
+        if let Some(value) = &this.status {
+            target.status = value.clone();
+        }
+        for op in &this.items {
+            match op {
+                OrderSubstructItemsOp::Upsert(item) => {
+                    match target
+                        .items
+                        .iter_mut()
+                        .find(|existing| existing.sku == item.sku)
+                    {
+                        Some(existing) => *existing = item.clone(),
+                        None => target.items.push(item.clone()),
+                    }
+                }
+                OrderSubstructItemsOp::Remove(key) => {
+                    target.items.retain(|existing| &existing.sku != key);
+                }
+                OrderSubstructItemsOp::Patch(key, patch) => {
+                    if let Some(existing) = target
+                        .items
+                        .iter_mut()
+                        .find(|existing| &existing.sku == key)
+                    {
+                        patch.apply_unchecked(existing);
+                    }
+                }
+            }
+        }
+    }
+    /// Checks if applying this update would modify the target struct.
+    ///
+    /// This method compares the values in this substruct with the corresponding fields
+    /// in the target struct to determine if any changes would occur.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target struct to compare against
+    ///
+    /// # Returns
+    ///
+    /// * `true` if applying this update would change the target struct
+    /// * `false` if no changes would occur
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Compared if `Some(value)` and different from target
+    /// - **Unwrapped fields**: Compared if not at their default and different from target
+    /// - **JSON fields**: Serialized and compared if `Some(value)` and different from target
+    /// - **Nested fields**: Recursively checked using their own `would_change` method
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let user = User::new("Alice".to_string(), false, 25);
+    /// let update = #update_struct_name::new(
+    ///     Some("Bob".to_string()),  // Would change name
+    ///     Some(false),             // Would not change active (same value)
+    /// );
+    ///
+    /// assert!(update.would_change(&user));  // Would change name
+    ///
+    /// let no_change = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // Same as current name
+    ///     Some(false),               // Same as current active
+    /// );
+    /// assert!(!no_change.would_change(&user));  // No changes
+    /// ```
+    pub fn would_change(&self, target: &Order) -> bool {
+        let this = self;
+        if let Some(value) = &this.status {
+            if value != &target.status {
+                return true;
+            }
+        }
+        if !this.items.is_empty() {
+            let mut items = target.items.clone();
+            for op in &this.items {
+                match op {
+                    OrderSubstructItemsOp::Upsert(item) => {
+                        match items.iter_mut().find(|existing| existing.sku == item.sku)
+                        {
+                            Some(existing) => *existing = item.clone(),
+                            None => items.push(item.clone()),
+                        }
+                    }
+                    OrderSubstructItemsOp::Remove(key) => {
+                        items.retain(|existing| &existing.sku != key);
+                    }
+                    OrderSubstructItemsOp::Patch(key, patch) => {
+                        if let Some(existing) = items
+                            .iter_mut()
+                            .find(|existing| &existing.sku == key)
+                        {
+                            patch.apply_unchecked(existing);
+                        }
+                    }
+                }
+            }
+            if items != target.items {
+                return true;
+            }
+        }
+        false
+    }
+    /// Combines two substructs, with the `other` substruct taking precedence for conflicting fields.
+    ///
+    /// This method merges the field values from two substructs, with the `other` substruct
+    /// taking precedence when both substructs have values for the same field.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other substruct to merge with (takes precedence for conflicts)
+    ///
+    /// # Returns
+    ///
+    /// A new substruct containing the merged field values.
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+    /// - **Unwrapped fields**: `other` value takes precedence if not at its default, otherwise uses `self`
+    /// - **JSON fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+    /// - **Nested fields**: Merged recursively when both are set; a cleared optional
+    ///   nested field in `other` takes precedence
+    ///
+    /// Fields with a `transform` are normalized first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update1 = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field
+    ///     None,                      // active field not set
+    /// );
+    ///
+    /// let update2 = #update_struct_name::new(
+    ///     None,                      // name field not set
+    ///     Some(true),               // active field
+    /// );
+    ///
+    /// let merged = update1.merge(update2);
+    /// // merged has name: Some("Alice") and active: Some(true)
+    /// ```
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            status: other.status.or(self.status),
+            items: {
+                let mut ops = self.items;
+                ops.extend(other.items);
+                ops
+            },
+        }
+    }
+    /// Checks if a specific field has a value set (non-default value).
+    ///
+    /// This method determines whether a field would actually change something when applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_name` - The name of the field to check (as a string)
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the field has a value set and would change something
+    /// * `false` if the field is in its "no change" state or doesn't exist
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Returns `true` if `Some(value)`
+    /// - **Unwrapped fields**: Returns `true` if not at their default
+    /// - **JSON fields**: Returns `true` if `Some(value)`
+    /// - **Nested fields**: Returns `true` if `Some(value)`
+    /// - **Nested paths**: `"address.city"` is checked recursively in the nested substruct
+    /// - **Non-existent fields**: Returns `false`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field is set
+    ///     None,                      // active field is not set
+    /// );
+    ///
+    /// assert!(update.has_field("name"));    // name field has a value
+    /// assert!(!update.has_field("active")); // active field is not set
+    /// assert!(!update.has_field("age"));    // age field doesn't exist in substruct
+    /// ```
+    pub fn has_field(&self, field_name: &str) -> bool {
+        if let Some((head, rest)) = field_name.split_once('.') {
+            return match head {
+                _ => false,
+            };
+        }
+        match field_name {
+            "status" => self.status.is_some(),
+            "items" => !self.items.is_empty(),
+            _ => false,
+        }
+    }
+    /// Converts the substruct into a flexible HashMap representation with string values.
+    ///
+    /// This method creates a HashMap where keys are field names and values are string
+    /// representations of the field values. This is useful for dynamic field access,
+    /// serialization, or when you need to work with field values in a generic way.
+    ///
+    /// # Returns
+    ///
+    /// A `HashMap<String, String>` containing only fields that have values set.
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Included if `Some(value)`, formatted using `{:?}`
+    /// - **Unwrapped fields**: Included if not at their default, formatted using `{:?}`
+    /// - **JSON fields**: Included if `Some(value)`, converted using `.to_string()`
+    /// - **Nested fields**: Recursively converted using their own `into_partial()` method
+    /// - **Sensitive fields**: Included as `"<redacted>"` instead of their value
+    /// - **Fields without values**: Not included in the result
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field
+    ///     Some(true),                // active field
+    /// );
+    ///
+    /// let partial = update.into_partial();
+    ///
+    /// // Check that fields are present
+    /// assert!(partial.contains_key("name"));
+    /// assert!(partial.contains_key("active"));
+    ///
+    /// // Compare actual values (as string representations)
+    /// assert_eq!(partial.get("name"), Some(&"\"Alice\"".to_string()));
+    /// assert_eq!(partial.get("active"), Some(&"true".to_string()));
+    ///
+    /// // Fields that aren't set are not included
+    /// assert!(!partial.contains_key("age")); // age field doesn't exist in substruct
+    /// ```
+    pub fn into_partial(self) -> std::collections::HashMap<String, String> {
+        let mut partial = std::collections::HashMap::new();
+        if let Some(value) = self.status {
+            partial
+                .insert(
+                    "status".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(format_args!("{0:?}", value))
+                    }),
+                );
+        }
+        if !self.items.is_empty() {
+            let ops: Vec<String> = self
+                .items
+                .into_iter()
+                .map(|op| match op {
+                    OrderSubstructItemsOp::Upsert(item) => {
+                        ::alloc::__export::must_use({
+                            ::alloc::fmt::format(
+                                format_args!(
+                                    "upsert {0:?} {1:?}", item.sku,
+                                    LineItemSubstruct::snapshot(& item).into_partial()
+                                ),
+                            )
+                        })
+                    }
+                    OrderSubstructItemsOp::Remove(key) => {
+                        ::alloc::__export::must_use({
+                            ::alloc::fmt::format(format_args!("remove {0:?}", key))
+                        })
+                    }
+                    OrderSubstructItemsOp::Patch(key, patch) => {
+                        ::alloc::__export::must_use({
+                            ::alloc::fmt::format(
+                                format_args!("patch {0:?} {1:?}", key, patch.into_partial()),
+                            )
+                        })
+                    }
+                })
+                .collect();
+            partial
+                .insert(
+                    "items".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(format_args!("{0:?}", ops))
+                    }),
+                );
+        }
+        partial
+    }
+}
+impl Default for OrderSubstruct {
+    fn default() -> Self {
+        Self {
+            status: Default::default(),
+            items: Default::default(),
+        }
+    }
+}
+impl From<Order> for OrderSubstruct {
+    fn from(source: Order) -> Self {
+        Self {
+            status: Default::default(),
+            items: Default::default(),
+        }
+    }
+}
+impl From<&Order> for OrderSubstruct {
+    fn from(source: &Order) -> Self {
+        Self {
+            status: Default::default(),
+            items: Default::default(),
+        }
+    }
+}
+impl OrderSubstruct {
+    /// Creates a substruct holding the source's current value in every field.
+    ///
+    /// Applying it to another value copies the source over, which is what a
+    /// full resync needs:
+    /// - Primitive and JSON fields are set to the source's values
+    /// - Nested substructs are snapshotted recursively; `Option`-wrapped ones are
+    ///   cleared when the source has none, `Vec`-wrapped ones are left unset
+    /// - Keyed collections become one `Upsert` per element
+    pub fn snapshot(source: &Order) -> Self {
+        Self {
+            status: Some(source.status.clone()),
+            items: source
+                .items
+                .iter()
+                .cloned()
+                .map(OrderSubstructItemsOp::Upsert)
+                .collect(),
+        }
+    }
+}
+/// All validation failures of a substruct, as `(field path, message)` pairs.
+pub struct OrderSubstructValidationErrors {
+    pub errors: Vec<(String, String)>,
+}
+impl OrderSubstructValidationErrors {
+    /// Returns the number of failures.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+    /// Returns `true` if there are no failures.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+    /// Returns the messages reported for a given field path.
+    pub fn messages_for(&self, path: &str) -> Vec<&str> {
+        self.errors
+            .iter()
+            .filter(|(field, _)| field == path)
+            .map(|(_, message)| message.as_str())
+            .collect()
+    }
+}
+impl std::fmt::Display for OrderSubstructValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (path, message)) in self.errors.iter().enumerate() {
+            if index > 0 {
+                f.write_fmt(format_args!("; "))?;
+            }
+            f.write_fmt(format_args!("{0}: {1}", path, message))?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for OrderSubstructValidationErrors {}
+impl OrderSubstruct {
+    /// Validates every field that is set against its `validate(...)` rules.
+    ///
+    /// The rules check the values `apply_to` writes, after the `transform`/`transform_with`
+    /// normalizers. Fields in their "no change" state are not checked. Nested substructs are
+    /// validated recursively and their errors are prefixed with the field path.
+    /// All failures are collected rather than stopping at the first one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(Some(200), None);
+    /// let errors = update.validate().unwrap_err();
+    /// assert_eq!(errors.messages_for("age"), vec!["must be at most 150"]);
+    /// ```
+    pub fn validate(&self) -> Result<(), OrderSubstructValidationErrors> {
+        let this = self;
+        #[allow(unused_mut)]
+        let mut errors: Vec<(String, String)> = Vec::new();
+        for op in &this.items {
+            if let OrderSubstructItemsOp::Patch(key, patch) = op {
+                if let Err(nested_errors) = patch.validate() {
+                    errors
+                        .extend(
+                            nested_errors
+                                .errors
+                                .into_iter()
+                                .map(|(path, message)| {
+                                    (
+                                        ::alloc::__export::must_use({
+                                            ::alloc::fmt::format(
+                                                format_args!("{0}[{1:?}].{2}", "items", key, path),
+                                            )
+                                        }),
+                                        message,
+                                    )
+                                }),
+                        );
+                }
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(OrderSubstructValidationErrors {
+                errors,
+            })
+        }
+    }
+    /// Validates the substruct and applies it to the target only if it is valid.
+    ///
+    /// The target is left untouched when validation or a guard fails. A failed guard,
+    /// including one of a nested substruct, is reported as an error on the guarded field.
+    pub fn try_apply_to(
+        &self,
+        target: &mut Order,
+    ) -> Result<(), OrderSubstructValidationErrors> {
+        self.validate()?;
+        self.check_guards(target)
+            .map_err(|err| OrderSubstructValidationErrors {
+                errors: ::alloc::boxed::box_assume_init_into_vec_unsafe(
+                    ::alloc::intrinsics::write_box_via_move(
+                        ::alloc::boxed::Box::new_uninit(),
+                        [(err.field().to_string(), err.to_string())],
+                    ),
+                ),
+            })?;
+        self.apply_unchecked(target);
+        Ok(())
+    }
+}
+/// A keyed operation on one element of the `#name` collection.
+#[serde(rename_all = "snake_case")]
+#[allow(private_interfaces)]
+pub enum OrderSubstructItemsOp {
+    /// Replaces the element with the same key, or appends it.
+    Upsert(LineItem),
+    /// Removes the element with this key.
+    Remove(String),
+    /// Patches the element with this key; missing keys are ignored.
+    Patch(String, LineItemSubstruct),
+}
+impl std::fmt::Debug for OrderSubstructItemsOp {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Upsert(item) => {
+                f.debug_tuple("Upsert")
+                    .field(&item.sku)
+                    .field(&LineItemSubstruct::snapshot(item))
+                    .finish()
+            }
+            Self::Remove(key) => f.debug_tuple("Remove").field(key).finish(),
+            Self::Patch(key, patch) => {
+                f.debug_tuple("Patch").field(key).field(patch).finish()
+            }
+        }
+    }
+}
+impl OrderSubstruct {
+    /// Queues an upsert of a whole element, matched by key.
+    pub fn upsert_items(mut self, item: LineItem) -> Self {
+        self.items.push(OrderSubstructItemsOp::Upsert(item));
+        self
+    }
+    /// Queues the removal of the element with the given key.
+    pub fn remove_items(mut self, key: impl Into<String>) -> Self {
+        self.items.push(OrderSubstructItemsOp::Remove(key.into()));
+        self
+    }
+    /// Queues a patch of the element with the given key.
+    pub fn patch_items(
+        mut self,
+        key: impl Into<String>,
+        patch: LineItemSubstruct,
+    ) -> Self {
+        self.items.push(OrderSubstructItemsOp::Patch(key.into(), patch));
+        self
+    }
+}
+/// Error returned by `apply_to` when a guard on the target fails.
+///
+/// Guards are checked before anything is written, so the target is unchanged.
+pub enum OrderSubstructApplyError {
+    /// A guard of the nested substruct or keyed element patch at `path` failed.
+    Nested { path: String, message: String },
+}
+impl OrderSubstructApplyError {
+    /// Returns the name of the guarded field that failed, or the path of the nested one.
+    pub fn field(&self) -> &str {
+        match self {
+            Self::Nested { path, .. } => path,
+        }
+    }
+}
+impl std::fmt::Display for OrderSubstructApplyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nested { path, message } => {
+                f.write_fmt(format_args!("guard failed in `{0}`: {1}", path, message))
+            }
+        }
+    }
+}
+impl std::error::Error for OrderSubstructApplyError {}
+impl OrderSubstruct {}
+#[serde(default)]
+pub struct LineItemSubstruct {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sku: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub quantity: Option<u32>,
+}
+impl LineItemSubstruct {
+    /// Creates a new substruct with the specified field values.
+    ///
+    /// # Arguments
+    ///
+    /// * `#(#field_names: #field_types)` - The values for each updatable field
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("John".to_string()),  // name field
+    ///     Some(true),               // active field
+    /// );
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(sku: Option<String>, quantity: Option<u32>) -> Self {
+        let mut update = Self { sku, quantity };
+        update.normalize();
+        update
+    }
+    /// Applies the `transform`/`transform_with` normalizers to every set field.
+    ///
+    /// This runs automatically in `new` and `set_by_path`, and `apply_to`/`would_change`
+    /// work on a normalized copy, so fields assigned directly are normalized too.
+    /// Nested substructs are normalized recursively.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut update = #update_struct_name::default();
+    /// update.email = Some("  Alice@Example.COM ".to_string());
+    /// update.normalize();
+    /// assert_eq!(update.email, Some("alice@example.com".to_string()));
+    /// ```
+    pub fn normalize(&mut self) {}
+    /// Creates a substruct from an existing instance, the same way as `From<&T>`.
+    ///
+    /// With the default `from = "empty"` policy all fields indicate "no change", which is
+    /// useful as a starting point that doesn't change anything when applied. With
+    /// `from = "snapshot"` it is the same as `snapshot`.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source struct to create the substruct from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let user = User::new("Alice".to_string(), false, 25);
+    /// let no_change_update = #update_struct_name::from_source(&user);
+    /// assert!(no_change_update.is_empty());
+    /// ```
+    pub fn from_source(source: &LineItem) -> Self {
+        Self::from(source)
+    }
+    /// Returns `true` if no fields would be changed by this update.
+    ///
+    /// This method checks if all fields are in their "no change" state:
+    /// - Wrapped fields are `None`
+    /// - Unwrapped fields are at their default (`Default::default()` or `default = "..."`)
+    /// - JSON fields are `None`
+    /// - Nested fields are `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let empty_update = #update_struct_name::default();
+    /// assert!(empty_update.is_empty());
+    ///
+    /// let update = #update_struct_name::new(Some("John".to_string()), None);
+    /// assert!(!update.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        if let Some(_) = &self.sku {
+            return false;
+        }
+        if let Some(_) = &self.quantity {
+            return false;
+        }
+        true
+    }
+    /// Returns the number of fields that have values set (non-default fields).
+    ///
+    /// This method counts fields that would actually change something when applied:
+    /// - Wrapped fields that are `Some(value)`
+    /// - Unwrapped fields that are not at their default
+    /// - JSON fields that are `Some(value)`
+    /// - Nested fields that are `Some(value)`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("John".to_string()),  // name field is set
+    ///     None,                     // active field is not set
+    /// );
+    /// assert_eq!(update.field_count(), 1);
+    ///
+    /// let full_update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),
+    ///     Some(true),
+    /// );
+    /// assert_eq!(full_update.field_count(), 2);
+    /// ```
+    pub fn field_count(&self) -> usize {
+        let mut count = 0;
+        if let Some(_) = &self.sku {
+            count += 1;
+        }
+        if let Some(_) = &self.quantity {
+            count += 1;
+        }
+        count
+    }
+    /// Resets all fields to their default values (no change state).
+    ///
+    /// This method sets all fields to their "no change" state:
+    /// - Wrapped fields become `None`
+    /// - Unwrapped fields are reset to their default
+    /// - JSON fields become `None`
+    /// - Nested fields become `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut update = #update_struct_name::new(
+    ///     Some("John".to_string()),
+    ///     Some(true),
+    /// );
+    /// assert_eq!(update.field_count(), 2);
+    ///
+    /// update.clear();
+    /// assert_eq!(update.field_count(), 0);
+    /// assert!(update.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.sku = None;
+        self.quantity = None;
+    }
+    /// Applies the updates to a target struct instance.
+    ///
+    /// This method modifies the target struct by applying all non-default field values
+    /// from this substruct. Fields that are in their "no change" state are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The mutable reference to the target struct to update
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Only applied if `Some(value)`
+    /// - **Unwrapped fields**: Only applied if not at their default
+    /// - **JSON fields**: Deserialized and applied if `Some(value)`
+    /// - **Nested fields**: Recursively applied, after their guards are checked too
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut user = User::new("Alice".to_string(), false, 25);
+    /// let update = #update_struct_name::new(
+    ///     Some("Bob".to_string()),  // Will change name
+    ///     None,                    // Will not change active
+    /// );
+    ///
+    /// update.apply_to(&mut user);
+    /// // user.name is now "Bob", user.active remains false
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Structs with a `#[substruct_field(version)]` or `expect` field, or with nested or
+    /// keyed fields whose guards may fail, return a `Result`. All guards, including
+    /// those of nested substructs and keyed element patches, are checked with
+    /// `check_guards` before anything is written:
+    /// - `VersionConflict` if `expected_version` is set and differs from the target's version
+    /// - `PreconditionFailed` if an `expect_<field>` is set and differs from the target's value
+    /// - `Nested` if a guard of a nested substruct or keyed element patch fails
+    ///
+    /// On success the target's version is incremented, unless the update is empty.
+    /// On failure nothing is written.
+    pub fn apply_to(&self, target: &mut LineItem) {
+        self.apply_unchecked(target);
+    }
+    /// Checks every guard of the update against `target` without modifying it.
+    ///
+    /// Covers the `expected_version` and `expect_<field>` guards, and recursively those
+    /// of nested substructs and keyed element patches, each against the value it would
+    /// be applied to.
+    ///
+    /// # Errors
+    ///
+    /// The first guard that fails, as `apply_to` would report it.
+    pub fn check_guards(
+        &self,
+        target: &LineItem,
+    ) -> Result<(), std::convert::Infallible> {
+        Ok(())
+    }
+    /// Applies the update like `apply_to`, without checking any guard.
+    ///
+    /// The version is still incremented for a non-empty update.
+    pub fn apply_unchecked(&self, target: &mut LineItem) {
+        let this = self;
+        if let Some(value) = &this.sku {
+            target.sku = value.clone();
+        }
+        if let Some(value) = &this.quantity {
+            target.quantity = value.clone();
+        }
+    }
+    /// Checks if applying this update would modify the target struct.
+    ///
+    /// This method compares the values in this substruct with the corresponding fields
+    /// in the target struct to determine if any changes would occur.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target struct to compare against
+    ///
+    /// # Returns
+    ///
+    /// * `true` if applying this update would change the target struct
+    /// * `false` if no changes would occur
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Compared if `Some(value)` and different from target
+    /// - **Unwrapped fields**: Compared if not at their default and different from target
+    /// - **JSON fields**: Serialized and compared if `Some(value)` and different from target
+    /// - **Nested fields**: Recursively checked using their own `would_change` method
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let user = User::new("Alice".to_string(), false, 25);
+    /// let update = #update_struct_name::new(
+    ///     Some("Bob".to_string()),  // Would change name
+    ///     Some(false),             // Would not change active (same value)
+    /// );
+    ///
+    /// assert!(update.would_change(&user));  // Would change name
+    ///
+    /// let no_change = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // Same as current name
+    ///     Some(false),               // Same as current active
+    /// );
+    /// assert!(!no_change.would_change(&user));  // No changes
+    /// ```
+    pub fn would_change(&self, target: &LineItem) -> bool {
+        let this = self;
+        if let Some(value) = &this.sku {
+            if value != &target.sku {
+                return true;
+            }
+        }
+        if let Some(value) = &this.quantity {
+            if value != &target.quantity {
+                return true;
+            }
+        }
+        false
+    }
+    /// Combines two substructs, with the `other` substruct taking precedence for conflicting fields.
+    ///
+    /// This method merges the field values from two substructs, with the `other` substruct
+    /// taking precedence when both substructs have values for the same field.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other substruct to merge with (takes precedence for conflicts)
+    ///
+    /// # Returns
+    ///
+    /// A new substruct containing the merged field values.
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+    /// - **Unwrapped fields**: `other` value takes precedence if not at its default, otherwise uses `self`
+    /// - **JSON fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+    /// - **Nested fields**: Merged recursively when both are set; a cleared optional
+    ///   nested field in `other` takes precedence
+    ///
+    /// Fields with a `transform` are normalized first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update1 = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field
+    ///     None,                      // active field not set
+    /// );
+    ///
+    /// let update2 = #update_struct_name::new(
+    ///     None,                      // name field not set
+    ///     Some(true),               // active field
+    /// );
+    ///
+    /// let merged = update1.merge(update2);
+    /// // merged has name: Some("Alice") and active: Some(true)
+    /// ```
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            sku: other.sku.or(self.sku),
+            quantity: other.quantity.or(self.quantity),
+        }
+    }
+    /// Checks if a specific field has a value set (non-default value).
+    ///
+    /// This method determines whether a field would actually change something when applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_name` - The name of the field to check (as a string)
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the field has a value set and would change something
+    /// * `false` if the field is in its "no change" state or doesn't exist
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Returns `true` if `Some(value)`
+    /// - **Unwrapped fields**: Returns `true` if not at their default
+    /// - **JSON fields**: Returns `true` if `Some(value)`
+    /// - **Nested fields**: Returns `true` if `Some(value)`
+    /// - **Nested paths**: `"address.city"` is checked recursively in the nested substruct
+    /// - **Non-existent fields**: Returns `false`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field is set
+    ///     None,                      // active field is not set
+    /// );
+    ///
+    /// assert!(update.has_field("name"));    // name field has a value
+    /// assert!(!update.has_field("active")); // active field is not set
+    /// assert!(!update.has_field("age"));    // age field doesn't exist in substruct
+    /// ```
+    pub fn has_field(&self, field_name: &str) -> bool {
+        if let Some((head, rest)) = field_name.split_once('.') {
+            return match head {
+                _ => false,
+            };
+        }
+        match field_name {
+            "sku" => self.sku.is_some(),
+            "quantity" => self.quantity.is_some(),
+            _ => false,
+        }
+    }
+    /// Converts the substruct into a flexible HashMap representation with string values.
+    ///
+    /// This method creates a HashMap where keys are field names and values are string
+    /// representations of the field values. This is useful for dynamic field access,
+    /// serialization, or when you need to work with field values in a generic way.
+    ///
+    /// # Returns
+    ///
+    /// A `HashMap<String, String>` containing only fields that have values set.
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Included if `Some(value)`, formatted using `{:?}`
+    /// - **Unwrapped fields**: Included if not at their default, formatted using `{:?}`
+    /// - **JSON fields**: Included if `Some(value)`, converted using `.to_string()`
+    /// - **Nested fields**: Recursively converted using their own `into_partial()` method
+    /// - **Sensitive fields**: Included as `"<redacted>"` instead of their value
+    /// - **Fields without values**: Not included in the result
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field
+    ///     Some(true),                // active field
+    /// );
+    ///
+    /// let partial = update.into_partial();
+    ///
+    /// // Check that fields are present
+    /// assert!(partial.contains_key("name"));
+    /// assert!(partial.contains_key("active"));
+    ///
+    /// // Compare actual values (as string representations)
+    /// assert_eq!(partial.get("name"), Some(&"\"Alice\"".to_string()));
+    /// assert_eq!(partial.get("active"), Some(&"true".to_string()));
+    ///
+    /// // Fields that aren't set are not included
+    /// assert!(!partial.contains_key("age")); // age field doesn't exist in substruct
+    /// ```
+    pub fn into_partial(self) -> std::collections::HashMap<String, String> {
+        let mut partial = std::collections::HashMap::new();
+        if let Some(value) = self.sku {
+            partial
+                .insert(
+                    "sku".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(format_args!("{0:?}", value))
+                    }),
+                );
+        }
+        if let Some(value) = self.quantity {
+            partial
+                .insert(
+                    "quantity".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(format_args!("{0:?}", value))
+                    }),
+                );
+        }
+        partial
+    }
+}
+impl Default for LineItemSubstruct {
+    fn default() -> Self {
+        Self {
+            sku: Default::default(),
+            quantity: Default::default(),
+        }
+    }
+}
+impl From<LineItem> for LineItemSubstruct {
+    fn from(source: LineItem) -> Self {
+        Self {
+            sku: Default::default(),
+            quantity: Default::default(),
+        }
+    }
+}
+impl From<&LineItem> for LineItemSubstruct {
+    fn from(source: &LineItem) -> Self {
+        Self {
+            sku: Default::default(),
+            quantity: Default::default(),
+        }
+    }
+}
+impl LineItemSubstruct {
+    /// Creates a substruct holding the source's current value in every field.
+    ///
+    /// Applying it to another value copies the source over, which is what a
+    /// full resync needs:
+    /// - Primitive and JSON fields are set to the source's values
+    /// - Nested substructs are snapshotted recursively; `Option`-wrapped ones are
+    ///   cleared when the source has none, `Vec`-wrapped ones are left unset
+    /// - Keyed collections become one `Upsert` per element
+    pub fn snapshot(source: &LineItem) -> Self {
+        Self {
+            sku: Some(source.sku.clone()),
+            quantity: Some(source.quantity.clone()),
+        }
+    }
+}
+/// All validation failures of a substruct, as `(field path, message)` pairs.
+pub struct LineItemSubstructValidationErrors {
+    pub errors: Vec<(String, String)>,
+}
+impl LineItemSubstructValidationErrors {
+    /// Returns the number of failures.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+    /// Returns `true` if there are no failures.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+    /// Returns the messages reported for a given field path.
+    pub fn messages_for(&self, path: &str) -> Vec<&str> {
+        self.errors
+            .iter()
+            .filter(|(field, _)| field == path)
+            .map(|(_, message)| message.as_str())
+            .collect()
+    }
+}
+impl std::fmt::Display for LineItemSubstructValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (path, message)) in self.errors.iter().enumerate() {
+            if index > 0 {
+                f.write_fmt(format_args!("; "))?;
+            }
+            f.write_fmt(format_args!("{0}: {1}", path, message))?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for LineItemSubstructValidationErrors {}
+impl LineItemSubstruct {
+    /// Validates every field that is set against its `validate(...)` rules.
+    ///
+    /// The rules check the values `apply_to` writes, after the `transform`/`transform_with`
+    /// normalizers. Fields in their "no change" state are not checked. Nested substructs are
+    /// validated recursively and their errors are prefixed with the field path.
+    /// All failures are collected rather than stopping at the first one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(Some(200), None);
+    /// let errors = update.validate().unwrap_err();
+    /// assert_eq!(errors.messages_for("age"), vec!["must be at most 150"]);
+    /// ```
+    pub fn validate(&self) -> Result<(), LineItemSubstructValidationErrors> {
+        let this = self;
+        #[allow(unused_mut)]
+        let mut errors: Vec<(String, String)> = Vec::new();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(LineItemSubstructValidationErrors {
+                errors,
+            })
+        }
+    }
+    /// Validates the substruct and applies it to the target only if it is valid.
+    ///
+    /// The target is left untouched when validation or a guard fails. A failed guard,
+    /// including one of a nested substruct, is reported as an error on the guarded field.
+    pub fn try_apply_to(
+        &self,
+        target: &mut LineItem,
+    ) -> Result<(), LineItemSubstructValidationErrors> {
+        self.validate()?;
+        self.apply_to(target);
+        Ok(())
+    }
+}
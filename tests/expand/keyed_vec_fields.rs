@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Order {
+    #[substruct_field(primitive)]
+    status: String,
+    #[substruct_field(nested_vec, key = "sku")]
+    items: Vec<LineItem>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct LineItem {
+    #[substruct_field(primitive)]
+    sku: String,
+    #[substruct_field(primitive)]
+    quantity: u32,
+}
+
+fn main() {}
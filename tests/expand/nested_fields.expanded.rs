@@ -0,0 +1,1425 @@
+#[serde(default)]
+pub struct CustomerSubstruct {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<AddressSubstruct>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "CustomerSubstruct::deserialize_present"
+    )]
+    pub billing: Option<Option<AddressSubstruct>>,
+}
+impl CustomerSubstruct {
+    /// Deserializes a field that is present, `null` included, as `Some`.
+    fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        T: serde::Deserialize<'de>,
+    {
+        T::deserialize(deserializer).map(Some)
+    }
+}
+impl CustomerSubstruct {
+    /// Creates a new substruct with the specified field values.
+    ///
+    /// # Arguments
+    ///
+    /// * `#(#field_names: #field_types)` - The values for each updatable field
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("John".to_string()),  // name field
+    ///     Some(true),               // active field
+    /// );
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: Option<String>,
+        address: Option<AddressSubstruct>,
+        billing: Option<Option<AddressSubstruct>>,
+    ) -> Self {
+        let mut update = Self { name, address, billing };
+        update.normalize();
+        update
+    }
+    /// Applies the `transform`/`transform_with` normalizers to every set field.
+    ///
+    /// This runs automatically in `new` and `set_by_path`, and `apply_to`/`would_change`
+    /// work on a normalized copy, so fields assigned directly are normalized too.
+    /// Nested substructs are normalized recursively.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut update = #update_struct_name::default();
+    /// update.email = Some("  Alice@Example.COM ".to_string());
+    /// update.normalize();
+    /// assert_eq!(update.email, Some("alice@example.com".to_string()));
+    /// ```
+    pub fn normalize(&mut self) {
+        if let Some(nested) = self.address.as_mut() {
+            nested.normalize();
+        }
+        if let Some(nested) = self.billing.as_mut().and_then(|nested| nested.as_mut()) {
+            nested.normalize();
+        }
+    }
+    /// Creates a substruct from an existing instance, the same way as `From<&T>`.
+    ///
+    /// With the default `from = "empty"` policy all fields indicate "no change", which is
+    /// useful as a starting point that doesn't change anything when applied. With
+    /// `from = "snapshot"` it is the same as `snapshot`.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source struct to create the substruct from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let user = User::new("Alice".to_string(), false, 25);
+    /// let no_change_update = #update_struct_name::from_source(&user);
+    /// assert!(no_change_update.is_empty());
+    /// ```
+    pub fn from_source(source: &Customer) -> Self {
+        Self::from(source)
+    }
+    /// Returns `true` if no fields would be changed by this update.
+    ///
+    /// This method checks if all fields are in their "no change" state:
+    /// - Wrapped fields are `None`
+    /// - Unwrapped fields are at their default (`Default::default()` or `default = "..."`)
+    /// - JSON fields are `None`
+    /// - Nested fields are `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let empty_update = #update_struct_name::default();
+    /// assert!(empty_update.is_empty());
+    ///
+    /// let update = #update_struct_name::new(Some("John".to_string()), None);
+    /// assert!(!update.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        if let Some(_) = &self.name {
+            return false;
+        }
+        if let Some(_) = &self.address {
+            return false;
+        }
+        if let Some(_) = &self.billing {
+            return false;
+        }
+        true
+    }
+    /// Returns the number of fields that have values set (non-default fields).
+    ///
+    /// This method counts fields that would actually change something when applied:
+    /// - Wrapped fields that are `Some(value)`
+    /// - Unwrapped fields that are not at their default
+    /// - JSON fields that are `Some(value)`
+    /// - Nested fields that are `Some(value)`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("John".to_string()),  // name field is set
+    ///     None,                     // active field is not set
+    /// );
+    /// assert_eq!(update.field_count(), 1);
+    ///
+    /// let full_update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),
+    ///     Some(true),
+    /// );
+    /// assert_eq!(full_update.field_count(), 2);
+    /// ```
+    pub fn field_count(&self) -> usize {
+        let mut count = 0;
+        if let Some(_) = &self.name {
+            count += 1;
+        }
+        if let Some(_) = &self.address {
+            count += 1;
+        }
+        if let Some(_) = &self.billing {
+            count += 1;
+        }
+        count
+    }
+    /// Resets all fields to their default values (no change state).
+    ///
+    /// This method sets all fields to their "no change" state:
+    /// - Wrapped fields become `None`
+    /// - Unwrapped fields are reset to their default
+    /// - JSON fields become `None`
+    /// - Nested fields become `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut update = #update_struct_name::new(
+    ///     Some("John".to_string()),
+    ///     Some(true),
+    /// );
+    /// assert_eq!(update.field_count(), 2);
+    ///
+    /// update.clear();
+    /// assert_eq!(update.field_count(), 0);
+    /// assert!(update.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.name = None;
+        self.address = None;
+        self.billing = None;
+    }
+    /// Applies the updates to a target struct instance.
+    ///
+    /// This method modifies the target struct by applying all non-default field values
+    /// from this substruct. Fields that are in their "no change" state are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The mutable reference to the target struct to update
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Only applied if `Some(value)`
+    /// - **Unwrapped fields**: Only applied if not at their default
+    /// - **JSON fields**: Deserialized and applied if `Some(value)`
+    /// - **Nested fields**: Recursively applied, after their guards are checked too
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut user = User::new("Alice".to_string(), false, 25);
+    /// let update = #update_struct_name::new(
+    ///     Some("Bob".to_string()),  // Will change name
+    ///     None,                    // Will not change active
+    /// );
+    ///
+    /// update.apply_to(&mut user);
+    /// // user.name is now "Bob", user.active remains false
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Structs with a `#[substruct_field(version)]` or `expect` field, or with nested or
+    /// keyed fields whose guards may fail, return a `Result`. All guards, including
+    /// those of nested substructs and keyed element patches, are checked with
+    /// `check_guards` before anything is written:
+    /// - `VersionConflict` if `expected_version` is set and differs from the target's version
+    /// - `PreconditionFailed` if an `expect_<field>` is set and differs from the target's value
+    /// - `Nested` if a guard of a nested substruct or keyed element patch fails
+    ///
+    /// On success the target's version is incremented, unless the update is empty.
+    /// On failure nothing is written.
+    pub fn apply_to(
+        &self,
+        target: &mut Customer,
+    ) -> Result<(), CustomerSubstructApplyError> {
+        self.check_guards(target)?;
+        self.apply_unchecked(target);
+        Ok(())
+    }
+    /// Checks every guard of the update against `target` without modifying it.
+    ///
+    /// Covers the `expected_version` and `expect_<field>` guards, and recursively those
+    /// of nested substructs and keyed element patches, each against the value it would
+    /// be applied to.
+    ///
+    /// # Errors
+    ///
+    /// The first guard that fails, as `apply_to` would report it.
+    pub fn check_guards(
+        &self,
+        target: &Customer,
+    ) -> Result<(), CustomerSubstructApplyError> {
+        if let Some(nested_update) = &self.address {
+            nested_update
+                .check_guards(&target.address)
+                .map_err(|err| CustomerSubstructApplyError::Nested {
+                    path: "address".to_string(),
+                    message: err.to_string(),
+                })?;
+        }
+        if let Some(Some(nested_update)) = &self.billing {
+            match &target.billing {
+                Some(current) => nested_update.check_guards(current),
+                None => nested_update.check_guards(&Default::default()),
+            }
+                .map_err(|err| CustomerSubstructApplyError::Nested {
+                    path: "billing".to_string(),
+                    message: err.to_string(),
+                })?;
+        }
+        Ok(())
+    }
+    /// Applies the update like `apply_to`, without checking any guard.
+    ///
+    /// The version is still incremented for a non-empty update.
+    pub fn apply_unchecked(&self, target: &mut Customer) {
+        let this = self;
+        if let Some(value) = &this.name {
+            target.name = value.clone();
+        }
+        if let Some(nested_update) = &this.address {
+            nested_update.apply_unchecked(&mut target.address);
+        }
+        match &this.billing {
+            Some(Some(nested_update)) => {
+                nested_update
+                    .apply_unchecked(
+                        target.billing.get_or_insert_with(Default::default),
+                    );
+            }
+            Some(None) => target.billing = None,
+            None => {}
+        }
+    }
+    /// Checks if applying this update would modify the target struct.
+    ///
+    /// This method compares the values in this substruct with the corresponding fields
+    /// in the target struct to determine if any changes would occur.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target struct to compare against
+    ///
+    /// # Returns
+    ///
+    /// * `true` if applying this update would change the target struct
+    /// * `false` if no changes would occur
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Compared if `Some(value)` and different from target
+    /// - **Unwrapped fields**: Compared if not at their default and different from target
+    /// - **JSON fields**: Serialized and compared if `Some(value)` and different from target
+    /// - **Nested fields**: Recursively checked using their own `would_change` method
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let user = User::new("Alice".to_string(), false, 25);
+    /// let update = #update_struct_name::new(
+    ///     Some("Bob".to_string()),  // Would change name
+    ///     Some(false),             // Would not change active (same value)
+    /// );
+    ///
+    /// assert!(update.would_change(&user));  // Would change name
+    ///
+    /// let no_change = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // Same as current name
+    ///     Some(false),               // Same as current active
+    /// );
+    /// assert!(!no_change.would_change(&user));  // No changes
+    /// ```
+    pub fn would_change(&self, target: &Customer) -> bool {
+        let this = self;
+        if let Some(value) = &this.name {
+            if value != &target.name {
+                return true;
+            }
+        }
+        if let Some(nested_update) = &this.address {
+            if nested_update.would_change(&target.address) {
+                return true;
+            }
+        }
+        match (&this.billing, &target.billing) {
+            (Some(Some(nested_update)), Some(current)) => {
+                if nested_update.would_change(current) {
+                    return true;
+                }
+            }
+            (Some(Some(_)), None) | (Some(None), Some(_)) => return true,
+            _ => {}
+        }
+        false
+    }
+    /// Combines two substructs, with the `other` substruct taking precedence for conflicting fields.
+    ///
+    /// This method merges the field values from two substructs, with the `other` substruct
+    /// taking precedence when both substructs have values for the same field.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other substruct to merge with (takes precedence for conflicts)
+    ///
+    /// # Returns
+    ///
+    /// A new substruct containing the merged field values.
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+    /// - **Unwrapped fields**: `other` value takes precedence if not at its default, otherwise uses `self`
+    /// - **JSON fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+    /// - **Nested fields**: Merged recursively when both are set; a cleared optional
+    ///   nested field in `other` takes precedence
+    ///
+    /// Fields with a `transform` are normalized first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update1 = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field
+    ///     None,                      // active field not set
+    /// );
+    ///
+    /// let update2 = #update_struct_name::new(
+    ///     None,                      // name field not set
+    ///     Some(true),               // active field
+    /// );
+    ///
+    /// let merged = update1.merge(update2);
+    /// // merged has name: Some("Alice") and active: Some(true)
+    /// ```
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            name: other.name.or(self.name),
+            address: match (self.address, other.address) {
+                (Some(first), Some(second)) => Some(first.merge(second)),
+                (first, second) => second.or(first),
+            },
+            billing: match (self.billing, other.billing) {
+                (Some(Some(first)), Some(Some(second))) => {
+                    Some(Some(first.merge(second)))
+                }
+                (first, None) => first,
+                (_, second) => second,
+            },
+        }
+    }
+    /// Checks if a specific field has a value set (non-default value).
+    ///
+    /// This method determines whether a field would actually change something when applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_name` - The name of the field to check (as a string)
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the field has a value set and would change something
+    /// * `false` if the field is in its "no change" state or doesn't exist
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Returns `true` if `Some(value)`
+    /// - **Unwrapped fields**: Returns `true` if not at their default
+    /// - **JSON fields**: Returns `true` if `Some(value)`
+    /// - **Nested fields**: Returns `true` if `Some(value)`
+    /// - **Nested paths**: `"address.city"` is checked recursively in the nested substruct
+    /// - **Non-existent fields**: Returns `false`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field is set
+    ///     None,                      // active field is not set
+    /// );
+    ///
+    /// assert!(update.has_field("name"));    // name field has a value
+    /// assert!(!update.has_field("active")); // active field is not set
+    /// assert!(!update.has_field("age"));    // age field doesn't exist in substruct
+    /// ```
+    pub fn has_field(&self, field_name: &str) -> bool {
+        if let Some((head, rest)) = field_name.split_once('.') {
+            return match head {
+                "address" => {
+                    self.address.as_ref().is_some_and(|nested| nested.has_field(rest))
+                }
+                "billing" => {
+                    self.billing
+                        .as_ref()
+                        .and_then(|nested| nested.as_ref())
+                        .is_some_and(|nested| nested.has_field(rest))
+                }
+                _ => false,
+            };
+        }
+        match field_name {
+            "name" => self.name.is_some(),
+            "address" => self.address.is_some(),
+            "billing" => self.billing.is_some(),
+            _ => false,
+        }
+    }
+    /// Converts the substruct into a flexible HashMap representation with string values.
+    ///
+    /// This method creates a HashMap where keys are field names and values are string
+    /// representations of the field values. This is useful for dynamic field access,
+    /// serialization, or when you need to work with field values in a generic way.
+    ///
+    /// # Returns
+    ///
+    /// A `HashMap<String, String>` containing only fields that have values set.
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Included if `Some(value)`, formatted using `{:?}`
+    /// - **Unwrapped fields**: Included if not at their default, formatted using `{:?}`
+    /// - **JSON fields**: Included if `Some(value)`, converted using `.to_string()`
+    /// - **Nested fields**: Recursively converted using their own `into_partial()` method
+    /// - **Sensitive fields**: Included as `"<redacted>"` instead of their value
+    /// - **Fields without values**: Not included in the result
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field
+    ///     Some(true),                // active field
+    /// );
+    ///
+    /// let partial = update.into_partial();
+    ///
+    /// // Check that fields are present
+    /// assert!(partial.contains_key("name"));
+    /// assert!(partial.contains_key("active"));
+    ///
+    /// // Compare actual values (as string representations)
+    /// assert_eq!(partial.get("name"), Some(&"\"Alice\"".to_string()));
+    /// assert_eq!(partial.get("active"), Some(&"true".to_string()));
+    ///
+    /// // Fields that aren't set are not included
+    /// assert!(!partial.contains_key("age")); // age field doesn't exist in substruct
+    /// ```
+    pub fn into_partial(self) -> std::collections::HashMap<String, String> {
+        let mut partial = std::collections::HashMap::new();
+        if let Some(value) = self.name {
+            partial
+                .insert(
+                    "name".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(format_args!("{0:?}", value))
+                    }),
+                );
+        }
+        if let Some(nested) = self.address {
+            partial
+                .insert(
+                    "address".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(
+                            format_args!("{0:?}", nested.into_partial()),
+                        )
+                    }),
+                );
+        }
+        match self.billing {
+            Some(Some(nested)) => {
+                partial
+                    .insert(
+                        "billing".to_string(),
+                        ::alloc::__export::must_use({
+                            ::alloc::fmt::format(
+                                format_args!("{0:?}", nested.into_partial()),
+                            )
+                        }),
+                    );
+            }
+            Some(None) => {
+                partial.insert("billing".to_string(), "None".to_string());
+            }
+            None => {}
+        }
+        partial
+    }
+}
+impl Default for CustomerSubstruct {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            address: Default::default(),
+            billing: Default::default(),
+        }
+    }
+}
+impl From<Customer> for CustomerSubstruct {
+    fn from(source: Customer) -> Self {
+        Self {
+            name: Default::default(),
+            address: Default::default(),
+            billing: Default::default(),
+        }
+    }
+}
+impl From<&Customer> for CustomerSubstruct {
+    fn from(source: &Customer) -> Self {
+        Self {
+            name: Default::default(),
+            address: Default::default(),
+            billing: Default::default(),
+        }
+    }
+}
+impl CustomerSubstruct {
+    /// Creates a substruct holding the source's current value in every field.
+    ///
+    /// Applying it to another value copies the source over, which is what a
+    /// full resync needs:
+    /// - Primitive and JSON fields are set to the source's values
+    /// - Nested substructs are snapshotted recursively; `Option`-wrapped ones are
+    ///   cleared when the source has none, `Vec`-wrapped ones are left unset
+    /// - Keyed collections become one `Upsert` per element
+    pub fn snapshot(source: &Customer) -> Self {
+        Self {
+            name: Some(source.name.clone()),
+            address: Some(AddressSubstruct::snapshot(&source.address)),
+            billing: Some(
+                source.billing.as_ref().map(|nested| AddressSubstruct::snapshot(nested)),
+            ),
+        }
+    }
+}
+/// All validation failures of a substruct, as `(field path, message)` pairs.
+pub struct CustomerSubstructValidationErrors {
+    pub errors: Vec<(String, String)>,
+}
+impl CustomerSubstructValidationErrors {
+    /// Returns the number of failures.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+    /// Returns `true` if there are no failures.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+    /// Returns the messages reported for a given field path.
+    pub fn messages_for(&self, path: &str) -> Vec<&str> {
+        self.errors
+            .iter()
+            .filter(|(field, _)| field == path)
+            .map(|(_, message)| message.as_str())
+            .collect()
+    }
+}
+impl std::fmt::Display for CustomerSubstructValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (path, message)) in self.errors.iter().enumerate() {
+            if index > 0 {
+                f.write_fmt(format_args!("; "))?;
+            }
+            f.write_fmt(format_args!("{0}: {1}", path, message))?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for CustomerSubstructValidationErrors {}
+impl CustomerSubstruct {
+    /// Validates every field that is set against its `validate(...)` rules.
+    ///
+    /// The rules check the values `apply_to` writes, after the `transform`/`transform_with`
+    /// normalizers. Fields in their "no change" state are not checked. Nested substructs are
+    /// validated recursively and their errors are prefixed with the field path.
+    /// All failures are collected rather than stopping at the first one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(Some(200), None);
+    /// let errors = update.validate().unwrap_err();
+    /// assert_eq!(errors.messages_for("age"), vec!["must be at most 150"]);
+    /// ```
+    pub fn validate(&self) -> Result<(), CustomerSubstructValidationErrors> {
+        let this = self;
+        #[allow(unused_mut)]
+        let mut errors: Vec<(String, String)> = Vec::new();
+        if let Some(nested) = this.address.as_ref() {
+            if let Err(nested_errors) = nested.validate() {
+                errors
+                    .extend(
+                        nested_errors
+                            .errors
+                            .into_iter()
+                            .map(|(path, message)| {
+                                (
+                                    ::alloc::__export::must_use({
+                                        ::alloc::fmt::format(
+                                            format_args!("{0}.{1}", "address", path),
+                                        )
+                                    }),
+                                    message,
+                                )
+                            }),
+                    );
+            }
+        }
+        if let Some(nested) = this.billing.as_ref().and_then(|nested| nested.as_ref()) {
+            if let Err(nested_errors) = nested.validate() {
+                errors
+                    .extend(
+                        nested_errors
+                            .errors
+                            .into_iter()
+                            .map(|(path, message)| {
+                                (
+                                    ::alloc::__export::must_use({
+                                        ::alloc::fmt::format(
+                                            format_args!("{0}.{1}", "billing", path),
+                                        )
+                                    }),
+                                    message,
+                                )
+                            }),
+                    );
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(CustomerSubstructValidationErrors {
+                errors,
+            })
+        }
+    }
+    /// Validates the substruct and applies it to the target only if it is valid.
+    ///
+    /// The target is left untouched when validation or a guard fails. A failed guard,
+    /// including one of a nested substruct, is reported as an error on the guarded field.
+    pub fn try_apply_to(
+        &self,
+        target: &mut Customer,
+    ) -> Result<(), CustomerSubstructValidationErrors> {
+        self.validate()?;
+        self.check_guards(target)
+            .map_err(|err| CustomerSubstructValidationErrors {
+                errors: ::alloc::boxed::box_assume_init_into_vec_unsafe(
+                    ::alloc::intrinsics::write_box_via_move(
+                        ::alloc::boxed::Box::new_uninit(),
+                        [(err.field().to_string(), err.to_string())],
+                    ),
+                ),
+            })?;
+        self.apply_unchecked(target);
+        Ok(())
+    }
+}
+/// Error returned by `apply_to` when a guard on the target fails.
+///
+/// Guards are checked before anything is written, so the target is unchanged.
+pub enum CustomerSubstructApplyError {
+    /// A guard of the nested substruct or keyed element patch at `path` failed.
+    Nested { path: String, message: String },
+}
+impl CustomerSubstructApplyError {
+    /// Returns the name of the guarded field that failed, or the path of the nested one.
+    pub fn field(&self) -> &str {
+        match self {
+            Self::Nested { path, .. } => path,
+        }
+    }
+}
+impl std::fmt::Display for CustomerSubstructApplyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Nested { path, message } => {
+                f.write_fmt(format_args!("guard failed in `{0}`: {1}", path, message))
+            }
+        }
+    }
+}
+impl std::error::Error for CustomerSubstructApplyError {}
+impl CustomerSubstruct {}
+/// Environment variables that could not be parsed, as `(variable, message)` pairs.
+pub struct CustomerSubstructEnvError {
+    pub errors: Vec<(String, String)>,
+}
+impl CustomerSubstructEnvError {
+    /// Returns the names of the variables that failed to parse.
+    pub fn variables(&self) -> Vec<&str> {
+        self.errors.iter().map(|(variable, _)| variable.as_str()).collect()
+    }
+}
+impl std::fmt::Display for CustomerSubstructEnvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (variable, message)) in self.errors.iter().enumerate() {
+            if index > 0 {
+                f.write_fmt(format_args!("; "))?;
+            }
+            f.write_fmt(format_args!("{0}: {1}", variable, message))?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for CustomerSubstructEnvError {}
+impl CustomerSubstruct {
+    /// Loads an update from environment variables, e.g. for config overlays.
+    ///
+    /// Each field is read from `PREFIX_FIELD` (or its `env = "..."` name) and parsed
+    /// with `FromStr`; JSON fields are parsed with `serde_json`. Nested substructs
+    /// are read from `PREFIX_FIELD__NESTED`. Unset variables leave the field unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns every variable that failed to parse, not just the first one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// // APP_PORT=8080 APP_DATABASE__HOST=db.internal
+    /// let overrides = #update_struct_name::from_env("APP")?;
+    /// overrides.apply_to(&mut config);
+    /// ```
+    pub fn from_env(prefix: &str) -> Result<Self, CustomerSubstructEnvError> {
+        Self::from_env_with(prefix, |key| std::env::var(key).ok())
+    }
+    /// Like `from_env`, but reads variables through `lookup` instead of the process
+    /// environment.
+    pub fn from_env_with(
+        prefix: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, CustomerSubstructEnvError> {
+        #[allow(unused_mut)]
+        let mut update = Self::default();
+        #[allow(unused_mut)]
+        let mut errors: Vec<(String, String)> = Vec::new();
+        {
+            let key = "CUSTOMER_NAME".to_string();
+            if let Some(raw) = lookup(&key) {
+                match raw.parse::<String>() {
+                    Ok(value) => update.name = Some(value),
+                    #[allow(unused_variables)]
+                    Err(err) => errors.push((key, err.to_string())),
+                }
+            }
+        }
+        {
+            let key = if prefix.is_empty() {
+                "ADDRESS".to_string()
+            } else {
+                ::alloc::__export::must_use({
+                    ::alloc::fmt::format(format_args!("{0}_{1}", prefix, "ADDRESS"))
+                })
+            };
+            match AddressSubstruct::from_env_with(
+                &::alloc::__export::must_use({
+                    ::alloc::fmt::format(format_args!("{0}_", key))
+                }),
+                &lookup,
+            ) {
+                Ok(nested) => {
+                    if !nested.is_empty() {
+                        update.address = Some(nested);
+                    }
+                }
+                Err(err) => errors.extend(err.errors),
+            }
+        }
+        {
+            let key = if prefix.is_empty() {
+                "BILLING".to_string()
+            } else {
+                ::alloc::__export::must_use({
+                    ::alloc::fmt::format(format_args!("{0}_{1}", prefix, "BILLING"))
+                })
+            };
+            match AddressSubstruct::from_env_with(
+                &::alloc::__export::must_use({
+                    ::alloc::fmt::format(format_args!("{0}_", key))
+                }),
+                &lookup,
+            ) {
+                Ok(nested) => {
+                    if !nested.is_empty() {
+                        update.billing = Some(Some(nested));
+                    }
+                }
+                Err(err) => errors.extend(err.errors),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(CustomerSubstructEnvError {
+                errors,
+            });
+        }
+        update.normalize();
+        Ok(update)
+    }
+}
+#[serde(default)]
+pub struct AddressSubstruct {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub city: Option<String>,
+}
+impl AddressSubstruct {
+    /// Creates a new substruct with the specified field values.
+    ///
+    /// # Arguments
+    ///
+    /// * `#(#field_names: #field_types)` - The values for each updatable field
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("John".to_string()),  // name field
+    ///     Some(true),               // active field
+    /// );
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(city: Option<String>) -> Self {
+        let mut update = Self { city };
+        update.normalize();
+        update
+    }
+    /// Applies the `transform`/`transform_with` normalizers to every set field.
+    ///
+    /// This runs automatically in `new` and `set_by_path`, and `apply_to`/`would_change`
+    /// work on a normalized copy, so fields assigned directly are normalized too.
+    /// Nested substructs are normalized recursively.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut update = #update_struct_name::default();
+    /// update.email = Some("  Alice@Example.COM ".to_string());
+    /// update.normalize();
+    /// assert_eq!(update.email, Some("alice@example.com".to_string()));
+    /// ```
+    pub fn normalize(&mut self) {}
+    /// Creates a substruct from an existing instance, the same way as `From<&T>`.
+    ///
+    /// With the default `from = "empty"` policy all fields indicate "no change", which is
+    /// useful as a starting point that doesn't change anything when applied. With
+    /// `from = "snapshot"` it is the same as `snapshot`.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source struct to create the substruct from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let user = User::new("Alice".to_string(), false, 25);
+    /// let no_change_update = #update_struct_name::from_source(&user);
+    /// assert!(no_change_update.is_empty());
+    /// ```
+    pub fn from_source(source: &Address) -> Self {
+        Self::from(source)
+    }
+    /// Returns `true` if no fields would be changed by this update.
+    ///
+    /// This method checks if all fields are in their "no change" state:
+    /// - Wrapped fields are `None`
+    /// - Unwrapped fields are at their default (`Default::default()` or `default = "..."`)
+    /// - JSON fields are `None`
+    /// - Nested fields are `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let empty_update = #update_struct_name::default();
+    /// assert!(empty_update.is_empty());
+    ///
+    /// let update = #update_struct_name::new(Some("John".to_string()), None);
+    /// assert!(!update.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        if let Some(_) = &self.city {
+            return false;
+        }
+        true
+    }
+    /// Returns the number of fields that have values set (non-default fields).
+    ///
+    /// This method counts fields that would actually change something when applied:
+    /// - Wrapped fields that are `Some(value)`
+    /// - Unwrapped fields that are not at their default
+    /// - JSON fields that are `Some(value)`
+    /// - Nested fields that are `Some(value)`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("John".to_string()),  // name field is set
+    ///     None,                     // active field is not set
+    /// );
+    /// assert_eq!(update.field_count(), 1);
+    ///
+    /// let full_update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),
+    ///     Some(true),
+    /// );
+    /// assert_eq!(full_update.field_count(), 2);
+    /// ```
+    pub fn field_count(&self) -> usize {
+        let mut count = 0;
+        if let Some(_) = &self.city {
+            count += 1;
+        }
+        count
+    }
+    /// Resets all fields to their default values (no change state).
+    ///
+    /// This method sets all fields to their "no change" state:
+    /// - Wrapped fields become `None`
+    /// - Unwrapped fields are reset to their default
+    /// - JSON fields become `None`
+    /// - Nested fields become `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut update = #update_struct_name::new(
+    ///     Some("John".to_string()),
+    ///     Some(true),
+    /// );
+    /// assert_eq!(update.field_count(), 2);
+    ///
+    /// update.clear();
+    /// assert_eq!(update.field_count(), 0);
+    /// assert!(update.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.city = None;
+    }
+    /// Applies the updates to a target struct instance.
+    ///
+    /// This method modifies the target struct by applying all non-default field values
+    /// from this substruct. Fields that are in their "no change" state are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The mutable reference to the target struct to update
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Only applied if `Some(value)`
+    /// - **Unwrapped fields**: Only applied if not at their default
+    /// - **JSON fields**: Deserialized and applied if `Some(value)`
+    /// - **Nested fields**: Recursively applied, after their guards are checked too
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut user = User::new("Alice".to_string(), false, 25);
+    /// let update = #update_struct_name::new(
+    ///     Some("Bob".to_string()),  // Will change name
+    ///     None,                    // Will not change active
+    /// );
+    ///
+    /// update.apply_to(&mut user);
+    /// // user.name is now "Bob", user.active remains false
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Structs with a `#[substruct_field(version)]` or `expect` field, or with nested or
+    /// keyed fields whose guards may fail, return a `Result`. All guards, including
+    /// those of nested substructs and keyed element patches, are checked with
+    /// `check_guards` before anything is written:
+    /// - `VersionConflict` if `expected_version` is set and differs from the target's version
+    /// - `PreconditionFailed` if an `expect_<field>` is set and differs from the target's value
+    /// - `Nested` if a guard of a nested substruct or keyed element patch fails
+    ///
+    /// On success the target's version is incremented, unless the update is empty.
+    /// On failure nothing is written.
+    pub fn apply_to(&self, target: &mut Address) {
+        self.apply_unchecked(target);
+    }
+    /// Checks every guard of the update against `target` without modifying it.
+    ///
+    /// Covers the `expected_version` and `expect_<field>` guards, and recursively those
+    /// of nested substructs and keyed element patches, each against the value it would
+    /// be applied to.
+    ///
+    /// # Errors
+    ///
+    /// The first guard that fails, as `apply_to` would report it.
+    pub fn check_guards(
+        &self,
+        target: &Address,
+    ) -> Result<(), std::convert::Infallible> {
+        Ok(())
+    }
+    /// Applies the update like `apply_to`, without checking any guard.
+    ///
+    /// The version is still incremented for a non-empty update.
+    pub fn apply_unchecked(&self, target: &mut Address) {
+        let this = self;
+        if let Some(value) = &this.city {
+            target.city = value.clone();
+        }
+    }
+    /// Checks if applying this update would modify the target struct.
+    ///
+    /// This method compares the values in this substruct with the corresponding fields
+    /// in the target struct to determine if any changes would occur.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target struct to compare against
+    ///
+    /// # Returns
+    ///
+    /// * `true` if applying this update would change the target struct
+    /// * `false` if no changes would occur
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Compared if `Some(value)` and different from target
+    /// - **Unwrapped fields**: Compared if not at their default and different from target
+    /// - **JSON fields**: Serialized and compared if `Some(value)` and different from target
+    /// - **Nested fields**: Recursively checked using their own `would_change` method
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let user = User::new("Alice".to_string(), false, 25);
+    /// let update = #update_struct_name::new(
+    ///     Some("Bob".to_string()),  // Would change name
+    ///     Some(false),             // Would not change active (same value)
+    /// );
+    ///
+    /// assert!(update.would_change(&user));  // Would change name
+    ///
+    /// let no_change = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // Same as current name
+    ///     Some(false),               // Same as current active
+    /// );
+    /// assert!(!no_change.would_change(&user));  // No changes
+    /// ```
+    pub fn would_change(&self, target: &Address) -> bool {
+        let this = self;
+        if let Some(value) = &this.city {
+            if value != &target.city {
+                return true;
+            }
+        }
+        false
+    }
+    /// Combines two substructs, with the `other` substruct taking precedence for conflicting fields.
+    ///
+    /// This method merges the field values from two substructs, with the `other` substruct
+    /// taking precedence when both substructs have values for the same field.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other substruct to merge with (takes precedence for conflicts)
+    ///
+    /// # Returns
+    ///
+    /// A new substruct containing the merged field values.
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+    /// - **Unwrapped fields**: `other` value takes precedence if not at its default, otherwise uses `self`
+    /// - **JSON fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+    /// - **Nested fields**: Merged recursively when both are set; a cleared optional
+    ///   nested field in `other` takes precedence
+    ///
+    /// Fields with a `transform` are normalized first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update1 = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field
+    ///     None,                      // active field not set
+    /// );
+    ///
+    /// let update2 = #update_struct_name::new(
+    ///     None,                      // name field not set
+    ///     Some(true),               // active field
+    /// );
+    ///
+    /// let merged = update1.merge(update2);
+    /// // merged has name: Some("Alice") and active: Some(true)
+    /// ```
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            city: other.city.or(self.city),
+        }
+    }
+    /// Checks if a specific field has a value set (non-default value).
+    ///
+    /// This method determines whether a field would actually change something when applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_name` - The name of the field to check (as a string)
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the field has a value set and would change something
+    /// * `false` if the field is in its "no change" state or doesn't exist
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Returns `true` if `Some(value)`
+    /// - **Unwrapped fields**: Returns `true` if not at their default
+    /// - **JSON fields**: Returns `true` if `Some(value)`
+    /// - **Nested fields**: Returns `true` if `Some(value)`
+    /// - **Nested paths**: `"address.city"` is checked recursively in the nested substruct
+    /// - **Non-existent fields**: Returns `false`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field is set
+    ///     None,                      // active field is not set
+    /// );
+    ///
+    /// assert!(update.has_field("name"));    // name field has a value
+    /// assert!(!update.has_field("active")); // active field is not set
+    /// assert!(!update.has_field("age"));    // age field doesn't exist in substruct
+    /// ```
+    pub fn has_field(&self, field_name: &str) -> bool {
+        if let Some((head, rest)) = field_name.split_once('.') {
+            return match head {
+                _ => false,
+            };
+        }
+        match field_name {
+            "city" => self.city.is_some(),
+            _ => false,
+        }
+    }
+    /// Converts the substruct into a flexible HashMap representation with string values.
+    ///
+    /// This method creates a HashMap where keys are field names and values are string
+    /// representations of the field values. This is useful for dynamic field access,
+    /// serialization, or when you need to work with field values in a generic way.
+    ///
+    /// # Returns
+    ///
+    /// A `HashMap<String, String>` containing only fields that have values set.
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Included if `Some(value)`, formatted using `{:?}`
+    /// - **Unwrapped fields**: Included if not at their default, formatted using `{:?}`
+    /// - **JSON fields**: Included if `Some(value)`, converted using `.to_string()`
+    /// - **Nested fields**: Recursively converted using their own `into_partial()` method
+    /// - **Sensitive fields**: Included as `"<redacted>"` instead of their value
+    /// - **Fields without values**: Not included in the result
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field
+    ///     Some(true),                // active field
+    /// );
+    ///
+    /// let partial = update.into_partial();
+    ///
+    /// // Check that fields are present
+    /// assert!(partial.contains_key("name"));
+    /// assert!(partial.contains_key("active"));
+    ///
+    /// // Compare actual values (as string representations)
+    /// assert_eq!(partial.get("name"), Some(&"\"Alice\"".to_string()));
+    /// assert_eq!(partial.get("active"), Some(&"true".to_string()));
+    ///
+    /// // Fields that aren't set are not included
+    /// assert!(!partial.contains_key("age")); // age field doesn't exist in substruct
+    /// ```
+    pub fn into_partial(self) -> std::collections::HashMap<String, String> {
+        let mut partial = std::collections::HashMap::new();
+        if let Some(value) = self.city {
+            partial
+                .insert(
+                    "city".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(format_args!("{0:?}", value))
+                    }),
+                );
+        }
+        partial
+    }
+}
+impl Default for AddressSubstruct {
+    fn default() -> Self {
+        Self { city: Default::default() }
+    }
+}
+impl From<Address> for AddressSubstruct {
+    fn from(source: Address) -> Self {
+        Self { city: Default::default() }
+    }
+}
+impl From<&Address> for AddressSubstruct {
+    fn from(source: &Address) -> Self {
+        Self { city: Default::default() }
+    }
+}
+impl AddressSubstruct {
+    /// Creates a substruct holding the source's current value in every field.
+    ///
+    /// Applying it to another value copies the source over, which is what a
+    /// full resync needs:
+    /// - Primitive and JSON fields are set to the source's values
+    /// - Nested substructs are snapshotted recursively; `Option`-wrapped ones are
+    ///   cleared when the source has none, `Vec`-wrapped ones are left unset
+    /// - Keyed collections become one `Upsert` per element
+    pub fn snapshot(source: &Address) -> Self {
+        Self {
+            city: Some(source.city.clone()),
+        }
+    }
+}
+/// All validation failures of a substruct, as `(field path, message)` pairs.
+pub struct AddressSubstructValidationErrors {
+    pub errors: Vec<(String, String)>,
+}
+impl AddressSubstructValidationErrors {
+    /// Returns the number of failures.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+    /// Returns `true` if there are no failures.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+    /// Returns the messages reported for a given field path.
+    pub fn messages_for(&self, path: &str) -> Vec<&str> {
+        self.errors
+            .iter()
+            .filter(|(field, _)| field == path)
+            .map(|(_, message)| message.as_str())
+            .collect()
+    }
+}
+impl std::fmt::Display for AddressSubstructValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (path, message)) in self.errors.iter().enumerate() {
+            if index > 0 {
+                f.write_fmt(format_args!("; "))?;
+            }
+            f.write_fmt(format_args!("{0}: {1}", path, message))?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for AddressSubstructValidationErrors {}
+impl AddressSubstruct {
+    /// Validates every field that is set against its `validate(...)` rules.
+    ///
+    /// The rules check the values `apply_to` writes, after the `transform`/`transform_with`
+    /// normalizers. Fields in their "no change" state are not checked. Nested substructs are
+    /// validated recursively and their errors are prefixed with the field path.
+    /// All failures are collected rather than stopping at the first one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(Some(200), None);
+    /// let errors = update.validate().unwrap_err();
+    /// assert_eq!(errors.messages_for("age"), vec!["must be at most 150"]);
+    /// ```
+    pub fn validate(&self) -> Result<(), AddressSubstructValidationErrors> {
+        let this = self;
+        #[allow(unused_mut)]
+        let mut errors: Vec<(String, String)> = Vec::new();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(AddressSubstructValidationErrors {
+                errors,
+            })
+        }
+    }
+    /// Validates the substruct and applies it to the target only if it is valid.
+    ///
+    /// The target is left untouched when validation or a guard fails. A failed guard,
+    /// including one of a nested substruct, is reported as an error on the guarded field.
+    pub fn try_apply_to(
+        &self,
+        target: &mut Address,
+    ) -> Result<(), AddressSubstructValidationErrors> {
+        self.validate()?;
+        self.apply_to(target);
+        Ok(())
+    }
+}
+/// Environment variables that could not be parsed, as `(variable, message)` pairs.
+pub struct AddressSubstructEnvError {
+    pub errors: Vec<(String, String)>,
+}
+impl AddressSubstructEnvError {
+    /// Returns the names of the variables that failed to parse.
+    pub fn variables(&self) -> Vec<&str> {
+        self.errors.iter().map(|(variable, _)| variable.as_str()).collect()
+    }
+}
+impl std::fmt::Display for AddressSubstructEnvError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (variable, message)) in self.errors.iter().enumerate() {
+            if index > 0 {
+                f.write_fmt(format_args!("; "))?;
+            }
+            f.write_fmt(format_args!("{0}: {1}", variable, message))?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for AddressSubstructEnvError {}
+impl AddressSubstruct {
+    /// Loads an update from environment variables, e.g. for config overlays.
+    ///
+    /// Each field is read from `PREFIX_FIELD` (or its `env = "..."` name) and parsed
+    /// with `FromStr`; JSON fields are parsed with `serde_json`. Nested substructs
+    /// are read from `PREFIX_FIELD__NESTED`. Unset variables leave the field unchanged.
+    ///
+    /// # Errors
+    ///
+    /// Returns every variable that failed to parse, not just the first one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// // APP_PORT=8080 APP_DATABASE__HOST=db.internal
+    /// let overrides = #update_struct_name::from_env("APP")?;
+    /// overrides.apply_to(&mut config);
+    /// ```
+    pub fn from_env(prefix: &str) -> Result<Self, AddressSubstructEnvError> {
+        Self::from_env_with(prefix, |key| std::env::var(key).ok())
+    }
+    /// Like `from_env`, but reads variables through `lookup` instead of the process
+    /// environment.
+    pub fn from_env_with(
+        prefix: &str,
+        lookup: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, AddressSubstructEnvError> {
+        #[allow(unused_mut)]
+        let mut update = Self::default();
+        #[allow(unused_mut)]
+        let mut errors: Vec<(String, String)> = Vec::new();
+        {
+            let key = if prefix.is_empty() {
+                "CITY".to_string()
+            } else {
+                ::alloc::__export::must_use({
+                    ::alloc::fmt::format(format_args!("{0}_{1}", prefix, "CITY"))
+                })
+            };
+            if let Some(raw) = lookup(&key) {
+                match raw.parse::<String>() {
+                    Ok(value) => update.city = Some(value),
+                    #[allow(unused_variables)]
+                    Err(err) => errors.push((key, err.to_string())),
+                }
+            }
+        }
+        if !errors.is_empty() {
+            return Err(AddressSubstructEnvError { errors });
+        }
+        update.normalize();
+        Ok(update)
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(env)]
+struct Customer {
+    #[substruct_field(primitive, env = "CUSTOMER_NAME")]
+    name: String,
+    #[substruct_field(nested)]
+    address: Address,
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(env)]
+struct Address {
+    #[substruct_field(primitive)]
+    city: String,
+}
+
+fn main() {}
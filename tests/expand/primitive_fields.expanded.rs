@@ -0,0 +1,628 @@
+#[serde(default)]
+pub struct UserUpdate {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "UserUpdate::deserialize_present"
+    )]
+    pub nickname: Option<Option<String>>,
+    #[serde(skip_serializing_if = "UserUpdate::is_unset_version")]
+    pub version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub preferences: Option<serde_json::Value>,
+}
+impl UserUpdate {
+    /// Deserializes a field that is present, `null` included, as `Some`.
+    fn deserialize_present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        T: serde::Deserialize<'de>,
+    {
+        T::deserialize(deserializer).map(Some)
+    }
+    /// Whether the field holds its "no change" value, so it is left out when serialized.
+    fn is_unset_version(value: &u32) -> bool {
+        *value == u32::MAX
+    }
+}
+impl UserUpdate {
+    /// Creates a new substruct with the specified field values.
+    ///
+    /// # Arguments
+    ///
+    /// * `#(#field_names: #field_types)` - The values for each updatable field
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("John".to_string()),  // name field
+    ///     Some(true),               // active field
+    /// );
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        name: Option<String>,
+        nickname: Option<Option<String>>,
+        version: u32,
+        preferences: Option<serde_json::Value>,
+    ) -> Self {
+        let mut update = Self {
+            name,
+            nickname,
+            version,
+            preferences,
+        };
+        update.normalize();
+        update
+    }
+    /// Applies the `transform`/`transform_with` normalizers to every set field.
+    ///
+    /// This runs automatically in `new` and `set_by_path`, and `apply_to`/`would_change`
+    /// work on a normalized copy, so fields assigned directly are normalized too.
+    /// Nested substructs are normalized recursively.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut update = #update_struct_name::default();
+    /// update.email = Some("  Alice@Example.COM ".to_string());
+    /// update.normalize();
+    /// assert_eq!(update.email, Some("alice@example.com".to_string()));
+    /// ```
+    pub fn normalize(&mut self) {}
+    /// Creates a substruct from an existing instance, the same way as `From<&T>`.
+    ///
+    /// With the default `from = "empty"` policy all fields indicate "no change", which is
+    /// useful as a starting point that doesn't change anything when applied. With
+    /// `from = "snapshot"` it is the same as `snapshot`.
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - The source struct to create the substruct from
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let user = User::new("Alice".to_string(), false, 25);
+    /// let no_change_update = #update_struct_name::from_source(&user);
+    /// assert!(no_change_update.is_empty());
+    /// ```
+    pub fn from_source(source: &User) -> Self {
+        Self::from(source)
+    }
+    /// Returns `true` if no fields would be changed by this update.
+    ///
+    /// This method checks if all fields are in their "no change" state:
+    /// - Wrapped fields are `None`
+    /// - Unwrapped fields are at their default (`Default::default()` or `default = "..."`)
+    /// - JSON fields are `None`
+    /// - Nested fields are `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let empty_update = #update_struct_name::default();
+    /// assert!(empty_update.is_empty());
+    ///
+    /// let update = #update_struct_name::new(Some("John".to_string()), None);
+    /// assert!(!update.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        if let Some(_) = &self.name {
+            return false;
+        }
+        if let Some(_) = &self.nickname {
+            return false;
+        }
+        if self.version != u32::MAX {
+            return false;
+        }
+        if let Some(_) = &self.preferences {
+            return false;
+        }
+        true
+    }
+    /// Returns the number of fields that have values set (non-default fields).
+    ///
+    /// This method counts fields that would actually change something when applied:
+    /// - Wrapped fields that are `Some(value)`
+    /// - Unwrapped fields that are not at their default
+    /// - JSON fields that are `Some(value)`
+    /// - Nested fields that are `Some(value)`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("John".to_string()),  // name field is set
+    ///     None,                     // active field is not set
+    /// );
+    /// assert_eq!(update.field_count(), 1);
+    ///
+    /// let full_update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),
+    ///     Some(true),
+    /// );
+    /// assert_eq!(full_update.field_count(), 2);
+    /// ```
+    pub fn field_count(&self) -> usize {
+        let mut count = 0;
+        if let Some(_) = &self.name {
+            count += 1;
+        }
+        if let Some(_) = &self.nickname {
+            count += 1;
+        }
+        if self.version != u32::MAX {
+            count += 1;
+        }
+        if let Some(_) = &self.preferences {
+            count += 1;
+        }
+        count
+    }
+    /// Resets all fields to their default values (no change state).
+    ///
+    /// This method sets all fields to their "no change" state:
+    /// - Wrapped fields become `None`
+    /// - Unwrapped fields are reset to their default
+    /// - JSON fields become `None`
+    /// - Nested fields become `None`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut update = #update_struct_name::new(
+    ///     Some("John".to_string()),
+    ///     Some(true),
+    /// );
+    /// assert_eq!(update.field_count(), 2);
+    ///
+    /// update.clear();
+    /// assert_eq!(update.field_count(), 0);
+    /// assert!(update.is_empty());
+    /// ```
+    pub fn clear(&mut self) {
+        self.name = None;
+        self.nickname = None;
+        self.version = u32::MAX;
+        self.preferences = None;
+    }
+    /// Applies the updates to a target struct instance.
+    ///
+    /// This method modifies the target struct by applying all non-default field values
+    /// from this substruct. Fields that are in their "no change" state are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The mutable reference to the target struct to update
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Only applied if `Some(value)`
+    /// - **Unwrapped fields**: Only applied if not at their default
+    /// - **JSON fields**: Deserialized and applied if `Some(value)`
+    /// - **Nested fields**: Recursively applied, after their guards are checked too
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mut user = User::new("Alice".to_string(), false, 25);
+    /// let update = #update_struct_name::new(
+    ///     Some("Bob".to_string()),  // Will change name
+    ///     None,                    // Will not change active
+    /// );
+    ///
+    /// update.apply_to(&mut user);
+    /// // user.name is now "Bob", user.active remains false
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Structs with a `#[substruct_field(version)]` or `expect` field, or with nested or
+    /// keyed fields whose guards may fail, return a `Result`. All guards, including
+    /// those of nested substructs and keyed element patches, are checked with
+    /// `check_guards` before anything is written:
+    /// - `VersionConflict` if `expected_version` is set and differs from the target's version
+    /// - `PreconditionFailed` if an `expect_<field>` is set and differs from the target's value
+    /// - `Nested` if a guard of a nested substruct or keyed element patch fails
+    ///
+    /// On success the target's version is incremented, unless the update is empty.
+    /// On failure nothing is written.
+    pub fn apply_to(&self, target: &mut User) {
+        self.apply_unchecked(target);
+    }
+    /// Checks every guard of the update against `target` without modifying it.
+    ///
+    /// Covers the `expected_version` and `expect_<field>` guards, and recursively those
+    /// of nested substructs and keyed element patches, each against the value it would
+    /// be applied to.
+    ///
+    /// # Errors
+    ///
+    /// The first guard that fails, as `apply_to` would report it.
+    pub fn check_guards(&self, target: &User) -> Result<(), std::convert::Infallible> {
+        Ok(())
+    }
+    /// Applies the update like `apply_to`, without checking any guard.
+    ///
+    /// The version is still incremented for a non-empty update.
+    pub fn apply_unchecked(&self, target: &mut User) {
+        let this = self;
+        if let Some(value) = &this.name {
+            target.name = value.clone();
+        }
+        if let Some(value) = &this.nickname {
+            target.nickname = value.clone();
+        }
+        if this.version != u32::MAX {
+            target.version = this.version.clone();
+        }
+        if let Some(value) = &this.preferences {
+            target.preferences = serde_json::from_value(value.clone())
+                .expect("Failed to deserialize JSON");
+        }
+    }
+    /// Checks if applying this update would modify the target struct.
+    ///
+    /// This method compares the values in this substruct with the corresponding fields
+    /// in the target struct to determine if any changes would occur.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - The target struct to compare against
+    ///
+    /// # Returns
+    ///
+    /// * `true` if applying this update would change the target struct
+    /// * `false` if no changes would occur
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Compared if `Some(value)` and different from target
+    /// - **Unwrapped fields**: Compared if not at their default and different from target
+    /// - **JSON fields**: Serialized and compared if `Some(value)` and different from target
+    /// - **Nested fields**: Recursively checked using their own `would_change` method
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let user = User::new("Alice".to_string(), false, 25);
+    /// let update = #update_struct_name::new(
+    ///     Some("Bob".to_string()),  // Would change name
+    ///     Some(false),             // Would not change active (same value)
+    /// );
+    ///
+    /// assert!(update.would_change(&user));  // Would change name
+    ///
+    /// let no_change = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // Same as current name
+    ///     Some(false),               // Same as current active
+    /// );
+    /// assert!(!no_change.would_change(&user));  // No changes
+    /// ```
+    pub fn would_change(&self, target: &User) -> bool {
+        let this = self;
+        if let Some(value) = &this.name {
+            if value != &target.name {
+                return true;
+            }
+        }
+        if let Some(value) = &this.nickname {
+            if value != &target.nickname {
+                return true;
+            }
+        }
+        if this.version != u32::MAX && this.version != target.version {
+            return true;
+        }
+        if let Some(value) = &this.preferences {
+            let original: serde_json::Value = serde_json::to_value(&target.preferences)
+                .expect("Failed to serialize to JSON");
+            if !serde_json::Value::eq(value, &original) {
+                return true;
+            }
+        }
+        false
+    }
+    /// Combines two substructs, with the `other` substruct taking precedence for conflicting fields.
+    ///
+    /// This method merges the field values from two substructs, with the `other` substruct
+    /// taking precedence when both substructs have values for the same field.
+    ///
+    /// # Arguments
+    ///
+    /// * `other` - The other substruct to merge with (takes precedence for conflicts)
+    ///
+    /// # Returns
+    ///
+    /// A new substruct containing the merged field values.
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+    /// - **Unwrapped fields**: `other` value takes precedence if not at its default, otherwise uses `self`
+    /// - **JSON fields**: `other` value takes precedence if `Some`, otherwise uses `self`
+    /// - **Nested fields**: Merged recursively when both are set; a cleared optional
+    ///   nested field in `other` takes precedence
+    ///
+    /// Fields with a `transform` are normalized first.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update1 = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field
+    ///     None,                      // active field not set
+    /// );
+    ///
+    /// let update2 = #update_struct_name::new(
+    ///     None,                      // name field not set
+    ///     Some(true),               // active field
+    /// );
+    ///
+    /// let merged = update1.merge(update2);
+    /// // merged has name: Some("Alice") and active: Some(true)
+    /// ```
+    pub fn merge(self, other: Self) -> Self {
+        Self {
+            name: other.name.or(self.name),
+            nickname: other.nickname.or(self.nickname),
+            version: if other.version != u32::MAX {
+                other.version
+            } else {
+                self.version
+            },
+            preferences: other.preferences.or(self.preferences),
+        }
+    }
+    /// Checks if a specific field has a value set (non-default value).
+    ///
+    /// This method determines whether a field would actually change something when applied.
+    ///
+    /// # Arguments
+    ///
+    /// * `field_name` - The name of the field to check (as a string)
+    ///
+    /// # Returns
+    ///
+    /// * `true` if the field has a value set and would change something
+    /// * `false` if the field is in its "no change" state or doesn't exist
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Returns `true` if `Some(value)`
+    /// - **Unwrapped fields**: Returns `true` if not at their default
+    /// - **JSON fields**: Returns `true` if `Some(value)`
+    /// - **Nested fields**: Returns `true` if `Some(value)`
+    /// - **Nested paths**: `"address.city"` is checked recursively in the nested substruct
+    /// - **Non-existent fields**: Returns `false`
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field is set
+    ///     None,                      // active field is not set
+    /// );
+    ///
+    /// assert!(update.has_field("name"));    // name field has a value
+    /// assert!(!update.has_field("active")); // active field is not set
+    /// assert!(!update.has_field("age"));    // age field doesn't exist in substruct
+    /// ```
+    pub fn has_field(&self, field_name: &str) -> bool {
+        if let Some((head, rest)) = field_name.split_once('.') {
+            return match head {
+                _ => false,
+            };
+        }
+        match field_name {
+            "name" => self.name.is_some(),
+            "nickname" => self.nickname.is_some(),
+            "version" => self.version != u32::MAX,
+            "preferences" => self.preferences.is_some(),
+            _ => false,
+        }
+    }
+    /// Converts the substruct into a flexible HashMap representation with string values.
+    ///
+    /// This method creates a HashMap where keys are field names and values are string
+    /// representations of the field values. This is useful for dynamic field access,
+    /// serialization, or when you need to work with field values in a generic way.
+    ///
+    /// # Returns
+    ///
+    /// A `HashMap<String, String>` containing only fields that have values set.
+    ///
+    /// # Behavior
+    ///
+    /// - **Wrapped fields**: Included if `Some(value)`, formatted using `{:?}`
+    /// - **Unwrapped fields**: Included if not at their default, formatted using `{:?}`
+    /// - **JSON fields**: Included if `Some(value)`, converted using `.to_string()`
+    /// - **Nested fields**: Recursively converted using their own `into_partial()` method
+    /// - **Sensitive fields**: Included as `"<redacted>"` instead of their value
+    /// - **Fields without values**: Not included in the result
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(
+    ///     Some("Alice".to_string()),  // name field
+    ///     Some(true),                // active field
+    /// );
+    ///
+    /// let partial = update.into_partial();
+    ///
+    /// // Check that fields are present
+    /// assert!(partial.contains_key("name"));
+    /// assert!(partial.contains_key("active"));
+    ///
+    /// // Compare actual values (as string representations)
+    /// assert_eq!(partial.get("name"), Some(&"\"Alice\"".to_string()));
+    /// assert_eq!(partial.get("active"), Some(&"true".to_string()));
+    ///
+    /// // Fields that aren't set are not included
+    /// assert!(!partial.contains_key("age")); // age field doesn't exist in substruct
+    /// ```
+    pub fn into_partial(self) -> std::collections::HashMap<String, String> {
+        let mut partial = std::collections::HashMap::new();
+        if let Some(value) = self.name {
+            partial
+                .insert(
+                    "name".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(format_args!("{0:?}", value))
+                    }),
+                );
+        }
+        if let Some(value) = self.nickname {
+            partial
+                .insert(
+                    "nickname".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(format_args!("{0:?}", value))
+                    }),
+                );
+        }
+        if self.version != u32::MAX {
+            partial
+                .insert(
+                    "version".to_string(),
+                    ::alloc::__export::must_use({
+                        ::alloc::fmt::format(format_args!("{0:?}", & self.version))
+                    }),
+                );
+        }
+        if let Some(value) = self.preferences {
+            partial.insert("preferences".to_string(), value.to_string());
+        }
+        partial
+    }
+}
+impl Default for UserUpdate {
+    fn default() -> Self {
+        Self {
+            name: Default::default(),
+            nickname: Default::default(),
+            version: u32::MAX,
+            preferences: Default::default(),
+        }
+    }
+}
+impl From<User> for UserUpdate {
+    fn from(source: User) -> Self {
+        Self {
+            name: Default::default(),
+            nickname: Default::default(),
+            version: u32::MAX,
+            preferences: Default::default(),
+        }
+    }
+}
+impl From<&User> for UserUpdate {
+    fn from(source: &User) -> Self {
+        Self {
+            name: Default::default(),
+            nickname: Default::default(),
+            version: u32::MAX,
+            preferences: Default::default(),
+        }
+    }
+}
+impl UserUpdate {
+    /// Creates a substruct holding the source's current value in every field.
+    ///
+    /// Applying it to another value copies the source over, which is what a
+    /// full resync needs:
+    /// - Primitive and JSON fields are set to the source's values
+    /// - Nested substructs are snapshotted recursively; `Option`-wrapped ones are
+    ///   cleared when the source has none, `Vec`-wrapped ones are left unset
+    /// - Keyed collections become one `Upsert` per element
+    pub fn snapshot(source: &User) -> Self {
+        Self {
+            name: Some(source.name.clone()),
+            nickname: Some(source.nickname.clone()),
+            version: source.version.clone(),
+            preferences: Some(
+                serde_json::to_value(&source.preferences)
+                    .expect("Failed to serialize field to JSON"),
+            ),
+        }
+    }
+}
+/// All validation failures of a substruct, as `(field path, message)` pairs.
+pub struct UserUpdateValidationErrors {
+    pub errors: Vec<(String, String)>,
+}
+impl UserUpdateValidationErrors {
+    /// Returns the number of failures.
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+    /// Returns `true` if there are no failures.
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+    /// Returns the messages reported for a given field path.
+    pub fn messages_for(&self, path: &str) -> Vec<&str> {
+        self.errors
+            .iter()
+            .filter(|(field, _)| field == path)
+            .map(|(_, message)| message.as_str())
+            .collect()
+    }
+}
+impl std::fmt::Display for UserUpdateValidationErrors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (index, (path, message)) in self.errors.iter().enumerate() {
+            if index > 0 {
+                f.write_fmt(format_args!("; "))?;
+            }
+            f.write_fmt(format_args!("{0}: {1}", path, message))?;
+        }
+        Ok(())
+    }
+}
+impl std::error::Error for UserUpdateValidationErrors {}
+impl UserUpdate {
+    /// Validates every field that is set against its `validate(...)` rules.
+    ///
+    /// The rules check the values `apply_to` writes, after the `transform`/`transform_with`
+    /// normalizers. Fields in their "no change" state are not checked. Nested substructs are
+    /// validated recursively and their errors are prefixed with the field path.
+    /// All failures are collected rather than stopping at the first one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let update = #update_struct_name::new(Some(200), None);
+    /// let errors = update.validate().unwrap_err();
+    /// assert_eq!(errors.messages_for("age"), vec!["must be at most 150"]);
+    /// ```
+    pub fn validate(&self) -> Result<(), UserUpdateValidationErrors> {
+        let this = self;
+        #[allow(unused_mut)]
+        let mut errors: Vec<(String, String)> = Vec::new();
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(UserUpdateValidationErrors {
+                errors,
+            })
+        }
+    }
+    /// Validates the substruct and applies it to the target only if it is valid.
+    ///
+    /// The target is left untouched when validation or a guard fails. A failed guard,
+    /// including one of a nested substruct, is reported as an error on the guarded field.
+    pub fn try_apply_to(
+        &self,
+        target: &mut User,
+    ) -> Result<(), UserUpdateValidationErrors> {
+        self.validate()?;
+        self.apply_to(target);
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(name = "UserUpdate")]
+struct User {
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(primitive, label = "Nickname")]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false, default = "u32::MAX")]
+    version: u32,
+    #[substruct_field(json)]
+    preferences: Preferences,
+    #[substruct_field(skip)]
+    password_hash: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Preferences {
+    theme: String,
+}
+
+fn main() {}
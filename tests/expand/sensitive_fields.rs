@@ -0,0 +1,14 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Credentials {
+    #[substruct_field(primitive)]
+    username: String,
+    #[substruct_field(primitive, sensitive, expect)]
+    password: String,
+    #[substruct_field(primitive, sensitive, option = false)]
+    pin: u32,
+}
+
+fn main() {}
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Signup {
+    #[substruct_field(primitive, transform = "trim|lowercase", validate(length(min = 3, max = 254)))]
+    email: String,
+    #[substruct_field(primitive, validate(range(min = 13, max = 150)))]
+    age: u32,
+}
+
+fn main() {}
@@ -0,0 +1,200 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// PER-FIELD DEFAULT TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(create)]
+struct Counter {
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(primitive, option = false, default = "u32::MAX")]
+    count: u32,
+    #[substruct_field(primitive, option = false, default = "\"unchanged\".to_string()")]
+    status: String,
+    #[substruct_field(primitive, option = false)]
+    step: u32,
+}
+
+fn counter() -> Counter {
+    Counter {
+        name: "hits".to_string(),
+        count: 7,
+        status: "active".to_string(),
+        step: 1,
+    }
+}
+
+#[test]
+fn test_default_expression_is_the_no_change_value() {
+    let empty = CounterSubstruct::default();
+    assert_eq!(empty.count, u32::MAX);
+    assert_eq!(empty.status, "unchanged");
+    assert_eq!(empty.step, 0);
+    assert!(empty.is_empty());
+    assert_eq!(empty.field_count(), 0);
+    assert!(!empty.has_field("count"));
+
+    let from_source = CounterSubstruct::from_source(&counter());
+    assert_eq!(from_source.count, u32::MAX);
+    assert!(from_source.is_empty());
+}
+
+#[test]
+fn test_zero_is_an_update_when_the_sentinel_is_not_zero() {
+    let mut target = counter();
+    let update = CounterSubstruct {
+        count: 0,
+        ..Default::default()
+    };
+
+    assert!(!update.is_empty());
+    assert_eq!(update.field_count(), 1);
+    assert!(update.has_field("count"));
+    assert!(update.would_change(&target));
+    update.apply_to(&mut target);
+
+    assert_eq!(target.count, 0);
+    assert_eq!(target.status, "active");
+}
+
+#[test]
+fn test_clear_resets_to_the_default_expression() {
+    let mut update = CounterSubstruct::new(Some("visits".to_string()), 3, "paused".to_string(), 2);
+    assert_eq!(update.field_count(), 4);
+
+    update.clear();
+    assert_eq!(update.count, u32::MAX);
+    assert_eq!(update.status, "unchanged");
+    assert!(update.is_empty());
+}
+
+#[test]
+fn test_merge_keeps_values_unless_other_is_at_its_default() {
+    let first = CounterSubstruct {
+        count: 0,
+        status: "paused".to_string(),
+        ..Default::default()
+    };
+    let second = CounterSubstruct {
+        status: "active".to_string(),
+        ..Default::default()
+    };
+
+    let merged = first.merge(second);
+    assert_eq!(merged.count, 0);
+    assert_eq!(merged.status, "active");
+    assert_eq!(merged.step, 0);
+}
+
+#[test]
+fn test_create_type_uses_the_same_default() {
+    let built = CreateCounter::new("hits".to_string(), 1)
+        .try_build()
+        .unwrap();
+    assert_eq!(built.count, u32::MAX);
+    assert_eq!(built.status, "unchanged");
+
+    let built = CreateCounter {
+        count: Some(0),
+        ..CreateCounter::new("hits".to_string(), 1)
+    }
+    .try_build()
+    .unwrap();
+    assert_eq!(built.count, 0);
+}
+
+// ============================================================================
+// SENTINEL VALIDATION TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Quota {
+    #[substruct_field(
+        primitive,
+        option = false,
+        default = "u32::MAX",
+        validate(range(min = 1, max = 100))
+    )]
+    limit: u32,
+    #[substruct_field(
+        primitive,
+        option = false,
+        default = "\"  keep  \".to_string()",
+        transform = "trim",
+        validate(length(max = 5))
+    )]
+    label: String,
+    #[substruct_field(
+        primitive,
+        option = false,
+        default = "Some(0)",
+        validate(range(min = 1))
+    )]
+    burst: Option<u32>,
+}
+
+fn quota() -> Quota {
+    Quota {
+        limit: 10,
+        label: "free".to_string(),
+        burst: Some(5),
+    }
+}
+
+#[test]
+fn test_sentinels_are_not_validated() {
+    let empty = QuotaSubstruct::default();
+    assert!(empty.validate().is_ok());
+
+    let mut target = quota();
+    empty.try_apply_to(&mut target).unwrap();
+    assert_eq!(target, quota());
+}
+
+#[test]
+fn test_values_other_than_the_sentinel_are_validated() {
+    // 0 is a real value here, since the sentinel is `u32::MAX`
+    let update = QuotaSubstruct {
+        limit: 0,
+        burst: None,
+        ..Default::default()
+    };
+    let errors = update.validate().unwrap_err();
+    assert_eq!(errors.messages_for("limit"), vec!["must be at least 1"]);
+    assert!(errors.messages_for("burst").is_empty());
+
+    let update = QuotaSubstruct {
+        burst: Some(0),
+        label: "far too long".to_string(),
+        ..Default::default()
+    };
+    let errors = update.validate().unwrap_err();
+    assert_eq!(
+        errors.messages_for("label"),
+        vec!["length must be at most 5"]
+    );
+    // `Some(0)` is the sentinel of `burst`
+    assert!(errors.messages_for("burst").is_empty());
+}
+
+#[test]
+fn test_transforms_leave_the_sentinel_alone() {
+    let mut update = QuotaSubstruct::default();
+    update.normalize();
+    assert_eq!(update.label, "  keep  ");
+    assert!(update.is_empty());
+
+    let mut target = quota();
+    update.apply_to(&mut target);
+    assert_eq!(target.label, "free");
+
+    let update = QuotaSubstruct {
+        label: " pro ".to_string(),
+        ..Default::default()
+    };
+    update.apply_to(&mut target);
+    assert_eq!(target.label, "pro");
+}
@@ -0,0 +1,196 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// FIELD MASK TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(mask)]
+struct Address {
+    #[substruct_field(primitive)]
+    city: String,
+    #[substruct_field(primitive)]
+    zip: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(mask)]
+struct Tag {
+    #[substruct_field(primitive)]
+    id: String,
+    #[substruct_field(primitive)]
+    color: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(mask)]
+struct User {
+    #[substruct_field(version)]
+    version: u64,
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(primitive)]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false)]
+    login_count: u32,
+    #[substruct_field(nested)]
+    address: Address,
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+    #[substruct_field(nested)]
+    previous: Vec<Address>,
+    #[substruct_field(nested_vec, key = "id")]
+    tags: Vec<Tag>,
+    internal: String,
+}
+
+fn user() -> User {
+    User {
+        version: 7,
+        name: "Alice".to_string(),
+        nickname: Some("Al".to_string()),
+        login_count: 3,
+        address: Address {
+            city: "NYC".to_string(),
+            zip: 10001,
+        },
+        billing: None,
+        previous: vec![],
+        tags: vec![Tag {
+            id: "a".to_string(),
+            color: "red".to_string(),
+        }],
+        internal: "secret".to_string(),
+    }
+}
+
+#[test]
+fn test_field_mask_lists_set_fields_with_nested_paths() {
+    let update = UserSubstruct {
+        name: Some("Bob".to_string()),
+        login_count: 4,
+        address: Some(AddressSubstruct {
+            city: Some("LA".to_string()),
+            ..Default::default()
+        }),
+        billing: Some(None),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        update.field_mask(),
+        vec!["name", "login_count", "address.city", "billing"]
+    );
+    assert!(UserSubstruct::default().field_mask().is_empty());
+}
+
+#[test]
+fn test_field_mask_empty_nested_uses_field_name() {
+    let update = UserSubstruct {
+        address: Some(AddressSubstruct::default()),
+        ..Default::default()
+    };
+
+    assert_eq!(update.field_mask(), vec!["address"]);
+}
+
+#[test]
+fn test_from_source_with_mask_copies_only_masked_fields() {
+    let update =
+        UserSubstruct::from_source_with_mask(&user(), &["name", "address.zip", "tags"]).unwrap();
+
+    assert_eq!(update.name, Some("Alice".to_string()));
+    assert_eq!(update.nickname, None);
+    assert_eq!(update.login_count, 0);
+    assert_eq!(update.expected_version, Some(7));
+    let address = update.address.as_ref().unwrap();
+    assert_eq!(address.zip, Some(10001));
+    assert_eq!(address.city, None);
+    assert_eq!(update.tags.len(), 1);
+    assert_eq!(update.field_mask(), vec!["name", "address.zip", "tags"]);
+
+    let mut stored = user();
+    stored.name = "Old".to_string();
+    stored.address.zip = 0;
+    stored.address.city = "Boston".to_string();
+    update.apply_to(&mut stored).unwrap();
+    assert_eq!(stored.name, "Alice");
+    assert_eq!(stored.address.zip, 10001);
+    assert_eq!(stored.address.city, "Boston");
+}
+
+#[test]
+fn test_from_source_with_mask_keyed_collections_are_additive() {
+    let tag = |id: &str, color: &str| Tag {
+        id: id.to_string(),
+        color: color.to_string(),
+    };
+    let mut stored = user();
+    stored.tags = vec![tag("a", "blue"), tag("b", "green")];
+    let mut pruned = stored.clone();
+
+    let update = UserSubstruct::from_source_with_mask(&user(), &["tags"]).unwrap();
+    update.apply_to(&mut stored).unwrap();
+    // "a" is replaced, and "b", missing from the source, is kept
+    assert_eq!(stored.tags, vec![tag("a", "red"), tag("b", "green")]);
+
+    update.remove_tags("b").apply_to(&mut pruned).unwrap();
+    assert_eq!(pruned.tags, vec![tag("a", "red")]);
+}
+
+#[test]
+fn test_from_source_with_mask_nested_paths_into_missing_option_clear_it() {
+    let update = UserSubstruct::from_source_with_mask(&user(), &["billing.city"]).unwrap();
+    assert!(matches!(update.billing, Some(None)));
+
+    let mut source = user();
+    source.billing = Some(Address {
+        city: "SF".to_string(),
+        zip: 94105,
+    });
+    let update =
+        UserSubstruct::from_source_with_mask(&source, &["billing", "billing.city"]).unwrap();
+    assert_eq!(update.field_mask(), vec!["billing.city", "billing.zip"]);
+
+    assert!(
+        UserSubstruct::from_source_with_mask(&source, &[])
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[test]
+fn test_from_source_with_mask_rejects_unpatchable_paths() {
+    let err = UserSubstruct::from_source_with_mask(
+        &user(),
+        &[
+            "name",
+            "version",
+            "internal",
+            "previous",
+            "address.country",
+            "name.first",
+            "",
+        ],
+    )
+    .unwrap_err();
+
+    assert_eq!(
+        err.paths,
+        vec![
+            "version",
+            "internal",
+            "previous",
+            "address.country",
+            "name.first",
+            ""
+        ]
+    );
+    assert_eq!(
+        err.to_string(),
+        "paths are not patchable: version, internal, previous, address.country, name.first, "
+    );
+    assert!(UserSubstruct::is_patchable_path("address.city"));
+    assert!(!UserSubstruct::is_patchable_path("tags.a"));
+}
@@ -22,6 +22,7 @@ struct PrimitiveStruct {
 }
 
 #[test]
+#[allow(clippy::approx_constant)]
 fn test_primitive_struct_creation() {
     let update = PrimitiveStructSubstruct::new(
         Some("test".to_string()),
@@ -171,7 +172,7 @@ fn test_nested_struct_apply_to() {
     let update = NestedStructSubstruct::new(Some("Bob".to_string()), Some(address_update));
 
     // Apply the update
-    update.apply_to(&mut target);
+    update.apply_to(&mut target).unwrap();
 
     // Verify the changes
     assert_eq!(target.name, "Bob");
@@ -209,6 +210,7 @@ fn test_nested_struct_would_change() {
 }
 
 #[test]
+#[allow(clippy::unnecessary_get_then_check)]
 fn test_nested_struct_into_partial() {
     let address_update =
         AddressSubstruct::new(Some("123 New St".to_string()), Some("New City".to_string()));
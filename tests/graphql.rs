@@ -0,0 +1,370 @@
+#![cfg(feature = "async-graphql")]
+
+use async_graphql::{Context, EmptySubscription, Json, Object, Request, Schema, Variables};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::sync::Mutex;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// GRAPHQL INPUT OBJECT TESTS (require `--features async-graphql`)
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(graphql)]
+struct Address {
+    /// City name
+    #[substruct_field(primitive)]
+    city: String,
+    #[substruct_field(primitive)]
+    zip: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Preferences {
+    theme: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(graphql)]
+struct Tag {
+    #[substruct_field(primitive)]
+    id: String,
+    #[substruct_field(primitive)]
+    color: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(graphql)]
+struct Shelf {
+    #[substruct_field(primitive)]
+    label: String,
+    #[substruct_field(nested_vec, key = "slot", key_type = "u32")]
+    items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(graphql)]
+struct Item {
+    #[substruct_field(primitive)]
+    slot: u32,
+    #[substruct_field(primitive)]
+    name: String,
+}
+
+/// Changes to a user account
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(graphql)]
+struct User {
+    #[substruct_field(version)]
+    version: u64,
+    /// The name shown to other users
+    #[substruct_field(primitive, expect)]
+    name: String,
+    #[substruct_field(primitive)]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false)]
+    login_count: u32,
+    #[substruct_field(json)]
+    preferences: Preferences,
+    #[substruct_field(nested)]
+    address: Address,
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+    #[substruct_field(nested_vec, key = "id")]
+    tags: Vec<Tag>,
+}
+
+fn user() -> User {
+    User {
+        version: 1,
+        name: "Alice".to_string(),
+        nickname: Some("Al".to_string()),
+        login_count: 3,
+        preferences: Preferences {
+            theme: "light".to_string(),
+        },
+        address: Address {
+            city: "Oslo".to_string(),
+            zip: Some("0150".to_string()),
+        },
+        billing: Some(Address::default()),
+        tags: vec![tag("a", "red"), tag("b", "green")],
+    }
+}
+
+fn tag(id: &str, color: &str) -> Tag {
+    Tag {
+        id: id.to_string(),
+        color: color.to_string(),
+    }
+}
+
+struct Query;
+
+#[Object]
+impl Query {
+    async fn user(&self) -> Json<User> {
+        Json(user())
+    }
+}
+
+struct Mutation;
+
+#[Object]
+impl Mutation {
+    async fn update_user(&self, patch: UserSubstructInput) -> async_graphql::Result<Json<User>> {
+        let mut user = user();
+        UserSubstruct::from(patch)
+            .apply_to(&mut user)
+            .map_err(|err| async_graphql::Error::new(err.to_string()))?;
+        Ok(Json(user))
+    }
+}
+
+fn schema() -> Schema<Query, Mutation, EmptySubscription> {
+    Schema::new(Query, Mutation, EmptySubscription)
+}
+
+async fn update_user(patch: &str) -> serde_json::Value {
+    let response = schema()
+        .execute(format!("mutation {{ updateUser(patch: {}) }}", patch))
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    response.data.into_json().unwrap()["updateUser"].clone()
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_input_object_sets_fields() {
+    let user = update_user(
+        r#"{ name: "Bob", loginCount: 4, preferences: { theme: "dark" }, address: { city: "Bergen" } }"#,
+    )
+    .await;
+
+    assert_eq!(user["name"], "Bob");
+    assert_eq!(user["nickname"], "Al");
+    assert_eq!(user["login_count"], 4);
+    assert_eq!(user["preferences"], json!({ "theme": "dark" }));
+    assert_eq!(user["address"], json!({ "city": "Bergen", "zip": "0150" }));
+    assert_eq!(user["version"], 2);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_input_object_null_clears_and_missing_leaves_unchanged() {
+    let user = update_user(r#"{ nickname: null, billing: null, address: { zip: null } }"#).await;
+
+    assert_eq!(user["nickname"], json!(null));
+    assert_eq!(user["billing"], json!(null));
+    assert_eq!(user["address"], json!({ "city": "Oslo", "zip": null }));
+    assert_eq!(user["name"], "Alice");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_input_object_guards() {
+    let response = schema()
+        .execute(r#"mutation { updateUser(patch: { name: "Bob", expectName: "Alicia", expectedVersion: 1 }) }"#)
+        .await;
+
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0].message.contains("name"));
+}
+
+#[test]
+fn test_input_object_schema() {
+    let sdl = schema().sdl();
+
+    assert!(sdl.contains("Changes to a user account"));
+    assert!(sdl.contains("input UserSubstructInput {"));
+    assert!(sdl.contains("The name shown to other users"));
+    assert!(sdl.contains("\tname: String\n"));
+    assert!(sdl.contains("\tnickname: String\n"));
+    assert!(sdl.contains("\tloginCount: Int\n"));
+    assert!(sdl.contains("\tpreferences: JSON\n"));
+    assert!(sdl.contains("\taddress: AddressSubstructInput\n"));
+    assert!(sdl.contains("\tbilling: AddressSubstructInput\n"));
+    assert!(sdl.contains("City name"));
+    assert!(sdl.contains("\ttags: [UserSubstructTagsOpInput!]\n"));
+}
+
+#[test]
+fn test_input_converts_to_substruct() {
+    let input = AddressSubstructInput {
+        city: Some("Bergen".to_string()),
+        zip: async_graphql::MaybeUndefined::Null,
+    };
+
+    let update = AddressSubstruct::from(input);
+    assert_eq!(update.city, Some("Bergen".to_string()));
+    assert_eq!(update.zip, Some(None));
+}
+
+// ============================================================================
+// KEYED COLLECTION INPUT TESTS
+// ============================================================================
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_input_object_keyed_operations() {
+    let user = update_user(
+        r#"{ tags: [
+            { upsert: { id: "c", color: "blue" } },
+            { upsert: { id: "a", color: "black" } },
+            { remove: "b" },
+            { patch: { key: "c", patch: { color: "grey" } } }
+        ] }"#,
+    )
+    .await;
+
+    assert_eq!(
+        user["tags"],
+        json!([{ "id": "a", "color": "black" }, { "id": "c", "color": "grey" }])
+    );
+    assert_eq!(user["version"], 2);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_input_object_keyed_operation_needs_exactly_one_variant() {
+    let response = schema()
+        .execute(r#"mutation { updateUser(patch: { tags: [{ remove: "a", upsert: { id: "a", color: "red" } }] }) }"#)
+        .await;
+
+    assert_eq!(response.errors.len(), 1);
+    assert!(response.errors[0].message.contains("exactly one field"));
+}
+
+#[test]
+fn test_keyed_operation_inputs_in_schema() {
+    let sdl = schema().sdl();
+
+    assert!(sdl.contains("input UserSubstructTagsOpInput @oneOf {"));
+    assert!(sdl.contains("One operation on `tags`, matching elements by `id`."));
+    assert!(sdl.contains("\tupsert: JSON\n"));
+    assert!(sdl.contains("\tremove: String\n"));
+    assert!(sdl.contains("\tpatch: UserSubstructTagsOpPatchInput\n"));
+    assert!(sdl.contains("input UserSubstructTagsOpPatchInput {"));
+    assert!(sdl.contains("\tpatch: TagSubstructInput!\n"));
+}
+
+#[test]
+fn test_keyed_operation_inputs_convert_to_ops() {
+    let input = ShelfSubstructInput {
+        label: None,
+        items: Some(vec![
+            ShelfSubstructItemsOpInput::Remove(3),
+            ShelfSubstructItemsOpInput::Patch(ShelfSubstructItemsOpPatchInput {
+                key: 1,
+                patch: ItemSubstructInput {
+                    slot: None,
+                    name: Some("Lamp".to_string()),
+                },
+            }),
+        ]),
+    };
+
+    let mut shelf = Shelf {
+        label: "Top".to_string(),
+        items: vec![
+            Item {
+                slot: 1,
+                name: "Vase".to_string(),
+            },
+            Item {
+                slot: 3,
+                name: "Clock".to_string(),
+            },
+        ],
+    };
+    ShelfSubstruct::from(input).apply_to(&mut shelf).unwrap();
+    assert_eq!(
+        shelf.items,
+        vec![Item {
+            slot: 1,
+            name: "Lamp".to_string(),
+        }]
+    );
+
+    // Leaving the list out queues nothing
+    let update = ShelfSubstruct::from(ShelfSubstructInput {
+        label: Some("Bottom".to_string()),
+        items: None,
+    });
+    assert!(update.items.is_empty());
+}
+
+// ============================================================================
+// STORED MUTATION TESTS
+// ============================================================================
+
+struct ShelfQuery;
+
+#[Object]
+impl ShelfQuery {
+    async fn shelf(&self, ctx: &Context<'_>) -> Json<Shelf> {
+        Json(ctx.data_unchecked::<Mutex<Shelf>>().lock().unwrap().clone())
+    }
+}
+
+struct ShelfMutation;
+
+#[Object]
+impl ShelfMutation {
+    async fn update_shelf(
+        &self,
+        ctx: &Context<'_>,
+        patch: ShelfSubstructInput,
+    ) -> async_graphql::Result<Json<Shelf>> {
+        let mut shelf = ctx.data_unchecked::<Mutex<Shelf>>().lock().unwrap();
+        ShelfSubstruct::from(patch)
+            .apply_to(&mut shelf)
+            .map_err(|err| async_graphql::Error::new(err.to_string()))?;
+        Ok(Json(shelf.clone()))
+    }
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_keyed_operations_mutate_a_stored_value_through_variables() {
+    let item = |slot: u32, name: &str| Item {
+        slot,
+        name: name.to_string(),
+    };
+    let schema = Schema::build(ShelfQuery, ShelfMutation, EmptySubscription)
+        .data(Mutex::new(Shelf {
+            label: "Top".to_string(),
+            items: vec![item(1, "Vase"), item(3, "Clock")],
+        }))
+        .finish();
+    let update = |patch: serde_json::Value| {
+        Request::new("mutation Update($patch: ShelfSubstructInput!) { updateShelf(patch: $patch) }")
+            .variables(Variables::from_json(json!({ "patch": patch })))
+    };
+
+    let response = schema
+        .execute(update(json!({
+            "label": "Bottom",
+            "items": [
+                { "upsert": { "slot": 2, "name": "Lamp" } },
+                { "patch": { "key": 1, "patch": { "name": "Bowl" } } },
+                { "remove": 3 }
+            ]
+        })))
+        .await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+
+    // An operation with two variants is rejected before anything is applied
+    let response = schema
+        .execute(update(json!({
+            "label": "Middle",
+            "items": [{ "remove": 1, "patch": { "key": 2, "patch": { "name": "Cup" } } }]
+        })))
+        .await;
+    assert_eq!(response.errors.len(), 1);
+
+    let response = schema.execute("{ shelf }").await;
+    assert!(response.errors.is_empty(), "{:?}", response.errors);
+    assert_eq!(
+        response.data.into_json().unwrap()["shelf"],
+        json!({
+            "label": "Bottom",
+            "items": [{ "slot": 1, "name": "Bowl" }, { "slot": 2, "name": "Lamp" }]
+        })
+    );
+}
@@ -127,7 +127,7 @@ fn test_mixed_field_operations() {
     let from_source = UserProfileBuilder::from_source(&source_profile);
     assert_eq!(from_source.username, None);
     assert_eq!(from_source.email, None);
-    assert!(from_source.preferences.is_some());
+    assert!(from_source.preferences.is_none()); // JSON fields are left unset too
     assert!(from_source.address.is_none());
     assert_eq!(from_source.version, None); // wrapped field gets default
 }
@@ -0,0 +1,311 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// JSON SCHEMA TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(json_schema)]
+struct Address {
+    #[substruct_field(primitive, label = "City")]
+    city: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(json_schema)]
+struct Tag {
+    #[substruct_field(primitive)]
+    id: String,
+    #[substruct_field(primitive)]
+    color: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Preferences {
+    theme: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(json_schema)]
+struct Profile {
+    #[substruct_field(primitive, validate(length(min = 1, max = 50)))]
+    name: String,
+    #[substruct_field(primitive)]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false, validate(range(min = 18, max = 130)))]
+    age: u32,
+    #[substruct_field(primitive, validate(regex = "^[^@]+@[^@]+$"))]
+    email: String,
+    #[substruct_field(primitive, sensitive)]
+    password: String,
+    #[substruct_field(primitive, validate(length(max = 3)))]
+    roles: Vec<String>,
+    #[substruct_field(json)]
+    preferences: Preferences,
+    #[substruct_field(nested)]
+    address: Address,
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+    #[substruct_field(nested_vec, key = "id")]
+    tags: Vec<Tag>,
+    #[substruct_field(version)]
+    version: u64,
+}
+
+#[test]
+fn test_primitive_fields_and_constraints() {
+    let schema = ProfileSubstruct::json_schema();
+    let properties = &schema["properties"];
+
+    assert_eq!(
+        schema["$schema"],
+        "https://json-schema.org/draft/2020-12/schema"
+    );
+    assert_eq!(schema["title"], "ProfileSubstruct");
+    assert_eq!(schema["type"], "object");
+    assert_eq!(schema.get("required"), None);
+
+    assert_eq!(
+        properties["name"],
+        json!({ "type": "string", "minLength": 1, "maxLength": 50 })
+    );
+    assert_eq!(
+        properties["age"],
+        json!({ "type": "integer", "minimum": 18, "maximum": 130 })
+    );
+    assert_eq!(
+        properties["email"],
+        json!({ "type": "string", "pattern": "^[^@]+@[^@]+$" })
+    );
+    assert_eq!(
+        properties["password"],
+        json!({ "type": "string", "writeOnly": true })
+    );
+    assert_eq!(
+        properties["roles"],
+        json!({ "type": "array", "items": { "type": "string" }, "maxItems": 3 })
+    );
+    assert_eq!(
+        properties["expected_version"],
+        json!({ "type": "integer", "minimum": 0 })
+    );
+}
+
+#[test]
+fn test_clearable_fields_are_nullable_and_json_is_free_form() {
+    let schema = ProfileSubstruct::json_schema();
+    let properties = &schema["properties"];
+
+    assert_eq!(
+        properties["nickname"],
+        json!({ "anyOf": [{ "type": "string" }, { "type": "null" }] })
+    );
+    assert_eq!(properties["preferences"], json!({}));
+    assert_eq!(
+        properties["billing"],
+        json!({ "anyOf": [{ "$ref": "#/$defs/AddressSubstruct" }, { "type": "null" }] })
+    );
+}
+
+#[test]
+fn test_nested_substructs_are_referenced_through_defs() {
+    let schema = ProfileSubstruct::json_schema();
+
+    assert_eq!(
+        schema["properties"]["address"],
+        json!({ "$ref": "#/$defs/AddressSubstruct" })
+    );
+    assert_eq!(
+        schema["$defs"]["AddressSubstruct"],
+        json!({
+            "title": "AddressSubstruct",
+            "type": "object",
+            "properties": { "city": { "type": "string", "title": "City" } }
+        })
+    );
+    assert!(schema["$defs"]["TagSubstruct"].is_object());
+    assert_eq!(AddressSubstruct::json_schema().get("$defs"), None);
+}
+
+#[test]
+fn test_keyed_operations_schema() {
+    let schema = ProfileSubstruct::json_schema();
+    let operations = &schema["properties"]["tags"]["items"]["oneOf"];
+
+    assert_eq!(schema["properties"]["tags"]["type"], "array");
+    assert_eq!(operations[0]["required"], json!(["upsert"]));
+    assert_eq!(
+        operations[1]["properties"]["remove"],
+        json!({ "type": "string" })
+    );
+    assert_eq!(
+        operations[2]["properties"]["patch"]["prefixItems"],
+        json!([{ "type": "string" }, { "$ref": "#/$defs/TagSubstruct" }])
+    );
+}
+
+#[test]
+fn test_every_field_is_optional_on_the_wire() {
+    let update: ProfileSubstruct = serde_json::from_value(json!({ "age": 40 })).unwrap();
+    assert_eq!(update.age, 40);
+    assert_eq!(update.field_count(), 1);
+
+    let empty: ProfileSubstruct = serde_json::from_value(json!({})).unwrap();
+    assert!(empty.is_empty());
+    assert!(empty.tags.is_empty());
+
+    // Documents in the schema's shape round-trip through the substruct
+    let update: ProfileSubstruct = serde_json::from_value(json!({
+        "billing": { "city": "Paris" },
+        "tags": [{ "remove": "a" }, { "patch": ["b", { "color": "red" }] }]
+    }))
+    .unwrap();
+    assert_eq!(
+        update.billing.unwrap().unwrap().city,
+        Some("Paris".to_string())
+    );
+    assert_eq!(update.tags.len(), 2);
+}
+
+// ============================================================================
+// GUARD, SENSITIVE AND SENTINEL SCHEMA TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(json_schema)]
+struct Credentials {
+    #[substruct_field(primitive, expect)]
+    user: String,
+    #[substruct_field(primitive, expect, sensitive)]
+    password: String,
+    #[substruct_field(primitive, option = false, default = "u32::MAX")]
+    max_sessions: u32,
+    #[substruct_field(version)]
+    version: u32,
+}
+
+#[test]
+fn test_guard_slots_are_described_and_sensitive_ones_write_only() {
+    let schema = CredentialsSubstruct::json_schema();
+    let properties = &schema["properties"];
+
+    assert_eq!(properties["expect_user"], json!({ "type": "string" }));
+    assert_eq!(
+        properties["expect_password"],
+        json!({ "type": "string", "writeOnly": true })
+    );
+    assert_eq!(
+        properties["password"],
+        json!({ "type": "string", "writeOnly": true })
+    );
+    assert_eq!(
+        properties["expected_version"],
+        json!({ "type": "integer", "minimum": 0 })
+    );
+    assert_eq!(properties.as_object().unwrap().len(), 6);
+}
+
+#[test]
+fn test_omitted_sentinel_fields_deserialize_to_no_change() {
+    let schema = CredentialsSubstruct::json_schema();
+    assert_eq!(
+        schema["properties"]["max_sessions"],
+        json!({ "type": "integer", "minimum": 0 })
+    );
+
+    let empty: CredentialsSubstruct = serde_json::from_value(json!({})).unwrap();
+    assert_eq!(empty.max_sessions, u32::MAX);
+    assert!(empty.is_empty());
+    assert_eq!(serde_json::to_value(&empty).unwrap(), json!({}));
+
+    let update: CredentialsSubstruct =
+        serde_json::from_value(json!({ "max_sessions": 0, "expected_version": 2 })).unwrap();
+    assert_eq!(update.max_sessions, 0);
+    assert_eq!(update.expected_version, Some(2));
+    assert_eq!(update.field_count(), 1);
+
+    // A document the schema accepts can still fail the guard it carries
+    let mut credentials = Credentials {
+        user: "alice".to_string(),
+        password: "pw".to_string(),
+        max_sessions: 5,
+        version: 3,
+    };
+    assert!(update.apply_to(&mut credentials).is_err());
+    assert_eq!(credentials.max_sessions, 5);
+}
+
+// ============================================================================
+// WIRE ROUND-TRIP TESTS
+// ============================================================================
+
+fn assert_valid(schema: &serde_json::Value, instance: &serde_json::Value) {
+    let validator = jsonschema::validator_for(schema).unwrap();
+    let errors: Vec<String> = validator
+        .iter_errors(instance)
+        .map(|err| err.to_string())
+        .collect();
+    assert!(errors.is_empty(), "{instance} is invalid: {errors:?}");
+}
+
+#[test]
+fn test_serialized_updates_validate_and_round_trip() {
+    let schema = ProfileSubstruct::json_schema();
+    let update = ProfileSubstruct {
+        name: Some("Alice".to_string()),
+        nickname: Some(None),
+        billing: Some(None),
+        address: Some(AddressSubstruct {
+            city: Some("Paris".to_string()),
+        }),
+        ..Default::default()
+    }
+    .remove_tags("a")
+    .patch_tags("b", TagSubstruct::new(None, Some("red".to_string())))
+    .with_expected_version(3);
+
+    // Unset fields are left out, and clears are sent as `null`
+    let wire = serde_json::to_value(&update).unwrap();
+    assert_eq!(
+        wire,
+        json!({
+            "name": "Alice",
+            "nickname": null,
+            "address": { "city": "Paris" },
+            "billing": null,
+            "tags": [{ "remove": "a" }, { "patch": ["b", { "color": "red" }] }],
+            "expected_version": 3
+        })
+    );
+    assert_valid(&schema, &wire);
+
+    let back: ProfileSubstruct = serde_json::from_value(wire).unwrap();
+    assert_eq!(back.nickname, Some(None));
+    assert!(matches!(back.billing, Some(None)));
+    assert_eq!(back.name, update.name);
+    assert_eq!(back.field_count(), update.field_count());
+    assert_eq!(
+        serde_json::to_value(&back).unwrap(),
+        serde_json::to_value(&update).unwrap()
+    );
+
+    // Set optional values and the empty update round-trip too
+    let update = ProfileSubstruct {
+        nickname: Some(Some("Al".to_string())),
+        billing: Some(Some(AddressSubstruct::default())),
+        ..Default::default()
+    };
+    let wire = serde_json::to_value(&update).unwrap();
+    assert_eq!(wire, json!({ "nickname": "Al", "billing": {} }));
+    assert_valid(&schema, &wire);
+    let back: ProfileSubstruct = serde_json::from_value(wire).unwrap();
+    assert_eq!(back.nickname, Some(Some("Al".to_string())));
+    assert!(matches!(back.billing, Some(Some(_))));
+
+    let empty = serde_json::to_value(ProfileSubstruct::default()).unwrap();
+    // `age` is at its no-change value, which the schema's minimum would reject
+    assert_eq!(empty, json!({}));
+    assert_valid(&schema, &empty);
+}
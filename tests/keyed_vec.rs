@@ -0,0 +1,173 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// KEYED VEC ELEMENT UPDATE TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(paths)]
+struct LineItem {
+    id: u64,
+    #[substruct_field(primitive)]
+    sku: String,
+    #[substruct_field(primitive, validate(range(min = 1)))]
+    quantity: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(paths)]
+struct Order {
+    #[substruct_field(primitive)]
+    status: String,
+    #[substruct_field(nested_vec, key = "id", key_type = "u64")]
+    items: Vec<LineItem>,
+}
+
+fn item(id: u64, sku: &str, quantity: u32) -> LineItem {
+    LineItem {
+        id,
+        sku: sku.to_string(),
+        quantity,
+    }
+}
+
+fn order() -> Order {
+    Order {
+        status: "open".to_string(),
+        items: vec![item(1, "apple", 2), item(2, "pear", 1)],
+    }
+}
+
+fn quantity(quantity: u32) -> LineItemSubstruct {
+    LineItemSubstruct::new(None, Some(quantity))
+}
+
+#[test]
+fn test_keyed_ops_field_type() {
+    let update = OrderSubstruct::default();
+
+    let _: Vec<OrderSubstructItemsOp> = update.items;
+    assert!(update.is_empty());
+    assert!(!update.has_field("items"));
+}
+
+#[test]
+fn test_apply_keyed_ops() {
+    let mut target = order();
+    let update = OrderSubstruct::default()
+        .patch_items(1u64, quantity(5))
+        .remove_items(2u64)
+        .upsert_items(item(3, "plum", 4))
+        .upsert_items(item(1, "green apple", 5));
+
+    assert_eq!(update.field_count(), 1);
+    assert!(update.has_field("items"));
+
+    update.apply_to(&mut target).unwrap();
+
+    assert_eq!(
+        target.items,
+        vec![item(1, "green apple", 5), item(3, "plum", 4)]
+    );
+    assert_eq!(target.status, "open");
+}
+
+#[test]
+fn test_patch_missing_key_is_ignored() {
+    let mut target = order();
+    let update = OrderSubstruct::default().patch_items(99u64, quantity(5));
+
+    assert!(!update.would_change(&target));
+    update.apply_to(&mut target).unwrap();
+    assert_eq!(target, order());
+}
+
+#[test]
+fn test_would_change_keyed_ops() {
+    let target = order();
+
+    let same = OrderSubstruct::default()
+        .patch_items(1u64, quantity(2))
+        .upsert_items(item(2, "pear", 1));
+    assert!(!same.would_change(&target));
+
+    assert!(
+        OrderSubstruct::default()
+            .remove_items(2u64)
+            .would_change(&target)
+    );
+    assert!(
+        OrderSubstruct::default()
+            .upsert_items(item(7, "fig", 1))
+            .would_change(&target)
+    );
+}
+
+#[test]
+fn test_merge_concatenates_keyed_ops() {
+    let first = OrderSubstruct::default().upsert_items(item(3, "plum", 1));
+    let second = OrderSubstruct::default().remove_items(3u64);
+
+    let merged = first.merge(second);
+    assert_eq!(merged.items.len(), 2);
+
+    let mut target = order();
+    merged.apply_to(&mut target).unwrap();
+    assert_eq!(target, order());
+}
+
+#[test]
+fn test_repeated_upserts_of_a_new_key_add_one_element() {
+    let mut target = order();
+    let update = OrderSubstruct::default()
+        .upsert_items(item(3, "plum", 1))
+        .upsert_items(item(3, "plum", 2))
+        .merge(OrderSubstruct::default().upsert_items(item(3, "prune", 6)));
+    assert_eq!(update.items.len(), 3);
+
+    update.clone().apply_to(&mut target).unwrap();
+    assert_eq!(
+        target.items,
+        vec![item(1, "apple", 2), item(2, "pear", 1), item(3, "prune", 6)]
+    );
+
+    // Replaying the same update is a no-op
+    assert!(!update.would_change(&target));
+    update.apply_to(&mut target).unwrap();
+    assert_eq!(target.items.len(), 3);
+}
+
+#[test]
+fn test_keyed_ops_partial_paths_and_validation() {
+    let update = OrderSubstruct::default()
+        .remove_items(2u64)
+        .patch_items(1u64, quantity(0));
+
+    let errors = update.validate().unwrap_err();
+    assert_eq!(
+        errors.messages_for("items[1].quantity"),
+        vec!["must be at least 1"]
+    );
+
+    assert_eq!(
+        update.get_by_path("items"),
+        Some(json!([{ "remove": 2 }, { "patch": [1, { "quantity": 0 }] }]))
+    );
+
+    let mut from_json = OrderSubstruct::default();
+    from_json
+        .set_by_path(
+            "items",
+            json!([{ "upsert": { "id": 9, "sku": "kiwi", "quantity": 3 } }]),
+        )
+        .unwrap();
+    assert_eq!(from_json.items.len(), 1);
+
+    let partial = update.into_partial();
+    let items = partial.get("items").unwrap();
+    assert!(items.contains("remove 2"));
+    assert!(items.contains("patch 1"));
+    assert!(items.contains("quantity"));
+}
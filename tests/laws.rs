@@ -0,0 +1,232 @@
+#![cfg(feature = "proptest")]
+
+use proptest::prelude::*;
+use proptest::test_runner::TestRunner;
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// PROPERTY-BASED LAW TESTS (require `--features proptest`)
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(proptest)]
+struct Address {
+    #[substruct_field(primitive)]
+    city: String,
+    #[substruct_field(primitive)]
+    zip: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(proptest)]
+struct Member {
+    #[substruct_field(primitive)]
+    id: u8,
+    #[substruct_field(primitive)]
+    role: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(proptest)]
+struct Team {
+    #[substruct_field(version)]
+    version: u64,
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(primitive)]
+    motto: Option<String>,
+    #[substruct_field(primitive, option = false)]
+    size: u8,
+    #[substruct_field(primitive, option = false, transform = "trim")]
+    slug: String,
+    #[substruct_field(json)]
+    settings: Settings,
+    #[substruct_field(nested)]
+    address: Address,
+    #[substruct_field(nested_vec, key = "id", key_type = "u8")]
+    members: Vec<Member>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Settings {
+    public: bool,
+}
+
+// Generated only in test builds, so `proptest` can stay a dev-dependency
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[cfg_attr(test, substruct_builder(proptest))]
+struct Profile {
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+}
+
+fn address() -> impl Strategy<Value = Address> {
+    (any::<String>(), any::<u32>()).prop_map(|(city, zip)| Address { city, zip })
+}
+
+fn team() -> impl Strategy<Value = Team> {
+    (
+        any::<u64>(),
+        any::<String>(),
+        any::<Option<String>>(),
+        any::<u8>(),
+        address(),
+        proptest::collection::vec(
+            (0..4u8, any::<String>()).prop_map(|(id, role)| Member { id, role }),
+            0..4,
+        ),
+    )
+        .prop_map(|(version, name, motto, size, address, members)| Team {
+            version,
+            name,
+            motto,
+            size,
+            slug: "team".to_string(),
+            settings: Settings { public: false },
+            address,
+            members,
+        })
+}
+
+proptest! {
+    #[test]
+    fn test_substruct_laws(
+        a: TeamSubstruct,
+        b: TeamSubstruct,
+        c: TeamSubstruct,
+        target in team(),
+    ) {
+        TeamSubstruct::check_laws(&a, &b, &c, &target).map_err(TestCaseError::fail)?;
+    }
+}
+
+#[test]
+fn test_arbitrary_leaves_guards_and_json_unset() {
+    let mut runner = TestRunner::deterministic();
+    let strategy = any::<TeamSubstruct>();
+    let mut set_names = 0;
+    for _ in 0..64 {
+        let update = strategy.new_tree(&mut runner).unwrap().current();
+        assert_eq!(update.expected_version, None);
+        assert_eq!(update.settings, None);
+        if update.name.is_some() {
+            set_names += 1;
+        }
+    }
+    assert!(set_names > 0 && set_names < 64);
+}
+
+#[test]
+fn test_merge_deep_merges_nested_fields() {
+    let first = TeamSubstruct {
+        address: Some(AddressSubstruct {
+            city: Some("Oslo".to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+    let second = TeamSubstruct {
+        address: Some(AddressSubstruct {
+            zip: Some(150),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    let address = first.merge(second).address.unwrap();
+    assert_eq!(address.city, Some("Oslo".to_string()));
+    assert_eq!(address.zip, Some(150));
+}
+
+#[test]
+fn test_merge_decides_precedence_on_normalized_values() {
+    let first = TeamSubstruct {
+        slug: "core".to_string(),
+        ..Default::default()
+    };
+    // Trims to the "no change" sentinel, so it must not override `first`
+    let second = TeamSubstruct {
+        slug: "   ".to_string(),
+        ..Default::default()
+    };
+
+    assert_eq!(first.clone().merge(second.clone()).slug, "core");
+    let target = Team {
+        version: 0,
+        name: "Core".to_string(),
+        motto: None,
+        size: 3,
+        slug: "team".to_string(),
+        settings: Settings { public: true },
+        address: Address::default(),
+        members: vec![],
+    };
+    assert_eq!(
+        TeamSubstruct::check_merge_apply(&first, &second, &target),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_law_checks_report_violations() {
+    let target = Profile {
+        name: "Alice".to_string(),
+        billing: Some(Address {
+            city: "Oslo".to_string(),
+            zip: 150,
+        }),
+    };
+    // A clear followed by a patch resets the value; a single substruct can only patch it
+    let clear = ProfileSubstruct {
+        billing: Some(None),
+        ..Default::default()
+    };
+    let patch = ProfileSubstruct {
+        billing: Some(Some(AddressSubstruct {
+            zip: Some(9000),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+
+    let err = ProfileSubstruct::check_merge_apply(&clear, &patch, &target).unwrap_err();
+    assert!(err.starts_with("applying a.merge(b) differs from applying a, then b"));
+    assert!(ProfileSubstruct::check_laws(&clear, &patch, &clear, &target).is_err());
+    assert_eq!(
+        ProfileSubstruct::check_merge_associative(&clear, &patch, &clear),
+        Ok(())
+    );
+    assert_eq!(
+        ProfileSubstruct::check_empty_no_change(&ProfileSubstruct::default(), &target),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_law_checks_report_failed_guards() {
+    let target = Team {
+        version: 4,
+        name: "Core".to_string(),
+        motto: None,
+        size: 3,
+        slug: "team".to_string(),
+        settings: Settings { public: true },
+        address: Address::default(),
+        members: vec![],
+    };
+    let stale = TeamSubstruct {
+        expected_version: Some(3),
+        name: Some("Platform".to_string()),
+        ..Default::default()
+    };
+
+    let err =
+        TeamSubstruct::check_merge_apply(&stale, &TeamSubstruct::default(), &target).unwrap_err();
+    assert!(err.starts_with("a.apply_to failed: "), "{err}");
+    let err =
+        TeamSubstruct::check_merge_apply(&TeamSubstruct::default(), &stale, &target).unwrap_err();
+    assert!(err.starts_with("b.apply_to failed: "), "{err}");
+}
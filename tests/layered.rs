@@ -0,0 +1,381 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// LAYERED MERGE TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(env, layered)]
+struct Database {
+    #[substruct_field(primitive)]
+    host: String,
+    #[substruct_field(primitive)]
+    port: u16,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(layered)]
+struct Plugin {
+    #[substruct_field(primitive)]
+    id: String,
+    #[substruct_field(primitive)]
+    enabled: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(env, layered)]
+struct Config {
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(primitive)]
+    port: u16,
+    #[substruct_field(primitive, option = false)]
+    workers: u32,
+    #[substruct_field(nested)]
+    database: Database,
+    #[substruct_field(nested)]
+    replica: Option<Database>,
+    #[substruct_field(nested_vec, key = "id")]
+    plugins: Vec<Plugin>,
+}
+
+#[test]
+fn test_later_layers_win_and_are_explained() {
+    let layered = ConfigSubstruct::merge_all([
+        (
+            "defaults",
+            ConfigSubstruct {
+                name: Some("api".to_string()),
+                port: Some(8080),
+                workers: 2,
+                ..Default::default()
+            },
+        ),
+        (
+            "file",
+            ConfigSubstruct {
+                port: Some(9000),
+                ..Default::default()
+            },
+        ),
+        (
+            "cli",
+            ConfigSubstruct {
+                workers: 8,
+                ..Default::default()
+            },
+        ),
+    ]);
+
+    assert_eq!(layered.merged.name, Some("api".to_string()));
+    assert_eq!(layered.merged.port, Some(9000));
+    assert_eq!(layered.merged.workers, 8);
+
+    assert_eq!(layered.explain("name"), Some("defaults"));
+    assert_eq!(layered.explain("port"), Some("file"));
+    assert_eq!(layered.explain("workers"), Some("cli"));
+    assert_eq!(layered.explain("database"), None);
+    assert_eq!(layered.explain("missing"), None);
+}
+
+#[test]
+fn test_nested_layers_are_merged_field_by_field() {
+    let env: HashMap<&str, &str> = HashMap::from([("APP_DATABASE__PORT", "5433")]);
+    let from_env =
+        ConfigSubstruct::from_env_with("APP", |key| env.get(key).map(|v| v.to_string())).unwrap();
+
+    let layered = ConfigSubstruct::merge_all([
+        (
+            "file",
+            ConfigSubstruct {
+                database: Some(DatabaseSubstruct::new(
+                    Some("db.internal".to_string()),
+                    Some(5432),
+                )),
+                ..Default::default()
+            },
+        ),
+        ("env", from_env),
+    ]);
+
+    let database = layered.merged.database.clone().unwrap();
+    assert_eq!(database.host, Some("db.internal".to_string()));
+    assert_eq!(database.port, Some(5433));
+
+    assert_eq!(layered.explain("database.host"), Some("file"));
+    assert_eq!(layered.explain("database.port"), Some("env"));
+    assert_eq!(layered.explain("database"), Some("env"));
+
+    let mut target = Config::default();
+    layered.into_inner().apply_to(&mut target).unwrap();
+    assert_eq!(target.database.host, "db.internal");
+    assert_eq!(target.database.port, 5433);
+}
+
+#[test]
+fn test_clearing_an_optional_nested_field_drops_earlier_layers() {
+    let replica = |host: &str| ConfigSubstruct {
+        replica: Some(Some(DatabaseSubstruct::new(Some(host.to_string()), None))),
+        ..Default::default()
+    };
+    let clear = ConfigSubstruct {
+        replica: Some(None),
+        ..Default::default()
+    };
+
+    let layered = ConfigSubstruct::merge_all([("file", replica("a")), ("env", clear.clone())]);
+    assert!(matches!(layered.merged.replica, Some(None)));
+    assert_eq!(layered.explain("replica"), Some("env"));
+    assert_eq!(layered.explain("replica.host"), None);
+
+    let layered = ConfigSubstruct::merge_all([
+        ("file", replica("a")),
+        ("env", clear),
+        ("cli", replica("b")),
+    ]);
+    let replica = layered.merged.replica.clone().unwrap().unwrap();
+    assert_eq!(replica.host, Some("b".to_string()));
+    assert_eq!(layered.explain("replica"), Some("cli"));
+    assert_eq!(layered.explain("replica.host"), Some("cli"));
+}
+
+#[test]
+fn test_keyed_operations_accumulate_across_layers() {
+    let plugin = |id: &str, enabled: bool| Plugin {
+        id: id.to_string(),
+        enabled,
+    };
+    let layered = ConfigSubstruct::merge_all([
+        (
+            "defaults".to_string(),
+            ConfigSubstruct::default()
+                .upsert_plugins(plugin("auth", true))
+                .upsert_plugins(plugin("cache", true)),
+        ),
+        (
+            "file".to_string(),
+            ConfigSubstruct::default().remove_plugins("cache".to_string()),
+        ),
+        (
+            "cli".to_string(),
+            ConfigSubstruct::default()
+                .upsert_plugins(plugin("metrics", false))
+                .upsert_plugins(plugin("auth", false)),
+        ),
+    ]);
+
+    assert_eq!(layered.explain("plugins"), Some("cli"));
+    assert_eq!(layered.merged.plugins.len(), 5);
+
+    // The same key upserted by two layers ends up once, with the later value
+    let mut target = Config::default();
+    layered.merged.clone().apply_to(&mut target).unwrap();
+    assert_eq!(
+        target.plugins,
+        vec![plugin("auth", false), plugin("metrics", false)]
+    );
+
+    let mut target = Config {
+        plugins: vec![plugin("auth", true), plugin("cache", true)],
+        ..Default::default()
+    };
+    layered.merged.apply_to(&mut target).unwrap();
+    assert_eq!(
+        target.plugins,
+        vec![plugin("auth", false), plugin("metrics", false)]
+    );
+}
+
+#[test]
+fn test_sources_and_empty_layers() {
+    let empty = ConfigSubstruct::merge_all(Vec::<(&str, ConfigSubstruct)>::new());
+    assert!(empty.merged.is_empty());
+    assert_eq!(empty.sources().count(), 0);
+
+    let layered = ConfigSubstruct::merge_all([
+        (
+            "file",
+            ConfigSubstruct {
+                port: Some(1),
+                database: Some(DatabaseSubstruct::new(Some("h".to_string()), None)),
+                ..Default::default()
+            },
+        ),
+        ("env", ConfigSubstruct::default()),
+    ]);
+    let sources: Vec<_> = layered.sources().collect();
+    assert_eq!(
+        sources,
+        vec![
+            ("port", "file"),
+            ("database", "file"),
+            ("database.host", "file")
+        ]
+    );
+}
+
+#[test]
+fn test_unwrapped_fields_at_their_sentinel_are_not_attributed() {
+    let layered = ConfigSubstruct::merge_all([
+        (
+            "file",
+            ConfigSubstruct {
+                workers: 8,
+                ..Default::default()
+            },
+        ),
+        // 0 is the no-change value of `workers`, so this layer doesn't override it
+        (
+            "env",
+            ConfigSubstruct {
+                workers: 0,
+                port: Some(0),
+                ..Default::default()
+            },
+        ),
+    ]);
+
+    assert_eq!(layered.merged.workers, 8);
+    assert_eq!(layered.explain("workers"), Some("file"));
+    assert_eq!(layered.merged.port, Some(0));
+    assert_eq!(layered.explain("port"), Some("env"));
+}
+
+// ============================================================================
+// CUSTOM SENTINEL TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(layered)]
+struct Pool {
+    #[substruct_field(primitive, option = false, default = "u32::MAX")]
+    size: u32,
+    #[substruct_field(primitive, option = false, default = "\"inherit\".to_string()")]
+    mode: String,
+}
+
+#[test]
+fn test_zero_overrides_when_the_sentinel_is_not_zero() {
+    let layered = PoolSubstruct::merge_all([
+        (
+            "defaults",
+            PoolSubstruct {
+                size: 16,
+                mode: "lifo".to_string(),
+            },
+        ),
+        (
+            "env",
+            PoolSubstruct {
+                size: 0,
+                ..Default::default()
+            },
+        ),
+        ("cli", PoolSubstruct::default()),
+    ]);
+
+    assert_eq!(layered.merged.size, 0);
+    assert_eq!(layered.merged.mode, "lifo");
+    assert_eq!(
+        layered.sources().collect::<Vec<_>>(),
+        vec![("size", "env"), ("mode", "defaults")]
+    );
+
+    let mut pool = Pool {
+        size: 4,
+        mode: "fifo".to_string(),
+    };
+    layered.into_inner().apply_to(&mut pool);
+    assert_eq!(
+        pool,
+        Pool {
+            size: 0,
+            mode: "lifo".to_string()
+        }
+    );
+}
+
+// ============================================================================
+// GUARDED AND SENSITIVE LAYER TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(layered)]
+struct Credentials {
+    #[substruct_field(primitive, expect)]
+    user: String,
+    #[substruct_field(primitive, sensitive)]
+    password: String,
+    #[substruct_field(version)]
+    version: u32,
+}
+
+#[test]
+fn test_guards_from_the_last_layer_are_checked_on_apply() {
+    let layered = CredentialsSubstruct::merge_all([
+        (
+            "file",
+            CredentialsSubstruct::default()
+                .with_expected_version(1)
+                .expect_user("admin".to_string()),
+        ),
+        (
+            "cli",
+            CredentialsSubstruct {
+                password: Some("s3cret".to_string()),
+                ..Default::default()
+            }
+            .with_expected_version(2),
+        ),
+    ]);
+
+    assert_eq!(layered.merged.expected_version, Some(2));
+    assert_eq!(layered.merged.expect_user, Some("admin".to_string()));
+    // Guards don't set anything, so they have no provenance
+    assert_eq!(layered.explain("expected_version"), None);
+    assert_eq!(layered.explain("password"), Some("cli"));
+
+    let mut credentials = Credentials {
+        user: "admin".to_string(),
+        password: "old".to_string(),
+        version: 1,
+    };
+    let err = layered
+        .merged
+        .clone()
+        .apply_to(&mut credentials)
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        CredentialsSubstructApplyError::VersionConflict {
+            expected: 2,
+            found: 1
+        }
+    ));
+    assert_eq!(credentials.password, "old");
+    assert_eq!(credentials.version, 1);
+
+    credentials.user = "root".to_string();
+    credentials.version = 2;
+    let err = layered.merged.apply_to(&mut credentials).unwrap_err();
+    assert_eq!(err.field(), "user");
+    assert_eq!(credentials.password, "old");
+}
+
+#[test]
+fn test_debug_output_redacts_sensitive_layers() {
+    let layered = CredentialsSubstruct::merge_all([(
+        "env",
+        CredentialsSubstruct {
+            password: Some("s3cret".to_string()),
+            ..Default::default()
+        },
+    )]);
+
+    let debug = format!("{:?}", layered);
+    assert!(!debug.contains("s3cret"));
+    assert!(debug.contains("password: Some(<redacted>)"));
+    assert!(debug.contains("\"password\", \"env\""));
+}
@@ -0,0 +1,162 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::rc::Rc;
+use std::sync::Arc;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// WRAPPED NESTED FIELD TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(paths)]
+struct Address {
+    #[substruct_field(primitive)]
+    street: String,
+    #[substruct_field(primitive)]
+    city: String,
+}
+
+// Arc/Rc need serde's `rc` feature, and the source struct doesn't have to be serializable
+#[derive(Debug, Clone, PartialEq, SubstructBuilder)]
+#[substruct_builder(paths)]
+struct Customer {
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+    #[substruct_field(nested)]
+    shipping: Box<Address>,
+    #[substruct_field(nested)]
+    office: Arc<Address>,
+    #[substruct_field(nested)]
+    home: Rc<Address>,
+    #[substruct_field(nested)]
+    previous: Vec<Address>,
+}
+
+fn address(street: &str, city: &str) -> Address {
+    Address {
+        street: street.to_string(),
+        city: city.to_string(),
+    }
+}
+
+fn customer() -> Customer {
+    Customer {
+        billing: None,
+        shipping: Box::new(address("1 Ship St", "Lyon")),
+        office: Arc::new(address("2 Office Rd", "Paris")),
+        home: Rc::new(address("3 Home Ln", "Nice")),
+        previous: vec![address("4 Old St", "Metz"), address("5 Old Rd", "Metz")],
+    }
+}
+
+fn city(city: &str) -> AddressSubstruct {
+    AddressSubstruct::new(None, Some(city.to_string()))
+}
+
+#[test]
+fn test_wrapped_nested_field_types() {
+    let update = CustomerSubstruct::default();
+
+    let _: Option<Option<AddressSubstruct>> = update.billing;
+    let _: Option<AddressSubstruct> = update.shipping;
+    let _: Option<AddressSubstruct> = update.office;
+    let _: Option<AddressSubstruct> = update.home;
+    let _: Option<AddressSubstruct> = update.previous;
+    assert!(update.is_empty());
+}
+
+#[test]
+fn test_option_nested_create_patch_and_clear() {
+    let mut target = customer();
+
+    // Creates the missing value from Default, then patches it
+    let create = CustomerSubstruct {
+        billing: Some(Some(city("Lille"))),
+        ..Default::default()
+    };
+    assert!(create.would_change(&target));
+    create.apply_to(&mut target).unwrap();
+    assert_eq!(target.billing, Some(address("", "Lille")));
+
+    // Patches the existing value in place
+    let patch = CustomerSubstruct {
+        billing: Some(Some(AddressSubstruct::new(
+            Some("6 Bill Ave".to_string()),
+            None,
+        ))),
+        ..Default::default()
+    };
+    patch.apply_to(&mut target).unwrap();
+    assert_eq!(target.billing, Some(address("6 Bill Ave", "Lille")));
+    assert!(!patch.would_change(&target));
+
+    // Clears it
+    let clear = CustomerSubstruct {
+        billing: Some(None),
+        ..Default::default()
+    };
+    assert!(clear.would_change(&target));
+    clear.apply_to(&mut target).unwrap();
+    assert_eq!(target.billing, None);
+    assert!(!clear.would_change(&target));
+}
+
+#[test]
+fn test_box_and_pointer_nested_patch_through() {
+    let mut target = customer();
+    let shared_office = Arc::clone(&target.office);
+
+    let update = CustomerSubstruct {
+        shipping: Some(city("Lyon")),
+        office: Some(city("Bordeaux")),
+        home: Some(city("Cannes")),
+        ..Default::default()
+    };
+    assert!(update.would_change(&target));
+
+    update.apply_to(&mut target).unwrap();
+
+    assert_eq!(target.shipping.city, "Lyon");
+    assert_eq!(target.office.city, "Bordeaux");
+    assert_eq!(target.home.city, "Cannes");
+    // Copy-on-write: the shared Arc is left untouched
+    assert_eq!(shared_office.city, "Paris");
+    assert!(!update.would_change(&target));
+}
+
+#[test]
+fn test_vec_nested_patches_every_element() {
+    let mut target = customer();
+    let update = CustomerSubstruct {
+        previous: Some(city("Strasbourg")),
+        ..Default::default()
+    };
+
+    assert!(update.would_change(&target));
+    update.apply_to(&mut target).unwrap();
+
+    assert!(target.previous.iter().all(|item| item.city == "Strasbourg"));
+    assert_eq!(target.previous[0].street, "4 Old St");
+    assert!(!update.would_change(&target));
+}
+
+#[test]
+fn test_wrapped_nested_paths_and_partial() {
+    let mut update = CustomerSubstruct::default();
+    update.set_by_path("billing.city", json!("Lille")).unwrap();
+    update.set_by_path("office.city", json!("Paris")).unwrap();
+
+    assert!(update.has_field("billing.city"));
+    assert!(update.has_field("office.city"));
+    assert_eq!(update.get_by_path("billing.city"), Some(json!("Lille")));
+
+    update.set_by_path("billing", json!(null)).unwrap();
+    assert!(matches!(update.billing, Some(None)));
+    assert!(update.has_field("billing"));
+    assert!(!update.has_field("billing.city"));
+
+    let partial = update.into_partial();
+    assert_eq!(partial.get("billing"), Some(&"None".to_string()));
+    assert!(partial.get("office").unwrap().contains("Paris"));
+}
@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// DOTTED-PATH ACCESS TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(paths)]
+struct Account {
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(primitive)]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false)]
+    version: u32,
+    #[substruct_field(json)]
+    preferences: Preferences,
+    #[substruct_field(nested)]
+    address: Address,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Preferences {
+    theme: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(paths)]
+struct Address {
+    #[substruct_field(primitive)]
+    city: String,
+    #[substruct_field(primitive)]
+    zip: u32,
+}
+
+#[test]
+fn test_set_by_path_top_level_fields() {
+    let mut update = AccountSubstruct::default();
+
+    update.set_by_path("name", json!("Alice")).unwrap();
+    update.set_by_path("nickname", json!(null)).unwrap();
+    update.set_by_path("version", json!(3)).unwrap();
+    update
+        .set_by_path("preferences", json!({ "theme": "dark" }))
+        .unwrap();
+
+    assert_eq!(update.name, Some("Alice".to_string()));
+    assert_eq!(update.nickname, Some(None));
+    assert_eq!(update.version, 3);
+    assert_eq!(update.preferences, Some(json!({ "theme": "dark" })));
+}
+
+#[test]
+fn test_set_by_path_nested_creates_substruct() {
+    let mut update = AccountSubstruct::default();
+    assert!(update.address.is_none());
+
+    update.set_by_path("address.city", json!("NYC")).unwrap();
+
+    let address = update.address.as_ref().unwrap();
+    assert_eq!(address.city, Some("NYC".to_string()));
+    assert_eq!(address.zip, None);
+    assert!(update.has_field("address.city"));
+    assert!(!update.has_field("address.zip"));
+    assert!(!update.has_field("name.city"));
+}
+
+#[test]
+fn test_set_by_path_unknown_field() {
+    let mut update = AccountSubstruct::default();
+
+    let err = update.set_by_path("age", json!(30)).unwrap_err();
+    assert_eq!(
+        err,
+        AccountSubstructPathError::UnknownField {
+            path: "age".to_string()
+        }
+    );
+
+    let err = update
+        .set_by_path("address.country", json!("US"))
+        .unwrap_err();
+    assert_eq!(err.path(), "address.country");
+    assert_eq!(err.to_string(), "unknown field path `address.country`");
+
+    // A failed nested set doesn't leave an empty substruct behind
+    assert!(update.address.is_none());
+
+    let err = update.set_by_path("name.first", json!("A")).unwrap_err();
+    assert_eq!(err.path(), "name.first");
+}
+
+#[test]
+fn test_set_by_path_type_mismatch() {
+    let mut update = AccountSubstruct::default();
+
+    let err = update.set_by_path("version", json!("three")).unwrap_err();
+    assert!(matches!(
+        err,
+        AccountSubstructPathError::TypeMismatch { ref path, .. } if path == "version"
+    ));
+
+    let err = update.set_by_path("address.zip", json!("abc")).unwrap_err();
+    assert!(matches!(
+        err,
+        AccountSubstructPathError::TypeMismatch { ref path, .. } if path == "address.zip"
+    ));
+    assert!(
+        err.to_string()
+            .starts_with("type mismatch at `address.zip`")
+    );
+
+    // JSON fields must hold a value of the source type
+    let err = update
+        .set_by_path("preferences", json!({ "theme": 5 }))
+        .unwrap_err();
+    assert!(matches!(
+        err,
+        AccountSubstructPathError::TypeMismatch { ref path, .. } if path == "preferences"
+    ));
+    assert!(update.preferences.is_none());
+
+    // A non-nullable field rejects null
+    assert!(update.set_by_path("name", json!(null)).is_err());
+}
+
+#[test]
+fn test_get_by_path() {
+    let update = AccountSubstruct::new(
+        Some("Alice".to_string()),
+        Some(None),
+        0,
+        None,
+        Some(AddressSubstruct::new(Some("NYC".to_string()), None)),
+    );
+
+    assert_eq!(update.get_by_path("name"), Some(json!("Alice")));
+    assert_eq!(update.get_by_path("nickname"), Some(json!(null)));
+    assert_eq!(update.get_by_path("version"), None);
+    assert_eq!(update.get_by_path("preferences"), None);
+    assert_eq!(update.get_by_path("address.city"), Some(json!("NYC")));
+    assert_eq!(update.get_by_path("address.zip"), None);
+    assert_eq!(
+        update.get_by_path("address"),
+        Some(json!({ "city": "NYC" }))
+    );
+    assert_eq!(update.get_by_path("unknown"), None);
+}
+
+#[test]
+fn test_set_by_path_round_trip_apply() {
+    let mut account = Account {
+        name: "Alice".to_string(),
+        nickname: Some("Al".to_string()),
+        version: 1,
+        preferences: Preferences {
+            theme: "light".to_string(),
+        },
+        address: Address {
+            city: "Boston".to_string(),
+            zip: 2101,
+        },
+    };
+
+    let mut update = AccountSubstruct::default();
+    for (path, value) in [
+        ("nickname", json!(null)),
+        ("address.city", json!("NYC")),
+        ("preferences", json!({ "theme": "dark" })),
+    ] {
+        update.set_by_path(path, value).unwrap();
+    }
+
+    update.apply_to(&mut account).unwrap();
+
+    assert_eq!(account.nickname, None);
+    assert_eq!(account.address.city, "NYC");
+    assert_eq!(account.address.zip, 2101);
+    assert_eq!(account.preferences.theme, "dark");
+}
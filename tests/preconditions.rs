@@ -0,0 +1,430 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// CONDITIONAL UPDATE TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum Status {
+    Pending,
+    Paid,
+    Shipped,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Order {
+    #[substruct_field(primitive, expect)]
+    status: Status,
+    #[substruct_field(primitive, expect)]
+    tracking: Option<String>,
+    #[substruct_field(primitive)]
+    note: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Shipment {
+    #[substruct_field(primitive, expect)]
+    carrier: String,
+    #[substruct_field(version)]
+    revision: u32,
+}
+
+fn paid_order() -> Order {
+    Order {
+        status: Status::Paid,
+        tracking: None,
+        note: String::new(),
+    }
+}
+
+#[test]
+fn test_precondition_met_applies() {
+    let mut order = paid_order();
+    let update = OrderSubstruct::new(Some(Status::Shipped), Some(Some("1Z999".to_string())), None)
+        .expect_status(Status::Paid)
+        .expect_tracking(None);
+
+    assert_eq!(update.expect_status, Some(Status::Paid));
+    update.apply_to(&mut order).unwrap();
+
+    assert_eq!(order.status, Status::Shipped);
+    assert_eq!(order.tracking, Some("1Z999".to_string()));
+}
+
+#[test]
+fn test_failed_precondition_writes_nothing() {
+    let mut order = paid_order();
+    order.status = Status::Pending;
+    let before = order.clone();
+
+    // The note and tracking are only written if every precondition holds
+    let update = OrderSubstruct::new(
+        Some(Status::Shipped),
+        Some(Some("1Z999".to_string())),
+        Some("shipped".to_string()),
+    )
+    .expect_tracking(None)
+    .expect_status(Status::Paid);
+
+    let err = update.apply_to(&mut order).unwrap_err();
+    assert_eq!(
+        err,
+        OrderSubstructApplyError::PreconditionFailed {
+            field: "status",
+            expected: "Paid".to_string(),
+            found: "Pending".to_string(),
+        }
+    );
+    assert_eq!(err.field(), "status");
+    assert_eq!(
+        err.to_string(),
+        "precondition failed on `status`: expected Paid, found Pending"
+    );
+    assert_eq!(order, before);
+}
+
+#[test]
+fn test_unset_preconditions_are_not_checked() {
+    let mut order = paid_order();
+    let update = OrderSubstruct::new(None, None, Some("gift".to_string()));
+
+    assert!(!update.is_empty());
+    update.apply_to(&mut order).unwrap();
+    assert_eq!(order.note, "gift");
+
+    // Preconditions alone don't make an update non-empty
+    let guard_only = OrderSubstruct::default().expect_status(Status::Paid);
+    assert!(guard_only.is_empty());
+    assert_eq!(guard_only.field_count(), 0);
+}
+
+#[test]
+fn test_preconditions_merge_and_try_apply_to() {
+    let mut order = paid_order();
+    let guard = OrderSubstruct::default().expect_status(Status::Shipped);
+    let change = OrderSubstruct::new(None, None, Some("late".to_string()));
+
+    let merged = guard.merge(change);
+    assert_eq!(merged.expect_status, Some(Status::Shipped));
+
+    let errors = merged.try_apply_to(&mut order).unwrap_err();
+    assert_eq!(
+        errors.messages_for("status"),
+        vec!["precondition failed on `status`: expected Shipped, found Paid"]
+    );
+    assert_eq!(order, paid_order());
+}
+
+#[test]
+fn test_preconditions_with_version() {
+    let mut shipment = Shipment {
+        carrier: "UPS".to_string(),
+        revision: 1,
+    };
+
+    let stale = ShipmentSubstruct::new(Some("DHL".to_string()))
+        .with_expected_version(0)
+        .expect_carrier("UPS".to_string());
+    assert!(matches!(
+        stale.apply_to(&mut shipment),
+        Err(ShipmentSubstructApplyError::VersionConflict {
+            expected: 0,
+            found: 1
+        })
+    ));
+
+    let wrong_carrier = ShipmentSubstruct::new(Some("DHL".to_string()))
+        .with_expected_version(1)
+        .expect_carrier("FedEx".to_string());
+    assert_eq!(
+        wrong_carrier.apply_to(&mut shipment).unwrap_err().field(),
+        "carrier"
+    );
+
+    let current = ShipmentSubstruct::new(Some("DHL".to_string()))
+        .with_expected_version(1)
+        .expect_carrier("UPS".to_string());
+    current.apply_to(&mut shipment).unwrap();
+    assert_eq!(shipment.carrier, "DHL");
+    assert_eq!(shipment.revision, 2);
+}
+
+// ============================================================================
+// NESTED GUARD TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Address {
+    #[substruct_field(primitive, expect)]
+    city: String,
+    #[substruct_field(primitive)]
+    street: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Customer {
+    #[substruct_field(version)]
+    version: u32,
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(nested)]
+    address: Address,
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+    #[substruct_field(nested)]
+    previous: Vec<Address>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Contact {
+    #[substruct_field(primitive)]
+    email: String,
+    #[substruct_field(nested)]
+    address: Address,
+}
+
+fn address(city: &str) -> Address {
+    Address {
+        city: city.to_string(),
+        street: "Main St".to_string(),
+    }
+}
+
+fn customer() -> Customer {
+    Customer {
+        version: 3,
+        name: "Alice".to_string(),
+        address: address("Paris"),
+        billing: None,
+        previous: vec![address("Lyon"), address("Nice")],
+    }
+}
+
+fn moved_to(expected_city: &str) -> AddressSubstruct {
+    AddressSubstruct::new(None, Some("Rue Neuve".to_string()))
+        .expect_city(expected_city.to_string())
+}
+
+#[test]
+fn test_failed_nested_precondition_writes_nothing() {
+    let mut target = customer();
+    let update = CustomerSubstruct::new(
+        Some("Bob".to_string()),
+        Some(moved_to("Berlin")),
+        None,
+        None,
+    );
+
+    let err = update.apply_to(&mut target).unwrap_err();
+    assert_eq!(
+        err,
+        CustomerSubstructApplyError::Nested {
+            path: "address".to_string(),
+            message: "precondition failed on `city`: expected \"Berlin\", found \"Paris\""
+                .to_string(),
+        }
+    );
+    assert_eq!(err.field(), "address");
+    assert_eq!(
+        err.to_string(),
+        "guard failed in `address`: precondition failed on `city`: expected \"Berlin\", found \"Paris\""
+    );
+
+    // Neither the parent's field nor its version were touched
+    assert_eq!(target, customer());
+    assert_eq!(update.check_guards(&target), Err(err));
+}
+
+#[test]
+fn test_nested_preconditions_met_apply_everything() {
+    let mut target = customer();
+    let update =
+        CustomerSubstruct::new(Some("Bob".to_string()), Some(moved_to("Paris")), None, None)
+            .with_expected_version(3);
+
+    assert_eq!(update.check_guards(&target), Ok(()));
+    update.apply_to(&mut target).unwrap();
+    assert_eq!(target.name, "Bob");
+    assert_eq!(target.address.street, "Rue Neuve");
+    assert_eq!(target.version, 4);
+}
+
+#[test]
+fn test_vec_nested_precondition_checks_every_element() {
+    let mut target = customer();
+    let update = CustomerSubstruct::new(None, None, None, Some(moved_to("Lyon")));
+
+    // "Nice" fails the precondition after "Lyon" passed it; neither element is patched
+    let err = update.apply_to(&mut target).unwrap_err();
+    assert_eq!(err.field(), "previous[1]");
+    assert_eq!(target, customer());
+
+    target.previous.truncate(1);
+    update.apply_to(&mut target).unwrap();
+    assert_eq!(target.previous[0].street, "Rue Neuve");
+}
+
+#[test]
+fn test_optional_nested_precondition_checks_created_value() {
+    let mut target = customer();
+
+    // A missing billing address is created from `Default`, whose city is empty
+    let update = CustomerSubstruct::new(None, None, Some(Some(moved_to("Paris"))), None);
+    assert_eq!(update.apply_to(&mut target).unwrap_err().field(), "billing");
+    assert_eq!(target.billing, None);
+
+    let update = CustomerSubstruct::new(None, None, Some(Some(moved_to(""))), None);
+    update.apply_to(&mut target).unwrap();
+    assert_eq!(target.billing.as_ref().unwrap().street, "Rue Neuve");
+
+    // Clearing has no guard to check
+    let clear = CustomerSubstruct::new(None, None, Some(None), None);
+    clear.apply_to(&mut target).unwrap();
+    assert_eq!(target.billing, None);
+}
+
+#[test]
+fn test_unguarded_parent_reports_nested_guard_failure() {
+    let mut contact = Contact {
+        email: "alice@example.com".to_string(),
+        address: address("Paris"),
+    };
+    let before = contact.clone();
+    let update = ContactSubstruct::new(Some("bob@example.com".to_string()), Some(moved_to("Rome")));
+
+    // `Contact` has no guards of its own, but the nested one still fails the whole update
+    let err = update.apply_to(&mut contact).unwrap_err();
+    assert_eq!(
+        err,
+        ContactSubstructApplyError::Nested {
+            path: "address".to_string(),
+            message: "precondition failed on `city`: expected \"Rome\", found \"Paris\""
+                .to_string(),
+        }
+    );
+    assert_eq!(contact, before);
+    assert_eq!(
+        update.check_guards(&contact).unwrap_err().field(),
+        "address"
+    );
+
+    let errors = update.try_apply_to(&mut contact).unwrap_err();
+    assert_eq!(
+        errors.messages_for("address"),
+        vec![
+            "guard failed in `address`: precondition failed on `city`: expected \"Rome\", found \"Paris\""
+        ]
+    );
+    assert_eq!(contact, before);
+
+    // `apply_unchecked` skips the guards on purpose
+    update.apply_unchecked(&mut contact);
+    assert_eq!(contact.email, "bob@example.com");
+    assert_eq!(contact.address.street, "Rue Neuve");
+}
+
+// ============================================================================
+// KEYED ELEMENT GUARD TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Parcel {
+    id: String,
+    #[substruct_field(primitive, expect)]
+    status: Status,
+    #[substruct_field(primitive)]
+    label: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Delivery {
+    #[substruct_field(primitive)]
+    courier: String,
+    #[substruct_field(nested_vec, key = "id")]
+    parcels: Vec<Parcel>,
+}
+
+fn parcel(id: &str, status: Status) -> Parcel {
+    Parcel {
+        id: id.to_string(),
+        status,
+        label: String::new(),
+    }
+}
+
+fn delivery() -> Delivery {
+    Delivery {
+        courier: "UPS".to_string(),
+        parcels: vec![parcel("a", Status::Paid), parcel("b", Status::Pending)],
+    }
+}
+
+fn ship(expected: Status) -> ParcelSubstruct {
+    ParcelSubstruct::new(Some(Status::Shipped), Some("1Z999".to_string())).expect_status(expected)
+}
+
+#[test]
+fn test_failed_element_precondition_leaves_target_unchanged() {
+    let mut target = delivery();
+    let update = DeliverySubstruct::new(Some("DHL".to_string()), Vec::new())
+        .remove_parcels("a".to_string())
+        .patch_parcels("b".to_string(), ship(Status::Paid));
+
+    // Neither the courier nor the removal before the failed patch is written
+    let err = update.apply_to(&mut target).unwrap_err();
+    assert_eq!(target, delivery());
+    assert_eq!(update.check_guards(&target), Err(err.clone()));
+
+    assert_eq!(
+        err,
+        DeliverySubstructApplyError::Nested {
+            path: "parcels[\"b\"]".to_string(),
+            message: "precondition failed on `status`: expected Paid, found Pending".to_string(),
+        }
+    );
+    assert_eq!(
+        update
+            .try_apply_to(&mut target)
+            .unwrap_err()
+            .messages_for("parcels[\"b\"]"),
+        vec![
+            "guard failed in `parcels[\"b\"]`: precondition failed on `status`: expected Paid, found Pending"
+        ]
+    );
+    assert_eq!(target, delivery());
+}
+
+#[test]
+fn test_element_preconditions_follow_earlier_operations() {
+    let mut target = delivery();
+
+    // The upsert replaces "b" before the patch checks it
+    let update = DeliverySubstruct::default()
+        .upsert_parcels(parcel("b", Status::Paid))
+        .patch_parcels("b".to_string(), ship(Status::Paid));
+    assert_eq!(update.check_guards(&target), Ok(()));
+    update.apply_to(&mut target).unwrap();
+    assert_eq!(target.parcels[1].status, Status::Shipped);
+    assert_eq!(target.parcels[1].label, "1Z999");
+
+    // A second patch of the same element sees the first one's result
+    let mut target = delivery();
+    let update = DeliverySubstruct::default()
+        .patch_parcels("a".to_string(), ship(Status::Paid))
+        .patch_parcels("a".to_string(), ship(Status::Paid));
+    assert_eq!(
+        update.check_guards(&target).unwrap_err().field(),
+        "parcels[\"a\"]"
+    );
+    assert!(update.apply_to(&mut target).is_err());
+    assert_eq!(target, delivery());
+
+    // Patches of removed or missing elements are ignored, guards included
+    let update = DeliverySubstruct::default()
+        .remove_parcels("a".to_string())
+        .patch_parcels("a".to_string(), ship(Status::Shipped))
+        .patch_parcels("z".to_string(), ship(Status::Shipped));
+    update.apply_to(&mut target).unwrap();
+    assert_eq!(target.parcels, vec![parcel("b", Status::Pending)]);
+}
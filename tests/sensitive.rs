@@ -0,0 +1,290 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// SENSITIVE FIELD TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(report)]
+struct Account {
+    #[substruct_field(primitive)]
+    username: String,
+    #[substruct_field(primitive, sensitive)]
+    password: String,
+    #[substruct_field(primitive, sensitive, option = false)]
+    pin: u32,
+    #[substruct_field(primitive, sensitive, expect)]
+    api_key: Option<String>,
+}
+
+fn account() -> Account {
+    Account {
+        username: "alice".to_string(),
+        password: "hunter2".to_string(),
+        pin: 1234,
+        api_key: Some("sk-old".to_string()),
+    }
+}
+
+#[test]
+fn test_debug_redacts_sensitive_fields() {
+    let update = AccountSubstruct::new(
+        Some("bob".to_string()),
+        Some("hunter3".to_string()),
+        4321,
+        Some(None),
+    )
+    .expect_api_key(Some("sk-old".to_string()));
+
+    let debug = format!("{:?}", update);
+    assert_eq!(
+        debug,
+        "AccountSubstruct { username: Some(\"bob\"), password: Some(<redacted>), \
+         pin: <redacted>, api_key: Some(<redacted>), expect_api_key: Some(<redacted>) }"
+    );
+    assert!(!debug.contains("hunter3"));
+    assert!(!debug.contains("sk-old"));
+
+    // Unset sensitive fields still show that they are unset
+    let debug = format!("{:?}", AccountSubstruct::default());
+    assert!(debug.contains("password: None"));
+}
+
+#[test]
+fn test_into_partial_redacts_sensitive_fields() {
+    let update = AccountSubstruct::new(
+        Some("bob".to_string()),
+        Some("hunter3".to_string()),
+        4321,
+        None,
+    );
+
+    let partial = update.into_partial();
+    assert_eq!(partial.get("username"), Some(&"\"bob\"".to_string()));
+    assert_eq!(partial.get("password"), Some(&"<redacted>".to_string()));
+    assert_eq!(partial.get("pin"), Some(&"<redacted>".to_string()));
+    assert!(!partial.contains_key("api_key"));
+}
+
+#[test]
+fn test_sensitive_fields_still_apply() {
+    let mut target = account();
+    let update = AccountSubstruct::new(None, Some("hunter3".to_string()), 4321, Some(None));
+
+    update.apply_to(&mut target).unwrap();
+
+    assert_eq!(target.password, "hunter3");
+    assert_eq!(target.pin, 4321);
+    assert_eq!(target.api_key, None);
+    assert_eq!(target.username, "alice");
+}
+
+#[test]
+fn test_change_report_redacts_sensitive_values() {
+    let mut target = account();
+    let update = AccountSubstruct::new(
+        Some("bob".to_string()),
+        Some("hunter3".to_string()),
+        1234,
+        None,
+    );
+
+    let changes = update.apply_with_report(&mut target).unwrap();
+
+    assert_eq!(changes.changed_paths(), vec!["username", "password"]);
+    assert_eq!(changes.unchanged, vec!["pin"]);
+    let password = changes.get("password").unwrap();
+    assert_eq!(password.old, json!("<redacted>"));
+    assert_eq!(password.new, json!("<redacted>"));
+    assert_eq!(changes.get("username").unwrap().new, json!("bob"));
+}
+
+#[test]
+fn test_precondition_failure_redacts_sensitive_values() {
+    let mut target = account();
+    let update = AccountSubstruct::new(None, None, 0, Some(Some("sk-new".to_string())))
+        .expect_api_key(Some("sk-stale".to_string()));
+
+    let err = update.apply_to(&mut target).unwrap_err();
+
+    assert_eq!(err.field(), "api_key");
+    assert_eq!(
+        err.to_string(),
+        "precondition failed on `api_key`: expected <redacted>, found <redacted>"
+    );
+    assert_eq!(target, account());
+}
+
+// ============================================================================
+// WHOLE-VALUE REDACTION TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(report)]
+struct Credentials {
+    #[substruct_field(primitive)]
+    user: String,
+    #[substruct_field(primitive, sensitive)]
+    #[serde(rename = "secret")]
+    token: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(report)]
+struct Webhook {
+    id: String,
+    #[substruct_field(primitive)]
+    url: String,
+    #[substruct_field(nested)]
+    credentials: Credentials,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(report)]
+struct Integration {
+    #[substruct_field(nested)]
+    creds: Option<Credentials>,
+    #[substruct_field(nested_vec, key = "id")]
+    webhooks: Vec<Webhook>,
+}
+
+fn credentials(token: &str) -> Credentials {
+    Credentials {
+        user: "bot".to_string(),
+        token: token.to_string(),
+    }
+}
+
+fn webhook(id: &str, token: &str) -> Webhook {
+    Webhook {
+        id: id.to_string(),
+        url: format!("https://example.com/{id}"),
+        credentials: credentials(token),
+    }
+}
+
+#[test]
+fn test_change_report_redacts_created_and_cleared_nested_values() {
+    let mut target = Integration {
+        creds: None,
+        webhooks: Vec::new(),
+    };
+    let create = IntegrationSubstruct::new(
+        Some(Some(CredentialsSubstruct::new(
+            Some("bot".to_string()),
+            Some("HUNTER2".to_string()),
+        ))),
+        Vec::new(),
+    );
+
+    let changes = create.apply_with_report(&mut target).unwrap();
+    let created = changes.get("creds").unwrap();
+    assert_eq!(created.old, json!(null));
+    assert_eq!(
+        created.new,
+        json!({ "user": "bot", "secret": "<redacted>" })
+    );
+    assert_eq!(target.creds, Some(credentials("HUNTER2")));
+
+    let clear = IntegrationSubstruct::new(Some(None), Vec::new());
+    let changes = clear.apply_with_report(&mut target).unwrap();
+    let cleared = changes.get("creds").unwrap();
+    assert_eq!(
+        cleared.old,
+        json!({ "user": "bot", "secret": "<redacted>" })
+    );
+    assert_eq!(cleared.new, json!(null));
+
+    // Patching an existing value still reports it field by field
+    target.creds = Some(credentials("OLD"));
+    let patch = IntegrationSubstruct::new(
+        Some(Some(CredentialsSubstruct::new(
+            None,
+            Some("NEW".to_string()),
+        ))),
+        Vec::new(),
+    );
+    let changes = patch.apply_with_report(&mut target).unwrap();
+    assert_eq!(changes.changed_paths(), vec!["creds.token"]);
+    assert_eq!(changes.get("creds.token").unwrap().new, json!("<redacted>"));
+}
+
+#[test]
+fn test_change_report_redacts_keyed_elements() {
+    let mut target = Integration {
+        creds: None,
+        webhooks: vec![webhook("a", "OLDSECRET"), webhook("b", "KEEPSECRET")],
+    };
+    let update = IntegrationSubstruct::default()
+        .upsert_webhooks(webhook("a", "NEWSECRET"))
+        .remove_webhooks("b".to_string())
+        .upsert_webhooks(webhook("c", "FRESHSECRET"));
+
+    let changes = update.apply_with_report(&mut target).unwrap();
+
+    assert_eq!(
+        changes.changed_paths(),
+        vec!["webhooks[\"a\"]", "webhooks[\"b\"]", "webhooks[\"c\"]"]
+    );
+    let report = format!("{:?}", changes);
+    for secret in ["OLDSECRET", "NEWSECRET", "KEEPSECRET", "FRESHSECRET"] {
+        assert!(!report.contains(secret), "{secret} leaked: {report}");
+    }
+    assert_eq!(
+        changes.get("webhooks[\"a\"]").unwrap().new["credentials"],
+        json!({ "user": "bot", "secret": "<redacted>" })
+    );
+    assert_eq!(changes.get("webhooks[\"b\"]").unwrap().new, json!(null));
+}
+
+#[test]
+fn test_redact_json_walks_arrays_and_ignores_other_values() {
+    let mut value = json!([
+        { "user": "a", "secret": "one" },
+        { "user": "b" },
+        null,
+        "plain"
+    ]);
+    CredentialsSubstruct::redact_json(&mut value);
+    assert_eq!(
+        value,
+        json!([
+            { "user": "a", "secret": "<redacted>" },
+            { "user": "b" },
+            null,
+            "plain"
+        ])
+    );
+}
+
+#[test]
+fn test_debug_and_into_partial_redact_upserted_elements() {
+    let update = IntegrationSubstruct::default()
+        .upsert_webhooks(webhook("a", "UPSERTSECRET"))
+        .patch_webhooks(
+            "b".to_string(),
+            WebhookSubstruct::new(
+                None,
+                Some(CredentialsSubstruct::new(
+                    None,
+                    Some("PATCHSECRET".to_string()),
+                )),
+            ),
+        )
+        .remove_webhooks("c".to_string());
+
+    let debug = format!("{:?}", update);
+    assert!(!debug.contains("UPSERTSECRET"), "{debug}");
+    assert!(!debug.contains("PATCHSECRET"), "{debug}");
+    assert!(debug.contains("Upsert(\"a\", WebhookSubstruct {"));
+    assert!(debug.contains("token: Some(<redacted>)"));
+    assert!(debug.contains("Remove(\"c\")"));
+
+    let ops = update.into_partial().remove("webhooks").unwrap();
+    assert!(!ops.contains("UPSERTSECRET"), "{ops}");
+    assert!(!ops.contains("PATCHSECRET"), "{ops}");
+    assert!(ops.contains("upsert \\\"a\\\""));
+    assert!(ops.contains("remove \\\"c\\\""));
+}
@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// SNAPSHOT TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Address {
+    #[substruct_field(primitive)]
+    city: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Tag {
+    #[substruct_field(primitive)]
+    id: String,
+    #[substruct_field(primitive)]
+    color: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Preferences {
+    theme: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Profile {
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(primitive)]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false)]
+    age: u32,
+    #[substruct_field(json)]
+    preferences: Preferences,
+    #[substruct_field(nested)]
+    address: Address,
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+    #[substruct_field(nested_vec, key = "id")]
+    tags: Vec<Tag>,
+    #[substruct_field(skip)]
+    session: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(from = "snapshot")]
+struct Document {
+    #[substruct_field(primitive)]
+    title: String,
+    #[substruct_field(version)]
+    version: u64,
+}
+
+fn profile() -> Profile {
+    Profile {
+        name: "Alice".to_string(),
+        nickname: None,
+        age: 30,
+        preferences: Preferences {
+            theme: "dark".to_string(),
+        },
+        address: Address {
+            city: "Boston".to_string(),
+        },
+        billing: None,
+        tags: vec![Tag {
+            id: "a".to_string(),
+            color: "red".to_string(),
+        }],
+        session: "abc".to_string(),
+    }
+}
+
+fn other_profile() -> Profile {
+    Profile {
+        name: "Bob".to_string(),
+        nickname: Some("B".to_string()),
+        age: 50,
+        preferences: Preferences {
+            theme: "light".to_string(),
+        },
+        address: Address {
+            city: "Paris".to_string(),
+        },
+        billing: Some(Address {
+            city: "Lyon".to_string(),
+        }),
+        tags: vec![],
+        session: "xyz".to_string(),
+    }
+}
+
+#[test]
+fn test_snapshot_fills_every_field() {
+    let snapshot = ProfileSubstruct::snapshot(&profile());
+    assert_eq!(snapshot.field_count(), 7);
+
+    assert_eq!(snapshot.name, Some("Alice".to_string()));
+    assert_eq!(snapshot.nickname, Some(None));
+    assert_eq!(snapshot.age, 30);
+    assert_eq!(snapshot.preferences, Some(json!({ "theme": "dark" })));
+    assert_eq!(snapshot.address.unwrap().city, Some("Boston".to_string()));
+    assert!(matches!(snapshot.billing, Some(None)));
+    assert_eq!(snapshot.tags.len(), 1);
+}
+
+#[test]
+fn test_applying_a_snapshot_resyncs_the_target() {
+    let source = profile();
+    let mut target = other_profile();
+
+    ProfileSubstruct::snapshot(&source)
+        .apply_to(&mut target)
+        .unwrap();
+
+    // Everything but skipped fields now matches the source
+    assert_eq!(
+        target,
+        Profile {
+            session: "xyz".to_string(),
+            ..profile()
+        }
+    );
+    assert!(!ProfileSubstruct::snapshot(&source).would_change(&target));
+}
+
+#[test]
+fn test_default_from_policy_is_empty() {
+    let source = profile();
+
+    let from_ref = ProfileSubstruct::from(&source);
+    assert!(from_ref.is_empty());
+    assert_eq!(from_ref.preferences, None);
+    assert!(ProfileSubstruct::from_source(&source).is_empty());
+    assert!(ProfileSubstruct::from(source).is_empty());
+}
+
+#[test]
+fn test_snapshot_from_policy() {
+    let document = Document {
+        title: "Draft".to_string(),
+        version: 4,
+    };
+
+    for update in [
+        DocumentSubstruct::from(&document),
+        DocumentSubstruct::from_source(&document),
+        DocumentSubstruct::snapshot(&document),
+        DocumentSubstruct::from(document.clone()),
+    ] {
+        assert_eq!(update.title, Some("Draft".to_string()));
+        assert_eq!(update.expected_version, Some(4));
+    }
+}
+
+#[test]
+fn test_keyed_snapshot_upserts_each_element_once() {
+    let tag = |id: &str, color: &str| Tag {
+        id: id.to_string(),
+        color: color.to_string(),
+    };
+    let source = Profile {
+        tags: vec![tag("a", "red"), tag("b", "green")],
+        ..profile()
+    };
+    let mut target = Profile {
+        tags: vec![tag("b", "blue"), tag("c", "gray")],
+        ..other_profile()
+    };
+
+    let snapshot = ProfileSubstruct::snapshot(&source);
+    snapshot.clone().apply_to(&mut target).unwrap();
+    snapshot.apply_to(&mut target).unwrap();
+
+    // Existing keys are replaced in place, and elements only in the target are kept
+    assert_eq!(
+        target.tags,
+        vec![tag("b", "green"), tag("c", "gray"), tag("a", "red")]
+    );
+}
+
+#[test]
+fn test_unwrapped_field_at_its_sentinel_is_not_synced() {
+    let source = Profile {
+        age: 0,
+        ..profile()
+    };
+    let mut target = other_profile();
+
+    let snapshot = ProfileSubstruct::snapshot(&source);
+    assert_eq!(snapshot.field_count(), 6);
+    assert!(!snapshot.has_field("age"));
+
+    // The no-change value can't be told apart from an unknown age
+    snapshot.apply_to(&mut target).unwrap();
+    assert_eq!(target.age, 50);
+    assert_eq!(target.name, "Alice");
+}
+
+// ============================================================================
+// GUARDED AND SENSITIVE SNAPSHOT TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Account {
+    #[substruct_field(primitive, expect)]
+    owner: String,
+    #[substruct_field(primitive, sensitive)]
+    api_key: String,
+    #[substruct_field(version)]
+    version: u64,
+}
+
+#[test]
+fn test_snapshot_only_applies_while_current() {
+    let mut account = Account {
+        owner: "alice".to_string(),
+        api_key: "old-key".to_string(),
+        version: 3,
+    };
+    let snapshot = AccountSubstruct::snapshot(&account);
+    assert_eq!(snapshot.expected_version, Some(3));
+    // Snapshots don't add preconditions of their own
+    assert_eq!(snapshot.expect_owner, None);
+
+    account.version = 4;
+    let err = snapshot.clone().apply_to(&mut account).unwrap_err();
+    assert!(matches!(
+        err,
+        AccountSubstructApplyError::VersionConflict {
+            expected: 3,
+            found: 4
+        }
+    ));
+
+    let mut current = AccountSubstruct::snapshot(&account);
+    current.api_key = Some("new-key".to_string());
+    current.apply_to(&mut account).unwrap();
+    assert_eq!(account.api_key, "new-key");
+    assert_eq!(account.version, 5);
+}
+
+#[test]
+fn test_snapshot_debug_redacts_sensitive_values() {
+    let account = Account {
+        owner: "alice".to_string(),
+        api_key: "sk-live-123".to_string(),
+        version: 1,
+    };
+    let snapshot = AccountSubstruct::snapshot(&account);
+
+    assert_eq!(snapshot.api_key, Some("sk-live-123".to_string()));
+    let debug = format!("{:?}", snapshot);
+    assert!(!debug.contains("sk-live-123"));
+    assert!(debug.contains("owner: Some(\"alice\")"));
+    assert!(debug.contains("api_key: Some(<redacted>)"));
+}
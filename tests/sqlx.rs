@@ -0,0 +1,212 @@
+#![cfg(feature = "sqlx")]
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Connection, FromRow, QueryBuilder, Sqlite, SqliteConnection};
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// SQLX QUERY BUILDER TESTS (require `--features sqlx`)
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, SubstructBuilder)]
+#[substruct_builder(sqlx)]
+struct Document {
+    id: i64,
+    #[substruct_field(primitive)]
+    title: String,
+    #[sqlx(rename = "revision")]
+    #[substruct_field(version)]
+    version: i64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Preferences {
+    #[substruct_field(primitive)]
+    theme: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow, SubstructBuilder)]
+#[substruct_builder(sqlx)]
+struct User {
+    id: i64,
+    #[sqlx(rename = "display_name")]
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(primitive)]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false)]
+    login_count: i64,
+    #[substruct_field(primitive, transform = "trim|lowercase")]
+    email: String,
+    #[sqlx(skip)]
+    #[substruct_field(nested)]
+    preferences: Preferences,
+}
+
+async fn connection() -> SqliteConnection {
+    let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+    sqlx::query(
+        "CREATE TABLE users (
+            id INTEGER PRIMARY KEY,
+            display_name TEXT NOT NULL,
+            nickname TEXT,
+            login_count INTEGER NOT NULL,
+            email TEXT NOT NULL
+        )",
+    )
+    .execute(&mut conn)
+    .await
+    .unwrap();
+    sqlx::query("INSERT INTO users VALUES (1, 'Alice', 'Al', 3, 'alice@example.com')")
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    conn
+}
+
+async fn update_user(conn: &mut SqliteConnection, update: &UserSubstruct) -> User {
+    let mut query = QueryBuilder::<Sqlite>::new("UPDATE users SET ");
+    update.push_set_clauses(&mut query).unwrap();
+    query.push(" WHERE id = ").push_bind(1);
+    query.build().execute(&mut *conn).await.unwrap();
+
+    sqlx::query_as("SELECT * FROM users WHERE id = 1")
+        .fetch_one(conn)
+        .await
+        .unwrap()
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_push_set_clauses_binds_set_columns() {
+    let update = UserSubstruct {
+        name: Some("Alicia".to_string()),
+        login_count: 4,
+        ..Default::default()
+    };
+
+    let mut query = QueryBuilder::<Sqlite>::new("UPDATE users SET ");
+    update.push_set_clauses(&mut query).unwrap();
+    assert_eq!(
+        query.sql(),
+        "UPDATE users SET display_name = ?, login_count = ?"
+    );
+
+    let user = update_user(&mut connection().await, &update).await;
+    assert_eq!(user.name, "Alicia");
+    assert_eq!(user.nickname, Some("Al".to_string()));
+    assert_eq!(user.login_count, 4);
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_push_set_clauses_clears_nullable_columns() {
+    let update = UserSubstruct {
+        nickname: Some(None),
+        ..Default::default()
+    };
+
+    let user = update_user(&mut connection().await, &update).await;
+    assert_eq!(user.nickname, None);
+    assert_eq!(user.name, "Alice");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_push_set_clauses_normalizes_values() {
+    let update = UserSubstruct {
+        email: Some("  ALICE@Work.COM ".to_string()),
+        ..Default::default()
+    };
+
+    let user = update_user(&mut connection().await, &update).await;
+    assert_eq!(user.email, "alice@work.com");
+}
+
+#[test]
+fn test_push_set_clauses_rejects_updates_without_columns() {
+    let update = UserSubstruct {
+        preferences: Some(PreferencesSubstruct {
+            theme: Some("dark".to_string()),
+        }),
+        ..Default::default()
+    };
+
+    let mut query = QueryBuilder::<Sqlite>::new("UPDATE users SET ");
+    assert_eq!(
+        update.push_set_clauses(&mut query),
+        Err(UserSubstructEmptyUpdateError)
+    );
+    assert_eq!(query.sql(), "UPDATE users SET ");
+}
+
+#[tokio::test(flavor = "current_thread")]
+async fn test_push_set_clauses_bumps_the_version_of_matching_rows() {
+    let mut conn = SqliteConnection::connect("sqlite::memory:").await.unwrap();
+    sqlx::query("CREATE TABLE documents (id INTEGER PRIMARY KEY, title TEXT NOT NULL, revision INTEGER NOT NULL)")
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    sqlx::query("INSERT INTO documents VALUES (1, 'Draft', 3)")
+        .execute(&mut conn)
+        .await
+        .unwrap();
+    let update = DocumentSubstruct {
+        expected_version: Some(3),
+        title: Some("Final".to_string()),
+    };
+
+    let mut query = QueryBuilder::<Sqlite>::new("UPDATE documents SET ");
+    update.push_set_clauses(&mut query).unwrap();
+    query.push(" WHERE id = ").push_bind(1);
+    update.push_version_filter(&mut query);
+    assert_eq!(
+        query.sql(),
+        "UPDATE documents SET title = ?, revision = revision + 1 WHERE id = ? AND revision = ?"
+    );
+    assert_eq!(
+        query
+            .build()
+            .execute(&mut conn)
+            .await
+            .unwrap()
+            .rows_affected(),
+        1
+    );
+
+    // The same update is now stale and matches no row
+    let mut query = QueryBuilder::<Sqlite>::new("UPDATE documents SET ");
+    update.push_set_clauses(&mut query).unwrap();
+    query.push(" WHERE id = ").push_bind(1);
+    update.push_version_filter(&mut query);
+    assert_eq!(
+        query
+            .build()
+            .execute(&mut conn)
+            .await
+            .unwrap()
+            .rows_affected(),
+        0
+    );
+
+    let document: Document = sqlx::query_as("SELECT * FROM documents WHERE id = 1")
+        .fetch_one(&mut conn)
+        .await
+        .unwrap();
+    assert_eq!(document.title, "Final");
+    assert_eq!(document.version, 4);
+}
+
+#[test]
+fn test_push_version_filter_skips_unset_versions() {
+    let update = DocumentSubstruct {
+        title: Some("Final".to_string()),
+        ..Default::default()
+    };
+
+    let mut query = QueryBuilder::<Sqlite>::new("UPDATE documents SET ");
+    update.push_set_clauses(&mut query).unwrap();
+    query.push(" WHERE id = ").push_bind(1);
+    update.push_version_filter(&mut query);
+    assert_eq!(
+        query.sql(),
+        "UPDATE documents SET title = ?, revision = revision + 1 WHERE id = ?"
+    );
+}
@@ -0,0 +1,144 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// VALUE TRANSFORM TESTS
+// ============================================================================
+
+fn collapse_spaces(value: String) -> String {
+    value.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(paths)]
+struct Signup {
+    #[substruct_field(primitive, transform = "trim|lowercase")]
+    email: String,
+    #[substruct_field(primitive, transform = "trim")]
+    username: String,
+    #[substruct_field(primitive, transform = "uppercase")]
+    country: Option<String>,
+    #[substruct_field(primitive, option = false, transform_with = "collapse_spaces")]
+    display_name: String,
+    #[substruct_field(primitive)]
+    bio: String,
+}
+
+fn signup() -> Signup {
+    Signup {
+        email: "alice@example.com".to_string(),
+        username: "Alice".to_string(),
+        country: Some("FR".to_string()),
+        display_name: "Alice Liddell".to_string(),
+        bio: " untouched ".to_string(),
+    }
+}
+
+#[test]
+fn test_constructor_normalizes() {
+    let update = SignupSubstruct::new(
+        Some("  Alice@Example.COM ".to_string()),
+        Some(" Alice ".to_string()),
+        Some(Some("fr".to_string())),
+        "Alice    Liddell".to_string(),
+        Some(" keep spaces ".to_string()),
+    );
+
+    assert_eq!(update.email, Some("alice@example.com".to_string()));
+    assert_eq!(update.username, Some("Alice".to_string()));
+    assert_eq!(update.country, Some(Some("FR".to_string())));
+    assert_eq!(update.display_name, "Alice Liddell");
+    assert_eq!(update.bio, Some(" keep spaces ".to_string()));
+}
+
+#[test]
+fn test_set_by_path_normalizes() {
+    let mut update = SignupSubstruct::default();
+    update
+        .set_by_path("email", json!(" BOB@EXAMPLE.COM"))
+        .unwrap();
+    update.set_by_path("country", json!(null)).unwrap();
+
+    assert_eq!(update.email, Some("bob@example.com".to_string()));
+    assert_eq!(update.country, Some(None));
+}
+
+#[test]
+fn test_apply_to_normalizes_directly_assigned_fields() {
+    let mut target = signup();
+    let update = SignupSubstruct {
+        username: Some("  Bob  ".to_string()),
+        country: Some(Some("de".to_string())),
+        ..Default::default()
+    };
+
+    update.apply_to(&mut target);
+
+    assert_eq!(target.username, "Bob");
+    assert_eq!(target.country, Some("DE".to_string()));
+    // The substruct itself is left as assigned
+    assert_eq!(update.username, Some("  Bob  ".to_string()));
+}
+
+#[test]
+fn test_would_change_compares_normalized_values() {
+    let target = signup();
+
+    let mut update = SignupSubstruct {
+        username: Some(" Alice ".to_string()),
+        email: Some("ALICE@example.com".to_string()),
+        ..Default::default()
+    };
+    assert!(!update.would_change(&target));
+
+    update.bio = Some("untouched".to_string());
+    assert!(update.would_change(&target));
+}
+
+#[test]
+fn test_normalize_in_place() {
+    let mut update = SignupSubstruct {
+        email: Some(" MiXeD@Case.Org ".to_string()),
+        display_name: " a  b ".to_string(),
+        ..Default::default()
+    };
+
+    update.normalize();
+
+    assert_eq!(update.email, Some("mixed@case.org".to_string()));
+    assert_eq!(update.display_name, "a b");
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Handle {
+    #[substruct_field(primitive, transform = "trim", validate(length(min = 3, max = 5)))]
+    name: String,
+}
+
+#[test]
+fn test_validate_checks_normalized_values() {
+    let mut handle = Handle {
+        name: "bob".to_string(),
+    };
+
+    // Stored as "abc", so the padding doesn't count against `max`
+    let padded = HandleSubstruct {
+        name: Some("  abc  ".to_string()),
+    };
+    assert_eq!(padded.validate(), Ok(()));
+    padded.try_apply_to(&mut handle).unwrap();
+    assert_eq!(handle.name, "abc");
+
+    // Stored as "a", so the padding doesn't satisfy `min`
+    let short = HandleSubstruct {
+        name: Some("  a  ".to_string()),
+    };
+    let errors = short.validate().unwrap_err();
+    assert_eq!(
+        errors.messages_for("name"),
+        vec!["length must be at least 3"]
+    );
+    assert!(short.try_apply_to(&mut handle).is_err());
+    assert_eq!(handle.name, "abc");
+}
@@ -0,0 +1,301 @@
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// TYPESCRIPT DECLARATION TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(json_schema, typescript)]
+struct Address {
+    #[substruct_field(primitive)]
+    city: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(json_schema, typescript)]
+struct Tag {
+    #[substruct_field(primitive)]
+    id: u32,
+    #[substruct_field(primitive)]
+    color: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Preferences {
+    theme: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(json_schema, typescript)]
+struct Profile {
+    #[substruct_field(primitive, label = "Display name")]
+    name: String,
+    #[substruct_field(primitive)]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false)]
+    age: u32,
+    #[substruct_field(primitive)]
+    scores: Vec<Option<f64>>,
+    #[substruct_field(json)]
+    preferences: Preferences,
+    #[substruct_field(nested)]
+    address: Address,
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+    #[substruct_field(nested_vec, key = "id", key_type = "u32")]
+    tags: Vec<Tag>,
+    #[substruct_field(version)]
+    version: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[serde(rename_all = "camelCase")]
+#[substruct_builder(json_schema, typescript, create)]
+struct Account {
+    #[substruct_field(primitive)]
+    display_name: String,
+    #[serde(rename = "e-mail")]
+    #[substruct_field(primitive)]
+    email_address: String,
+    #[substruct_field(primitive, expect)]
+    login_count: u32,
+    #[substruct_field(version)]
+    version: u64,
+}
+
+#[test]
+fn test_interface_with_optional_properties() {
+    let declaration = ProfileSubstruct::ts_declaration();
+    let interface = declaration.split("\n\n").next().unwrap();
+
+    assert_eq!(
+        interface,
+        "export interface ProfileSubstruct {
+  /** Display name */
+  name?: string;
+  nickname?: string | null;
+  age?: number;
+  scores?: (number | null)[];
+  preferences?: unknown;
+  address?: AddressSubstruct;
+  billing?: AddressSubstruct | null;
+  tags?: Array<{ upsert: unknown } | { remove: number } | { patch: [number, TagSubstruct] }>;
+  expected_version?: number;
+}"
+    );
+}
+
+#[test]
+fn test_nested_interfaces_are_declared_once() {
+    let declaration = ProfileSubstruct::ts_declaration();
+
+    assert_eq!(declaration.matches("export interface").count(), 3);
+    assert_eq!(
+        declaration
+            .matches("export interface AddressSubstruct")
+            .count(),
+        1
+    );
+    assert!(
+        declaration
+            .ends_with("export interface TagSubstruct {\n  id?: number;\n  color?: string;\n}\n")
+    );
+    assert_eq!(
+        AddressSubstruct::ts_declaration(),
+        "export interface AddressSubstruct {\n  city?: string;\n}\n"
+    );
+}
+
+#[test]
+fn test_serde_renames_are_followed() {
+    assert_eq!(
+        AccountSubstruct::ts_declaration(),
+        "export interface AccountSubstruct {
+  displayName?: string;
+  \"e-mail\"?: string;
+  loginCount?: number;
+  expectedVersion?: number;
+  expectLoginCount?: number;
+}
+"
+    );
+
+    let schema = AccountSubstruct::json_schema();
+    let mut properties: Vec<_> = schema["properties"].as_object().unwrap().keys().collect();
+    properties.sort();
+    assert_eq!(
+        properties,
+        vec![
+            "displayName",
+            "e-mail",
+            "expectLoginCount",
+            "expectedVersion",
+            "loginCount"
+        ]
+    );
+}
+
+#[test]
+fn test_renamed_substruct_matches_the_source_wire_format() {
+    let update: AccountSubstruct = serde_json::from_value(json!({
+        "displayName": "Alice",
+        "e-mail": "alice@example.com",
+        "expectedVersion": 3
+    }))
+    .unwrap();
+
+    assert_eq!(update.display_name, Some("Alice".to_string()));
+    assert_eq!(update.email_address, Some("alice@example.com".to_string()));
+    assert_eq!(update.expected_version, Some(3));
+    assert_eq!(
+        serde_json::to_value(AccountSubstruct {
+            login_count: Some(2),
+            ..Default::default()
+        })
+        .unwrap()["loginCount"],
+        2
+    );
+
+    let create: CreateAccount = serde_json::from_value(json!({
+        "displayName": "Bob",
+        "e-mail": "bob@example.com",
+        "loginCount": 0
+    }))
+    .unwrap();
+    let account = create.try_build().unwrap();
+    assert_eq!(account.email_address, "bob@example.com");
+    assert_eq!(
+        serde_json::to_value(&account).unwrap()["displayName"],
+        "Bob"
+    );
+}
+
+#[test]
+fn test_null_clears_and_omitted_properties_keep() {
+    let mut profile = Profile {
+        name: "Alice".to_string(),
+        nickname: Some("Al".to_string()),
+        age: 30,
+        scores: vec![Some(1.0)],
+        preferences: Preferences {
+            theme: "dark".to_string(),
+        },
+        address: Address {
+            city: "NYC".to_string(),
+        },
+        billing: Some(Address {
+            city: "LA".to_string(),
+        }),
+        tags: Vec::new(),
+        version: 1,
+    };
+
+    // `nickname?: string | null` and `billing?: AddressSubstruct | null`: `null` clears
+    let clear: ProfileSubstruct = serde_json::from_value(json!({
+        "nickname": null,
+        "billing": null
+    }))
+    .unwrap();
+    assert_eq!(clear.nickname, Some(None));
+    assert_eq!(
+        serde_json::to_value(&clear).unwrap(),
+        json!({ "nickname": null, "billing": null })
+    );
+    clear.apply_to(&mut profile).unwrap();
+    assert_eq!(profile.nickname, None);
+    assert_eq!(profile.billing, None);
+
+    // An omitted property is left unchanged, and unset fields are not serialized as `null`
+    let rename: ProfileSubstruct = serde_json::from_value(json!({ "name": "Bob" })).unwrap();
+    assert_eq!(rename.nickname, None);
+    assert_eq!(
+        serde_json::to_value(&rename).unwrap(),
+        json!({ "name": "Bob" })
+    );
+    profile.nickname = Some("Bobby".to_string());
+    rename.apply_to(&mut profile).unwrap();
+    assert_eq!(profile.name, "Bob");
+    assert_eq!(profile.nickname, Some("Bobby".to_string()));
+}
+
+// ============================================================================
+// GUARD AND SENTINEL DECLARATION TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(typescript)]
+struct Quota {
+    #[substruct_field(primitive, expect)]
+    owner: String,
+    #[substruct_field(primitive, sensitive)]
+    api_key: String,
+    #[substruct_field(primitive, option = false, default = "u32::MAX")]
+    limit: u32,
+    #[substruct_field(version)]
+    version: u64,
+}
+
+#[test]
+fn test_guards_sensitive_and_sentinel_fields_are_optional_properties() {
+    assert_eq!(
+        QuotaSubstruct::ts_declaration(),
+        "export interface QuotaSubstruct {
+  owner?: string;
+  api_key?: string;
+  limit?: number;
+  expected_version?: number;
+  expect_owner?: string;
+}
+"
+    );
+}
+
+#[test]
+fn test_client_payloads_apply_with_their_guards() {
+    let mut quota = Quota {
+        owner: "alice".to_string(),
+        api_key: "old".to_string(),
+        limit: 10,
+        version: 2,
+    };
+
+    // An omitted `limit` stays at its sentinel, so only `api_key` is set
+    let stale: QuotaSubstruct = serde_json::from_value(json!({
+        "api_key": "new",
+        "expected_version": 1
+    }))
+    .unwrap();
+    assert_eq!(stale.limit, u32::MAX);
+    assert_eq!(stale.field_count(), 1);
+    assert!(matches!(
+        stale.apply_to(&mut quota),
+        Err(QuotaSubstructApplyError::VersionConflict {
+            expected: 1,
+            found: 2
+        })
+    ));
+
+    let wrong_owner: QuotaSubstruct = serde_json::from_value(json!({
+        "limit": 0,
+        "expect_owner": "bob"
+    }))
+    .unwrap();
+    assert_eq!(
+        wrong_owner.apply_to(&mut quota).unwrap_err().field(),
+        "owner"
+    );
+    assert_eq!(quota.limit, 10);
+
+    let current: QuotaSubstruct = serde_json::from_value(json!({
+        "limit": 0,
+        "expected_version": 2,
+        "expect_owner": "alice"
+    }))
+    .unwrap();
+    current.apply_to(&mut quota).unwrap();
+    assert_eq!(quota.limit, 0);
+    assert_eq!(quota.api_key, "old");
+    assert_eq!(quota.version, 3);
+}
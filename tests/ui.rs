@@ -0,0 +1,17 @@
+// ============================================================================
+// COMPILE-FAIL UI TESTS
+// ============================================================================
+
+// Every diagnostic the macro can emit has a fixture in `tests/ui/` with the
+// expected compiler output next to it in a `.stderr` file. After changing a
+// diagnostic, regenerate the snapshots with:
+//
+//     TRYBUILD=overwrite cargo test --test ui
+//
+// and review the diff.
+
+#[test]
+fn test_compile_fail_diagnostics() {
+    let cases = trybuild::TestCases::new();
+    cases.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,9 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct User {
+    #[substruct_field(primitive, json)]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,39 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct User {
+    #[substruct_field(json, option = false)]
+    preferences: Preferences,
+    #[substruct_field(nested, option = true)]
+    address: Address,
+    #[substruct_field(primitive, nested_type = "NameUpdate")]
+    name: String,
+    #[substruct_field(primitive, key = "id")]
+    tags: Vec<String>,
+    #[substruct_field(json, validate(length(max = 10)))]
+    settings: Preferences,
+    #[substruct_field(nested, transform = "trim")]
+    billing: Address,
+    #[substruct_field(json, expect)]
+    metadata: Preferences,
+    #[substruct_field(nested, sensitive)]
+    owner: Address,
+    #[substruct_field(skip, label = "Internal")]
+    internal: String,
+    #[substruct_field(version, env = "USER_VERSION")]
+    version: u64,
+}
+
+#[derive(Clone, PartialEq, Serialize, Deserialize)]
+struct Preferences {
+    theme: String,
+}
+
+#[derive(SubstructBuilder)]
+struct Address {
+    #[substruct_field(primitive)]
+    city: String,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+#[substruct_builder(create)]
+struct User {
+    #[substruct_field(primitive)]
+    name: String,
+    created_at: u64,
+    #[substruct_field(skip)]
+    password_hash: String,
+    #[substruct_field(default = "0")]
+    login_count: u32,
+}
+
+fn main() {}
@@ -0,0 +1,15 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+#[substruct_builder(name = "UserUpdate", name = "UserPatch")]
+struct User {
+    #[substruct_field(primitive, option = false, option = true)]
+    age: u32,
+    #[substruct_field(primitive, primitive)]
+    name: String,
+    #[substruct_field(primitive)]
+    #[substruct_field(option = false)]
+    email: String,
+}
+
+fn main() {}
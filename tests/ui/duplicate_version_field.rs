@@ -0,0 +1,13 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct Document {
+    #[substruct_field(primitive)]
+    title: String,
+    #[substruct_field(version)]
+    version: u64,
+    #[substruct_field(version)]
+    revision: u64,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+enum Status {
+    Active,
+    Inactive,
+}
+
+fn main() {}
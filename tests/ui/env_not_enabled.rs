@@ -0,0 +1,9 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct Config {
+    #[substruct_field(primitive, env = "APP_PORT")]
+    port: u16,
+}
+
+fn main() {}
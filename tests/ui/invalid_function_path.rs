@@ -0,0 +1,11 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct User {
+    #[substruct_field(primitive, validate(custom = "not a path"))]
+    name: String,
+    #[substruct_field(primitive, transform_with = "normalize(email)")]
+    email: String,
+}
+
+fn main() {}
@@ -0,0 +1,17 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+#[substruct_builder(name = "User Update")]
+struct User {
+    #[substruct_field(primitive)]
+    name: String,
+}
+
+#[derive(SubstructBuilder)]
+#[substruct_builder(name = UserUpdate)]
+struct Account {
+    #[substruct_field(primitive)]
+    name: String,
+}
+
+fn main() {}
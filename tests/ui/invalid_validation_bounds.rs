@@ -0,0 +1,13 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct User {
+    #[substruct_field(primitive, validate(range()))]
+    age: u32,
+    #[substruct_field(primitive, validate(length(min = 1, min = 2)))]
+    name: String,
+    #[substruct_field(primitive, validate(length(minimum = 1)))]
+    email: String,
+}
+
+fn main() {}
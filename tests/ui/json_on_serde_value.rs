@@ -0,0 +1,9 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct User {
+    #[substruct_field(json)]
+    metadata: serde_json::Value,
+}
+
+fn main() {}
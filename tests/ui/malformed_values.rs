@@ -0,0 +1,17 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct User {
+    #[substruct_field(primitive, option = "no")]
+    age: u32,
+    #[substruct_field(primitive, option)]
+    name: String,
+    #[substruct_field(json = true)]
+    preferences: String,
+    #[substruct_field(primitive, transform = trim)]
+    email: String,
+    #[substruct_field(primitive, default = "u32::")]
+    score: u32,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct User {
+    #[substruct_field(option = false)]
+    age: u32,
+    #[substruct_field(primitive)]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct Order {
+    #[substruct_field(nested_vec, key = "id")]
+    item: Item,
+}
+
+#[derive(SubstructBuilder)]
+struct Item {
+    #[substruct_field(primitive)]
+    id: String,
+}
+
+fn main() {}
@@ -0,0 +1,20 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct User {
+    #[substruct_field(primtive)]
+    name: String,
+    #[substruct_field(nested, nested_typ = "AddressUpdate")]
+    address: Address,
+    #[substruct_field(primitive, colour = "red")]
+    theme: String,
+}
+
+#[derive(SubstructBuilder)]
+struct Address {
+    #[substruct_field(primitive)]
+    city: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+#[substruct_builder(from = "full")]
+struct User {
+    #[substruct_field(primitive)]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+#[substruct_builder(nme = "UserUpdate")]
+struct User {
+    #[substruct_field(primitive)]
+    name: String,
+}
+
+fn main() {}
@@ -0,0 +1,11 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct User {
+    #[substruct_field(primitive, transform = "trim|lowercse")]
+    email: String,
+    #[substruct_field(primitive, transform = "capitalize")]
+    name: String,
+}
+
+fn main() {}
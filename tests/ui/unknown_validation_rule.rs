@@ -0,0 +1,11 @@
+use substruct_genesis::SubstructBuilder;
+
+#[derive(SubstructBuilder)]
+struct User {
+    #[substruct_field(primitive, validate(rang(min = 0, max = 150)))]
+    age: u32,
+    #[substruct_field(primitive, validate(email))]
+    email: String,
+}
+
+fn main() {}
@@ -0,0 +1,611 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value, json};
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// UPDATE DOCUMENT TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(document)]
+struct Address {
+    #[substruct_field(primitive)]
+    city: String,
+    #[substruct_field(primitive)]
+    zip: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(document)]
+struct Tag {
+    #[substruct_field(primitive)]
+    id: String,
+    #[substruct_field(primitive)]
+    color: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(document)]
+struct Parcel {
+    #[substruct_field(primitive)]
+    id: u32,
+    #[substruct_field(nested)]
+    label: Option<Address>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(document)]
+struct Shipment {
+    #[substruct_field(nested_vec, key = "id", key_type = "u32")]
+    parcels: Vec<Parcel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct Preferences {
+    theme: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(document)]
+#[serde(rename_all = "camelCase")]
+struct User {
+    #[substruct_field(version)]
+    version: u64,
+    #[substruct_field(primitive, transform = "trim")]
+    display_name: String,
+    #[substruct_field(primitive)]
+    nickname: Option<String>,
+    #[substruct_field(primitive, option = false)]
+    login_count: u32,
+    #[substruct_field(json)]
+    preferences: Preferences,
+    #[substruct_field(nested)]
+    address: Address,
+    #[substruct_field(nested)]
+    billing: Option<Address>,
+    #[substruct_field(nested)]
+    previous: Vec<Address>,
+    #[substruct_field(nested_vec, key = "id")]
+    tags: Vec<Tag>,
+}
+
+#[test]
+fn test_update_document_sets_and_unsets_fields() {
+    let update = UserSubstruct {
+        display_name: Some("  Bob ".to_string()),
+        nickname: Some(None),
+        login_count: 4,
+        preferences: Some(json!({ "theme": "dark" })),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        update.to_update_document(),
+        json!({
+            "$set": {
+                "displayName": "Bob",
+                "loginCount": 4,
+                "preferences": { "theme": "dark" }
+            },
+            "$unset": { "nickname": "" },
+            "$inc": { "version": 1 }
+        })
+    );
+}
+
+#[test]
+fn test_update_document_uses_dotted_paths_for_nested_fields() {
+    let update = UserSubstruct {
+        address: Some(AddressSubstruct {
+            city: Some("Boston".to_string()),
+            zip: Some(None),
+        }),
+        billing: Some(None),
+        previous: Some(AddressSubstruct {
+            city: Some("Paris".to_string()),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        update.to_update_document(),
+        json!({
+            "$set": {
+                "address.city": "Boston",
+                "previous.$[elem0].city": "Paris"
+            },
+            "$unset": { "address.zip": "", "billing": "" },
+            "$inc": { "version": 1 }
+        })
+    );
+    assert_eq!(
+        update.update_array_filters(),
+        vec![json!({ "elem0": { "$type": "object" } })]
+    );
+}
+
+#[test]
+fn test_update_stages_replace_or_push_upserts_and_pull_removals() {
+    let update = UserSubstruct::default()
+        .upsert_tags(Tag {
+            id: "new".to_string(),
+            color: "red".to_string(),
+        })
+        .remove_tags("old")
+        .remove_tags("older");
+
+    let stages = update.to_update_stages();
+    assert_eq!(stages.len(), 3);
+    // An existing element is replaced in place...
+    assert_eq!(stages[0].filter, json!({}));
+    assert_eq!(
+        stages[0].update,
+        json!({ "$set": { "tags.$[elem0]": { "id": "new", "color": "red" } } })
+    );
+    assert_eq!(stages[0].array_filters, vec![json!({ "elem0.id": "new" })]);
+    // ...and a missing one is pushed
+    assert_eq!(stages[1].filter, json!({ "tags.id": { "$ne": "new" } }));
+    assert_eq!(
+        stages[1].update,
+        json!({ "$push": { "tags": { "id": "new", "color": "red" } } })
+    );
+    assert_eq!(
+        stages[2].update,
+        json!({
+            "$pull": { "tags": { "id": { "$in": ["old", "older"] } } },
+            "$inc": { "version": 1 }
+        })
+    );
+    // A single document pushes and pulls instead
+    assert_eq!(
+        update.to_update_document(),
+        json!({
+            "$push": { "tags": { "$each": [{ "id": "new", "color": "red" }] } },
+            "$pull": { "tags": { "id": { "$in": ["old", "older"] } } },
+            "$inc": { "version": 1 }
+        })
+    );
+}
+
+#[test]
+fn test_update_stages_split_conflicting_operators() {
+    let update = UserSubstruct::default()
+        .remove_tags("old")
+        .patch_tags(
+            "a",
+            TagSubstruct {
+                color: Some("blue".to_string()),
+                ..Default::default()
+            },
+        )
+        .patch_tags(
+            "b",
+            TagSubstruct {
+                color: Some("green".to_string()),
+                ..Default::default()
+            },
+        );
+
+    let stages = update.to_update_stages();
+    let updates: Vec<_> = stages.iter().map(|stage| stage.update.clone()).collect();
+    assert_eq!(
+        updates,
+        vec![
+            json!({ "$pull": { "tags": { "id": { "$in": ["old"] } } } }),
+            json!({ "$set": { "tags.$[elem0].color": "blue" } }),
+            json!({ "$set": { "tags.$[elem1].color": "green" }, "$inc": { "version": 1 } }),
+        ]
+    );
+    assert_eq!(stages[2].array_filters, vec![json!({ "elem1.id": "b" })]);
+    assert_eq!(
+        update.to_update_document(),
+        json!({
+            "$pull": { "tags": { "id": { "$in": ["old"] } } },
+            "$set": { "tags.$[elem0].color": "blue", "tags.$[elem1].color": "green" },
+            "$inc": { "version": 1 }
+        })
+    );
+    assert_eq!(
+        update.update_array_filters(),
+        vec![json!({ "elem0.id": "a" }), json!({ "elem1.id": "b" })]
+    );
+}
+
+#[test]
+fn test_update_stages_set_created_optional_values_whole() {
+    let update = UserSubstruct {
+        billing: Some(Some(AddressSubstruct {
+            city: Some("Boston".to_string()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+
+    let stages = update.to_update_stages();
+    assert_eq!(stages.len(), 3);
+    assert_eq!(stages[0].filter, json!({ "billing": { "$ne": null } }));
+    assert_eq!(
+        stages[0].update,
+        json!({ "$set": { "billing.city": "Boston" } })
+    );
+    assert_eq!(stages[1].filter, json!({ "billing": null }));
+    assert_eq!(
+        stages[1].update,
+        json!({ "$set": { "billing": { "city": "Boston", "zip": null } } })
+    );
+    assert_eq!(stages[2].update, json!({ "$inc": { "version": 1 } }));
+}
+
+#[test]
+fn test_update_patches_keyed_elements_through_array_filters() {
+    let update = UserSubstruct::default()
+        .patch_tags(
+            "a",
+            TagSubstruct {
+                color: Some("blue".to_string()),
+                ..Default::default()
+            },
+        )
+        .patch_tags("b", TagSubstruct::default());
+
+    assert_eq!(
+        update.to_update_document(),
+        json!({
+            "$set": { "tags.$[elem0].color": "blue" },
+            "$inc": { "version": 1 }
+        })
+    );
+    assert_eq!(
+        update.update_array_filters(),
+        vec![json!({ "elem0.id": "a" })]
+    );
+}
+
+#[test]
+fn test_version_filter_guards_the_expected_version() {
+    let update = UserSubstruct {
+        expected_version: Some(7),
+        login_count: 4,
+        ..Default::default()
+    };
+    assert_eq!(update.version_filter(), json!({ "version": 7 }));
+    assert_eq!(UserSubstruct::default().version_filter(), json!({}));
+}
+
+#[test]
+fn test_update_document_empty_update() {
+    assert_eq!(UserSubstruct::default().to_update_document(), json!({}));
+    assert!(UserSubstruct::default().update_array_filters().is_empty());
+    assert!(UserSubstruct::default().to_update_stages().is_empty());
+}
+
+// ============================================================================
+// STAGES AGAINST APPLY_TO
+// ============================================================================
+
+/// The values at a dotted `path`, looking into every element of the arrays on the way
+fn values_at<'a>(value: &'a Value, path: &str) -> Vec<&'a Value> {
+    let mut values = vec![value];
+    for segment in path.split('.') {
+        values = values
+            .into_iter()
+            .flat_map(|value| match value {
+                Value::Array(items) => items
+                    .iter()
+                    .filter_map(|item| item.get(segment))
+                    .collect::<Vec<_>>(),
+                _ => value.get(segment).into_iter().collect(),
+            })
+            .collect();
+    }
+    values
+}
+
+/// Whether `document` matches a filter of `{path: predicate}` conditions
+fn matches(document: &Value, filter: &Map<String, Value>) -> bool {
+    filter.iter().all(|(path, predicate)| {
+        let values = if path.is_empty() {
+            vec![document]
+        } else {
+            values_at(document, path)
+        };
+        match predicate {
+            Value::Null => values.iter().all(|value| value.is_null()),
+            // A missing value counts as `null`
+            Value::Object(operator) if operator.contains_key("$ne") => {
+                let other = &operator["$ne"];
+                values.iter().all(|value| *value != other)
+                    && !(other.is_null() && values.is_empty())
+            }
+            Value::Object(operator) if operator.contains_key("$type") => {
+                values.iter().any(|value| value.is_object())
+            }
+            literal => values.contains(&literal),
+        }
+    })
+}
+
+/// Calls `write` with every slot `segments` addresses, creating missing fields
+fn visit(
+    value: &mut Value,
+    segments: &[&str],
+    array_filters: &[Value],
+    write: &mut dyn FnMut(&mut Value),
+) {
+    let Some((first, rest)) = segments.split_first() else {
+        return write(value);
+    };
+    if let Some(identifier) = first
+        .strip_prefix("$[")
+        .and_then(|identifier| identifier.strip_suffix(']'))
+    {
+        // The element conditions, relative to the element
+        let conditions: Map<String, Value> = array_filters
+            .iter()
+            .flat_map(|filter| filter.as_object().unwrap())
+            .filter_map(|(path, predicate)| {
+                let relative = path.strip_prefix(identifier)?;
+                Some((
+                    relative.trim_start_matches('.').to_string(),
+                    predicate.clone(),
+                ))
+            })
+            .collect();
+        let items = value.as_array_mut().expect("array updates need an array");
+        for item in items.iter_mut() {
+            if matches(item, &conditions) {
+                visit(item, rest, array_filters, write);
+            }
+        }
+    } else {
+        assert!(
+            value.is_object(),
+            "cannot create field `{first}` in {value}"
+        );
+        let object = value.as_object_mut().unwrap();
+        let next = object.entry(first.to_string()).or_insert_with(|| {
+            if rest.is_empty() {
+                Value::Null
+            } else {
+                json!({})
+            }
+        });
+        visit(next, rest, array_filters, write);
+    }
+}
+
+/// Runs one stage against `document` the way a document store would
+fn run_stage(document: &mut Value, filter: &Value, update: &Value, array_filters: &[Value]) {
+    if !matches(document, filter.as_object().unwrap()) {
+        return;
+    }
+    for (operator, paths) in update.as_object().unwrap() {
+        for (path, argument) in paths.as_object().unwrap() {
+            let segments: Vec<_> = path.split('.').collect();
+            match operator.as_str() {
+                "$set" => visit(document, &segments, array_filters, &mut |slot| {
+                    *slot = argument.clone()
+                }),
+                "$unset" => {
+                    let (last, parent) = segments.split_last().unwrap();
+                    visit(document, parent, array_filters, &mut |slot| {
+                        slot.as_object_mut().unwrap().remove(*last);
+                    })
+                }
+                "$inc" => visit(document, &segments, array_filters, &mut |slot| {
+                    *slot = json!(slot.as_u64().unwrap_or(0) + argument.as_u64().unwrap())
+                }),
+                "$push" => visit(document, &segments, array_filters, &mut |slot| {
+                    let items = slot.as_array_mut().unwrap();
+                    match argument.get("$each") {
+                        Some(each) => items.extend(each.as_array().unwrap().iter().cloned()),
+                        None => items.push(argument.clone()),
+                    }
+                }),
+                "$pull" => visit(document, &segments, array_filters, &mut |slot| {
+                    let (key, condition) = argument.as_object().unwrap().iter().next().unwrap();
+                    let keys = condition["$in"].as_array().unwrap();
+                    slot.as_array_mut()
+                        .unwrap()
+                        .retain(|item| !keys.contains(&item[key.as_str()]));
+                }),
+                other => panic!("unexpected operator {other}"),
+            }
+        }
+    }
+}
+
+fn user() -> User {
+    let tag = |id: &str, color: &str| Tag {
+        id: id.to_string(),
+        color: color.to_string(),
+    };
+    User {
+        version: 2,
+        display_name: "Alice".to_string(),
+        nickname: Some("Al".to_string()),
+        login_count: 3,
+        preferences: Preferences {
+            theme: "light".to_string(),
+        },
+        address: Address {
+            city: "Oslo".to_string(),
+            zip: Some("0150".to_string()),
+        },
+        billing: None,
+        previous: vec![
+            Address {
+                city: "Bergen".to_string(),
+                zip: None,
+            },
+            Address::default(),
+        ],
+        tags: vec![tag("a", "red"), tag("b", "green"), tag("c", "blue")],
+    }
+}
+
+fn assert_stages_match_apply_to(target: &User, update: &UserSubstruct) {
+    let mut expected = target.clone();
+    update.apply_to(&mut expected).unwrap();
+
+    let mut document = serde_json::to_value(target).unwrap();
+    for stage in update.to_update_stages() {
+        run_stage(
+            &mut document,
+            &stage.filter,
+            &stage.update,
+            &stage.array_filters,
+        );
+    }
+    assert_eq!(serde_json::from_value::<User>(document).unwrap(), expected);
+}
+
+#[test]
+fn test_update_stages_match_apply_to_for_keyed_operations() {
+    let tag = |id: &str, color: &str| Tag {
+        id: id.to_string(),
+        color: color.to_string(),
+    };
+    // An upsert of an existing key replaces it in place instead of adding a duplicate
+    assert_stages_match_apply_to(
+        &user(),
+        &UserSubstruct::default().upsert_tags(tag("a", "black")),
+    );
+
+    let update = UserSubstruct::default()
+        .upsert_tags(tag("b", "black"))
+        .upsert_tags(tag("d", "white"))
+        .remove_tags("a")
+        .patch_tags(
+            "c",
+            TagSubstruct {
+                color: Some("grey".to_string()),
+                ..Default::default()
+            },
+        )
+        .patch_tags(
+            "d",
+            TagSubstruct {
+                color: Some("pink".to_string()),
+                ..Default::default()
+            },
+        )
+        .remove_tags("b")
+        .upsert_tags(tag("b", "navy"));
+
+    assert_stages_match_apply_to(&user(), &update);
+}
+
+#[test]
+fn test_update_document_matches_apply_to_without_conflicts() {
+    let update = UserSubstruct {
+        display_name: Some("  Bob ".to_string()),
+        nickname: Some(None),
+        address: Some(AddressSubstruct {
+            zip: Some(None),
+            ..Default::default()
+        }),
+        previous: Some(AddressSubstruct {
+            zip: Some(Some("9999".to_string())),
+            ..Default::default()
+        }),
+        ..Default::default()
+    }
+    .upsert_tags(Tag {
+        id: "d".to_string(),
+        color: "white".to_string(),
+    });
+
+    let mut expected = user();
+    update.apply_to(&mut expected).unwrap();
+    let mut document = serde_json::to_value(user()).unwrap();
+    run_stage(
+        &mut document,
+        &json!({}),
+        &update.to_update_document(),
+        &update.update_array_filters(),
+    );
+    assert_eq!(serde_json::from_value::<User>(document).unwrap(), expected);
+}
+
+#[test]
+fn test_update_stages_match_apply_to_for_nested_values() {
+    let update = UserSubstruct {
+        display_name: Some("  Bob ".to_string()),
+        nickname: Some(None),
+        address: Some(AddressSubstruct {
+            zip: Some(None),
+            ..Default::default()
+        }),
+        billing: Some(Some(AddressSubstruct {
+            city: Some("Boston".to_string()),
+            ..Default::default()
+        })),
+        previous: Some(AddressSubstruct {
+            zip: Some(Some("9999".to_string())),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    // Created under a missing value...
+    assert_stages_match_apply_to(&user(), &update);
+    // ...and patched under an existing one
+    let mut target = user();
+    target.billing = Some(Address {
+        city: "Oslo".to_string(),
+        zip: Some("0150".to_string()),
+    });
+    assert_stages_match_apply_to(&target, &update);
+}
+
+#[test]
+fn test_update_stages_match_apply_to_inside_keyed_elements() {
+    let target = Shipment {
+        parcels: vec![
+            Parcel { id: 1, label: None },
+            Parcel {
+                id: 2,
+                label: Some(Address {
+                    city: "Oslo".to_string(),
+                    zip: None,
+                }),
+            },
+        ],
+    };
+    let relabel = ParcelSubstruct {
+        label: Some(Some(AddressSubstruct {
+            city: Some("Boston".to_string()),
+            ..Default::default()
+        })),
+        ..Default::default()
+    };
+    let update = ShipmentSubstruct::default()
+        .patch_parcels(1u32, relabel.clone())
+        .patch_parcels(2u32, relabel);
+
+    // The element's label is checked by its array filter, not the query filter
+    let stages = update.to_update_stages();
+    assert_eq!(stages[0].filter, json!({}));
+    assert_eq!(
+        stages[0].array_filters,
+        vec![json!({ "elem0.id": 1, "elem0.label": { "$ne": null } })]
+    );
+
+    let mut expected = target.clone();
+    update.apply_to(&mut expected).unwrap();
+    let mut document = serde_json::to_value(&target).unwrap();
+    for stage in stages {
+        run_stage(
+            &mut document,
+            &stage.filter,
+            &stage.update,
+            &stage.array_filters,
+        );
+    }
+    assert_eq!(
+        serde_json::from_value::<Shipment>(document).unwrap(),
+        expected
+    );
+}
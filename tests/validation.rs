@@ -0,0 +1,147 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// FIELD VALIDATION TESTS
+// ============================================================================
+
+fn no_admin(value: &str) -> Result<(), String> {
+    if value.eq_ignore_ascii_case("admin") {
+        Err("is reserved".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Member {
+    #[substruct_field(primitive, validate(length(min = 3, max = 8), custom = "no_admin"))]
+    username: String,
+    #[substruct_field(primitive, validate(range(min = 0, max = 150)))]
+    age: i32,
+    #[substruct_field(primitive, validate(regex = r"^[^@\s]+@[^@\s]+$"))]
+    email: Option<String>,
+    #[substruct_field(primitive, option = false, validate(range(max = 10)))]
+    level: u8,
+    #[substruct_field(nested)]
+    address: PostalAddress,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct PostalAddress {
+    #[substruct_field(primitive, validate(length(min = 1)))]
+    city: String,
+    #[substruct_field(primitive, validate(length(max = 2)))]
+    tags: Vec<String>,
+}
+
+fn member() -> Member {
+    Member {
+        username: "alice".to_string(),
+        age: 30,
+        email: None,
+        level: 1,
+        address: PostalAddress {
+            city: "Paris".to_string(),
+            tags: Vec::new(),
+        },
+    }
+}
+
+#[test]
+fn test_validate_unset_fields_are_skipped() {
+    assert!(MemberSubstruct::default().validate().is_ok());
+
+    // Clearing an optional field is never checked against the regex
+    let update = MemberSubstruct::new(None, None, Some(None), 0, None);
+    assert!(update.validate().is_ok());
+}
+
+#[test]
+fn test_validate_valid_update() {
+    let update = MemberSubstruct::new(
+        Some("bob".to_string()),
+        Some(150),
+        Some(Some("bob@example.com".to_string())),
+        10,
+        Some(PostalAddressSubstruct::new(Some("Rome".to_string()), None)),
+    );
+
+    assert!(update.validate().is_ok());
+}
+
+#[test]
+fn test_validate_collects_all_errors() {
+    let update = MemberSubstruct::new(
+        Some("ab".to_string()),
+        Some(-1),
+        Some(Some("not-an-email".to_string())),
+        11,
+        None,
+    );
+
+    let errors = update.validate().unwrap_err();
+    assert_eq!(errors.len(), 4);
+    assert_eq!(
+        errors.messages_for("username"),
+        vec!["length must be at least 3"]
+    );
+    assert_eq!(errors.messages_for("age"), vec!["must be at least 0"]);
+    assert_eq!(errors.messages_for("level"), vec!["must be at most 10"]);
+    assert_eq!(
+        errors.messages_for("email"),
+        vec![r"must match pattern `^[^@\s]+@[^@\s]+$`"]
+    );
+}
+
+#[test]
+fn test_validate_custom_and_multiple_rules_on_one_field() {
+    let update = MemberSubstruct::new(Some("admin".to_string()), None, None, 0, None);
+    let errors = update.validate().unwrap_err();
+    assert_eq!(errors.messages_for("username"), vec!["is reserved"]);
+
+    let update = MemberSubstruct::new(Some("Administrator".to_string()), None, None, 0, None);
+    let errors = update.validate().unwrap_err();
+    assert_eq!(
+        errors.messages_for("username"),
+        vec!["length must be at most 8"]
+    );
+}
+
+#[test]
+fn test_validate_nested_errors_are_path_prefixed() {
+    let address = PostalAddressSubstruct::new(
+        Some(String::new()),
+        Some(vec!["a".to_string(), "b".to_string(), "c".to_string()]),
+    );
+    let update = MemberSubstruct::new(None, Some(200), None, 0, Some(address));
+
+    let errors = update.validate().unwrap_err();
+    assert_eq!(errors.len(), 3);
+    assert_eq!(
+        errors.messages_for("address.city"),
+        vec!["length must be at least 1"]
+    );
+    assert_eq!(
+        errors.messages_for("address.tags"),
+        vec!["length must be at most 2"]
+    );
+    assert_eq!(
+        errors.to_string(),
+        "age: must be at most 150; address.city: length must be at least 1; address.tags: length must be at most 2"
+    );
+}
+
+#[test]
+fn test_try_apply_to() {
+    let mut target = member();
+
+    let invalid = MemberSubstruct::new(Some("bob".to_string()), Some(151), None, 0, None);
+    assert!(invalid.try_apply_to(&mut target).is_err());
+    assert_eq!(target, member()); // untouched on failure
+
+    let valid = MemberSubstruct::new(Some("bob".to_string()), Some(40), None, 0, None);
+    valid.try_apply_to(&mut target).unwrap();
+    assert_eq!(target.username, "bob");
+    assert_eq!(target.age, 40);
+}
@@ -0,0 +1,175 @@
+use serde::{Deserialize, Serialize};
+use substruct_genesis::SubstructBuilder;
+
+// ============================================================================
+// OPTIMISTIC CONCURRENCY TESTS
+// ============================================================================
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Document {
+    #[substruct_field(primitive)]
+    title: String,
+    #[substruct_field(primitive)]
+    body: String,
+    #[substruct_field(version)]
+    version: u64,
+    #[substruct_field(nested)]
+    owner: Owner,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+struct Owner {
+    #[substruct_field(primitive)]
+    name: String,
+}
+
+fn document() -> Document {
+    Document {
+        title: "Draft".to_string(),
+        body: "Hello".to_string(),
+        version: 3,
+        owner: Owner {
+            name: "Alice".to_string(),
+        },
+    }
+}
+
+#[test]
+fn test_matching_version_applies_and_bumps() {
+    let mut doc = document();
+    let update =
+        DocumentSubstruct::new(Some("Final".to_string()), None, None).with_expected_version(3);
+
+    assert_eq!(update.expected_version, Some(3));
+    update.apply_to(&mut doc).unwrap();
+
+    assert_eq!(doc.title, "Final");
+    assert_eq!(doc.version, 4);
+}
+
+#[test]
+fn test_version_conflict_writes_nothing() {
+    let mut doc = document();
+    let update = DocumentSubstruct::new(
+        Some("Final".to_string()),
+        None,
+        Some(OwnerSubstruct::new(Some("Bob".to_string()))),
+    )
+    .with_expected_version(2);
+
+    let err = update.apply_to(&mut doc).unwrap_err();
+    assert_eq!(
+        err,
+        DocumentSubstructApplyError::VersionConflict {
+            expected: 2,
+            found: 3
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "version conflict on `version`: expected 2, found 3"
+    );
+    assert_eq!(doc, document());
+}
+
+#[test]
+fn test_unguarded_update_still_bumps_version() {
+    let mut doc = document();
+    let update = DocumentSubstruct::new(None, Some("World".to_string()), None);
+
+    assert_eq!(update.expected_version, None);
+    update.apply_to(&mut doc).unwrap();
+
+    assert_eq!(doc.body, "World");
+    assert_eq!(doc.version, 4);
+
+    // An empty update is not a write and leaves the version alone
+    DocumentSubstruct::default().apply_to(&mut doc).unwrap();
+    assert_eq!(doc.version, 4);
+}
+
+#[test]
+fn test_from_source_expects_snapshot_version() {
+    let mut doc = document();
+    let mut first = DocumentSubstruct::from_source(&doc);
+    let mut second = DocumentSubstruct::from_source(&doc);
+    assert_eq!(first.expected_version, Some(3));
+    assert!(first.is_empty());
+
+    // Two writers start from the same snapshot; the second one loses
+    first.title = Some("First".to_string());
+    second.title = Some("Second".to_string());
+    first.apply_to(&mut doc).unwrap();
+    assert!(second.apply_to(&mut doc).is_err());
+
+    assert_eq!(doc.title, "First");
+    assert_eq!(doc.version, 4);
+}
+
+#[test]
+fn test_merge_and_try_apply_to_with_version() {
+    let mut doc = document();
+    let guarded = DocumentSubstruct::default().with_expected_version(9);
+    let change = DocumentSubstruct::new(Some("Final".to_string()), None, None);
+
+    let merged = guarded.merge(change);
+    assert_eq!(merged.expected_version, Some(9));
+
+    let errors = merged.try_apply_to(&mut doc).unwrap_err();
+    assert_eq!(
+        errors.messages_for("version"),
+        vec!["version conflict on `version`: expected 9, found 3"]
+    );
+    assert_eq!(doc, document());
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(report)]
+struct Row {
+    #[substruct_field(primitive)]
+    value: String,
+    #[substruct_field(version)]
+    version: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, SubstructBuilder)]
+#[substruct_builder(report)]
+struct Parent {
+    #[substruct_field(primitive)]
+    name: String,
+    #[substruct_field(nested)]
+    row: Row,
+}
+
+#[test]
+fn test_nested_version_conflict_fails_an_unversioned_parent() {
+    let parent = Parent {
+        name: "alice".to_string(),
+        row: Row::default(),
+    };
+    let update = ParentSubstruct {
+        name: Some("bob".to_string()),
+        row: Some(RowSubstruct::default().with_expected_version(7)),
+    };
+
+    let mut target = parent.clone();
+    let err = update.apply_to(&mut target).unwrap_err();
+    assert_eq!(err.field(), "row");
+    assert_eq!(
+        err.to_string(),
+        "guard failed in `row`: version conflict on `version`: expected 7, found 0"
+    );
+    assert_eq!(target, parent);
+
+    assert!(update.apply_with_report(&mut target).is_err());
+    assert_eq!(target, parent);
+
+    let current = ParentSubstruct {
+        row: Some(RowSubstruct::default().with_expected_version(0)),
+        ..update
+    };
+    let changes = current.apply_with_report(&mut target).unwrap();
+    assert_eq!(changes.changed_paths(), vec!["name"]);
+    // The row update only carries a guard, so the row's version stays put
+    assert_eq!(target.row.version, 0);
+}